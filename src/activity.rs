@@ -0,0 +1,89 @@
+use crate::circuit::CircuitBreaker;
+use crate::plain;
+use ghs::{BoxError, GhsClient, Repository};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories' statistics may be requested at once.
+const CONCURRENCY: usize = 8;
+
+const BARS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+
+/// Weekly commit counts for each repository, keyed by full name. Repositories
+/// whose statistics could not be had are missing.
+pub async fn fetch_activity(
+    api: &GhsClient,
+    repositories: &[Repository],
+) -> Result<HashMap<String, Vec<u64>>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let weeks = breaker
+                .call(
+                    &format!("commit activity of {}", full_name),
+                    api.commit_activity(&full_name),
+                )
+                .await?
+                .flatten()
+                .map(|weeks| weeks.iter().map(|week| week.total).collect());
+            Ok::<_, BoxError>((full_name, weeks))
+        });
+    }
+
+    let mut activity = HashMap::new();
+    while let Some(result) = tasks.join_next().await {
+        if let (full_name, Some(weeks)) = result?? {
+            activity.insert(full_name, weeks);
+        }
+    }
+    Ok(activity)
+}
+
+/// One bar per week, scaled to the busiest week; weeks without commits stay
+/// blank so dormant stretches stand out.
+pub fn sparkline(weeks: &[u64]) -> String {
+    let max = weeks.iter().copied().max().unwrap_or(0);
+    weeks
+        .iter()
+        .map(|&commits| {
+            if commits == 0 {
+                ' '
+            } else {
+                let level = (commits * (BARS.len() as u64 - 1)).div_ceil(max.max(1));
+                BARS[level as usize]
+            }
+        })
+        .collect()
+}
+
+pub fn print_activity(weeks: Option<&Vec<u64>>) {
+    match weeks {
+        Some(weeks) if weeks.iter().all(|&commits| commits == 0) => {
+            println!("Activity: no commits in the last year")
+        }
+        Some(weeks) if plain::enabled() => println!(
+            "Activity: {} commits in the last year",
+            weeks.iter().sum::<u64>()
+        ),
+        Some(weeks) => println!(
+            "Activity: [{}] {} commits in the last year",
+            sparkline(weeks),
+            weeks.iter().sum::<u64>()
+        ),
+        None => println!("Activity: not available yet"),
+    }
+    if !plain::enabled() {
+        println!("---");
+    }
+}
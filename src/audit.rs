@@ -0,0 +1,255 @@
+use crate::circuit::CircuitBreaker;
+use crate::codeowners;
+use crate::export;
+use crate::table;
+use ghs::model::Protection;
+use ghs::{BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories may be audited at once.
+const CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Present,
+    Missing,
+    /// The lookup failed, so compliance could not be established.
+    Unknown,
+}
+
+/// Whether one repository has each of the required paths.
+#[derive(Debug)]
+pub struct Compliance {
+    pub full_name: String,
+    pub presence: Vec<Presence>,
+}
+
+impl Compliance {
+    pub fn is_compliant(&self) -> bool {
+        self.presence
+            .iter()
+            .all(|presence| *presence == Presence::Present)
+    }
+}
+
+/// The paths that satisfy `requirement`. A bare `CODEOWNERS` is accepted in
+/// any of the places GitHub reads it from.
+fn locations(requirement: &str) -> Vec<String> {
+    if requirement == "CODEOWNERS" {
+        codeowners::LOCATIONS
+            .iter()
+            .map(|path| path.to_string())
+            .collect()
+    } else {
+        vec![requirement.to_string()]
+    }
+}
+
+/// Checks every repository for each of `required` via the contents API and
+/// returns the results sorted by repository name.
+pub async fn audit_files(
+    api: &GhsClient,
+    repositories: &[Repository],
+    required: &[String],
+) -> Result<Vec<Compliance>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let required: Arc<[String]> = required.into();
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let required = Arc::clone(&required);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let mut presence = Vec::with_capacity(required.len());
+            for requirement in required.iter() {
+                let mut found = Presence::Missing;
+                for path in locations(requirement) {
+                    match breaker
+                        .call(
+                            &format!("{} of {}", path, full_name),
+                            api.path_exists(&full_name, &path),
+                        )
+                        .await?
+                    {
+                        Some(true) => {
+                            found = Presence::Present;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => found = Presence::Unknown,
+                    }
+                }
+                presence.push(found);
+            }
+            Ok::<_, BoxError>(Compliance {
+                full_name,
+                presence,
+            })
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        results.push(result??);
+    }
+    results.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+    Ok(results)
+}
+
+/// Prints one row per repository and one column per requirement, followed by
+/// a summary line.
+pub fn print_matrix(results: &[Compliance], required: &[String]) {
+    let mut header = vec!["Repository".to_string()];
+    header.extend(required.iter().cloned());
+    let mut rows = vec![header];
+    for compliance in results {
+        let mut row = vec![compliance.full_name.clone()];
+        row.extend(compliance.presence.iter().map(|presence| {
+            match presence {
+                Presence::Present => "yes",
+                Presence::Missing => "MISSING",
+                Presence::Unknown => "?",
+            }
+            .to_string()
+        }));
+        rows.push(row);
+    }
+    print!("{}", table::render(&rows));
+
+    let compliant = results.iter().filter(|c| c.is_compliant()).count();
+    println!(
+        "{} of {} repositories have every required file",
+        compliant,
+        results.len()
+    );
+}
+
+/// The protection of one repository's default branch; `None` if the lookup
+/// failed.
+#[derive(Debug)]
+pub struct BranchAudit {
+    pub full_name: String,
+    pub branch: String,
+    pub protection: Option<Protection>,
+}
+
+impl BranchAudit {
+    /// Protected, with at least one approving review required to merge.
+    pub fn is_compliant(&self) -> bool {
+        self.required_reviews().is_some_and(|count| count > 0)
+    }
+
+    fn required_reviews(&self) -> Option<u32> {
+        match &self.protection {
+            Some(Protection::Protected(rules)) => Some(
+                rules
+                    .required_pull_request_reviews
+                    .as_ref()
+                    .map_or(0, |reviews| reviews.required_approving_review_count),
+            ),
+            _ => None,
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match (&self.protection, self.required_reviews()) {
+            (None, _) => "lookup failed",
+            (Some(Protection::NoAccess), _) => "no access",
+            (Some(Protection::Unprotected), _) => "unprotected",
+            (_, Some(0)) => "no required reviews",
+            _ => "ok",
+        }
+    }
+}
+
+/// Looks up the protection rules of every repository's default branch and
+/// returns the results sorted by repository name.
+pub async fn audit_protection(
+    api: &GhsClient,
+    repositories: &[Repository],
+) -> Result<Vec<BranchAudit>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let Some(branch) = repo.default_branch.clone() else {
+            continue;
+        };
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let protection = breaker
+                .call(
+                    &format!("protection of {}:{}", full_name, branch),
+                    api.branch_protection(&full_name, &branch),
+                )
+                .await?;
+            Ok::<_, BoxError>(BranchAudit {
+                full_name,
+                branch,
+                protection,
+            })
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        results.push(result??);
+    }
+    results.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+    Ok(results)
+}
+
+/// Prints the branch audit as a table with a summary line, or as CSV for
+/// spreadsheets.
+pub fn print_protection(results: &[BranchAudit], csv: bool) {
+    let mut rows = vec![vec![
+        "Repository".to_string(),
+        "Branch".to_string(),
+        "Required reviews".to_string(),
+        "Status".to_string(),
+    ]];
+    for audit in results {
+        rows.push(vec![
+            audit.full_name.clone(),
+            audit.branch.clone(),
+            audit
+                .required_reviews()
+                .map_or("-".to_string(), |count| count.to_string()),
+            audit.status().to_string(),
+        ]);
+    }
+
+    if csv {
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|field| export::csv_field(field)).collect();
+            println!("{}", fields.join(","));
+        }
+        return;
+    }
+
+    print!("{}", table::render(&rows));
+    let compliant = results.iter().filter(|audit| audit.is_compliant()).count();
+    let unreadable = results
+        .iter()
+        .filter(|audit| matches!(audit.protection, Some(Protection::NoAccess)))
+        .count();
+    print!(
+        "{} of {} default branches require reviews",
+        compliant,
+        results.len()
+    );
+    if unreadable > 0 {
+        print!(" ({} could not be read with this token)", unreadable);
+    }
+    println!();
+}
@@ -0,0 +1,272 @@
+//! Where a client's access token comes from.
+//!
+//! [`GhsClientBuilder::auth`](crate::GhsClientBuilder::auth) takes any
+//! [`AuthProvider`] and asks it for the token once, when the client is
+//! built. Besides the providers here, any closure returning
+//! `Result<Option<String>, BoxError>` is one, so a credential store such as
+//! Vault, or a GitHub App minting installation tokens, plugs in without
+//! changes to ghs:
+//!
+//! ```no_run
+//! use ghs::auth::{Chain, Env, GhConfig};
+//!
+//! # fn example() -> Result<(), ghs::BoxError> {
+//! let client = ghs::GhsClient::builder()
+//!     .auth(Chain::new(vec![
+//!         Box::new(Env::github()),
+//!         Box::new(GhConfig::new("github.com")),
+//!         Box::new(|| Ok(std::fs::read_to_string("/run/secrets/github").ok())),
+//!     ]))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::BoxError;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+/// A source of access tokens.
+pub trait AuthProvider: Send + Sync {
+    /// The token to send, or `None` to go unauthenticated. An error means
+    /// the source exists but failed, and stops the client from being built.
+    fn token(&self) -> Result<Option<String>, BoxError>;
+}
+
+impl<F> AuthProvider for F
+where
+    F: Fn() -> Result<Option<String>, BoxError> + Send + Sync,
+{
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        self()
+    }
+}
+
+/// The provider a [`GhsClientBuilder`](crate::GhsClientBuilder) was given,
+/// which may hold a secret and so is not printed.
+pub(crate) struct Source(pub(crate) Box<dyn AuthProvider>);
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthProvider(..)")
+    }
+}
+
+/// A token known up front, as [`GhsClientBuilder::token`](crate::GhsClientBuilder::token) takes it.
+#[derive(Clone)]
+pub struct StaticToken(pub String);
+
+impl AuthProvider for StaticToken {
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        Ok(Some(self.0.clone()))
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StaticToken(..)")
+    }
+}
+
+/// The first of these environment variables that is set and not empty.
+#[derive(Debug, Clone)]
+pub struct Env(pub Vec<String>);
+
+impl Env {
+    /// `GITHUB_ACCESS_TOKEN`, then `GH_TOKEN`, as the CLI reads them.
+    pub fn github() -> Env {
+        Env(vec!["GITHUB_ACCESS_TOKEN".into(), "GH_TOKEN".into()])
+    }
+}
+
+impl AuthProvider for Env {
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        Ok(self
+            .0
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|token| !token.trim().is_empty()))
+    }
+}
+
+/// The token the GitHub CLI is logged in with for a host: from its
+/// `hosts.yml`, or from `gh auth token` where gh keeps tokens in the
+/// system keyring instead.
+#[derive(Debug, Clone)]
+pub struct GhConfig {
+    host: String,
+}
+
+impl GhConfig {
+    pub fn new(host: impl Into<String>) -> GhConfig {
+        GhConfig { host: host.into() }
+    }
+
+    /// `$GH_CONFIG_DIR`, else `gh` under `$XDG_CONFIG_HOME` or `~/.config`.
+    fn hosts_file() -> Option<PathBuf> {
+        let dir = env::var_os("GH_CONFIG_DIR")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("XDG_CONFIG_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+                    .map(|dir| dir.join("gh"))
+            })?;
+        Some(dir.join("hosts.yml"))
+    }
+}
+
+impl AuthProvider for GhConfig {
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        let hosts = Self::hosts_file().and_then(|path| fs::read_to_string(path).ok());
+        let Some(hosts) = hosts else {
+            return Ok(None);
+        };
+        if let Some(token) = token_in_hosts(&hosts, &self.host) {
+            return Ok(Some(token));
+        }
+        // Logged in, but with the token in the keyring.
+        Command::new("gh", ["auth", "token", "--hostname", &self.host])
+            .token()
+            .or(Ok(None))
+    }
+}
+
+/// The `oauth_token` under `host` in a gh `hosts.yml`, e.g.
+///
+/// ```yaml
+/// github.com:
+///     user: octocat
+///     oauth_token: gho_...
+/// ```
+fn token_in_hosts(hosts: &str, host: &str) -> Option<String> {
+    let mut in_host = false;
+    for line in hosts.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            in_host = line.trim_end().strip_suffix(':') == Some(host);
+            continue;
+        }
+        if let Some(token) = line.trim().strip_prefix("oauth_token:") {
+            if in_host {
+                let token = token.trim().trim_matches(['"', '\'']);
+                return (!token.is_empty()).then(|| token.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// What a command prints, e.g. a password manager's or the system
+/// keyring's (see [`Command::keyring`]).
+#[derive(Debug, Clone)]
+pub struct Command {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Command {
+    pub fn new(
+        program: impl Into<String>,
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Command {
+        Command {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The password of `service` in the system keyring: the login keychain
+    /// through `security` on macOS, the Secret Service through
+    /// `secret-tool` elsewhere.
+    pub fn keyring(service: &str) -> Command {
+        if cfg!(target_os = "macos") {
+            Command::new("security", ["find-generic-password", "-s", service, "-w"])
+        } else {
+            Command::new("secret-tool", ["lookup", "service", service])
+        }
+    }
+}
+
+impl AuthProvider for Command {
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        let output = process::Command::new(&self.program)
+            .args(&self.args)
+            .output()
+            .map_err(|err| format!("could not run {}: {}", self.program, err))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed ({}): {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!token.is_empty()).then_some(token))
+    }
+}
+
+/// The first token any of the providers has, asking them in order.
+pub struct Chain(Vec<Box<dyn AuthProvider>>);
+
+impl Chain {
+    pub fn new(providers: Vec<Box<dyn AuthProvider>>) -> Chain {
+        Chain(providers)
+    }
+}
+
+impl AuthProvider for Chain {
+    fn token(&self) -> Result<Option<String>, BoxError> {
+        for provider in &self.0 {
+            if let Some(token) = provider.token()? {
+                return Ok(Some(token));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_token_of_the_host_in_gh_hosts() {
+        let hosts = "github.com:\n    user: octocat\n    oauth_token: gho_public\n\
+                     ghe.example.com:\n    oauth_token: \"gho_enterprise\"\n    git_protocol: ssh\n";
+        assert_eq!(
+            token_in_hosts(hosts, "github.com").as_deref(),
+            Some("gho_public")
+        );
+        assert_eq!(
+            token_in_hosts(hosts, "ghe.example.com").as_deref(),
+            Some("gho_enterprise")
+        );
+        assert_eq!(token_in_hosts(hosts, "gitlab.com"), None);
+        // Newer gh versions keep only the user here.
+        assert_eq!(
+            token_in_hosts("github.com:\n    user: octocat\n", "github.com"),
+            None
+        );
+    }
+
+    #[test]
+    fn chains_providers_in_order() {
+        let nothing = || Ok(None);
+        let chain = Chain::new(vec![
+            Box::new(nothing),
+            Box::new(StaticToken("first".into())),
+            Box::new(|| -> Result<Option<String>, BoxError> { Err("never asked".into()) }),
+        ]);
+        assert_eq!(chain.token().unwrap().as_deref(), Some("first"));
+
+        let failing = Chain::new(vec![Box::new(|| -> Result<Option<String>, BoxError> {
+            Err("vault is sealed".into())
+        })]);
+        assert_eq!(failing.token().unwrap_err().to_string(), "vault is sealed");
+    }
+}
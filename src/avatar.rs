@@ -0,0 +1,230 @@
+use crate::plain;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use ghs::{GhsClient, Repository};
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+/// Size of the image requested from GitHub, in pixels.
+const AVATAR_PIXELS: u32 = 128;
+
+/// Terminal cells the avatar is scaled to; roughly square in most fonts.
+const AVATAR_COLUMNS: u32 = 8;
+const AVATAR_ROWS: u32 = 4;
+
+/// Kitty caps each escape sequence's payload at 4096 bytes.
+const KITTY_CHUNK: usize = 4096;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Sixel has no notion of cells, so the avatar is drawn at a pixel size
+/// that covers about as many as the other protocols with 8x16 cells.
+const SIXEL_PIXELS: u32 = 64;
+
+/// The inline image protocols ghs can speak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+}
+
+/// The protocol the terminal on stdout understands, judged from the
+/// environment variables these terminals set.
+fn detect() -> Option<Protocol> {
+    if plain::enabled() || !io::stdout().is_terminal() {
+        return None;
+    }
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    if !var("KITTY_WINDOW_ID").is_empty() || var("TERM") == "xterm-kitty" {
+        Some(Protocol::Kitty)
+    } else if matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm")
+        || var("LC_TERMINAL") == "iTerm2"
+    {
+        Some(Protocol::Iterm)
+    } else if var("TERM").contains("sixel")
+        || ["foot", "mlterm", "yaft"]
+            .iter()
+            .any(|term| var("TERM").starts_with(term))
+        || !var("MLTERM").is_empty()
+    {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// The escape sequence that draws `image`, or `None` if the protocol cannot
+/// show it (kitty only takes PNG without decoding; sixel takes whatever
+/// decodes).
+fn encode(protocol: Protocol, image: &[u8]) -> Option<String> {
+    if protocol == Protocol::Sixel {
+        return sixel(image);
+    }
+    let data = STANDARD.encode(image);
+    match protocol {
+        Protocol::Iterm => Some(format!(
+            "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+            image.len(),
+            AVATAR_COLUMNS,
+            AVATAR_ROWS,
+            data
+        )),
+        Protocol::Kitty if image.starts_with(PNG_SIGNATURE) => {
+            let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut out = String::new();
+            for (index, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(index + 1 < chunks.len());
+                let chunk = std::str::from_utf8(chunk).ok()?;
+                if index == 0 {
+                    out.push_str(&format!(
+                        "\x1b_Ga=T,f=100,c={},r={},m={};{}\x1b\\",
+                        AVATAR_COLUMNS, AVATAR_ROWS, more, chunk
+                    ));
+                } else {
+                    out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                }
+            }
+            Some(out)
+        }
+        Protocol::Kitty | Protocol::Sixel => None,
+    }
+}
+
+/// `image` as sixel: decoded, scaled to [`SIXEL_PIXELS`] square and mapped
+/// onto a 6x6x6 colour cube. Mostly transparent pixels are left unpainted.
+fn sixel(image: &[u8]) -> Option<String> {
+    let image = image::load_from_memory(image).ok()?.resize_exact(
+        SIXEL_PIXELS,
+        SIXEL_PIXELS,
+        image::imageops::FilterType::Triangle,
+    );
+    let image = image.to_rgba8();
+    let level = |channel: u8| (u16::from(channel) * 5 + 127) / 255;
+    let colour = |x: u32, y: u32| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    // P2 = 1: pixels no colour paints keep the background.
+    let mut out = String::from("\x1bP0;1;0q");
+    out.push_str(&format!("\"1;1;{};{}", image.width(), image.height()));
+    for index in 0..216u16 {
+        let percent = |level: u16| level * 20;
+        out.push_str(&format!(
+            "#{};2;{};{};{}",
+            index,
+            percent(index / 36),
+            percent(index / 6 % 6),
+            percent(index % 6)
+        ));
+    }
+    for band in (0..image.height()).step_by(6) {
+        let rows = band..(band + 6).min(image.height());
+        let mut used: Vec<u16> = rows
+            .clone()
+            .flat_map(|y| (0..image.width()).filter_map(move |x| colour(x, y)))
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+        for index in used {
+            out.push_str(&format!("#{}", index));
+            let sixels: Vec<u8> = (0..image.width())
+                .map(|x| {
+                    let bits = rows
+                        .clone()
+                        .filter(|&y| colour(x, y) == Some(index))
+                        .fold(0, |bits, y| bits | 1 << (y - band));
+                    63 + bits
+                })
+                .collect();
+            push_runs(&mut out, &sixels);
+            // Back to the start of the band for the next colour.
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    Some(out)
+}
+
+/// Appends `sixels`, with runs of four or more as `!<count><sixel>`.
+fn push_runs(out: &mut String, sixels: &[u8]) {
+    let mut rest = sixels;
+    while let Some(&sixel) = rest.first() {
+        let run = rest.iter().take_while(|&&next| next == sixel).count();
+        let sixel = char::from(sixel);
+        if run >= 4 {
+            out.push_str(&format!("!{}{}", run, sixel));
+        } else {
+            out.extend(std::iter::repeat_n(sixel, run));
+        }
+        rest = &rest[run..];
+    }
+}
+
+/// Draws the owner's avatar above the detail card when the terminal can
+/// show images. Any failure (no protocol, no avatar, a download error, an
+/// unsupported format) just leaves the card without it.
+///
+/// Only `repo view` draws it: the TUI browses a saved session without going
+/// to the network, and redraws the whole screen on every key.
+pub async fn print_owner_avatar(api: &GhsClient, repo: &Repository) {
+    let Some(protocol) = detect() else {
+        return;
+    };
+    let Some(avatar_url) = repo
+        .owner
+        .as_ref()
+        .and_then(|owner| owner.avatar_url.as_deref())
+    else {
+        return;
+    };
+    let Ok(image) = api.avatar(avatar_url, AVATAR_PIXELS).await else {
+        return;
+    };
+    if let Some(sequence) = encode(protocol, &image) {
+        let mut stdout = io::stdout().lock();
+        let _ = writeln!(stdout, "{}", sequence);
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    fn png(image: &RgbaImage) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn run_lengths_from_four_on() {
+        let mut out = String::new();
+        push_runs(&mut out, b"???@@@@@A");
+        assert_eq!(out, "???!5@A");
+    }
+
+    #[test]
+    fn sixel_paints_each_colour_and_skips_transparency() {
+        let red = Rgba([255, 0, 0, 255]);
+        let sequence = encode(Protocol::Sixel, &png(&RgbaImage::from_pixel(4, 4, red))).unwrap();
+        assert!(sequence.starts_with("\x1bP0;1;0q\"1;1;64;64"));
+        assert!(sequence.ends_with("\x1b\\"));
+        // Red is 5 * 36 in the cube: every band paints it across the row.
+        assert!(sequence.contains("#180;2;100;0;0"));
+        assert_eq!(sequence.matches("#180!64~$").count(), 10);
+        assert!(sequence.contains("#180!64N$-"));
+
+        let clear = RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 0]));
+        let sequence = encode(Protocol::Sixel, &png(&clear)).unwrap();
+        assert!(!sequence.contains('$'));
+        assert_eq!(sequence.matches('-').count(), 11);
+
+        assert_eq!(encode(Protocol::Sixel, b"not an image"), None);
+    }
+}
@@ -0,0 +1,57 @@
+//! `ghs bench`: how long searches take end to end, cache and retries
+//! included, so that changes to either can be measured.
+
+use crate::table;
+use ghs::stats::{format_duration, percentile};
+use std::time::Duration;
+
+/// Searches of the shapes people run most: one user's repositories, a
+/// topic, and a popular language with a star range.
+pub const QUERIES: [&str; 3] = [
+    "user:shinshin86",
+    "topic:cli language:rust",
+    "language:go stars:>1000",
+];
+
+/// How long every run of one query took, and how many requests the runs
+/// sent between them.
+pub struct Sample {
+    pub query: String,
+    pub durations: Vec<Duration>,
+    pub requests: usize,
+}
+
+/// A table of each query's latency percentiles, with a last row over all
+/// of them.
+pub fn report(samples: &[Sample]) -> String {
+    let mut rows = vec![["Query", "Runs", "Requests", "p50", "p95", "Max"]
+        .iter()
+        .map(|cell| cell.to_string())
+        .collect()];
+    let row = |query: &str, durations: &[Duration], requests: usize| {
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        vec![
+            query.to_string(),
+            sorted.len().to_string(),
+            requests.to_string(),
+            format_duration(percentile(&sorted, 50)),
+            format_duration(percentile(&sorted, 95)),
+            format_duration(sorted[sorted.len() - 1]),
+        ]
+    };
+    rows.extend(
+        samples
+            .iter()
+            .map(|sample| row(&sample.query, &sample.durations, sample.requests)),
+    );
+    if samples.len() > 1 {
+        let durations: Vec<Duration> = samples
+            .iter()
+            .flat_map(|sample| sample.durations.iter().copied())
+            .collect();
+        let requests = samples.iter().map(|sample| sample.requests).sum();
+        rows.push(row("(all)", &durations, requests));
+    }
+    table::render(&rows)
+}
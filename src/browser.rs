@@ -0,0 +1,42 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Opens `url` in the default browser.
+pub fn open(url: &str) -> io::Result<()> {
+    let mut command = opener(url);
+    let status = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "could not open {} ({})",
+            url, status
+        )))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("open");
+    command.arg(url);
+    command
+}
+
+/// `cmd /C start` would treat the `&` in query strings as a command
+/// separator, so the URL handler is invoked directly instead.
+#[cfg(windows)]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("rundll32");
+    command.args(["url.dll,FileProtocolHandler", url]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(url);
+    command
+}
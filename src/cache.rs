@@ -0,0 +1,310 @@
+use ghs::{datetime, query, BoxError, Repositories};
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+/// How long cached search results are served before GitHub is asked again.
+const TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Search results stored by the normalized query, so that equivalent
+/// searches share an entry. The store can be a local directory, a shared
+/// network path or an HTTP bucket, letting a team share one warm cache.
+pub struct Cache {
+    store: Store,
+    /// Whether stale entries are served as they are, without revalidating.
+    offline: bool,
+    /// A hash of the tokens searched with, if any; see [`Cache::searching_with`].
+    identity: Option<String>,
+}
+
+enum Store {
+    Dir(PathBuf),
+    /// Objects are read and written with plain GET and PUT, e.g. on an
+    /// S3-compatible bucket that allows it; `GHS_CACHE_TOKEN`, if set, is
+    /// sent as a bearer token.
+    Bucket {
+        client: reqwest::Client,
+        url: Url,
+        token: Option<String>,
+    },
+}
+
+/// A cached search. Stale entries are still useful: when they carry GitHub's
+/// ETag they can be revalidated with a conditional request.
+#[derive(Serialize, Deserialize)]
+pub struct Entry<R = Repositories> {
+    key: String,
+    pub fetched_at: i64,
+    #[serde(default)]
+    pub etag: Option<String>,
+    pub repositories: R,
+}
+
+impl<R> Entry<R> {
+    pub fn is_fresh(&self) -> bool {
+        datetime::now().saturating_sub(self.fetched_at) < TTL.as_secs() as i64
+    }
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache {
+            store: Store::Dir(dir.into()),
+            offline: false,
+            identity: None,
+        }
+    }
+
+    /// A cache at `location`: a directory path, a `file://` URL or an
+    /// `http(s)://` bucket URL.
+    pub fn from_url(location: &str) -> Result<Self, BoxError> {
+        if !location.contains("://") {
+            return Ok(Cache::new(location));
+        }
+
+        let mut url = Url::parse(location)?;
+        match url.scheme() {
+            "file" => {
+                let dir = url
+                    .to_file_path()
+                    .map_err(|()| format!("`{}` is not a local path", location))?;
+                Ok(Cache::new(dir))
+            }
+            "http" | "https" => {
+                if !url.path().ends_with('/') {
+                    url.set_path(&format!("{}/", url.path()));
+                }
+                Ok(Cache {
+                    store: Store::Bucket {
+                        client: reqwest::Client::new(),
+                        url,
+                        token: env::var("GHS_CACHE_TOKEN").ok(),
+                    },
+                    offline: false,
+                    identity: None,
+                })
+            }
+            scheme => Err(format!("unsupported cache URL scheme `{}`", scheme).into()),
+        }
+    }
+
+    /// Serves entries however old they are, for `--offline`. Only a local
+    /// store can be read without the network.
+    pub fn offline(self) -> Result<Self, BoxError> {
+        match self.store {
+            Store::Dir(_) => Ok(Cache {
+                offline: true,
+                ..self
+            }),
+            Store::Bucket { .. } => Err("--offline needs a local cache, not a bucket".into()),
+        }
+    }
+
+    /// Keeps the results of searches made with `tokens` apart from everyone
+    /// else's: an authenticated search can see private repositories, which
+    /// must not be served to another account sharing the store. Only a hash
+    /// of the tokens goes into the key.
+    pub fn searching_with(self, tokens: &[Option<String>]) -> Self {
+        let tokens: Vec<&str> = tokens.iter().flatten().map(String::as_str).collect();
+        let identity = (!tokens.is_empty()).then(|| {
+            let hash = format!("{:x}", Sha256::digest(tokens.join("\n").as_bytes()));
+            hash[..16].to_string()
+        });
+        Cache { identity, ..self }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// `$XDG_CACHE_HOME/ghs`, falling back to `~/.cache/ghs`.
+    pub fn default_dir() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|dir| dir.join("ghs"))
+    }
+
+    /// The cached results for the search, fresh or not. An unreadable store
+    /// counts as a miss.
+    pub async fn get(&self, search_query: &str, per_page: u8, all: bool) -> Option<Entry> {
+        let key = key(search_query, per_page, all, self.identity.as_deref());
+        let contents = match &self.store {
+            Store::Dir(dir) => fs::read(dir.join(file_name(&key))).ok()?,
+            Store::Bucket { client, url, token } => {
+                let mut request = client.get(url.join(&file_name(&key)).ok()?);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let response = request.send().await.ok()?;
+                if response.status() != StatusCode::OK {
+                    return None;
+                }
+                response.bytes().await.ok()?.to_vec()
+            }
+        };
+        let entry: Entry = serde_json::from_slice(&contents).ok()?;
+        (entry.key == key).then_some(entry)
+    }
+
+    pub async fn put(
+        &self,
+        search_query: &str,
+        per_page: u8,
+        all: bool,
+        etag: Option<String>,
+        repositories: &Repositories,
+    ) -> Result<(), BoxError> {
+        let key = key(search_query, per_page, all, self.identity.as_deref());
+        let name = file_name(&key);
+        let contents = serde_json::to_vec(&Entry {
+            key,
+            fetched_at: datetime::now(),
+            etag,
+            repositories,
+        })?;
+
+        match &self.store {
+            Store::Dir(dir) => {
+                let path = dir.join(name);
+                fs::create_dir_all(dir)
+                    .and_then(|()| fs::write(&path, contents))
+                    .map_err(|err| {
+                        format!("could not write cache entry {}: {}", path.display(), err)
+                    })?;
+            }
+            Store::Bucket { client, url, token } => {
+                let url = url.join(&name)?;
+                let mut request = client
+                    .put(url.clone())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(contents);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let status = request.send().await?.status();
+                if !status.is_success() {
+                    return Err(format!("could not write cache entry {}: {}", url, status).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every cached search and returns how many there were. Other
+    /// state kept in the same directory, such as the update check's, stays.
+    pub fn clear(&self) -> Result<(usize, String), BoxError> {
+        let Store::Dir(dir) = &self.store else {
+            return Err(
+                "a bucket cache cannot be cleared from here; remove its objects instead".into(),
+            );
+        };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((0, dir.display().to_string()))
+            }
+            Err(err) => return Err(format!("could not read {}: {}", dir.display(), err).into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            let is_entry = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+                .is_some_and(|hash| {
+                    hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+                });
+            if is_entry {
+                fs::remove_file(&path)
+                    .map_err(|err| format!("could not remove {}: {}", path.display(), err))?;
+                removed += 1;
+            }
+        }
+        Ok((removed, dir.display().to_string()))
+    }
+}
+
+/// What was fetched and with whose token, not how it is filtered
+/// afterwards: client-side filters run on the cached results.
+fn key(search_query: &str, per_page: u8, all: bool, identity: Option<&str>) -> String {
+    let mut key = if all {
+        format!("{} all", query::normalize(search_query))
+    } else {
+        format!("{} per_page={}", query::normalize(search_query), per_page)
+    };
+    if let Some(identity) = identity {
+        key.push_str(&format!(" token={}", identity));
+    }
+    key
+}
+
+/// Named by a hash that is stable across builds and platforms, so a shared
+/// store works for everyone using it.
+fn file_name(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}.json", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equivalent_searches_share_a_key() {
+        assert_eq!(
+            key("language:Rust cli user:foo user:foo", 30, false, None),
+            key("user:foo  CLI language:rust", 30, false, None)
+        );
+        assert_eq!(key("user:foo cli", 30, true, None), "cli user:foo all");
+        assert_eq!(
+            key("user:foo cli", 50, false, None),
+            "cli user:foo per_page=50"
+        );
+    }
+
+    #[test]
+    fn different_searches_do_not() {
+        let key = |query: &str, per_page: u8, all: bool| key(query, per_page, all, None);
+        assert_ne!(
+            key("rust NOT cli", 30, false),
+            key("cli NOT rust", 30, false)
+        );
+        assert_ne!(
+            key("rust NOT cli", 30, false),
+            key("rust not cli", 30, false)
+        );
+        assert_ne!(key("cli", 30, false), key("cli", 100, false));
+        assert_ne!(key("cli", 100, false), key("cli", 100, true));
+        assert_ne!(
+            file_name(&key("rust NOT cli", 30, true)),
+            file_name(&key("cli NOT rust", 30, true))
+        );
+    }
+
+    #[test]
+    fn each_token_gets_entries_of_its_own() {
+        let identity = |tokens: &[Option<&str>]| {
+            let tokens: Vec<Option<String>> = tokens.iter().map(|t| t.map(String::from)).collect();
+            Cache::new("unused").searching_with(&tokens).identity
+        };
+        assert_eq!(identity(&[None, None]), None);
+        let work = identity(&[Some("work-token")]).unwrap();
+        assert_eq!(work.len(), 16);
+        assert!(!work.contains("work-token"));
+        assert_ne!(identity(&[Some("personal-token")]).unwrap(), work);
+        assert_eq!(identity(&[None, Some("work-token")]).unwrap(), work);
+
+        let anonymous = key("user:foo", 30, false, None);
+        let authenticated = key("user:foo", 30, false, Some(&work));
+        assert_eq!(authenticated, format!("{} token={}", anonymous, work));
+    }
+}
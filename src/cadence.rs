@@ -0,0 +1,116 @@
+use crate::circuit::CircuitBreaker;
+use crate::table;
+use ghs::{datetime, BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories' releases may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// How regularly a repository ships releases.
+#[derive(Debug)]
+pub struct Cadence {
+    pub releases: usize,
+    /// Average over the time since the first release, counted as at least a
+    /// year so a couple of recent releases do not look like a torrent.
+    pub per_year: f64,
+    pub days_since_last: Option<i64>,
+}
+
+impl Cadence {
+    /// Computes the cadence from release timestamps; drafts have none and
+    /// are skipped.
+    pub fn from_timestamps(published: &[&str], now: i64) -> Cadence {
+        let times: Vec<i64> = published
+            .iter()
+            .filter_map(|timestamp| datetime::parse_timestamp(timestamp))
+            .collect();
+        let first = times.iter().copied().min();
+        let last = times.iter().copied().max();
+        let years = first.map_or(1.0, |first| {
+            ((now - first) as f64 / (365.25 * SECONDS_PER_DAY as f64)).max(1.0)
+        });
+
+        Cadence {
+            releases: times.len(),
+            per_year: times.len() as f64 / years,
+            days_since_last: last.map(|last| (now - last).div_euclid(SECONDS_PER_DAY)),
+        }
+    }
+}
+
+/// Fetches every repository's releases concurrently and computes their
+/// cadence, in the order given. Failed lookups are `None`.
+pub async fn fetch_cadences(
+    api: &GhsClient,
+    repositories: &[Repository],
+) -> Result<Vec<(String, Option<Cadence>)>, BoxError> {
+    let now = datetime::now();
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for (index, repo) in repositories.iter().enumerate() {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let releases = breaker
+                .call(
+                    &format!("releases of {}", full_name),
+                    api.releases(&full_name),
+                )
+                .await?;
+            let cadence = releases.map(|releases| {
+                let published: Vec<&str> = releases
+                    .iter()
+                    .filter_map(|release| release.published_at.as_deref())
+                    .collect();
+                Cadence::from_timestamps(&published, now)
+            });
+            Ok::<_, BoxError>((index, full_name, cadence))
+        });
+    }
+
+    let mut cadences = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        cadences.push(result??);
+    }
+    cadences.sort_by_key(|(index, _, _)| *index);
+    Ok(cadences
+        .into_iter()
+        .map(|(_, full_name, cadence)| (full_name, cadence))
+        .collect())
+}
+
+pub fn report(cadences: &[(String, Option<Cadence>)]) -> String {
+    let mut rows = vec![vec![
+        "Repository".to_string(),
+        "Releases".to_string(),
+        "Per year".to_string(),
+        "Days since last".to_string(),
+    ]];
+    for (full_name, cadence) in cadences {
+        rows.push(match cadence {
+            Some(cadence) => vec![
+                full_name.clone(),
+                cadence.releases.to_string(),
+                format!("{:.1}", cadence.per_year),
+                cadence
+                    .days_since_last
+                    .map_or("-".to_string(), |days| days.to_string()),
+            ],
+            None => vec![
+                full_name.clone(),
+                "?".to_string(),
+                "?".to_string(),
+                "?".to_string(),
+            ],
+        });
+    }
+    format!("Release cadence:\n{}", table::render(&rows))
+}
@@ -0,0 +1,71 @@
+//! Stopping long searches early, e.g. on Ctrl-C. A client given a
+//! [`Cancel`] with [`GhsClientBuilder::cancel`](crate::GhsClientBuilder::cancel)
+//! stops paging once it is cancelled and returns what it has fetched, with
+//! [`incomplete_results`](crate::Repositories::incomplete_results) set.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// A cancellation flag shared by clones; cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl Cancel {
+    pub fn new() -> Cancel {
+        Cancel::default()
+    }
+
+    /// Cancels, waking everything waiting in [`cancelled`](Self::cancelled).
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once cancelled, at once if that already happened.
+    pub async fn cancelled(&self) {
+        let notified = self.0.notify.notified();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn wakes_waiters_and_later_callers() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let cancel = Cancel::new();
+            let waiter = tokio::spawn({
+                let cancel = cancel.clone();
+                async move { cancel.cancelled().await }
+            });
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            assert!(!cancel.is_cancelled());
+            cancel.cancel();
+            waiter.await.unwrap();
+            // Already cancelled: no waiting.
+            cancel.cancelled().await;
+            assert!(cancel.is_cancelled());
+        });
+    }
+}
@@ -0,0 +1,29 @@
+use crate::readme::GrepProgress;
+use ghs::BoxError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub const DEFAULT_PATH: &str = "ghs-checkpoint.json";
+
+/// State of an interrupted `--grep-readme` run, written on Ctrl-C so that
+/// `--resume` can skip the READMEs that were already fetched.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub query: String,
+    pub grep_term: String,
+    pub progress: GrepProgress,
+}
+
+impl Checkpoint {
+    pub fn load(path: &str) -> Result<Checkpoint, BoxError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("could not read checkpoint {}: {}", path, err))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), BoxError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|err| format!("could not write checkpoint {}: {}", path, err))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::circuit::CircuitBreaker;
+use crate::color;
+use crate::plain;
+use crate::table;
+use ghs::model::WorkflowRun;
+use ghs::{BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories' runs may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+/// How a repository's mainline is doing, worst first so broken builds sort
+/// to the top of the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    Failing,
+    Running,
+    Passing,
+    NoRuns,
+    Unknown,
+}
+
+impl Health {
+    fn of(run: Option<&WorkflowRun>) -> Health {
+        let Some(run) = run else {
+            return Health::NoRuns;
+        };
+        if run.status.as_deref() != Some("completed") {
+            return Health::Running;
+        }
+        match run.conclusion.as_deref() {
+            Some("success" | "neutral" | "skipped") => Health::Passing,
+            _ => Health::Failing,
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Health::Failing => color::RED,
+            Health::Running => color::YELLOW,
+            Health::Passing => color::GREEN,
+            Health::NoRuns | Health::Unknown => color::DIM,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BranchStatus {
+    pub full_name: String,
+    pub branch: String,
+    /// `None` if there were no runs or the lookup failed; see `health`.
+    pub run: Option<WorkflowRun>,
+    pub health: Health,
+}
+
+/// Fetches the latest workflow run on `branch` (or on each repository's
+/// default branch) and returns the statuses failing first.
+pub async fn fetch_statuses(
+    api: &GhsClient,
+    repositories: &[Repository],
+    branch: Option<&str>,
+) -> Result<Vec<BranchStatus>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let Some(branch) = branch
+            .map(str::to_string)
+            .or_else(|| repo.default_branch.clone())
+        else {
+            continue;
+        };
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let run = breaker
+                .call(
+                    &format!("workflow runs of {}:{}", full_name, branch),
+                    api.latest_workflow_run(&full_name, &branch),
+                )
+                .await?;
+            let (run, health) = match run {
+                Some(run) => {
+                    let health = Health::of(run.as_ref());
+                    (run, health)
+                }
+                None => (None, Health::Unknown),
+            };
+            Ok::<_, BoxError>(BranchStatus {
+                full_name,
+                branch,
+                run,
+                health,
+            })
+        });
+    }
+
+    let mut statuses = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        statuses.push(result??);
+    }
+    statuses.sort_by(|a, b| {
+        a.health
+            .cmp(&b.health)
+            .then_with(|| a.full_name.cmp(&b.full_name))
+    });
+    Ok(statuses)
+}
+
+pub fn print_dashboard(statuses: &[BranchStatus]) {
+    let mut rows = vec![vec![
+        "Status".to_string(),
+        "Repository".to_string(),
+        "Branch".to_string(),
+        "Workflow".to_string(),
+        "Updated".to_string(),
+        "Link".to_string(),
+    ]];
+    for status in statuses {
+        let label = match (&status.run, status.health) {
+            (_, Health::NoRuns) => "no runs".to_string(),
+            (_, Health::Unknown) => "?".to_string(),
+            (Some(run), Health::Running) => run.status.clone().unwrap_or_default(),
+            (Some(run), _) => run.conclusion.clone().unwrap_or_default(),
+            (None, _) => String::new(),
+        };
+        let run = status.run.as_ref();
+        rows.push(vec![
+            if plain::enabled() {
+                label
+            } else {
+                color::paint(&format!("● {}", label), status.health.color())
+            },
+            status.full_name.clone(),
+            status.branch.clone(),
+            run.and_then(|run| run.name.clone()).unwrap_or_default(),
+            run.and_then(|run| run.updated_at.clone())
+                .unwrap_or_default(),
+            run.map(|run| run.html_url.clone()).unwrap_or_default(),
+        ]);
+    }
+    print!("{}", table::render(&rows));
+
+    let count = |health: Health| statuses.iter().filter(|s| s.health == health).count();
+    println!(
+        "{} passing, {} failing, {} running, {} without runs",
+        count(Health::Passing),
+        count(Health::Failing),
+        count(Health::Running),
+        count(Health::NoRuns)
+    );
+}
@@ -0,0 +1,69 @@
+use ghs::BoxError;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// How many enrichment requests may fail in a row before the rest are skipped.
+const THRESHOLD: u32 = 5;
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Makes every breaker fail on the first error for the rest of the run,
+/// for `--strict`, instead of warning and carrying on without the data.
+pub fn enable_strict() {
+    STRICT.store(true, Ordering::Relaxed);
+}
+
+/// Stops a batch of per-repository enrichment requests once they keep
+/// failing (abuse detection, an outage, a revoked token), so the command
+/// degrades to the data it already has instead of hammering the API and
+/// printing one error per repository.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    consecutive_failures: AtomicU32,
+    open: AtomicBool,
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        CircuitBreaker::default()
+    }
+
+    /// Whether the breaker has tripped; once open it stays open.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    /// Runs `request` unless the breaker is open. Failures are reported as
+    /// warnings and yield `None`, as does a skipped request; with
+    /// [`enable_strict`] they are returned instead.
+    pub async fn call<T>(
+        &self,
+        what: &str,
+        request: impl Future<Output = Result<T, BoxError>>,
+    ) -> Result<Option<T>, BoxError> {
+        if self.is_open() {
+            return Ok(None);
+        }
+
+        match request.await {
+            Ok(value) => {
+                self.consecutive_failures.store(0, Ordering::SeqCst);
+                Ok(Some(value))
+            }
+            // The error names the URL, and keeps its type for the exit status.
+            Err(err) if STRICT.load(Ordering::Relaxed) => Err(err),
+            Err(err) => {
+                let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+                if failures < THRESHOLD {
+                    eprintln!("warning: {}: {}", what, err);
+                } else if !self.open.swap(true, Ordering::SeqCst) {
+                    eprintln!(
+                        "warning: {}: {}\nwarning: {} requests failed in a row; skipping the rest and showing what was fetched so far",
+                        what, err, failures
+                    );
+                }
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,1375 @@
+use crate::predicate::Predicate;
+use crate::{
+    checkpoint, edit, langdetect, leaderboard, output, placeholder, rank, redact, sort, store,
+    transform, trending,
+};
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use ghs::filters::MatchMode;
+use ghs::locale::Locale;
+use std::env;
+use std::ffi::OsString;
+
+/// The `ghs` command, with every subcommand.
+pub fn command() -> Command {
+    Command::new("GitHub Repository Search")
+        .subcommand_negates_reqs(true)
+        .args_override_self(true)
+        .arg(
+            Arg::new("username")
+                .short('u')
+                .long("username")
+                .value_name("USERNAME")
+                .help("GitHub username [default: the repository of the current directory's origin remote, unless --org or QUERY is given]"),
+        )
+        .arg(
+            Arg::new("org")
+                .long("org")
+                .value_name("ORG")
+                .conflicts_with("username")
+                .help("Search the repositories of this organization"),
+        )
+        .arg(
+            Arg::new("query")
+                .value_name("QUERY")
+                .num_args(1..)
+                .help("Search terms and qualifiers, e.g. 'cli language:rust', combined with --username or --org"),
+        )
+        .arg(
+            Arg::new("mine-and-orgs")
+                .long("mine-and-orgs")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["username", "org"])
+                .help("Search your repositories and those of every organization you belong to (looked up once a day)"),
+        )
+        .arg(
+            Arg::new("queries")
+                .long("query")
+                .value_name("QUERY")
+                .action(ArgAction::Append)
+                .conflicts_with_all(["username", "org", "query", "mine-and-orgs"])
+                .help("Run this complete search query; repeat it to run several concurrently, with each result tagged by the queries that found it"),
+        )
+        .arg(
+            Arg::new("repositories")
+                .short('r')
+                .long("repositories")
+                .value_name("REPOSITORIES")
+                .help("Filter by the specified repository name"),
+        )
+        .args(filter_args())
+        .arg(
+            Arg::new("rank")
+                .long("rank")
+                .value_name("EXPR")
+                .value_parser(rank::parse_rank)
+                .help(
+                    "Sort by a computed score, e.g. \"stars*0.6 + recency*0.4\", \
+                     or a preset: popular, active, fresh",
+                ),
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("KEY")
+                .value_parser(sort::SortKey::parse)
+                .conflicts_with("rank")
+                .help("Order results by stars, forks, updated, name, --score or smart (the searched-for language and active, original repositories first, weighted by the config's [sort.smart] section) [default: the config's `sort`, else search relevance]"),
+        )
+        .arg(
+            Arg::new("score")
+                .long("score")
+                .value_name("KIND")
+                .value_parser(["contrib"])
+                .help("Score each result from 0 to 100 and sort by the score unless --sort or --rank is given; contrib weighs open help wanted and good first issues, recent pushes and how soon issues get a first response, at a few requests per repository"),
+        )
+        .arg(
+            Arg::new("desc")
+                .long("desc")
+                .action(ArgAction::SetTrue)
+                .requires("sort")
+                .help("Sort in descending order [default for stars, forks and updated]"),
+        )
+        .arg(
+            Arg::new("asc")
+                .long("asc")
+                .action(ArgAction::SetTrue)
+                .requires("sort")
+                .conflicts_with("desc")
+                .help("Sort in ascending order [default for name]"),
+        )
+        .arg(
+            Arg::new("pipeline")
+                .long("pipeline")
+                .value_name("NAME")
+                .conflicts_with_all(["sort", "rank", "limit", "random", "format", "output", "heatmap"])
+                .help("Post-process the results with the [pipeline.NAME] stages of the config file"),
+        )
+        .arg(
+            Arg::new("with-activity")
+                .long("with-activity")
+                .action(ArgAction::SetTrue)
+                .help("Show a 52-week commit activity sparkline for each repository"),
+        )
+        .arg(
+            Arg::new("annotate-starred")
+                .long("annotate-starred")
+                .action(ArgAction::SetTrue)
+                .help("Mark the results you have already starred; needs a token"),
+        )
+        .arg(
+            Arg::new("heatmap")
+                .long("heatmap")
+                .action(ArgAction::SetTrue)
+                .help("Show a histogram of results by last push age instead of listing them"),
+        )
+        .args(output_args().map(|arg| arg.conflicts_with_all(["heatmap", "with-activity"])))
+        .arg(
+            Arg::new("redact")
+                .long("redact")
+                .value_name("FIELDS")
+                .value_parser(redact::Field::parse)
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+                .help("Hide owner, name, description or urls in the results, e.g. to share an audit outside the organization; owners and names become stable pseudonyms"),
+        )
+        .arg(
+            Arg::new("null-placeholder")
+                .long("null-placeholder")
+                .value_name("FIELD=TEXT")
+                .value_parser(placeholder::parse)
+                .action(ArgAction::Append)
+                .help("Print TEXT for a missing description or language, e.g. description=- or language= for nothing [default: \"No description\" and \"No language specified\" in the listing, nothing in other formats]"),
+        )
+        .arg(
+            Arg::new("transform")
+                .long("transform")
+                .value_name("FIELD=EXPR")
+                .value_parser(transform::parse)
+                .action(ArgAction::Append)
+                .help("Print FIELD as EXPR in every format, e.g. 'description=truncate(description, 80)', 'language=upper(language)' or 'pushed_at=date(pushed_at, \"%Y-%m-%d\")'; overrides the config's [transform] section"),
+        )
+        .arg(
+            Arg::new("grep-readme")
+                .long("grep-readme")
+                .value_name("TERM")
+                .help("Keep only repositories whose README contains the term"),
+        )
+        .arg(
+            Arg::new("grep-context")
+                .long("grep-context")
+                .value_name("LINES")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .help("Lines of README context to show around each match"),
+        )
+        .arg(
+            Arg::new("checkpoint")
+                .long("checkpoint")
+                .value_name("PATH")
+                .default_value(checkpoint::DEFAULT_PATH)
+                .help("Where to write the resume checkpoint when interrupted with Ctrl-C"),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .value_name("PATH")
+                .requires("grep-readme")
+                .help("Continue an interrupted --grep-readme run from its checkpoint"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("Fetch every page of results instead of just the first"),
+        )
+        .arg(
+            Arg::new("max-results")
+                .long("max-results")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Fetch pages until N results are in hand, then stop (implies --all)"),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Show at most N repositories"),
+        )
+        .arg(
+            Arg::new("random")
+                .long("random")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .num_args(0..=1)
+                .default_missing_value("1")
+                .help("Show N repositories picked at random from the results [default: 1]"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .value_parser(clap::value_parser!(u64))
+                .requires("random")
+                .help("Seed for --random, to repeat the same picks"),
+        )
+        .arg(
+            Arg::new("open")
+                .long("open")
+                .action(ArgAction::SetTrue)
+                .help("Open the results in the browser"),
+        )
+        .arg(
+            Arg::new("per-page")
+                .long("per-page")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u8).range(1..=100))
+                .help("Number of results to request per page (1-100)"),
+        )
+        .arg(
+            Arg::new("tui")
+                .long("tui")
+                .action(ArgAction::SetTrue)
+                .help("Browse the results in a full-screen terminal UI"),
+        )
+        .arg(
+            Arg::new("interactive")
+                .long("interactive")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("tui")
+                .help("Pick a repository: type to fuzzy-filter, Enter to open it, c to clone it"),
+        )
+        .arg(
+            Arg::new("session")
+                .long("session")
+                .value_name("NAME")
+                .requires("tui")
+                .help("Save the TUI session under NAME, to resume with `ghs tui --session NAME`"),
+        )
+        .arg(
+            Arg::new("interactive-pages")
+                .long("interactive-pages")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "tui", "interactive", "refine", "exec", "open", "heatmap", "format", "output",
+                    "all", "max-results", "limit", "sort", "rank", "random", "score", "pipeline",
+                    "grep-readme", "with-activity", "annotate-starred",
+                ])
+                .help("Print the results a page at a time, fetching each when asked for, with a prompt to go to the next or previous page or open a result by its number"),
+        )
+        .arg(
+            Arg::new("refine")
+                .long("refine")
+                .action(ArgAction::SetTrue)
+                .help("Interactively narrow the fetched results before printing or exporting"),
+        )
+        .arg(
+            Arg::new("exec")
+                .long("exec")
+                .value_name("TEMPLATE")
+                .help("Run a shell command per result, e.g. 'git clone {ssh_url}' or 'echo {upper(name)}'; values are quoted for the shell, though on Windows cmd still expands %NAME% in them"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .requires("exec")
+                .help("How many --exec commands to run at once"),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .help("Do not ask for confirmation before opening several repositories or running --exec for more than the config's bulk_confirm_threshold [default: 5]"),
+        )
+        .arg(
+            Arg::new("plain")
+                .long("plain")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("Plain output for screen readers and dumb terminals: no colors, graphics or progress, one record per line"),
+        )
+        .arg(
+            Arg::new("locale")
+                .long("locale")
+                .value_name("LOCALE")
+                .value_parser(Locale::parse)
+                .global(true)
+                .help("Write counts and dates in the listing and tables for LOCALE, e.g. ja-JP for 1.2万 and 2026年10月14日, or C for plain digits and ISO dates; JSON and CSV are never localized [default: from LC_ALL or LANG]"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .global(true)
+                .help("Use the token and default flags of a [profile.NAME] section of the config file [default: $GHS_PROFILE, or the config's `profile`]"),
+        )
+        .arg(
+            Arg::new("preset")
+                .long("preset")
+                .value_name("NAME")
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Apply the flags of a [preset.NAME] section of the config file; later flags override them"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("Stop at the first repository whose details cannot be fetched, instead of warning and leaving them out"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .value_name("KIND")
+                .value_parser(["timing", "quota", "releases"])
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Print run statistics to stderr after completion"),
+        )
+        // Searches are cached by default now; the flag is kept so that
+        // existing scripts still parse.
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .hide(true),
+        )
+        .arg(
+            Arg::new("no-cache")
+                .long("no-cache")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["cache", "cache-dir", "cache-url"])
+                .global(true)
+                .help("Always search afresh, neither reusing nor saving results (by default, results from the last 15 minutes are reused)"),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-cache")
+                .global(true)
+                .help("Serve cached results however old, and never touch the network"),
+        )
+        .arg(
+            Arg::new("replay")
+                .long("replay")
+                .value_name("DIR")
+                .conflicts_with("offline")
+                .global(true)
+                .help("Answer every request from fixtures written by `ghs fixtures generate` instead of the network"),
+        )
+        .arg(
+            Arg::new("cache-dir")
+                .long("cache-dir")
+                .value_name("DIR")
+                .global(true)
+                .help("Cache directory, e.g. one shared by a team [default: ~/.cache/ghs]"),
+        )
+        .arg(
+            Arg::new("cache-url")
+                .long("cache-url")
+                .value_name("URL")
+                .conflicts_with("cache-dir")
+                .global(true)
+                .help("Shared cache at a file:// path or an http(s):// bucket"),
+        )
+        .arg(
+            Arg::new("provider")
+                .long("provider")
+                .value_name("PROVIDER")
+                .value_parser(["github", "gitea", "gitlab", "all"])
+                .default_value("github")
+                .global(true)
+                .help("Forge to search; gitea also covers Forgejo and takes its token from GITEA_TOKEN, gitlab from GITLAB_TOKEN, and all searches every configured one"),
+        )
+        .arg(
+            Arg::new("host")
+                .long("host")
+                .value_name("HOST")
+                .global(true)
+                .help("Server to talk to: a GitHub Enterprise host, or the Gitea or GitLab instance [defaults: gitea_host and gitlab_host from the config, else codeberg.org and gitlab.com]"),
+        )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .value_name("N")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("3")
+                .global(true)
+                .help("How many times to retry requests that fail transiently"),
+        )
+        .arg(
+            Arg::new("http2-prior-knowledge")
+                .long("http2-prior-knowledge")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("Speak HTTP/2 without negotiating it first, e.g. to a plain-text proxy known to support it"),
+        )
+        .arg(
+            Arg::new("pool-idle-timeout")
+                .long("pool-idle-timeout")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64))
+                .global(true)
+                .help("Keep unused connections open this long for reuse [default: 90]"),
+        )
+        .arg(
+            Arg::new("tcp-keepalive")
+                .long("tcp-keepalive")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .global(true)
+                .help("Send TCP keepalive probes at this interval, so long-lived connections survive NATs and firewalls"),
+        )
+        .arg(
+            Arg::new("retry-mutations")
+                .long("retry-mutations")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("Also retry mutating requests (star, fork, archive, ...), which may repeat their effect"),
+        )
+        .subcommand(
+            Command::new("compare-repos")
+                .about("Compare repositories side by side")
+                .arg(
+                    Arg::new("repos")
+                        .value_name("OWNER/NAME")
+                        .num_args(2..)
+                        .required(true)
+                        .help("Repositories to compare"),
+                ),
+        )
+        .subcommand(
+            Command::new("repo")
+                .about("Work with a single repository")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("view")
+                        .about("Show everything about a repository")
+                        .arg(
+                            Arg::new("repo")
+                                .value_name("OWNER/NAME")
+                                .help("Repository to show [default: the origin remote's]"),
+                        )
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the details as JSON instead of a card"),
+                        )
+                        .arg(
+                            Arg::new("avatar")
+                                .long("avatar")
+                                .action(ArgAction::SetTrue)
+                                .conflicts_with("json")
+                                .help("Show the owner's avatar in terminals that support inline images"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("edit")
+                        .about("Change the description, homepage and features of a repository, or of each one in a CSV file")
+                        .arg(
+                            Arg::new("repo")
+                                .value_name("OWNER/NAME")
+                                .conflicts_with("from-csv")
+                                .help("Repository to change [default: the origin remote's]"),
+                        )
+                        .arg(
+                            Arg::new("description")
+                                .long("description")
+                                .value_name("TEXT")
+                                .help("New description; '' clears it"),
+                        )
+                        .arg(
+                            Arg::new("homepage")
+                                .long("homepage")
+                                .value_name("URL")
+                                .help("New homepage; '' clears it"),
+                        )
+                        .arg(
+                            Arg::new("issues")
+                                .long("issues")
+                                .value_name("on|off")
+                                .value_parser(edit::parse_toggle)
+                                .help("Turn issues on or off"),
+                        )
+                        .arg(
+                            Arg::new("wiki")
+                                .long("wiki")
+                                .value_name("on|off")
+                                .value_parser(edit::parse_toggle)
+                                .help("Turn the wiki on or off"),
+                        )
+                        .arg(
+                            Arg::new("projects")
+                                .long("projects")
+                                .value_name("on|off")
+                                .value_parser(edit::parse_toggle)
+                                .help("Turn projects on or off"),
+                        )
+                        .arg(
+                            Arg::new("from-csv")
+                                .long("from-csv")
+                                .value_name("FILE")
+                                .conflicts_with_all(["description", "homepage", "issues", "wiki", "projects"])
+                                .help("Apply the changes in a CSV file with a full_name column and any of description, homepage, issues, wiki and projects; empty cells change nothing"),
+                        )
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .action(ArgAction::SetTrue)
+                                .help("List what would change, and change nothing"),
+                        )
+                        .arg(
+                            Arg::new("yes")
+                                .long("yes")
+                                .short('y')
+                                .action(ArgAction::SetTrue)
+                                .help("Do not ask before changing more than the config's bulk_confirm_threshold repositories"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("transfer")
+                        .about("Move repositories to another user or organization, then check that they arrived")
+                        .arg(
+                            Arg::new("repos")
+                                .value_name("OWNER/NAME")
+                                .num_args(1..)
+                                .required_unless_present("query")
+                                .help("Repositories to transfer"),
+                        )
+                        .arg(
+                            Arg::new("to")
+                                .long("to")
+                                .value_name("OWNER")
+                                .required(true)
+                                .help("User or organization to transfer to"),
+                        )
+                        .arg(
+                            Arg::new("query")
+                                .long("query")
+                                .value_name("QUERY")
+                                .conflicts_with("repos")
+                                .help("Transfer every result of this search that passes the filters, e.g. 'org:old-org topic:payments'"),
+                        )
+                        .arg(
+                            Arg::new("yes")
+                                .long("yes")
+                                .short('y')
+                                .action(ArgAction::SetTrue)
+                                .help("Transfer without asking first"),
+                        )
+                        .args(filter_args()),
+                ),
+        )
+        .subcommand(
+            Command::new("org-members-repos")
+                .about("List the personal repositories of an organization's members")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization whose members to look up")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("team-repos")
+                .about("List the repositories a team has access to")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization the team belongs to")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("team")
+                        .long("team")
+                        .value_name("TEAM")
+                        .help("Team slug, as in github.com/orgs/ORG/teams/TEAM")
+                        .required(true),
+                )
+                .args(filter_args())
+                .args(output_args()),
+        )
+        .subcommand(
+            Command::new("issues-of-results")
+                .about("Rank repositories by their open issues with a label, e.g. for onboarding")
+                .arg(
+                    Arg::new("user")
+                        .long("user")
+                        .value_name("USER")
+                        .help("Search the repositories of this user"),
+                )
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("Search the repositories of this organization"),
+                )
+                .group(ArgGroup::new("owner").args(["user", "org"]))
+                .arg(
+                    Arg::new("label")
+                        .long("label")
+                        .value_name("LABEL")
+                        .action(ArgAction::Append)
+                        .default_value("help wanted")
+                        .help("Count issues with this label (repeat to require several)"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("topics")
+                .about("Work with the topics of a user's or organization's repositories")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("suggest")
+                        .about("Suggest topics for each repository from its name, description and language, and the topics the others use")
+                        .arg(
+                            Arg::new("user")
+                                .long("user")
+                                .value_name("USER")
+                                .help("Suggest topics for the repositories of this user"),
+                        )
+                        .arg(
+                            Arg::new("org")
+                                .long("org")
+                                .value_name("ORG")
+                                .help("Suggest topics for the repositories of this organization"),
+                        )
+                        .group(ArgGroup::new("owner").args(["user", "org"]).required(true))
+                        .arg(
+                            Arg::new("apply")
+                                .long("apply")
+                                .action(ArgAction::SetTrue)
+                                .help("Offer to add the suggested topics to each repository"),
+                        )
+                        .arg(
+                            Arg::new("yes")
+                                .long("yes")
+                                .short('y')
+                                .action(ArgAction::SetTrue)
+                                .requires("apply")
+                                .help("Add every suggested topic without asking"),
+                        )
+                        .args(filter_args()),
+                ),
+        )
+        .subcommand(
+            Command::new("audit-files")
+                .about("Check that every repository of an organization has the required files")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to audit")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("require")
+                        .long("require")
+                        .value_name("PATHS")
+                        .value_delimiter(',')
+                        .action(ArgAction::Append)
+                        .help("Comma-separated files or directories each repository must have")
+                        .required(true),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("audit-protection")
+                .about("Report which default branches of an organization lack protection or required reviews")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to audit")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(["table", "csv"])
+                        .default_value("table")
+                        .help("Print a table or CSV"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("ci-status")
+                .about("Show the latest workflow run of every repository of an organization")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to check")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("branch")
+                        .long("branch")
+                        .value_name("BRANCH")
+                        .help("Branch to check [default: each repository's default branch]"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("discussions")
+                .about("Search GitHub Discussions")
+                .arg(
+                    Arg::new("query")
+                        .value_name("QUERY")
+                        .help("Search terms")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("user")
+                        .long("user")
+                        .value_name("USER")
+                        .help("Only discussions in this user's repositories"),
+                )
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("Only discussions in this organization's repositories"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("30")
+                        .help("Show at most N discussions"),
+                ),
+        )
+        .subcommand(
+            Command::new("repos")
+                .about("Search repositories, as ghs does without a subcommand; takes the same arguments"),
+        )
+        .subcommand(
+            Command::new("code")
+                .about("Search code")
+                .arg(
+                    Arg::new("query")
+                        .value_name("QUERY")
+                        .num_args(1..)
+                        .help("Search terms and qualifiers")
+                        .required(true),
+                )
+                .args(scope_args())
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .short('l')
+                        .value_name("LANGUAGE")
+                        .help("Only files in this language"),
+                )
+                .arg(
+                    Arg::new("path")
+                        .long("path")
+                        .value_name("PATH")
+                        .help("Only files under this path"),
+                )
+                .arg(
+                    Arg::new("extension")
+                        .long("extension")
+                        .value_name("EXT")
+                        .help("Only files with this extension, without the dot"),
+                )
+                .arg(
+                    Arg::new("filename")
+                        .long("filename")
+                        .value_name("NAME")
+                        .help("Only files with this name"),
+                )
+                .arg(limit_arg("files"))
+                .args(output_args()),
+        )
+        .subcommand(
+            Command::new("issue")
+                .about("Work with issues")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("create")
+                        .about("Open an issue in a repository, or the same one in every result of a search")
+                        .arg(
+                            Arg::new("repo")
+                                .value_name("OWNER/NAME")
+                                .conflicts_with("query")
+                                .help("Repository to open the issue in [default: the origin remote's]"),
+                        )
+                        .arg(
+                            Arg::new("query")
+                                .long("query")
+                                .value_name("QUERY")
+                                .help("Open it in every result of this search that passes --where, e.g. 'org:my-org topic:cli'"),
+                        )
+                        .arg(
+                            Arg::new("title")
+                                .long("title")
+                                .value_name("TEXT")
+                                .required(true)
+                                .help("Title of the issue; with --query, like the body, a template where {name}, {full_name}, {upper(name)} and so on are the repository's, and {{ and }} are braces"),
+                        )
+                        .arg(
+                            Arg::new("body")
+                                .long("body")
+                                .value_name("TEXT")
+                                .help("Body of the issue, in Markdown"),
+                        )
+                        .arg(
+                            Arg::new("body-file")
+                                .long("body-file")
+                                .value_name("FILE")
+                                .conflicts_with("body")
+                                .help("Read the body from this file"),
+                        )
+                        .arg(
+                            Arg::new("label")
+                                .long("label")
+                                .value_name("LABEL")
+                                .action(ArgAction::Append)
+                                .help("Label to add; it must exist in the repository (repeatable)"),
+                        )
+                        .arg(
+                            Arg::new("dry-run")
+                                .long("dry-run")
+                                .action(ArgAction::SetTrue)
+                                .help("Print each issue as it would be opened, and open none"),
+                        )
+                        .arg(
+                            Arg::new("yes")
+                                .long("yes")
+                                .short('y')
+                                .action(ArgAction::SetTrue)
+                                .help("Do not ask before opening issues in more than the config's bulk_confirm_threshold repositories"),
+                        )
+                        .arg(
+                            Arg::new("where")
+                                .long("where")
+                                .value_name("EXPR")
+                                .value_parser(Predicate::parse)
+                                .action(ArgAction::Append)
+                                .requires("query")
+                                .help("Only the results matching a comparison such as 'stars>=10' (repeatable)"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("issues")
+                .about("Search issues and pull requests")
+                .arg(
+                    Arg::new("query")
+                        .value_name("QUERY")
+                        .num_args(1..)
+                        .help("Search terms and qualifiers"),
+                )
+                .args(scope_args())
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .value_name("STATE")
+                        .value_parser(["open", "closed"])
+                        .help("Only open or closed ones"),
+                )
+                .arg(
+                    Arg::new("type")
+                        .long("type")
+                        .value_name("TYPE")
+                        .value_parser(["issue", "pr"])
+                        .help("Only issues or only pull requests"),
+                )
+                .arg(
+                    Arg::new("author")
+                        .long("author")
+                        .value_name("USER")
+                        .help("Only ones opened by this user"),
+                )
+                .arg(
+                    Arg::new("label")
+                        .long("label")
+                        .value_name("LABEL")
+                        .action(ArgAction::Append)
+                        .help("Only ones with this label; repeat to require several"),
+                )
+                .arg(limit_arg("issues"))
+                .args(output_args()),
+        )
+        .subcommand(
+            Command::new("labels")
+                .about("Inventory the issue labels of an organization's repositories")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to inventory")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("missing")
+                        .long("missing")
+                        .value_name("LABEL")
+                        .action(ArgAction::Append)
+                        .help("List the repositories without this label, in any spelling"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("milestones")
+                .about("List the milestones of an organization's repositories with their progress")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to list")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .value_name("STATE")
+                        .value_parser(["open", "closed", "all"])
+                        .default_value("open")
+                        .help("Which milestones to list"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("notifications")
+                .about("List your notifications with why each was sent, and optionally mark them read")
+                .arg(
+                    Arg::new("unread")
+                        .long("unread")
+                        .action(ArgAction::SetTrue)
+                        .help("Only unread notifications"),
+                )
+                .arg(
+                    Arg::new("repo")
+                        .long("repo")
+                        .value_name("OWNER/NAME")
+                        .help("Only this repository's notifications"),
+                )
+                .arg(
+                    Arg::new("mark-read")
+                        .long("mark-read")
+                        .action(ArgAction::SetTrue)
+                        .help("Mark the listed unread notifications as read"),
+                )
+                .args(output_args()),
+        )
+        .subcommand(
+            Command::new("whoowns")
+                .about("Look up the code owners of a path from the repository's CODEOWNERS file")
+                .arg(
+                    Arg::new("repo")
+                        .value_name("OWNER/REPO")
+                        .help("Repository to look in [default: the origin remote's]"),
+                )
+                .arg(
+                    Arg::new("path")
+                        .value_name("PATH")
+                        .help("File or directory path within the repository"),
+                ),
+        )
+        .subcommand(
+            Command::new("self-update")
+                .about("Install the latest release of ghs over this executable, once its minisign signature checks out against the release key built into ghs")
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .action(ArgAction::SetTrue)
+                        .help("Do not ask for confirmation before replacing the executable"),
+                ),
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Resume a saved TUI session")
+                .arg(
+                    Arg::new("session")
+                        .long("session")
+                        .value_name("NAME")
+                        .help("Name the session was saved under")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("triage")
+                .about("Run a saved TUI session's search again and browse what is new or changed since, to mark, tag and export it")
+                .arg(
+                    Arg::new("session")
+                        .long("session")
+                        .value_name("NAME")
+                        .help("Name the session was saved under, e.g. with `ghs QUERY --tui --session NAME`")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("rename-branch")
+                .about("Rename the default branch of every repository of an organization that uses it, e.g. master to main")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("Organization (or user) whose repositories to change")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("BRANCH")
+                        .help("Default branch to rename")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("BRANCH")
+                        .help("New name for it")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("List the repositories that would change, and change nothing"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .action(ArgAction::SetTrue)
+                        .help("Do not ask before renaming in more than the config's bulk_confirm_threshold repositories"),
+                )
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("dupes")
+                .about("Find repositories with near-identical names or descriptions")
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("GitHub organization to scan")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .value_name("SIMILARITY")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("0.8")
+                        .help("Minimum similarity (0.0-1.0) to report a pair"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export the SQLite store for analysis in other tools")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("parquet")
+                        .about("Write a table of the SQLite store as a Parquet file, e.g. for DuckDB or pandas")
+                        .arg(
+                            Arg::new("db")
+                                .long("db")
+                                .value_name("PATH")
+                                .help("Database written by `--output sqlite:PATH`")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("table")
+                                .long("table")
+                                .value_name("TABLE")
+                                .value_parser(store::TABLES)
+                                .default_value("snapshots")
+                                .help("Table to export: the snapshot history, or the latest repositories"),
+                        )
+                        .arg(
+                            Arg::new("file")
+                                .value_name("FILE")
+                                .help("Parquet file to write")
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("leaderboard")
+                .about("Rank stored repositories by the stars or forks they gained recently")
+                .arg(
+                    Arg::new("db")
+                        .long("db")
+                        .value_name("PATH")
+                        .help("Database written by `--output sqlite:PATH`")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("metric")
+                        .long("metric")
+                        .value_name("METRIC")
+                        .value_parser(leaderboard::Metric::parse)
+                        .default_value("stars")
+                        .help("What to rank by: stars or forks"),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("PERIOD")
+                        .value_parser(leaderboard::parse_period)
+                        .default_value("30d")
+                        .help("How far back to measure from, in hours, days or weeks (e.g. 12h, 30d, 2w)"),
+                )
+                .arg(
+                    Arg::new("org")
+                        .long("org")
+                        .value_name("ORG")
+                        .help("Only rank repositories of this organization (or user)"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10")
+                        .help("How many repositories to list"),
+                )
+                .arg(
+                    Arg::new("markdown")
+                        .long("markdown")
+                        .action(ArgAction::SetTrue)
+                        .help("Print a Markdown table, e.g. for a newsletter"),
+                ),
+        )
+        .subcommand(
+            Command::new("trending")
+                .about("List the repositories that gained the most stars recently, estimated from search results as GitHub has no trending API")
+                .arg(
+                    Arg::new("language")
+                        .long("language")
+                        .short('l')
+                        .value_name("LANGUAGE")
+                        .help("Only repositories in this language"),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("PERIOD")
+                        .value_parser(trending::Since::parse)
+                        .default_value("weekly")
+                        .help("Period to measure over: daily, weekly or monthly"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("25")
+                        .help("How many repositories to list"),
+                ),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Time searches end to end and report latency percentiles; --no-cache, --offline and --host compare the cache, replayed results and other servers")
+                .arg(
+                    Arg::new("iterations")
+                        .long("iterations")
+                        .short('n')
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("5")
+                        .help("How many times to run each query"),
+                )
+                .arg(
+                    Arg::new("queries")
+                        .value_name("QUERY")
+                        .num_args(1..)
+                        .help("Complete search queries to time [default: a user's repositories, a topic and a language with a star range]"),
+                ),
+        )
+        .subcommand(
+            Command::new("cache")
+                .about("Manage cached search results")
+                .subcommand_required(true)
+                .subcommand(Command::new("clear").about("Remove every cached search")),
+        )
+        .subcommand(
+            Command::new("selftest")
+                .about("Check this build and its environment, e.g. proxy variables, by searching, filtering and formatting against a stand-in for the GitHub API served locally; needs no token and never contacts GitHub"),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the JSON Schema of the JSON that --format json and exports write"),
+        )
+        .subcommand(
+            Command::new("fixtures")
+                .about("Record API responses for tests to replay")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("generate")
+                        .about("Run a search and save every response as a fixture file, for --replay and the client's replay_fixtures")
+                        .arg(
+                            Arg::new("query")
+                                .long("query")
+                                .value_name("QUERY")
+                                .help("Search whose first page to record")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("get")
+                                .long("get")
+                                .value_name("PATH")
+                                .action(ArgAction::Append)
+                                .help("Also record a GET of this API path, e.g. /repos/OWNER/REPO/languages (repeatable)"),
+                        )
+                        .arg(
+                            Arg::new("per-page")
+                                .long("per-page")
+                                .value_name("N")
+                                .value_parser(clap::value_parser!(u8).range(1..=100))
+                                .default_value("5")
+                                .help("Search results to record"),
+                        )
+                        .arg(
+                            Arg::new("dir")
+                                .long("dir")
+                                .value_name("DIR")
+                                .default_value("tests/fixtures")
+                                .help("Directory to write the fixtures to"),
+                        )
+                        .arg(
+                            Arg::new("anonymize")
+                                .long("anonymize")
+                                .action(ArgAction::SetTrue)
+                                .help("Replace e-mail addresses and anything shaped like a token, for fixtures to be committed"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("trend")
+                .about("Chart changes across the snapshots in an SQLite store")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("languages")
+                        .about("Show how an organization's language mix changed between snapshots")
+                        .arg(
+                            Arg::new("org")
+                                .long("org")
+                                .value_name("ORG")
+                                .help("Organization (or user) whose repositories to chart")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("db")
+                                .long("db")
+                                .value_name("PATH")
+                                .help("Database written by `--output sqlite:PATH`")
+                                .required(true),
+                        ),
+                ),
+        )
+}
+
+/// The client-side `--title`/`--description`/`--language`/`--where` filters,
+/// shared by every command that lists repositories.
+pub fn filter_args() -> [Arg; 7] {
+    [
+        Arg::new("title")
+            .short('t')
+            .long("title")
+            .value_name("TITLE")
+            .help("Filter by the specified title"),
+        Arg::new("description")
+            .short('d')
+            .long("description")
+            .value_name("DESCRIPTION")
+            .help("Filter by the specified repository description"),
+        Arg::new("match-mode")
+            .long("match-mode")
+            .value_name("MODE")
+            .value_parser(MatchMode::parse)
+            .help("How --title and --description match: substring (default), word or phrase"),
+        Arg::new("description-lang")
+            .long("description-lang")
+            .value_name("LANG")
+            .value_parser(langdetect::LANGUAGES.to_vec())
+            .help("Keep repositories whose description is written in LANG (e.g. ja, en)"),
+        Arg::new("include-archived")
+            .long("include-archived")
+            .action(ArgAction::SetTrue)
+            .help("Keep archived repositories even if the config excludes them by default"),
+        Arg::new("language")
+            .short('l')
+            .long("language")
+            .value_name("LANGUAGE")
+            .help("Filter by the specified programming language"),
+        Arg::new("where")
+            .long("where")
+            .value_name("EXPR")
+            .value_parser(Predicate::parse)
+            .action(ArgAction::Append)
+            .help("Keep repositories matching a comparison such as 'forks>=10' (repeatable)"),
+    ]
+}
+
+/// `--repo`, `--org` and `--user`, narrowing a code or issue search.
+pub fn scope_args() -> [Arg; 3] {
+    [
+        Arg::new("repo")
+            .long("repo")
+            .value_name("OWNER/NAME")
+            .help("Only this repository"),
+        Arg::new("org")
+            .long("org")
+            .value_name("ORG")
+            .conflicts_with("user")
+            .help("Only this organization's repositories"),
+        Arg::new("user")
+            .long("user")
+            .value_name("USER")
+            .help("Only this user's repositories"),
+    ]
+}
+
+pub fn limit_arg(noun: &str) -> Arg {
+    Arg::new("limit")
+        .long("limit")
+        .value_name("N")
+        .value_parser(clap::value_parser!(usize))
+        .default_value("30")
+        .help(format!("Show at most N {}", noun))
+}
+
+/// `--format` and `--output`, for commands that list repositories.
+pub fn output_args() -> [Arg; 2] {
+    [
+        Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .value_parser(output::Format::parse)
+            .help("Output format: plain (default), json, csv or table"),
+        Arg::new("output")
+            .long("output")
+            .short('o')
+            .value_name("DEST")
+            .help("Write the results to a file, sqlite:PATH or an http(s):// webhook instead of stdout"),
+    ]
+}
+
+/// `ghs repos ARGS` is `ghs ARGS`: repository search takes the top-level
+/// arguments, so the subcommand word is dropped before parsing.
+pub fn repos_alias(args: env::ArgsOs) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.collect();
+    if args.get(1).is_some_and(|arg| arg == "repos") {
+        args.remove(1);
+    }
+    args
+}
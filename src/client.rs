@@ -0,0 +1,1634 @@
+use crate::auth::{self, AuthProvider};
+use crate::cancel::Cancel;
+use crate::datetime;
+use crate::error::ApiError;
+use crate::fixtures::{self, Fixture};
+use crate::links::PageLinks;
+use crate::model::{
+    CodeResult, Discussion, Issue, IssueComment, IssueResult, Label, Milestone, Notification,
+    Protection, Release, SearchResults, Topics, User, WeeklyCommits, WorkflowRun, WorkflowRuns,
+};
+use crate::progress::{Event, Listener};
+use crate::retry::{self, RetryPolicy};
+use crate::stats::Stats;
+use crate::{BoxError, Repositories, Repository};
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Method, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Longest rate-limit reset we are willing to sleep through before giving up.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// REST API of github.com, used unless another host is configured.
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Default per-request timeout, matching the CLI.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest page size the search API accepts.
+pub const MAX_PER_PAGE: u8 = 100;
+
+/// GitHub's search API never returns more than this many results per query.
+pub const SEARCH_RESULT_CAP: u64 = 1000;
+
+/// No repository on GitHub was created before this year, so date slicing
+/// starts here.
+const GITHUB_LAUNCH_YEAR: i64 = 2007;
+
+/// How many search pages may be fetched at once.
+const PAGE_CONCURRENCY: usize = 4;
+
+/// How often to ask again while GitHub is still computing repository
+/// statistics, and how long to wait in between.
+const STATS_POLL_ATTEMPTS: u32 = 5;
+const STATS_POLL_DELAY: Duration = Duration::from_secs(2);
+
+/// A completed API response.
+#[derive(Debug)]
+pub struct ApiResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// One page of search results and the `Link` header that came with it.
+#[derive(Debug)]
+pub struct SearchPage {
+    /// Validator for revalidating this page later with
+    /// [`search_repositories_if_changed`](GhsClient::search_repositories_if_changed).
+    pub etag: Option<String>,
+    pub repositories: Repositories,
+    pub links: PageLinks,
+}
+
+/// Client for the GitHub REST API shared by every command. Cloning is cheap,
+/// so concurrent tasks each take their own handle.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), ghs::BoxError> {
+/// let client = ghs::GhsClient::builder()
+///     .token("ghp_...")
+///     .timeout(std::time::Duration::from_secs(10))
+///     .build()?;
+/// let repositories = client.search_repositories("user:octocat", 100).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GhsClient {
+    client: Client,
+    base_url: Arc<str>,
+    access_token: Option<Arc<str>>,
+    user_agent: Arc<str>,
+    retry_policy: RetryPolicy,
+    retries_spent: Arc<AtomicU32>,
+    stats: Arc<Mutex<Stats>>,
+    offline: bool,
+    progress: Listener,
+    fixtures: Option<Arc<fixtures::Mode>>,
+    cancel: Cancel,
+}
+
+/// Configures a [`GhsClient`]. Every setting is optional; unset ones fall back
+/// to the same defaults the CLI uses.
+#[derive(Debug, Default)]
+pub struct GhsClientBuilder {
+    host: Option<String>,
+    auth: Option<auth::Source>,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    user_agent: Option<String>,
+    offline: bool,
+    http2_prior_knowledge: bool,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    progress: Listener,
+    fixtures: Option<fixtures::Mode>,
+    cancel: Cancel,
+}
+
+impl GhsClientBuilder {
+    /// GitHub host to talk to: `github.com` (the default), a GitHub Enterprise
+    /// Server hostname, or a full API base URL such as `http://localhost:8080`.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Access token sent as a bearer token. Without one, requests are
+    /// unauthenticated and subject to much lower rate limits.
+    pub fn token(self, token: impl Into<String>) -> Self {
+        self.auth(auth::StaticToken(token.into()))
+    }
+
+    /// Where the access token comes from, instead of a fixed
+    /// [`token`](Self::token): the environment, the GitHub CLI's login, a
+    /// command, or a closure of the embedder's. It is asked once, by
+    /// [`build`](Self::build), and whichever of the two was set last wins.
+    pub fn auth(mut self, provider: impl AuthProvider + 'static) -> Self {
+        self.auth = Some(auth::Source(Box::new(provider)));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Fails every request without sending it, for working from cached
+    /// results only.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Speaks HTTP/2 from the first byte instead of negotiating it, for
+    /// servers and proxies known to support it without TLS.
+    pub fn http2_prior_knowledge(mut self, http2_prior_knowledge: bool) -> Self {
+        self.http2_prior_knowledge = http2_prior_knowledge;
+        self
+    }
+
+    /// How long an unused pooled connection is kept open (90 seconds unless
+    /// set), so that a long-running process can reuse connections across
+    /// quiet spells.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends TCP keepalive probes at this interval, so that idle connections
+    /// are not silently dropped by NATs and firewalls.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Calls `listener` with every [`Event`] of the client and its clones,
+    /// e.g. to forward them over a channel; see [`crate::progress`].
+    pub fn on_progress(mut self, listener: impl Fn(&Event) + Send + Sync + 'static) -> Self {
+        self.progress = Listener::new(listener);
+        self
+    }
+
+    /// Saves every response to `dir` as a [fixture](crate::fixtures), with
+    /// e-mail addresses and tokens scrubbed when `anonymize` is set.
+    pub fn record_fixtures(mut self, dir: impl Into<PathBuf>, anonymize: bool) -> Self {
+        self.fixtures = Some(fixtures::Mode::Record {
+            dir: dir.into(),
+            anonymize,
+        });
+        self
+    }
+
+    /// Answers every request from the [fixtures](crate::fixtures) in `dir`
+    /// instead of the network; a request without one fails.
+    pub fn replay_fixtures(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.fixtures = Some(fixtures::Mode::Replay(dir.into()));
+        self
+    }
+
+    /// Stops searches that page, such as
+    /// [`search_all_repositories`](GhsClient::search_all_repositories), once
+    /// `cancel` is cancelled; they return what they have fetched so far.
+    pub fn cancel(mut self, cancel: Cancel) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn build(self) -> Result<GhsClient, BoxError> {
+        let mut client = Client::builder()
+            .timeout(self.timeout.unwrap_or(DEFAULT_TIMEOUT))
+            .tcp_keepalive(self.tcp_keepalive);
+        if let Some(timeout) = self.pool_idle_timeout {
+            client = client.pool_idle_timeout(timeout);
+        }
+        if self.http2_prior_knowledge {
+            client = client.http2_prior_knowledge();
+        }
+        let client = client.build()?;
+        let access_token = match &self.auth {
+            Some(auth) => auth
+                .0
+                .token()
+                .map_err(|err| format!("could not get an access token: {}", err))?,
+            None => None,
+        };
+        let base_url = match self.host.as_deref() {
+            None | Some("github.com") | Some("api.github.com") => DEFAULT_API_URL.to_string(),
+            Some(url) if url.contains("://") => url.trim_end_matches('/').to_string(),
+            Some(host) => format!("https://{}/api/v3", host),
+        };
+        let user_agent = self
+            .user_agent
+            .unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+
+        Ok(GhsClient {
+            client,
+            base_url: base_url.into(),
+            access_token: access_token.map(Into::into),
+            user_agent: user_agent.into(),
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            retries_spent: Arc::new(AtomicU32::new(0)),
+            stats: Arc::new(Mutex::new(Stats::start())),
+            offline: self.offline,
+            progress: self.progress,
+            fixtures: self.fixtures.map(Arc::new),
+            cancel: self.cancel,
+        })
+    }
+}
+
+impl GhsClient {
+    pub fn builder() -> GhsClientBuilder {
+        GhsClientBuilder::default()
+    }
+
+    /// Base URL of the REST API, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Whether requests carry an access token.
+    pub fn has_token(&self) -> bool {
+        self.access_token.is_some()
+    }
+
+    /// The GraphQL endpoint matching [`base_url`](Self::base_url); on GitHub
+    /// Enterprise it lives at `/api/graphql` rather than under `/api/v3`.
+    pub fn graphql_url(&self) -> String {
+        match self.base_url.strip_suffix("/api/v3") {
+            Some(host) => format!("{}/api/graphql", host),
+            None => format!("{}/graphql", self.base_url),
+        }
+    }
+
+    /// Timings and quota usage collected from every request made so far.
+    pub fn stats(&self) -> MutexGuard<'_, Stats> {
+        self.stats.lock().unwrap()
+    }
+
+    /// Takes one retry from the client-wide budget, if any is left.
+    fn spend_retry(&self) -> bool {
+        self.retries_spent
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |spent| {
+                (spent < self.retry_policy.budget).then_some(spent + 1)
+            })
+            .is_ok()
+    }
+
+    pub async fn get(&self, url: &str, accept: &str) -> Result<ApiResponse, BoxError> {
+        self.send(Method::GET, url, accept, None).await
+    }
+
+    /// Sends a request, sleeping through short rate-limit windows and retrying
+    /// transient failures as the retry policy allows for `method`, and returns
+    /// the final status along with the raw body.
+    pub async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        accept: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<ApiResponse, BoxError> {
+        self.send_with_headers(method, url, accept, body, &HeaderMap::new())
+            .await
+    }
+
+    /// Like [`send`](Self::send), with extra request headers such as
+    /// `If-None-Match`.
+    pub async fn send_with_headers(
+        &self,
+        method: Method,
+        url: &str,
+        accept: &str,
+        body: Option<&serde_json::Value>,
+        headers: &HeaderMap,
+    ) -> Result<ApiResponse, BoxError> {
+        if let Some(fixtures::Mode::Replay(dir)) = self.fixtures.as_deref() {
+            return self.replay(dir, &method, url);
+        }
+        self.check_online(url)?;
+        let retryable = self.retry_policy.allows(&method);
+        let mut attempt = 0;
+        let mut rate_limit_waits = 0;
+        let may_retry = |attempt: u32| {
+            retryable && attempt < self.retry_policy.max_retries && self.spend_retry()
+        };
+
+        loop {
+            let started = Instant::now();
+            let mut request = self
+                .client
+                .request(method.clone(), url)
+                .header(header::ACCEPT, accept)
+                .header(header::USER_AGENT, &*self.user_agent)
+                .header("X-GitHub-Api-Version", "2022-11-28")
+                .headers(headers.clone());
+            if let Some(access_token) = &self.access_token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", access_token));
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(_) if may_retry(attempt) => {
+                    self.record_request(started);
+                    tokio::time::sleep(self.retry_policy.delay(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            {
+                let mut stats = self.stats();
+                stats.quota.record(response.headers());
+                stats.deprecations.record(&method, url, response.headers());
+            }
+
+            if let Some(wait) = rate_limit_wait(&response) {
+                if rate_limit_waits >= self.retry_policy.max_rate_limit_waits {
+                    let response = ApiResponse {
+                        status: response.status(),
+                        headers: response.headers().clone(),
+                        body: response.bytes().await?.to_vec(),
+                    };
+                    self.record_request(started);
+                    return Err(ApiError::from_response(url, &response).into());
+                }
+                rate_limit_waits += 1;
+                self.record_request(started);
+                self.progress.emit(|| Event::RateLimited {
+                    until: datetime::now() + wait.as_secs() as i64,
+                });
+                tokio::time::sleep(wait).await;
+                self.stats().timings.record_rate_limit_wait(wait);
+                continue;
+            }
+
+            let status = response.status();
+            if retry::is_transient(status) && may_retry(attempt) {
+                self.record_request(started);
+                tokio::time::sleep(self.retry_policy.delay(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+
+            let headers = response.headers().clone();
+            let body = response.bytes().await?;
+            self.record_request(started);
+            let response = ApiResponse {
+                status,
+                headers,
+                body: body.to_vec(),
+            };
+            if let Some(fixtures::Mode::Record { dir, anonymize }) = self.fixtures.as_deref() {
+                self.record(dir, *anonymize, &method, url, &response)?;
+            }
+            return Ok(response);
+        }
+    }
+
+    fn record(
+        &self,
+        dir: &Path,
+        anonymize: bool,
+        method: &Method,
+        url: &str,
+        response: &ApiResponse,
+    ) -> Result<(), BoxError> {
+        let headers = response.headers.iter().filter_map(|(name, value)| {
+            let value = value.to_str().ok()?;
+            Some((name.as_str().to_string(), value.to_string()))
+        });
+        let mut fixture = Fixture::new(
+            &self.base_url,
+            method.as_str(),
+            url,
+            response.status.as_u16(),
+            headers,
+            &response.body,
+        );
+        if anonymize {
+            fixture.anonymize();
+        }
+        fixtures::save(dir, &fixture)
+    }
+
+    fn replay(&self, dir: &Path, method: &Method, url: &str) -> Result<ApiResponse, BoxError> {
+        let path = url.strip_prefix(&*self.base_url).unwrap_or(url);
+        let fixture = fixtures::load(dir, method.as_str(), path)?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in fixture.headers_for(&self.base_url) {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(&value)?,
+            );
+        }
+        Ok(ApiResponse {
+            status: StatusCode::from_u16(fixture.status)?,
+            headers,
+            body: fixture.body_bytes(),
+        })
+    }
+
+    fn check_online(&self, url: &str) -> Result<(), BoxError> {
+        match self.offline {
+            true => Err(format!("{} needs the network, but the client is offline", url).into()),
+            false => Ok(()),
+        }
+    }
+
+    fn record_request(&self, started: Instant) {
+        self.stats().timings.record_request(started.elapsed());
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// `future`'s output, or `None` if the client is cancelled first.
+    pub(crate) async fn unless_cancelled<T>(&self, future: impl Future<Output = T>) -> Option<T> {
+        tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => None,
+            value = future => Some(value),
+        }
+    }
+
+    fn page_fetched(&self, url: &str, items: usize) {
+        self.progress.emit(|| Event::PageFetched {
+            url: url.to_string(),
+            items,
+        });
+    }
+
+    fn enriched(&self, full_name: &str, detail: &'static str) {
+        self.progress.emit(|| Event::ItemEnriched {
+            full_name: full_name.to_string(),
+            detail,
+        });
+    }
+
+    /// Fetches the first page of repository search results.
+    pub async fn search_repositories(
+        &self,
+        query: &str,
+        per_page: u8,
+    ) -> Result<Repositories, BoxError> {
+        Ok(self
+            .search_repositories_page(query, per_page, 1)
+            .await?
+            .repositories)
+    }
+
+    /// Fetches one page of repository search results along with the
+    /// pagination links GitHub advertised for it.
+    pub async fn search_repositories_page(
+        &self,
+        query: &str,
+        per_page: u8,
+        page: u32,
+    ) -> Result<SearchPage, BoxError> {
+        let url = search_url(&self.base_url, query, per_page, page)?;
+        let response = self
+            .get(url.as_str(), "application/vnd.github+json")
+            .await?;
+        self.search_page(url.as_str(), response)
+    }
+
+    /// Fetches the first page of search results again, unless it is unchanged
+    /// since the response that carried `etag`, in which case `None` is
+    /// returned. GitHub does not count such 304 responses against the rate
+    /// limit.
+    pub async fn search_repositories_if_changed(
+        &self,
+        query: &str,
+        per_page: u8,
+        etag: &str,
+    ) -> Result<Option<SearchPage>, BoxError> {
+        let url = search_url(&self.base_url, query, per_page, 1)?;
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.parse()?);
+        let response = self
+            .send_with_headers(
+                Method::GET,
+                url.as_str(),
+                "application/vnd.github+json",
+                None,
+                &headers,
+            )
+            .await?;
+        if response.status == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        self.search_page(url.as_str(), response).map(Some)
+    }
+
+    fn search_page(&self, url: &str, response: ApiResponse) -> Result<SearchPage, BoxError> {
+        if !response.status.is_success() {
+            return Err(request_error(url, &response));
+        }
+        let etag = response
+            .headers
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let links = response
+            .headers
+            .get(header::LINK)
+            .and_then(|value| value.to_str().ok())
+            .map(PageLinks::parse)
+            .unwrap_or_default();
+        let repositories: Repositories = self
+            .stats()
+            .timings
+            .time_serialization(|| serde_json::from_slice(&response.body))?;
+        self.page_fetched(url, repositories.items.len());
+
+        Ok(SearchPage {
+            etag,
+            repositories,
+            links,
+        })
+    }
+
+    /// Fetches every page of repository search results. The first page tells
+    /// how many pages there are (via its `last` link); the rest are then
+    /// fetched concurrently. `on_page` is called with the number of pages
+    /// fetched so far and the total after each one.
+    ///
+    /// GitHub never serves more than [`SEARCH_RESULT_CAP`] results for one
+    /// query; compare [`Repositories::total_count`] against the items to spot
+    /// truncation.
+    pub async fn search_all_repositories(
+        &self,
+        query: &str,
+        per_page: u8,
+        on_page: impl Fn(u32, u32),
+    ) -> Result<Repositories, BoxError> {
+        let Some(first) = self
+            .unless_cancelled(self.search_repositories_page(query, per_page, 1))
+            .await
+            .transpose()?
+        else {
+            return Ok(Repositories::cancelled());
+        };
+        self.fetch_remaining_pages(query, per_page, first, None, &on_page)
+            .await
+    }
+
+    /// Like [`search_all_repositories`](Self::search_all_repositories), but
+    /// gets around the [`SEARCH_RESULT_CAP`] by splitting the query into
+    /// `created:` date ranges, halving any range that still matches too many
+    /// repositories, and merging the results. `on_progress` is called with
+    /// the number of repositories fetched so far and the overall total.
+    ///
+    /// Queries that already carry a `created:` qualifier are not sliced. If a
+    /// single day still exceeds the cap, that day's results stay truncated.
+    pub async fn search_every_repository(
+        &self,
+        query: &str,
+        per_page: u8,
+        on_progress: impl Fn(usize, u64),
+    ) -> Result<Repositories, BoxError> {
+        let Some(first) = self
+            .unless_cancelled(self.search_repositories_page(query, per_page, 1))
+            .await
+            .transpose()?
+        else {
+            return Ok(Repositories::cancelled());
+        };
+        let total_count = first.repositories.total_count;
+
+        if total_count <= SEARCH_RESULT_CAP || query.contains("created:") {
+            let repositories = self
+                .fetch_remaining_pages(query, per_page, first, None, &|_, _| {})
+                .await?;
+            on_progress(repositories.items.len(), total_count);
+            return Ok(repositories);
+        }
+
+        let mut repositories = Repositories {
+            total_count,
+            incomplete_results: first.repositories.incomplete_results,
+            items: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        let today = datetime::now().div_euclid(86_400);
+        let mut pending = vec![(datetime::days_from_civil(GITHUB_LAUNCH_YEAR, 1, 1), today)];
+
+        while let Some((from, to)) = pending.pop() {
+            let slice_query = format!(
+                "{} created:{}..{}",
+                query,
+                datetime::format_date(from),
+                datetime::format_date(to)
+            );
+            let Some(first) = self
+                .unless_cancelled(self.search_repositories_page(&slice_query, per_page, 1))
+                .await
+                .transpose()?
+            else {
+                repositories.incomplete_results = true;
+                break;
+            };
+
+            if first.repositories.total_count > SEARCH_RESULT_CAP && from < to {
+                let middle = from + (to - from) / 2;
+                // Pushed in reverse so the older half is fetched first.
+                pending.push((middle + 1, to));
+                pending.push((from, middle));
+                continue;
+            }
+
+            let slice = self
+                .fetch_remaining_pages(&slice_query, per_page, first, None, &|_, _| {})
+                .await?;
+            repositories.incomplete_results |= slice.incomplete_results;
+            for repo in slice.items {
+                if seen.insert(repo.full_name.clone()) {
+                    repositories.items.push(repo);
+                }
+            }
+            on_progress(repositories.items.len(), total_count);
+            if self.is_cancelled() {
+                repositories.incomplete_results = true;
+                break;
+            }
+        }
+
+        Ok(repositories)
+    }
+
+    /// Fetches search results until `max_results` are in hand, requesting
+    /// only the pages needed to get there. A cap beyond
+    /// [`SEARCH_RESULT_CAP`] needs the date slicing of
+    /// [`search_every_repository`](Self::search_every_repository), which is
+    /// used and cut short afterwards.
+    pub async fn search_repositories_up_to(
+        &self,
+        query: &str,
+        per_page: u8,
+        max_results: usize,
+        on_progress: impl Fn(usize, u64),
+    ) -> Result<Repositories, BoxError> {
+        if max_results as u64 > SEARCH_RESULT_CAP {
+            let mut repositories = self
+                .search_every_repository(query, per_page, on_progress)
+                .await?;
+            repositories.items.truncate(max_results);
+            return Ok(repositories);
+        }
+
+        let Some(first) = self
+            .unless_cancelled(self.search_repositories_page(query, per_page, 1))
+            .await
+            .transpose()?
+        else {
+            return Ok(Repositories::cancelled());
+        };
+        let total_count = first.repositories.total_count;
+        let pages = max_results.div_ceil(usize::from(per_page.max(1))) as u32;
+        let mut repositories = self
+            .fetch_remaining_pages(query, per_page, first, Some(pages), &|_, _| {})
+            .await?;
+        repositories.items.truncate(max_results);
+        on_progress(repositories.items.len(), total_count);
+        Ok(repositories)
+    }
+
+    /// Fetches pages 2 through the `last` link of `first` (or `max_pages`)
+    /// concurrently and appends them, in page order, to the first page's
+    /// results.
+    async fn fetch_remaining_pages(
+        &self,
+        query: &str,
+        per_page: u8,
+        first: SearchPage,
+        max_pages: Option<u32>,
+        on_page: &dyn Fn(u32, u32),
+    ) -> Result<Repositories, BoxError> {
+        let last_page = first.links.last_page().unwrap_or(1);
+        let last_page = max_pages.map_or(last_page, |max| last_page.min(max));
+        let mut repositories = first.repositories;
+        on_page(1, last_page);
+
+        let mut tasks = JoinSet::new();
+        let semaphore = Arc::new(Semaphore::new(PAGE_CONCURRENCY));
+        for page in 2..=last_page {
+            let client = self.clone();
+            let query = query.to_string();
+            let semaphore = Arc::clone(&semaphore);
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                let page_result = client
+                    .search_repositories_page(&query, per_page, page)
+                    .await?;
+                Ok::<_, BoxError>((page, page_result.repositories))
+            });
+        }
+
+        let mut pages = Vec::new();
+        loop {
+            let result = tokio::select! {
+                result = tasks.join_next() => match result {
+                    Some(result) => result,
+                    None => break,
+                },
+                _ = self.cancel.cancelled() => {
+                    // The pages still in flight are dropped, and the results
+                    // say they are incomplete.
+                    tasks.abort_all();
+                    repositories.incomplete_results = true;
+                    break;
+                }
+            };
+            pages.push(result??);
+            on_page(pages.len() as u32 + 1, last_page);
+        }
+        pages.sort_by_key(|(page, _)| *page);
+
+        for (_, page) in pages {
+            repositories.incomplete_results |= page.incomplete_results;
+            repositories.items.extend(page.items);
+        }
+        Ok(repositories)
+    }
+
+    /// Fetches the raw README of `full_name` (`owner/repo`), or `None` if the
+    /// repository has none.
+    pub async fn readme(&self, full_name: &str) -> Result<Option<String>, BoxError> {
+        let url = format!("{}/repos/{}/readme", self.base_url, full_name);
+        let response = self.get(&url, "application/vnd.github.raw").await?;
+
+        if response.status == StatusCode::NOT_FOUND {
+            self.enriched(full_name, "readme");
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        self.enriched(full_name, "readme");
+        Ok(Some(String::from_utf8_lossy(&response.body).into_owned()))
+    }
+
+    /// Runs a GraphQL query and returns its `data`. GraphQL reports most
+    /// failures with a 200 and an `errors` list, which become the error.
+    pub async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, BoxError> {
+        let url = self.graphql_url();
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        let response = self
+            .send(Method::POST, &url, "application/json", Some(&body))
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+
+        let mut value: serde_json::Value = serde_json::from_slice(&response.body)?;
+        if let Some(errors) = value["errors"]
+            .as_array()
+            .filter(|errors| !errors.is_empty())
+        {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|error| error["message"].as_str())
+                .collect();
+            return Err(format!("GraphQL query failed: {}", messages.join("; ")).into());
+        }
+        Ok(serde_json::from_value(value["data"].take())?)
+    }
+
+    /// Files matching `query` (GitHub code search syntax), up to `limit`,
+    /// with the fragments that matched. Code search needs a token.
+    pub async fn search_code(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<SearchResults<CodeResult>, BoxError> {
+        self.search_items(
+            "code",
+            "application/vnd.github.text-match+json",
+            query,
+            limit,
+        )
+        .await
+    }
+
+    /// Issues and pull requests matching `query` (GitHub issue search
+    /// syntax), up to `limit`.
+    pub async fn search_issues(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<SearchResults<IssueResult>, BoxError> {
+        self.search_items("issues", "application/vnd.github+json", query, limit)
+            .await
+    }
+
+    /// Pages through `/search/{kind}` until `limit` items, the last match or
+    /// the [`SEARCH_RESULT_CAP`].
+    async fn search_items<T: DeserializeOwned>(
+        &self,
+        kind: &str,
+        accept: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<SearchResults<T>, BoxError> {
+        let per_page = limit.clamp(1, MAX_PER_PAGE as usize) as u8;
+        let mut results = SearchResults {
+            total_count: 0,
+            incomplete_results: false,
+            items: Vec::new(),
+        };
+        for page in 1.. {
+            let url = kind_search_url(&self.base_url, kind, query, per_page, page)?;
+            let response = self.get(url.as_str(), accept).await?;
+            if !response.status.is_success() {
+                return Err(request_error(url.as_str(), &response));
+            }
+            let page: SearchResults<T> = self
+                .stats()
+                .timings
+                .time_serialization(|| serde_json::from_slice(&response.body))?;
+            let fetched = page.items.len();
+            results.total_count = page.total_count;
+            results.incomplete_results |= page.incomplete_results;
+            results.items.extend(page.items);
+            let available = results.total_count.min(SEARCH_RESULT_CAP) as usize;
+            if fetched < per_page as usize
+                || results.items.len() >= limit
+                || results.items.len() >= available
+            {
+                break;
+            }
+        }
+        results.items.truncate(limit);
+        Ok(results)
+    }
+
+    /// Discussions matching `query` (GitHub search syntax), up to `limit`.
+    pub async fn search_discussions(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<Discussion>, BoxError> {
+        const QUERY: &str = "query($q: String!, $first: Int!, $after: String) {
+  search(query: $q, type: DISCUSSION, first: $first, after: $after) {
+    pageInfo { hasNextPage endCursor }
+    nodes {
+      ... on Discussion {
+        title url isAnswered
+        category { name }
+        repository { nameWithOwner }
+      }
+    }
+  }
+}";
+
+        let mut discussions = Vec::new();
+        let mut after: Option<String> = None;
+        while discussions.len() < limit {
+            let first = (limit - discussions.len()).min(MAX_PER_PAGE as usize);
+            let data: serde_json::Value = self
+                .graphql(
+                    QUERY,
+                    serde_json::json!({ "q": query, "first": first, "after": after }),
+                )
+                .await?;
+            let search = &data["search"];
+            let nodes: Vec<Discussion> = serde_json::from_value(search["nodes"].clone())?;
+            let fetched = nodes.len();
+            discussions.extend(nodes);
+
+            after = search["pageInfo"]["endCursor"].as_str().map(str::to_string);
+            if fetched == 0 || !search["pageInfo"]["hasNextPage"].as_bool().unwrap_or(false) {
+                break;
+            }
+        }
+        discussions.truncate(limit);
+        Ok(discussions)
+    }
+
+    /// The raw contents of the file at `path` on the default branch of
+    /// `full_name`, or `None` if there is no such file.
+    pub async fn file_contents(
+        &self,
+        full_name: &str,
+        path: &str,
+    ) -> Result<Option<String>, BoxError> {
+        let url = contents_url(&self.base_url, full_name, path)?;
+        let response = self.get(&url, "application/vnd.github.raw").await?;
+
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(Some(String::from_utf8_lossy(&response.body).into_owned()))
+    }
+
+    /// Whether `path` (a file or a directory) exists on the default branch
+    /// of `full_name`, according to the contents API.
+    pub async fn path_exists(&self, full_name: &str, path: &str) -> Result<bool, BoxError> {
+        let url = contents_url(&self.base_url, full_name, path)?;
+        let response = self.get(&url, "application/vnd.github+json").await?;
+
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(true)
+    }
+
+    /// The protection rules of `branch`. GitHub answers 404 both for an
+    /// unprotected branch and for a missing one, and 403 when the token may
+    /// not read the rules.
+    pub async fn branch_protection(
+        &self,
+        full_name: &str,
+        branch: &str,
+    ) -> Result<Protection, BoxError> {
+        let url = format!(
+            "{}/repos/{}/branches/{}/protection",
+            self.base_url, full_name, branch
+        );
+        let response = self.get(&url, "application/vnd.github+json").await?;
+
+        match response.status {
+            StatusCode::NOT_FOUND => Ok(Protection::Unprotected),
+            StatusCode::FORBIDDEN => Ok(Protection::NoAccess),
+            status if status.is_success() => Ok(Protection::Protected(serde_json::from_slice(
+                &response.body,
+            )?)),
+            _ => Err(request_error(&url, &response)),
+        }
+    }
+
+    /// The most recent GitHub Actions run on `branch`, or `None` if there
+    /// has been none.
+    pub async fn latest_workflow_run(
+        &self,
+        full_name: &str,
+        branch: &str,
+    ) -> Result<Option<WorkflowRun>, BoxError> {
+        let url = Url::parse_with_params(
+            &format!("{}/repos/{}/actions/runs", self.base_url, full_name),
+            &[("branch", branch), ("per_page", "1")],
+        )?;
+        let runs: Option<WorkflowRuns> = self.get_json(url.as_str()).await?;
+        Ok(runs.and_then(|runs| runs.workflow_runs.into_iter().next()))
+    }
+
+    pub async fn repository(&self, full_name: &str) -> Result<Option<Repository>, BoxError> {
+        self.get_json(&format!("{}/repos/{}", self.base_url, full_name))
+            .await
+    }
+
+    /// Changes the settings of `full_name` named in `changes`, e.g.
+    /// `{"has_wiki": false}`, and returns the repository as it is now.
+    pub async fn edit_repository(
+        &self,
+        full_name: &str,
+        changes: &serde_json::Value,
+    ) -> Result<Repository, BoxError> {
+        let url = format!("{}/repos/{}", self.base_url, full_name);
+        let response = self
+            .send(
+                Method::PATCH,
+                &url,
+                "application/vnd.github+json",
+                Some(changes),
+            )
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    /// Asks GitHub to move `full_name` to the user or organization
+    /// `new_owner`. GitHub finishes the move in the background, so the
+    /// repository may still be at its old name right after.
+    pub async fn transfer_repository(
+        &self,
+        full_name: &str,
+        new_owner: &str,
+    ) -> Result<(), BoxError> {
+        let url = format!("{}/repos/{}/transfer", self.base_url, full_name);
+        let body = serde_json::json!({ "new_owner": new_owner });
+        let response = self
+            .send(
+                Method::POST,
+                &url,
+                "application/vnd.github+json",
+                Some(&body),
+            )
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(())
+    }
+
+    /// Renames `branch` of `full_name` to `new_name`. GitHub retargets its
+    /// pull requests and branch protection, and redirects the old name.
+    pub async fn rename_branch(
+        &self,
+        full_name: &str,
+        branch: &str,
+        new_name: &str,
+    ) -> Result<(), BoxError> {
+        let url = format!(
+            "{}/repos/{}/branches/{}/rename",
+            self.base_url, full_name, branch
+        );
+        let body = serde_json::json!({ "new_name": new_name });
+        let response = self
+            .send(
+                Method::POST,
+                &url,
+                "application/vnd.github+json",
+                Some(&body),
+            )
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(())
+    }
+
+    /// Bytes of code per language, as reported by GitHub's linguist.
+    pub async fn languages(&self, full_name: &str) -> Result<BTreeMap<String, u64>, BoxError> {
+        let url = format!("{}/repos/{}/languages", self.base_url, full_name);
+        let languages = self.get_json(&url).await?.unwrap_or_default();
+        self.enriched(full_name, "languages");
+        Ok(languages)
+    }
+
+    /// The most recent published release, or `None` if there is none.
+    pub async fn latest_release(&self, full_name: &str) -> Result<Option<Release>, BoxError> {
+        let release = self
+            .get_json(&format!(
+                "{}/repos/{}/releases/latest",
+                self.base_url, full_name
+            ))
+            .await?;
+        self.enriched(full_name, "release");
+        Ok(release)
+    }
+
+    /// Commits per week over the last year, oldest first. GitHub computes these
+    /// statistics on demand and answers 202 until they are ready, so this
+    /// polls a few times; `None` means they were still not ready (or the
+    /// repository does not exist).
+    pub async fn commit_activity(
+        &self,
+        full_name: &str,
+    ) -> Result<Option<Vec<WeeklyCommits>>, BoxError> {
+        let url = format!(
+            "{}/repos/{}/stats/commit_activity",
+            self.base_url, full_name
+        );
+        for attempt in 0..STATS_POLL_ATTEMPTS {
+            let response = self.get(&url, "application/vnd.github+json").await?;
+            match response.status {
+                StatusCode::ACCEPTED => {
+                    if attempt + 1 < STATS_POLL_ATTEMPTS {
+                        tokio::time::sleep(STATS_POLL_DELAY).await;
+                    }
+                }
+                // An empty repository has no statistics to compute.
+                StatusCode::NO_CONTENT => {
+                    self.enriched(full_name, "activity");
+                    return Ok(Some(Vec::new()));
+                }
+                StatusCode::NOT_FOUND => return Ok(None),
+                status if status.is_success() => {
+                    let weeks = self
+                        .stats()
+                        .timings
+                        .time_serialization(|| serde_json::from_slice(&response.body))?;
+                    self.enriched(full_name, "activity");
+                    return Ok(Some(weeks));
+                }
+                _ => return Err(request_error(&url, &response)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Open issues of `full_name` carrying every one of `labels`. Pull
+    /// requests, which the issues API lists as well, are left out.
+    pub async fn open_issues(
+        &self,
+        full_name: &str,
+        labels: &[&str],
+    ) -> Result<Vec<Issue>, BoxError> {
+        let url = Url::parse_with_params(
+            &format!("{}/repos/{}/issues", self.base_url, full_name),
+            [
+                ("state", "open"),
+                ("labels", &labels.join(",")),
+                ("per_page", &MAX_PER_PAGE.to_string()),
+            ],
+        )?;
+        let issues: Vec<Issue> = self.get_paginated(url.as_str()).await?.unwrap_or_default();
+        Ok(issues
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .collect())
+    }
+
+    /// Opens an issue in `full_name`, with `labels` that must already exist.
+    pub async fn create_issue(
+        &self,
+        full_name: &str,
+        title: &str,
+        body: &str,
+        labels: &[String],
+    ) -> Result<Issue, BoxError> {
+        let url = format!("{}/repos/{}/issues", self.base_url, full_name);
+        let body = serde_json::json!({ "title": title, "body": body, "labels": labels });
+        let response = self
+            .send(
+                Method::POST,
+                &url,
+                "application/vnd.github+json",
+                Some(&body),
+            )
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    /// The `count` most recently opened issues of `full_name`, open or
+    /// closed, pull requests left out.
+    pub async fn recent_issues(&self, full_name: &str, count: u8) -> Result<Vec<Issue>, BoxError> {
+        let url = Url::parse_with_params(
+            &format!("{}/repos/{}/issues", self.base_url, full_name),
+            [
+                ("state", "all"),
+                ("sort", "created"),
+                ("direction", "desc"),
+                ("per_page", &count.min(MAX_PER_PAGE).to_string()),
+            ],
+        )?;
+        let issues: Vec<Issue> = self.get_json(url.as_str()).await?.unwrap_or_default();
+        Ok(issues
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .collect())
+    }
+
+    /// The first `count` comments on issue `number` of `full_name`, oldest
+    /// first.
+    pub async fn issue_comments(
+        &self,
+        full_name: &str,
+        number: u64,
+        count: u8,
+    ) -> Result<Vec<IssueComment>, BoxError> {
+        let url = format!(
+            "{}/repos/{}/issues/{}/comments?per_page={}",
+            self.base_url,
+            full_name,
+            number,
+            count.min(MAX_PER_PAGE)
+        );
+        Ok(self.get_json(&url).await?.unwrap_or_default())
+    }
+
+    /// Downloads an avatar image, scaled by GitHub to `size` pixels square.
+    pub async fn avatar(&self, avatar_url: &str, size: u32) -> Result<Vec<u8>, BoxError> {
+        let mut url = Url::parse(avatar_url)?;
+        url.query_pairs_mut().append_pair("s", &size.to_string());
+        self.download(url.as_str()).await
+    }
+
+    /// Downloads `url` without the API token, for files such as release
+    /// assets and avatars that GitHub serves from other hosts.
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, BoxError> {
+        self.check_online(url)?;
+        let response = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &*self.user_agent)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// The user the access token belongs to.
+    pub async fn authenticated_user(&self) -> Result<User, BoxError> {
+        let url = format!("{}/user", self.base_url);
+        self.get_json(&url)
+            .await?
+            .ok_or_else(|| format!("{} found no user; is the token valid?", url).into())
+    }
+
+    /// The organizations the authenticated user is a member of, with only
+    /// their logins and avatars.
+    pub async fn user_orgs(&self) -> Result<Vec<User>, BoxError> {
+        let url = format!("{}/user/orgs?per_page={}", self.base_url, MAX_PER_PAGE);
+        Ok(self.get_paginated(&url).await?.unwrap_or_default())
+    }
+
+    /// Whether the authenticated user has starred `full_name`.
+    pub async fn is_starred(&self, full_name: &str) -> Result<bool, BoxError> {
+        let url = format!("{}/user/starred/{}", self.base_url, full_name);
+        let response = self.get(&url, "application/vnd.github+json").await?;
+        let starred = match response.status {
+            StatusCode::NO_CONTENT => true,
+            StatusCode::NOT_FOUND => false,
+            _ => return Err(request_error(&url, &response)),
+        };
+        self.enriched(full_name, "starred");
+        Ok(starred)
+    }
+
+    /// The authenticated user's notifications, newest first: only the unread
+    /// ones unless `all`, and only those of `full_name` if given.
+    pub async fn notifications(
+        &self,
+        all: bool,
+        full_name: Option<&str>,
+    ) -> Result<Vec<Notification>, BoxError> {
+        let url = match full_name {
+            Some(full_name) => format!("{}/repos/{}/notifications", self.base_url, full_name),
+            None => format!("{}/notifications", self.base_url),
+        };
+        let url = Url::parse_with_params(
+            &url,
+            [
+                ("all", all.to_string()),
+                ("per_page", MAX_PER_PAGE.to_string()),
+            ],
+        )?;
+        self.get_paginated(url.as_str()).await?.ok_or_else(|| {
+            format!("{} does not exist", full_name.unwrap_or("notifications")).into()
+        })
+    }
+
+    /// Marks the notification thread `id` as read.
+    pub async fn mark_notification_read(&self, id: &str) -> Result<(), BoxError> {
+        let url = format!("{}/notifications/threads/{}", self.base_url, id);
+        let response = self
+            .send(Method::PATCH, &url, "application/vnd.github+json", None)
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(())
+    }
+
+    /// The topics of `full_name` as they are now, unlike the possibly cached
+    /// ones of a search result.
+    pub async fn topics(&self, full_name: &str) -> Result<Vec<String>, BoxError> {
+        let url = format!("{}/repos/{}/topics", self.base_url, full_name);
+        let response = self.get(&url, "application/vnd.github+json").await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(serde_json::from_slice::<Topics>(&response.body)?.names)
+    }
+
+    /// Sets the topics of `full_name` to `topics`, replacing the ones it
+    /// had, and returns them as GitHub stored them.
+    pub async fn replace_topics(
+        &self,
+        full_name: &str,
+        topics: &[String],
+    ) -> Result<Vec<String>, BoxError> {
+        let url = format!("{}/repos/{}/topics", self.base_url, full_name);
+        let body = serde_json::json!({ "names": topics });
+        let response = self
+            .send(
+                Method::PUT,
+                &url,
+                "application/vnd.github+json",
+                Some(&body),
+            )
+            .await?;
+        if !response.status.is_success() {
+            return Err(request_error(&url, &response));
+        }
+        Ok(serde_json::from_slice::<Topics>(&response.body)?.names)
+    }
+
+    /// Every label defined in `full_name`.
+    pub async fn labels(&self, full_name: &str) -> Result<Vec<Label>, BoxError> {
+        Ok(self
+            .get_paginated(&format!(
+                "{}/repos/{}/labels?per_page={}",
+                self.base_url, full_name, MAX_PER_PAGE
+            ))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// The milestones of `full_name` in `state` (`open`, `closed` or `all`).
+    pub async fn milestones(
+        &self,
+        full_name: &str,
+        state: &str,
+    ) -> Result<Vec<Milestone>, BoxError> {
+        let url = Url::parse_with_params(
+            &format!("{}/repos/{}/milestones", self.base_url, full_name),
+            [("state", state), ("per_page", &MAX_PER_PAGE.to_string())],
+        )?;
+        Ok(self.get_paginated(url.as_str()).await?.unwrap_or_default())
+    }
+
+    /// Every release of `full_name`, newest first.
+    pub async fn releases(&self, full_name: &str) -> Result<Vec<Release>, BoxError> {
+        Ok(self
+            .get_paginated(&format!(
+                "{}/repos/{}/releases?per_page={}",
+                self.base_url, full_name, MAX_PER_PAGE
+            ))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Public members of `org`.
+    pub async fn org_members(&self, org: &str) -> Result<Vec<User>, BoxError> {
+        self.get_paginated(&format!(
+            "{}/orgs/{}/members?per_page={}",
+            self.base_url, org, MAX_PER_PAGE
+        ))
+        .await?
+        .ok_or_else(|| format!("organization {} not found", org).into())
+    }
+
+    /// Repositories owned by `login` (not ones they merely contribute to).
+    pub async fn user_repositories(&self, login: &str) -> Result<Vec<Repository>, BoxError> {
+        Ok(self
+            .get_paginated(&format!(
+                "{}/users/{}/repos?type=owner&per_page={}",
+                self.base_url, login, MAX_PER_PAGE
+            ))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Repositories the team `team_slug` in `org` has access to.
+    pub async fn team_repositories(
+        &self,
+        org: &str,
+        team_slug: &str,
+    ) -> Result<Vec<Repository>, BoxError> {
+        let url = format!(
+            "{}/orgs/{}/teams/{}/repos?per_page={}",
+            self.base_url, org, team_slug, MAX_PER_PAGE
+        );
+        self.get_paginated(&url).await?.ok_or_else(|| {
+            format!(
+                "team {}/{} not found (or not visible to this token)",
+                org, team_slug
+            )
+            .into()
+        })
+    }
+
+    /// GETs a list endpoint and follows its `next` links until exhausted,
+    /// concatenating every page. Returns `None` if the list does not exist.
+    pub async fn get_paginated<T: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<Option<Vec<T>>, BoxError> {
+        let mut items = Vec::new();
+        let mut next = Some(url.to_string());
+
+        while let Some(url) = next {
+            let response = self.get(&url, "application/vnd.github+json").await?;
+            if response.status == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            if !response.status.is_success() {
+                return Err(request_error(&url, &response));
+            }
+
+            next = response
+                .headers
+                .get(header::LINK)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| PageLinks::parse(value).next);
+            let page: Vec<T> = self
+                .stats()
+                .timings
+                .time_serialization(|| serde_json::from_slice(&response.body))?;
+            self.page_fetched(&url, page.len());
+            items.extend(page);
+        }
+
+        Ok(Some(items))
+    }
+
+    /// GETs `url` and deserializes the JSON body, mapping 404 to `None`.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<Option<T>, BoxError> {
+        let response = self.get(url, "application/vnd.github+json").await?;
+        if response.status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(request_error(url, &response));
+        }
+        let value = self
+            .stats()
+            .timings
+            .time_serialization(|| serde_json::from_slice(&response.body))?;
+        Ok(Some(value))
+    }
+}
+
+/// Describes a failed request as an [`ApiError`], including GitHub's own
+/// `message` when present.
+pub(crate) fn request_error(url: &str, response: &ApiResponse) -> BoxError {
+    ApiError::from_response(url, response).into()
+}
+
+/// Builds the repository search URL, percent-encoding the query so spaces,
+/// `+`, `#` and friends reach GitHub intact.
+fn search_url(
+    base_url: &str,
+    query: &str,
+    per_page: u8,
+    page: u32,
+) -> Result<Url, url::ParseError> {
+    kind_search_url(base_url, "repositories", query, per_page, page)
+}
+
+/// The URL of the contents API for `path` in `full_name`, each segment
+/// percent-encoded so `#`, `?`, `%` and spaces name the file they are in.
+fn contents_url(base_url: &str, full_name: &str, path: &str) -> Result<String, BoxError> {
+    let mut url = Url::parse(&format!("{}/repos/{}/contents", base_url, full_name))?;
+    url.path_segments_mut()
+        .map_err(|()| format!("`{}` cannot have a path", base_url))?
+        .extend(path.split('/').filter(|segment| !segment.is_empty()));
+    Ok(url.into())
+}
+
+/// The URL of `/search/{kind}`, e.g. `code` or `issues`.
+fn kind_search_url(
+    base_url: &str,
+    kind: &str,
+    query: &str,
+    per_page: u8,
+    page: u32,
+) -> Result<Url, url::ParseError> {
+    let mut params = vec![("q", query.to_string()), ("per_page", per_page.to_string())];
+    if page > 1 {
+        params.push(("page", page.to_string()));
+    }
+    Url::parse_with_params(&format!("{}/search/{}", base_url, kind), &params)
+}
+
+/// Returns how long to sleep before retrying when `response` was rejected by
+/// the rate limiter, or `None` if it was not (or the reset is too far away).
+fn rate_limit_wait(response: &Response) -> Option<Duration> {
+    let status = response.status();
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let header_secs = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+
+    let wait = if let Some(retry_after) = header_secs("retry-after") {
+        Duration::from_secs(retry_after)
+    } else if header_secs("x-ratelimit-remaining") == Some(0) {
+        let reset = header_secs("x-ratelimit-reset")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Duration::from_secs(reset.saturating_sub(now) + 1)
+    } else {
+        return None;
+    };
+
+    (wait <= MAX_RATE_LIMIT_WAIT).then_some(wait)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded_query(query: &str) -> String {
+        let url = search_url(DEFAULT_API_URL, query, 100, 1).unwrap();
+        url.query().unwrap().to_string()
+    }
+
+    #[test]
+    fn search_url_keeps_plain_queries_readable() {
+        assert_eq!(
+            encoded_query("user:octocat"),
+            "q=user%3Aoctocat&per_page=100"
+        );
+    }
+
+    #[test]
+    fn search_url_encodes_spaces_and_quotes() {
+        assert_eq!(
+            encoded_query("topic:\"machine learning\" user:a"),
+            "q=topic%3A%22machine+learning%22+user%3Aa&per_page=100"
+        );
+    }
+
+    #[test]
+    fn search_url_encodes_plus_and_hash() {
+        assert_eq!(encoded_query("c++ c#"), "q=c%2B%2B+c%23&per_page=100");
+    }
+
+    #[test]
+    fn contents_url_encodes_each_path_segment() {
+        let url = |path: &str| contents_url(DEFAULT_API_URL, "octo/ghs", path).unwrap();
+        assert_eq!(
+            url("/.github/CODEOWNERS"),
+            "https://api.github.com/repos/octo/ghs/contents/.github/CODEOWNERS"
+        );
+        assert_eq!(
+            url("docs/C# notes/100%?.md"),
+            "https://api.github.com/repos/octo/ghs/contents/docs/C%23%20notes/100%25%3F.md"
+        );
+        assert_eq!(
+            contents_url("https://ghe.example/api/v3", "octo/ghs", "a b").unwrap(),
+            "https://ghe.example/api/v3/repos/octo/ghs/contents/a%20b"
+        );
+    }
+
+    #[test]
+    fn search_url_cannot_inject_parameters() {
+        let url = search_url(DEFAULT_API_URL, "a&per_page=1", 100, 1).unwrap();
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("q".to_string(), "a&per_page=1".to_string()),
+                ("per_page".to_string(), "100".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_url_adds_page_after_the_first() {
+        let url = search_url(DEFAULT_API_URL, "user:a", 50, 3).unwrap();
+        assert_eq!(url.query(), Some("q=user%3Aa&per_page=50&page=3"));
+    }
+
+    #[test]
+    fn search_url_round_trips_unicode() {
+        let url = search_url(DEFAULT_API_URL, "user:日本", 100, 1).unwrap();
+        let (_, query) = url.query_pairs().next().unwrap();
+        assert_eq!(query, "user:日本");
+    }
+
+    #[test]
+    fn graphql_url_follows_the_rest_host() {
+        let graphql_url = |host: &str| {
+            GhsClient::builder()
+                .host(host)
+                .build()
+                .unwrap()
+                .graphql_url()
+        };
+        assert_eq!(graphql_url("github.com"), "https://api.github.com/graphql");
+        assert_eq!(
+            graphql_url("ghe.example.com"),
+            "https://ghe.example.com/api/graphql"
+        );
+    }
+}
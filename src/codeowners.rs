@@ -0,0 +1,188 @@
+use ghs::{BoxError, GhsClient};
+
+/// Where GitHub looks for a CODEOWNERS file, in the order it checks them.
+pub const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// One `pattern @owner...` line.
+#[derive(Debug)]
+pub struct Rule {
+    pub line: usize,
+    pub pattern: String,
+    /// Empty when the rule deliberately leaves paths without an owner.
+    pub owners: Vec<String>,
+}
+
+/// A repository's CODEOWNERS file and where it was found.
+#[derive(Debug)]
+pub struct CodeOwners {
+    pub location: &'static str,
+    pub rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Fetches the first CODEOWNERS file GitHub would use, or `None` if the
+    /// repository has none.
+    pub async fn fetch(api: &GhsClient, full_name: &str) -> Result<Option<CodeOwners>, BoxError> {
+        for location in LOCATIONS {
+            if let Some(text) = api.file_contents(full_name, location).await? {
+                return Ok(Some(CodeOwners {
+                    location,
+                    rules: parse(&text),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The rule that decides who owns `path`: as in GitHub, the last
+    /// matching one wins.
+    pub fn owner_of(&self, path: &str) -> Option<&Rule> {
+        let path: Vec<&str> = path
+            .trim_start_matches("./")
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| pattern_matches(&rule.pattern, &path))
+    }
+}
+
+pub fn parse(text: &str) -> Vec<Rule> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line_text = line.split_once(" #").map_or(line, |(rule, _)| rule);
+            let mut words = line_text.split_whitespace();
+            let pattern = words.next().filter(|word| !word.starts_with('#'))?;
+            Some(Rule {
+                line: index + 1,
+                pattern: pattern.to_string(),
+                owners: words.map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Matches a gitignore-style pattern against a path split into segments.
+/// Patterns with a leading or inner slash are anchored to the repository
+/// root; others match at any depth. A pattern also matches everything under
+/// a directory it names, except that `dir/*` covers direct children only
+/// and `dir/` covers nothing but its contents.
+fn pattern_matches(pattern: &str, path: &[&str]) -> bool {
+    let directory_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_matches('/');
+    let anchored = pattern.starts_with('/') || trimmed.contains('/');
+    let segments: Vec<&str> = trimmed.split('/').collect();
+    let contents = segments.last() != Some(&"*");
+
+    let matches_at =
+        |start: usize| match_segments(&segments, &path[start..], contents, directory_only);
+    if anchored {
+        matches_at(0)
+    } else {
+        (0..path.len()).any(matches_at)
+    }
+}
+
+fn match_segments(pattern: &[&str], path: &[&str], contents: bool, directory_only: bool) -> bool {
+    match pattern.split_first() {
+        None if directory_only => !path.is_empty(),
+        None => path.is_empty() || contents,
+        Some((&"**", rest)) => (0..=path.len())
+            .any(|skip| match_segments(rest, &path[skip..], contents, directory_only)),
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path)| {
+            glob(segment.as_bytes(), name.as_bytes())
+                && match_segments(rest, path, contents, directory_only)
+        }),
+    }
+}
+
+/// `*` and `?` wildcards within a single path segment.
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| glob(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && glob(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && glob(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').collect();
+        pattern_matches(pattern, &path)
+    }
+
+    fn owners(text: &str, path: &str) -> Option<Vec<String>> {
+        let codeowners = CodeOwners {
+            location: LOCATIONS[0],
+            rules: parse(text),
+        };
+        codeowners.owner_of(path).map(|rule| rule.owners.clone())
+    }
+
+    #[test]
+    fn parses_rules_and_skips_comments() {
+        let rules = parse("# Owners\n\n*.rs @octo @cat # Rust\n/docs/\n");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].line, 3);
+        assert_eq!(rules[0].pattern, "*.rs");
+        assert_eq!(rules[0].owners, ["@octo", "@cat"]);
+        assert_eq!(rules[1].line, 4);
+        assert!(rules[1].owners.is_empty());
+    }
+
+    #[test]
+    fn slashes_anchor_patterns_to_the_root() {
+        assert!(matches("build", "build"));
+        assert!(matches("build", "src/build"));
+        assert!(matches("/build", "build/out.o"));
+        assert!(!matches("/build", "src/build"));
+        assert!(matches("src/build", "src/build/out.o"));
+        assert!(!matches("src/build", "lib/src/build"));
+    }
+
+    #[test]
+    fn names_cover_what_is_under_them_but_dir_star_only_children() {
+        assert!(matches("docs", "docs/guide/intro.md"));
+        assert!(matches("docs/*", "docs/intro.md"));
+        assert!(!matches("docs/*", "docs/guide/intro.md"));
+        assert!(matches("*.md", "docs/guide/intro.md"));
+        assert!(!matches("*.md", "docs/guide/intro.mdx"));
+        assert!(matches("v?.txt", "v1.txt"));
+        assert!(!matches("v?.txt", "v10.txt"));
+    }
+
+    #[test]
+    fn a_trailing_slash_matches_only_a_directory() {
+        assert!(matches("logs/", "logs/today.log"));
+        assert!(matches("logs/", "app/logs/today.log"));
+        assert!(!matches("logs/", "logs"));
+        assert!(!matches("/logs/", "app/logs/today.log"));
+    }
+
+    #[test]
+    fn double_stars_span_any_number_of_directories() {
+        assert!(matches("**/logs", "logs"));
+        assert!(matches("**/logs", "a/b/logs/today.log"));
+        assert!(matches("docs/**/*.md", "docs/intro.md"));
+        assert!(matches("docs/**/*.md", "docs/a/b/intro.md"));
+        assert!(!matches("docs/**/*.md", "src/docs/intro.md"));
+    }
+
+    #[test]
+    fn the_last_matching_rule_wins() {
+        let text = "* @everyone\n/docs/ @writers\n/docs/api/ \n*.rs @rustaceans\n";
+        assert_eq!(owners(text, "README.md").unwrap(), ["@everyone"]);
+        assert_eq!(owners(text, "docs/guide.md").unwrap(), ["@writers"]);
+        // An owner-less rule later on takes the owners away again.
+        assert!(owners(text, "docs/api/index.md").unwrap().is_empty());
+        assert_eq!(owners(text, "./docs/api/lib.rs").unwrap(), ["@rustaceans"]);
+        assert_eq!(owners("/docs/ @writers", "src/main.rs"), None);
+    }
+}
@@ -0,0 +1,28 @@
+use crate::console;
+use crate::plain;
+use std::env;
+use std::io::{self, IsTerminal};
+
+pub const GREEN: &str = "32";
+pub const YELLOW: &str = "33";
+pub const RED: &str = "31";
+pub const DIM: &str = "2";
+
+/// Whether ANSI colors should be written to stdout: only for terminals, and
+/// never when `NO_COLOR` is set, in plain mode, or on a console that would
+/// print the escape sequences verbatim.
+pub fn enabled() -> bool {
+    !plain::enabled()
+        && console::supports_ansi()
+        && env::var_os("NO_COLOR").is_none()
+        && io::stdout().is_terminal()
+}
+
+/// Wraps `text` in the given SGR color code when colors are enabled.
+pub fn paint(text: &str, code: &str) -> String {
+    if enabled() {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,44 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{audit, interrupt};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, audit_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    let org = audit_matches.get_one::<String>("org").unwrap();
+    let required: Vec<String> = audit_matches
+        .get_many::<String>("require")
+        .unwrap()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect();
+
+    let search_query = SearchQuery::new().org(org).build()?;
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let repositories = filter_by_args(repositories.items, audit_matches, exclude_archived);
+    let results = interrupt::or_exit(audit::audit_files(api, &repositories, &required)).await?;
+    audit::print_matrix(&results, &required);
+
+    let failing = results.iter().filter(|c| !c.is_compliant()).count();
+    if failing > 0 {
+        return Err(format!(
+            "{} of {} repositories are missing required files",
+            failing,
+            results.len()
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use super::Context;
+use crate::bench;
+use crate::fetch::fetch_repositories;
+use clap::ArgMatches;
+use ghs::client;
+use ghs::BoxError;
+use std::time::Instant;
+
+pub async fn run(ctx: &Context, bench_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let provider = ctx.provider();
+    let iterations = *bench_matches.get_one::<u32>("iterations").unwrap();
+    let queries: Vec<&str> = match bench_matches.get_many::<String>("queries") {
+        Some(queries) => queries.map(String::as_str).collect(),
+        None => bench::QUERIES.to_vec(),
+    };
+    let mut samples = Vec::with_capacity(queries.len());
+    for query in queries {
+        let requests_before = api.stats().timings.request_count();
+        let mut durations = Vec::with_capacity(iterations as usize);
+        for _ in 0..iterations {
+            let started = Instant::now();
+            fetch_repositories(
+                provider,
+                cache.as_ref(),
+                query,
+                client::MAX_PER_PAGE,
+                false,
+                None,
+            )
+            .await?;
+            durations.push(started.elapsed());
+        }
+        samples.push(bench::Sample {
+            query: query.to_string(),
+            durations,
+            requests: api.stats().timings.request_count() - requests_before,
+        });
+    }
+    print!("{}", bench::report(&samples));
+    Ok(())
+}
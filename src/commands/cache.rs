@@ -0,0 +1,18 @@
+use super::Context;
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, cache_matches: &ArgMatches) -> Result<(), BoxError> {
+    let cache = &ctx.cache;
+    match cache_matches.subcommand() {
+        Some(("clear", _)) => {
+            let cache = cache
+                .as_ref()
+                .ok_or("there is no cache directory to clear")?;
+            let (removed, dir) = cache.clear()?;
+            eprintln!("Removed {} cached searches from {}", removed, dir);
+        }
+        _ => unreachable!("clap requires a cache subcommand"),
+    }
+    Ok(())
+}
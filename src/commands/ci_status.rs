@@ -0,0 +1,29 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{ci, interrupt};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, ci_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    let org = ci_matches.get_one::<String>("org").unwrap();
+    let branch = ci_matches.get_one::<String>("branch").map(String::as_str);
+
+    let search_query = SearchQuery::new().org(org).build()?;
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let repositories = filter_by_args(repositories.items, ci_matches, exclude_archived);
+    let statuses = interrupt::or_exit(ci::fetch_statuses(api, &repositories, branch)).await?;
+    ci::print_dashboard(&statuses);
+    Ok(())
+}
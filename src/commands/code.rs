@@ -0,0 +1,31 @@
+use super::{lint, report_truncation, scoped_query, Context};
+use crate::{interrupt, listing, output};
+use clap::ArgMatches;
+use ghs::query;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, code_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let mut search_query = scoped_query(code_matches);
+    for (qualifier, id) in [
+        ("language", "language"),
+        ("path", "path"),
+        ("extension", "extension"),
+        ("filename", "filename"),
+    ] {
+        if let Some(value) = code_matches.get_one::<String>(id) {
+            search_query = search_query.qualifier(qualifier, value);
+        }
+    }
+    let limit = *code_matches.get_one::<usize>("limit").unwrap();
+    let search_query = search_query.build()?;
+    lint(query::lint(&search_query, "code"))?;
+    let results = interrupt::or_exit(api.search_code(&search_query, limit)).await?;
+    report_truncation(results.items.len(), results.total_count);
+    listing::write(
+        &results.items,
+        code_matches.get_one::<output::Format>("format").copied(),
+        code_matches.get_one::<String>("output").map(String::as_str),
+    )?;
+    Ok(())
+}
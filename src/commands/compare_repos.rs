@@ -0,0 +1,16 @@
+use super::Context;
+use crate::{compare, details, interrupt};
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, compare_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let full_names: Vec<String> = compare_matches
+        .get_many::<String>("repos")
+        .unwrap()
+        .cloned()
+        .collect();
+    let details = interrupt::or_exit(details::fetch_all_details(api, &full_names)).await?;
+    compare::print_comparison(&details);
+    Ok(())
+}
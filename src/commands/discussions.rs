@@ -0,0 +1,21 @@
+use super::Context;
+use crate::{discussions, interrupt};
+use clap::ArgMatches;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, discussions_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let mut search_query =
+        SearchQuery::new().keywords(discussions_matches.get_one::<String>("query").unwrap());
+    if let Some(user) = discussions_matches.get_one::<String>("user") {
+        search_query = search_query.user(user);
+    }
+    if let Some(org) = discussions_matches.get_one::<String>("org") {
+        search_query = search_query.org(org);
+    }
+    let limit = *discussions_matches.get_one::<usize>("limit").unwrap();
+    let discussions =
+        interrupt::or_exit(api.search_discussions(&search_query.build()?, limit)).await?;
+    discussions::print_discussions(&discussions);
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use super::Context;
+use crate::dupes;
+use crate::fetch::fetch_repositories;
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, dupes_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let org = dupes_matches.get_one::<String>("org").unwrap();
+    let threshold = *dupes_matches.get_one::<f64>("threshold").unwrap();
+
+    let search_query = SearchQuery::new().org(org).build()?;
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+
+    let pairs = dupes::find_duplicates(&repositories.items, threshold);
+    dupes::print_duplicates(&pairs);
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use crate::store;
+use clap::ArgMatches;
+use ghs::BoxError;
+use std::path::Path;
+
+pub fn run(export_matches: &ArgMatches) -> Result<(), BoxError> {
+    match export_matches.subcommand() {
+        Some(("parquet", parquet_matches)) => {
+            let db = parquet_matches.get_one::<String>("db").unwrap();
+            let table = parquet_matches.get_one::<String>("table").unwrap();
+            let file = parquet_matches.get_one::<String>("file").unwrap();
+            let rows = store::export_parquet(Path::new(db), table, Path::new(file))?;
+            eprintln!("Wrote {} rows of {} to {}", rows, table, file);
+        }
+        _ => unreachable!("clap requires an export subcommand"),
+    }
+    Ok(())
+}
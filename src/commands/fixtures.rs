@@ -0,0 +1,39 @@
+use super::Context;
+use crate::interrupt;
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, fixtures_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let provider = ctx.provider();
+    match fixtures_matches.subcommand() {
+        Some(("generate", generate_matches)) => {
+            let query = generate_matches.get_one::<String>("query").unwrap();
+            let per_page = *generate_matches.get_one::<u8>("per-page").unwrap();
+            interrupt::or_exit(provider.search_page(query, per_page, 1)).await?;
+            let paths: Vec<&String> = generate_matches
+                .get_many("get")
+                .into_iter()
+                .flatten()
+                .collect();
+            for path in &paths {
+                let url = format!("{}/{}", api.base_url(), path.trim_start_matches('/'));
+                let response =
+                    interrupt::or_exit(api.get(&url, "application/vnd.github+json")).await?;
+                if !response.status.is_success() {
+                    eprintln!(
+                        "warning: {} answered {}; recorded anyway",
+                        path, response.status
+                    );
+                }
+            }
+            eprintln!(
+                "Recorded {} responses in {}",
+                paths.len() + 1,
+                generate_matches.get_one::<String>("dir").unwrap()
+            );
+        }
+        _ => unreachable!("clap requires a fixtures subcommand"),
+    }
+    Ok(())
+}
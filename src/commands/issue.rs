@@ -0,0 +1,115 @@
+use super::{origin_repository, Context};
+use crate::fetch::fetch_repositories;
+use crate::predicate::Predicate;
+use crate::{exec, interrupt};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, Repository};
+use std::fs;
+
+pub async fn run(ctx: &Context, issue_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let bulk_threshold = ctx.bulk_threshold;
+    match issue_matches.subcommand() {
+        Some(("create", create_matches)) => {
+            let title = create_matches.get_one::<String>("title").unwrap();
+            let body = match create_matches.get_one::<String>("body-file") {
+                Some(path) => fs::read_to_string(path)
+                    .map_err(|err| format!("could not read {}: {}", path, err))?,
+                None => create_matches
+                    .get_one::<String>("body")
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            let labels: Vec<String> = create_matches
+                .get_many::<String>("label")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+            let repositories = match create_matches.get_one::<String>("query") {
+                Some(query) => {
+                    // Never a cached listing: it may be out of date.
+                    let (repositories, _) =
+                        fetch_repositories(api, None, query, client::MAX_PER_PAGE, true, None)
+                            .await?;
+                    let predicates: Vec<&Predicate> = create_matches
+                        .get_many::<Predicate>("where")
+                        .unwrap_or_default()
+                        .collect();
+                    // Archived repositories take no new issues.
+                    let mut repositories = repositories.items;
+                    repositories.retain(|repo| {
+                        !repo.archived && predicates.iter().all(|predicate| predicate.matches(repo))
+                    });
+                    repositories
+                }
+                None => {
+                    let full_name = match create_matches.get_one::<String>("repo") {
+                        Some(full_name) => full_name.clone(),
+                        None => origin_repository()?,
+                    };
+                    let repo = interrupt::or_exit(api.repository(&full_name))
+                        .await?
+                        .ok_or_else(|| format!("{} does not exist", full_name))?;
+                    vec![repo]
+                }
+            };
+            if repositories.is_empty() {
+                return Err("no repository to open the issue in".into());
+            }
+            // Only an issue opened across a search is a template, and
+            // every one is checked before the first issue opens.
+            let templated = create_matches.contains_id("query");
+            let fill = |text: &str, repo: &Repository| match templated {
+                true => exec::fill(text, repo),
+                false => Ok(text.to_string()),
+            };
+            let issues = repositories
+                .iter()
+                .map(|repo| {
+                    Ok((
+                        repo.full_name.as_str(),
+                        fill(title, repo)?,
+                        fill(&body, repo)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            if create_matches.get_flag("dry-run") {
+                for (full_name, title, body) in &issues {
+                    println!("{}: {}\n{}\n", full_name, title, body.trim_end());
+                }
+                return Ok(());
+            }
+            let names: Vec<&str> = issues.iter().map(|(full_name, _, _)| *full_name).collect();
+            if !exec::confirm_bulk(
+                &format!("open \"{}\" in", title),
+                &names,
+                bulk_threshold,
+                create_matches.get_flag("yes"),
+            )? {
+                return Ok(());
+            }
+            let mut failures = 0;
+            for (full_name, title, body) in &issues {
+                match interrupt::or_exit(api.create_issue(full_name, title, body, &labels)).await {
+                    Ok(issue) => println!("{}", issue.html_url),
+                    Err(err) => {
+                        eprintln!("warning: {}", err);
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(format!(
+                    "{} of {} issues could not be opened",
+                    failures,
+                    issues.len()
+                )
+                .into());
+            }
+        }
+        _ => unreachable!("clap requires an issue subcommand"),
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use super::{lint, report_truncation, scoped_query, Context};
+use crate::{interrupt, listing, output};
+use clap::ArgMatches;
+use ghs::query;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, issues_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let mut search_query = scoped_query(issues_matches);
+    if let Some(state) = issues_matches.get_one::<String>("state") {
+        search_query = search_query.qualifier("state", state);
+    }
+    if let Some(kind) = issues_matches.get_one::<String>("type") {
+        search_query = search_query.qualifier("type", kind);
+    }
+    if let Some(author) = issues_matches.get_one::<String>("author") {
+        search_query = search_query.qualifier("author", author);
+    }
+    for label in issues_matches
+        .get_many::<String>("label")
+        .unwrap_or_default()
+    {
+        search_query = search_query.qualifier("label", label);
+    }
+    let limit = *issues_matches.get_one::<usize>("limit").unwrap();
+    let search_query = search_query.build()?;
+    lint(query::lint(&search_query, "issues"))?;
+    let results = interrupt::or_exit(api.search_issues(&search_query, limit)).await?;
+    report_truncation(results.items.len(), results.total_count);
+    listing::write(
+        &results.items,
+        issues_matches.get_one::<output::Format>("format").copied(),
+        issues_matches
+            .get_one::<String>("output")
+            .map(String::as_str),
+    )?;
+    Ok(())
+}
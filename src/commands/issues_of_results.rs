@@ -0,0 +1,41 @@
+use super::{filter_by_args, origin_repository, Context};
+use crate::fetch::fetch_repositories;
+use crate::{interrupt, issues};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, issues_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    let search_query = match (
+        issues_matches.get_one::<String>("user"),
+        issues_matches.get_one::<String>("org"),
+    ) {
+        (_, Some(org)) => SearchQuery::new().org(org),
+        (Some(user), None) => SearchQuery::new().user(user),
+        (None, None) => SearchQuery::new().repo(origin_repository()?),
+    }
+    .build()?;
+    let labels: Vec<String> = issues_matches
+        .get_many::<String>("label")
+        .unwrap()
+        .cloned()
+        .collect();
+
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let repositories = filter_by_args(repositories.items, issues_matches, exclude_archived);
+    let counts =
+        interrupt::or_exit(issues::count_labeled_issues(api, repositories, &labels)).await?;
+    issues::print_ranking(&counts, &labels);
+    Ok(())
+}
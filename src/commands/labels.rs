@@ -0,0 +1,45 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{interrupt, labels};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, labels_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    let org = labels_matches.get_one::<String>("org").unwrap();
+
+    let search_query = SearchQuery::new().org(org).build()?;
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let repositories = filter_by_args(repositories.items, labels_matches, exclude_archived);
+    let labels = interrupt::or_exit(labels::fetch_labels(api, &repositories)).await?;
+
+    match labels_matches.get_many::<String>("missing") {
+        Some(missing) => {
+            for label in missing {
+                let without = labels::missing(&labels, label);
+                println!(
+                    "{} of {} repositories have no `{}` label",
+                    without.len(),
+                    labels.len(),
+                    label
+                );
+                for full_name in without {
+                    println!("  {}", full_name);
+                }
+            }
+        }
+        None => labels::print_inventory(&labels::inventory(&labels), labels.len()),
+    }
+    Ok(())
+}
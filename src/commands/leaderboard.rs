@@ -0,0 +1,24 @@
+use crate::leaderboard;
+use clap::ArgMatches;
+use ghs::BoxError;
+use std::path::Path;
+
+pub fn run(leaderboard_matches: &ArgMatches) -> Result<(), BoxError> {
+    let db = leaderboard_matches.get_one::<String>("db").unwrap();
+    print!(
+        "{}",
+        leaderboard::report(
+            Path::new(db),
+            *leaderboard_matches
+                .get_one::<leaderboard::Metric>("metric")
+                .unwrap(),
+            *leaderboard_matches.get_one::<i64>("since").unwrap(),
+            leaderboard_matches
+                .get_one::<String>("org")
+                .map(String::as_str),
+            *leaderboard_matches.get_one::<usize>("limit").unwrap(),
+            leaderboard_matches.get_flag("markdown"),
+        )?
+    );
+    Ok(())
+}
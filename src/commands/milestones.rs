@@ -0,0 +1,30 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{interrupt, milestones};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, milestones_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    let org = milestones_matches.get_one::<String>("org").unwrap();
+    let state = milestones_matches.get_one::<String>("state").unwrap();
+
+    let search_query = SearchQuery::new().org(org).build()?;
+    let (repositories, _) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &search_query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let repositories = filter_by_args(repositories.items, milestones_matches, exclude_archived);
+    let milestones =
+        interrupt::or_exit(milestones::fetch_milestones(api, &repositories, state)).await?;
+    milestones::print_milestones(&milestones);
+    Ok(())
+}
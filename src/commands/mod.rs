@@ -0,0 +1,422 @@
+pub mod audit_files;
+pub mod audit_protection;
+pub mod bench;
+pub mod cache;
+pub mod ci_status;
+pub mod code;
+pub mod compare_repos;
+pub mod discussions;
+pub mod dupes;
+pub mod export;
+pub mod fixtures;
+pub mod issue;
+pub mod issues;
+pub mod issues_of_results;
+pub mod labels;
+pub mod leaderboard;
+pub mod milestones;
+pub mod notifications;
+pub mod org_members_repos;
+pub mod rename_branch;
+pub mod repo;
+pub mod search;
+pub mod team_repos;
+pub mod topics;
+pub mod trend;
+pub mod trending;
+pub mod triage;
+pub mod whoowns;
+
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::pipeline::Pipeline;
+use crate::predicate::Predicate;
+use crate::profile::Profile;
+use crate::provenance::Provenance;
+use crate::{exec, gitremote, interrupt, langdetect, output, smart, sort, transform};
+use clap::ArgMatches;
+use ghs::client::GhsClient;
+use ghs::federated::Federated;
+use ghs::filters::{MatchMode, RepositoryFilter};
+use ghs::gitea::GiteaClient;
+use ghs::gitlab::GitLabClient;
+use ghs::retry::RetryPolicy;
+use ghs::{BoxError, OutputSink, QueryError, Repository, SearchProvider, SearchQuery};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+/// What every subcommand runs with: the parsed arguments, the client and
+/// provider built from them, and the settings read from the config.
+pub struct Context {
+    pub matches: ArgMatches,
+    profile: Option<Profile>,
+    /// The GitHub client, and the transport of every other forge, so that
+    /// `--stats` covers them all.
+    pub api: GhsClient,
+    /// The forge searched instead of GitHub, for any other `--provider`.
+    pub forge: Option<Box<dyn SearchProvider>>,
+    pub cache: Option<Cache>,
+    pub offline: bool,
+    /// Whether requests go to github.com itself, rather than being served
+    /// offline or from fixtures or sent to another host or forge.
+    pub reaches_github_com: bool,
+    pub exclude_archived: bool,
+    pub default_sort: Option<sort::SortKey>,
+    pub smart_weights: smart::Weights,
+    pub bulk_threshold: usize,
+    pub transforms: Vec<transform::Transform>,
+    pub pipeline: Option<Pipeline>,
+}
+
+impl Context {
+    /// Reads the settings from `config` and builds the client and provider
+    /// that `matches` asks for.
+    pub fn new(
+        config: &Config,
+        matches: ArgMatches,
+        profile: Option<Profile>,
+    ) -> Result<Context, BoxError> {
+        let exclude_archived = config
+            .boolean("exclude_archived_by_default")?
+            .unwrap_or(false);
+        let default_sort = config
+            .string("sort")?
+            .map(|key| match sort::SortKey::parse(key) {
+                Ok(sort::SortKey::Score) => {
+                    Err("sort: score needs a --score to sort by".to_string())
+                }
+                key => key.map_err(|err| format!("sort: {}", err)),
+            })
+            .transpose()?;
+        let smart_weights = smart::Weights::from_config(config)?;
+        let bulk_threshold = config
+            .count("bulk_confirm_threshold")?
+            .unwrap_or(exec::BULK_CONFIRM_THRESHOLD);
+
+        let transforms = transform::from_config(
+            config,
+            &matches
+                .get_many::<transform::Transform>("transform")
+                .unwrap_or_default()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )?;
+        // Read before any request, so that mistakes in it cost nothing.
+        let pipeline = matches
+            .get_one::<String>("pipeline")
+            .map(|name| Pipeline::from_config(config, name))
+            .transpose()?;
+
+        let provider_name = matches.get_one::<String>("provider").unwrap().as_str();
+        if provider_name != "github" {
+            if let Some(name) = matches.subcommand_name() {
+                return Err(format!(
+                    "`{}` only works with GitHub, not --provider {}",
+                    name, provider_name
+                )
+                .into());
+            }
+            if matches.contains_id("grep-readme")
+                || matches.get_flag("with-activity")
+                || matches.get_flag("annotate-starred")
+                || matches.contains_id("score")
+                || matches.get_flag("mine-and-orgs")
+            {
+                return Err(
+                    "--grep-readme, --with-activity, --annotate-starred, --score and --mine-and-orgs only work with GitHub"
+                        .into(),
+                );
+            }
+        }
+        let retry_policy = RetryPolicy {
+            max_retries: *matches.get_one::<u32>("retries").unwrap(),
+            retry_mutations: matches.get_flag("retry-mutations"),
+            ..RetryPolicy::default()
+        };
+        let cache = match (
+            matches.get_one::<String>("cache-dir"),
+            matches.get_one::<String>("cache-url"),
+        ) {
+            (Some(dir), _) => Some(Cache::new(dir)),
+            (None, Some(url)) => Some(Cache::from_url(url)?),
+            (None, None) if matches.get_flag("no-cache") => None,
+            (None, None) => Cache::default_dir().map(Cache::new),
+        };
+        let offline = matches.get_flag("offline");
+        let cache = match cache {
+            Some(cache) if offline => Some(cache.offline()?),
+            None if offline => {
+                return Err("--offline needs a cache directory; pass --cache-dir".into())
+            }
+            cache => cache,
+        };
+        let pool_idle_timeout = matches.get_one::<u64>("pool-idle-timeout").copied();
+        let tcp_keepalive = matches.get_one::<u64>("tcp-keepalive").copied();
+        let replay = matches.get_one::<String>("replay");
+        let recording = match matches.subcommand() {
+            Some(("fixtures", fixtures_matches)) => fixtures_matches.subcommand_matches("generate"),
+            _ => None,
+        };
+        let builder = |access_token: Option<String>| {
+            let mut builder = GhsClient::builder()
+                .retry_policy(retry_policy)
+                .offline(offline)
+                .http2_prior_knowledge(matches.get_flag("http2-prior-knowledge"))
+                .cancel(interrupt::cancel());
+            if let Some(dir) = replay {
+                builder = builder.replay_fixtures(dir);
+            }
+            if let Some(generate_matches) = recording {
+                builder = builder.record_fixtures(
+                    generate_matches.get_one::<String>("dir").unwrap(),
+                    generate_matches.get_flag("anonymize"),
+                );
+            }
+            if let Some(seconds) = pool_idle_timeout {
+                builder = builder.pool_idle_timeout(Duration::from_secs(seconds));
+            }
+            if let Some(seconds) = tcp_keepalive {
+                builder = builder.tcp_keepalive(Duration::from_secs(seconds));
+            }
+            match access_token {
+                Some(access_token) => builder.token(access_token),
+                None => builder,
+            }
+        };
+        let host = matches.get_one::<String>("host").map(String::as_str);
+        let gitea_token = env::var("GITEA_TOKEN").ok();
+        let gitlab_token = env::var("GITLAB_TOKEN").ok();
+        let gitea_host = config.string("gitea_host")?;
+        let gitlab_host = config.string("gitlab_host")?;
+        // Other forges get every request, so `api` is their transport and
+        // `--stats` covers them. The tokens searched with key the cache.
+        let (api, forge, tokens): (GhsClient, Option<Box<dyn SearchProvider>>, Vec<_>) =
+            match provider_name {
+                "gitea" => {
+                    let host = host.or(gitea_host).unwrap_or("codeberg.org");
+                    let gitea = GiteaClient::new(host, builder(gitea_token.clone()))?;
+                    (
+                        gitea.api().clone(),
+                        Some(Box::new(gitea)),
+                        vec![gitea_token],
+                    )
+                }
+                "gitlab" => {
+                    let host = host.or(gitlab_host).unwrap_or("gitlab.com");
+                    let gitlab = GitLabClient::new(host, builder(gitlab_token.clone()))?;
+                    (
+                        gitlab.api().clone(),
+                        Some(Box::new(gitlab)),
+                        vec![gitlab_token],
+                    )
+                }
+                "all" => {
+                    if host.is_some() {
+                        return Err(
+                        "--host names one server; set gitea_host and gitlab_host in the config for --provider all"
+                            .into(),
+                    );
+                    }
+                    // A forge counts as configured once it has a token or a host.
+                    let github_token = github_token(profile.as_ref())?;
+                    let api = builder(github_token.clone()).build()?;
+                    let mut forges: Vec<Box<dyn SearchProvider>> = Vec::new();
+                    if github_token.is_some() {
+                        forges.push(Box::new(api.clone()));
+                    }
+                    if gitea_token.is_some() || gitea_host.is_some() {
+                        let host = gitea_host.unwrap_or("codeberg.org");
+                        forges.push(Box::new(GiteaClient::new(
+                            host,
+                            builder(gitea_token.clone()),
+                        )?));
+                    }
+                    if gitlab_token.is_some() || gitlab_host.is_some() {
+                        let host = gitlab_host.unwrap_or("gitlab.com");
+                        forges.push(Box::new(GitLabClient::new(
+                            host,
+                            builder(gitlab_token.clone()),
+                        )?));
+                    }
+                    if forges.is_empty() {
+                        return Err("--provider all found no forge to search; set GITHUB_ACCESS_TOKEN, GH_TOKEN, \
+                                GITEA_TOKEN or GITLAB_TOKEN, or gitea_host or gitlab_host in the config"
+                        .into());
+                    }
+                    let tokens = vec![github_token, gitea_token, gitlab_token];
+                    (api, Some(Box::new(Federated::new(forges))), tokens)
+                }
+                _ => {
+                    let access_token = github_token(profile.as_ref())?;
+                    if access_token.is_none() && io::stderr().is_terminal() {
+                        eprintln!(
+                        "warning: neither GITHUB_ACCESS_TOKEN nor GH_TOKEN is set; searching unauthenticated, with much lower rate limits"
+                    );
+                    }
+                    let mut builder = builder(access_token.clone());
+                    if let Some(host) = host {
+                        builder = builder.host(host);
+                    }
+                    (builder.build()?, None, vec![access_token])
+                }
+            };
+        let cache = cache.map(|cache| cache.searching_with(&tokens));
+        let reaches_github_com = !offline
+            && replay.is_none()
+            && recording.is_none()
+            && host.is_none()
+            && forge.is_none();
+        Ok(Context {
+            matches,
+            profile,
+            api,
+            forge,
+            cache,
+            offline,
+            reaches_github_com,
+            exclude_archived,
+            default_sort,
+            smart_weights,
+            bulk_threshold,
+            transforms,
+            pipeline,
+        })
+    }
+
+    /// What repository searches go to: the `--provider` forge, or GitHub.
+    pub fn provider(&self) -> &dyn SearchProvider {
+        self.forge.as_deref().unwrap_or(&self.api)
+    }
+
+    pub fn github_token(&self) -> Result<Option<String>, BoxError> {
+        github_token(self.profile.as_ref())
+    }
+}
+
+/// A profile names the account to use, so its token comes first.
+fn github_token(profile: Option<&Profile>) -> Result<Option<String>, BoxError> {
+    let token = match profile {
+        Some(profile) => profile.token()?,
+        None => None,
+    };
+    Ok(token
+        .or_else(|| env::var("GITHUB_ACCESS_TOKEN").ok())
+        .or_else(|| env::var("GH_TOKEN").ok()))
+}
+
+/// Applies the arguments from [`filter_args`](crate::cli::filter_args) to `repositories`, dropping
+/// archived ones when `exclude_archived` (from the config) is set.
+pub fn filter_by_args(
+    repositories: Vec<Repository>,
+    matches: &ArgMatches,
+    exclude_archived: bool,
+) -> Vec<Repository> {
+    let predicates: Vec<&Predicate> = matches
+        .get_many::<Predicate>("where")
+        .unwrap_or_default()
+        .collect();
+    let filter = RepositoryFilter {
+        title: matches.get_one::<String>("title").cloned(),
+        description: matches.get_one::<String>("description").cloned(),
+        language: matches.get_one::<String>("language").cloned(),
+        match_mode: matches
+            .get_one::<MatchMode>("match-mode")
+            .copied()
+            .unwrap_or_default(),
+        exclude_archived: exclude_archived && !matches.get_flag("include-archived"),
+    };
+    let mut repositories = filter.apply(repositories);
+    repositories.retain(|repo| predicates.iter().all(|predicate| predicate.matches(repo)));
+    if let Some(lang) = matches.get_one::<String>("description-lang") {
+        repositories.retain(|repo| {
+            repo.description
+                .as_deref()
+                .and_then(langdetect::detect)
+                .is_some_and(|detected| detected == lang)
+        });
+    }
+    repositories
+}
+
+pub fn print_repo(repo: &Repository) {
+    print!("{}", output::plain(repo));
+}
+
+/// The QUERY words and `--repo`, `--org` and `--user` of a code or issue
+/// search.
+pub fn scoped_query(matches: &ArgMatches) -> SearchQuery {
+    let keywords: Vec<&str> = matches
+        .get_many::<String>("query")
+        .unwrap_or_default()
+        .map(String::as_str)
+        .collect();
+    let mut search_query = SearchQuery::new().keywords(keywords.join(" "));
+    if let Some(repo) = matches.get_one::<String>("repo") {
+        search_query = search_query.repo(repo);
+    }
+    if let Some(org) = matches.get_one::<String>("org") {
+        search_query = search_query.org(org);
+    }
+    if let Some(user) = matches.get_one::<String>("user") {
+        search_query = search_query.user(user);
+    }
+    search_query
+}
+
+/// Prints the warnings of a query's lint, or passes its error on.
+pub fn lint(lint: Result<Vec<String>, QueryError>) -> Result<(), QueryError> {
+    for warning in lint? {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(())
+}
+
+pub fn report_truncation(shown: usize, total: u64) {
+    if (shown as u64) < total {
+        eprintln!(
+            "Showing {} of {} matches; pass --limit to see more",
+            shown, total
+        );
+    }
+}
+
+/// Where `--format` and `--output`, or the pipeline's format stage, send the
+/// results, or `None` for the default listing on stdout.
+pub fn output_sink(
+    matches: &ArgMatches,
+    pipeline: Option<&Pipeline>,
+    provenance: &Provenance,
+) -> Result<Option<Box<dyn OutputSink>>, BoxError> {
+    let (format, output) = match pipeline.and_then(Pipeline::output) {
+        Some((format, output)) => (format, output),
+        None => (
+            matches.get_one::<output::Format>("format").copied(),
+            matches.get_one::<String>("output").map(String::as_str),
+        ),
+    };
+    match output {
+        Some(output) => output::open(output, format, provenance).map(Some),
+        None if format.is_some() => output::open("-", format, provenance).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// The repository to act on when none was named: the one the current
+/// directory's `origin` remote points at.
+pub fn origin_repository() -> Result<String, BoxError> {
+    gitremote::origin_repository().ok_or_else(|| {
+        "no repository given, and the current directory has no GitHub `origin` remote".into()
+    })
+}
+
+pub fn print_stats(matches: &ArgMatches, api: &GhsClient) {
+    let stats = api.stats();
+    for kind in matches.get_many::<String>("stats").unwrap_or_default() {
+        match kind.as_str() {
+            "timing" => eprint!("{}", stats.timings.report()),
+            "quota" => eprint!("{}", stats.quota.report()),
+            _ => {}
+        }
+    }
+}
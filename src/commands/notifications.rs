@@ -0,0 +1,41 @@
+use super::Context;
+use crate::{interrupt, listing, output};
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, notifications_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    if !api.has_token() {
+        return Err("notifications are per user; set GITHUB_ACCESS_TOKEN or GH_TOKEN".into());
+    }
+    let notifications = interrupt::or_exit(
+        api.notifications(
+            !notifications_matches.get_flag("unread"),
+            notifications_matches
+                .get_one::<String>("repo")
+                .map(String::as_str),
+        ),
+    )
+    .await?;
+    listing::write(
+        &notifications,
+        notifications_matches
+            .get_one::<output::Format>("format")
+            .copied(),
+        notifications_matches
+            .get_one::<String>("output")
+            .map(String::as_str),
+    )?;
+    if notifications_matches.get_flag("mark-read") {
+        let unread: Vec<&str> = notifications
+            .iter()
+            .filter(|notification| notification.unread)
+            .map(|notification| notification.id.as_str())
+            .collect();
+        for id in &unread {
+            interrupt::or_exit(api.mark_notification_read(id)).await?;
+        }
+        eprintln!("Marked {} notifications as read", unread.len());
+    }
+    Ok(())
+}
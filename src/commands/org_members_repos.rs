@@ -0,0 +1,12 @@
+use super::Context;
+use crate::{interrupt, members};
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, members_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let org = members_matches.get_one::<String>("org").unwrap();
+    let rollup = interrupt::or_exit(members::member_repositories(api, org)).await?;
+    members::print_rollup(&rollup);
+    Ok(())
+}
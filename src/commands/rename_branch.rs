@@ -0,0 +1,59 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{exec, rename};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+
+pub async fn run(ctx: &Context, rename_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let bulk_threshold = ctx.bulk_threshold;
+    let org = rename_matches.get_one::<String>("org").unwrap();
+    let from = rename_matches.get_one::<String>("from").unwrap();
+    let to = rename_matches.get_one::<String>("to").unwrap();
+    let search_query = SearchQuery::new().org(org).build()?;
+    // Never a cached listing: it may be out of date.
+    let (repositories, _) =
+        fetch_repositories(api, None, &search_query, client::MAX_PER_PAGE, true, None).await?;
+    // Archived ones are kept, to be reported as skipped.
+    let repositories = filter_by_args(repositories.items, rename_matches, false);
+    let plan = rename::plan(&repositories, from);
+    if plan.is_empty() {
+        return Err(format!(
+            "no repository of {} has {} as its default branch",
+            org, from
+        )
+        .into());
+    }
+    if rename_matches.get_flag("dry-run") {
+        for (repo, skip) in &plan {
+            match skip {
+                Some(reason) => println!("skip {}: {}", repo.full_name, reason),
+                None => println!("rename {} to {} in {}", from, to, repo.full_name),
+            }
+        }
+        return Ok(());
+    }
+    let names: Vec<&str> = plan
+        .iter()
+        .filter(|(_, skip)| skip.is_none())
+        .map(|(repo, _)| repo.full_name.as_str())
+        .collect();
+    if !exec::confirm_bulk(
+        &format!("rename {} to {} in", from, to),
+        &names,
+        bulk_threshold,
+        rename_matches.get_flag("yes"),
+    )? {
+        return Ok(());
+    }
+    let outcomes = rename::apply(api, &plan, from, to).await;
+    print!("{}", rename::report(&outcomes, from, to));
+    if outcomes
+        .iter()
+        .any(|(_, outcome)| matches!(outcome, rename::Outcome::Failed(_)))
+    {
+        return Err("some branches could not be renamed".into());
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use super::{filter_by_args, origin_repository, Context};
+use crate::circuit::CircuitBreaker;
+use crate::fetch::fetch_repositories;
+use crate::{avatar, details, edit, exec, interrupt, transfer};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::BoxError;
+use std::fs;
+
+pub async fn run(ctx: &Context, repo_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let exclude_archived = ctx.exclude_archived;
+    let bulk_threshold = ctx.bulk_threshold;
+    match repo_matches.subcommand() {
+        Some(("view", view_matches)) => {
+            let full_name = match view_matches.get_one::<String>("repo") {
+                Some(full_name) => full_name.clone(),
+                None => origin_repository()?,
+            };
+            let details = interrupt::or_exit(details::fetch_details(
+                api,
+                &full_name,
+                &CircuitBreaker::new(),
+            ))
+            .await?;
+            if view_matches.get_flag("json") {
+                println!("{}", serde_json::to_string_pretty(&details)?);
+            } else {
+                if view_matches.get_flag("avatar") {
+                    avatar::print_owner_avatar(api, &details.repo).await;
+                }
+                details::print_card(&details);
+            }
+        }
+        Some(("edit", edit_matches)) => {
+            let edits = match edit_matches.get_one::<String>("from-csv") {
+                Some(path) => {
+                    let text = fs::read_to_string(path)
+                        .map_err(|err| format!("could not read {}: {}", path, err))?;
+                    edit::from_csv(&text).map_err(|err| format!("{}: {}", path, err))?
+                }
+                None => {
+                    let full_name = match edit_matches.get_one::<String>("repo") {
+                        Some(full_name) => full_name.clone(),
+                        None => origin_repository()?,
+                    };
+                    let edit = edit::Edit {
+                        description: edit_matches.get_one::<String>("description").cloned(),
+                        homepage: edit_matches.get_one::<String>("homepage").cloned(),
+                        has_issues: edit_matches.get_one::<bool>("issues").copied(),
+                        has_wiki: edit_matches.get_one::<bool>("wiki").copied(),
+                        has_projects: edit_matches.get_one::<bool>("projects").copied(),
+                    };
+                    if edit.is_empty() {
+                        return Err("nothing to change; pass --description, --homepage, --issues, --wiki, --projects or --from-csv".into());
+                    }
+                    vec![(full_name, edit)]
+                }
+            };
+            let edits: Vec<(String, edit::Edit)> = edits
+                .into_iter()
+                .filter(|(_, edit)| !edit.is_empty())
+                .collect();
+            if edits.is_empty() {
+                return Err("the CSV file changes nothing".into());
+            }
+            if edit_matches.get_flag("dry-run") {
+                for (full_name, edit) in &edits {
+                    println!("{}: {}", full_name, edit.summary());
+                }
+                return Ok(());
+            }
+            let names: Vec<&str> = edits
+                .iter()
+                .map(|(full_name, _)| full_name.as_str())
+                .collect();
+            if !exec::confirm_bulk(
+                "change",
+                &names,
+                bulk_threshold,
+                edit_matches.get_flag("yes"),
+            )? {
+                return Ok(());
+            }
+            let mut failures = 0;
+            for (full_name, edit) in &edits {
+                match interrupt::or_exit(api.edit_repository(full_name, &edit.to_json())).await {
+                    Ok(_) => eprintln!("Updated {}: {}", full_name, edit.summary()),
+                    Err(err) => {
+                        eprintln!("warning: {}", err);
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(format!(
+                    "{} of {} repositories could not be changed",
+                    failures,
+                    edits.len()
+                )
+                .into());
+            }
+        }
+        Some(("transfer", transfer_matches)) => {
+            let new_owner = transfer_matches.get_one::<String>("to").unwrap();
+            let full_names: Vec<String> = match transfer_matches.get_one::<String>("query") {
+                Some(query) => {
+                    // Never a cached listing: it may be out of date.
+                    let (repositories, _) =
+                        fetch_repositories(api, None, query, client::MAX_PER_PAGE, true, None)
+                            .await?;
+                    filter_by_args(repositories.items, transfer_matches, exclude_archived)
+                        .into_iter()
+                        .map(|repo| repo.full_name)
+                        .collect()
+                }
+                None => transfer_matches
+                    .get_many::<String>("repos")
+                    .unwrap()
+                    .cloned()
+                    .collect(),
+            };
+            if full_names.is_empty() {
+                return Err("no repository to transfer".into());
+            }
+            // Hard to undo, so it always asks, however few there are.
+            if !transfer_matches.get_flag("yes") {
+                let prompt = match full_names.as_slice() {
+                    [full_name] => format!("Transfer {} to {}?", full_name, new_owner),
+                    _ => {
+                        eprintln!("This would transfer to {}:", new_owner);
+                        for full_name in &full_names {
+                            eprintln!("  {}", full_name);
+                        }
+                        format!("Transfer these {} repositories?", full_names.len())
+                    }
+                };
+                if !exec::confirm(&prompt)? {
+                    return Ok(());
+                }
+            }
+            let mut failures = 0;
+            for full_name in &full_names {
+                match transfer::transfer(api, full_name, new_owner).await {
+                    Ok(new_full_name) => {
+                        eprintln!("Transferred {} to {}", full_name, new_full_name)
+                    }
+                    Err(err) => {
+                        eprintln!("warning: {}", err);
+                        failures += 1;
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(format!(
+                    "{} of {} transfers failed or are still pending",
+                    failures,
+                    full_names.len()
+                )
+                .into());
+            }
+        }
+        _ => unreachable!("clap requires a repo subcommand"),
+    }
+    Ok(())
+}
@@ -0,0 +1,447 @@
+use super::{filter_by_args, lint, output_sink, print_repo, print_stats, Context};
+use crate::cache::Cache;
+use crate::checkpoint::Checkpoint;
+use crate::fetch::{fetch_each, fetch_repositories};
+use crate::provenance::Provenance;
+use crate::readme::GrepProgress;
+use crate::session::Session;
+use crate::{
+    activity, browser, cadence, contrib, exec, gitremote, heatmap, interrupt, membership, output,
+    pages, rank, readme, redact, refine, sample, smart, sort, starred, transform, tui,
+};
+use clap::error::ErrorKind;
+use ghs::{client, datetime};
+use ghs::{BoxError, Repository, SearchQuery};
+use std::collections::HashMap;
+use std::time::Instant;
+
+pub async fn run(ctx: &Context) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let provider = ctx.provider();
+    let matches = &ctx.matches;
+    let exclude_archived = ctx.exclude_archived;
+    let default_sort = ctx.default_sort;
+    let smart_weights = ctx.smart_weights;
+    let bulk_threshold = ctx.bulk_threshold;
+    let transforms = &ctx.transforms;
+    let pipeline = &ctx.pipeline;
+    let offline = ctx.offline;
+    let forge = &ctx.forge;
+    let title = matches.get_one::<String>("title").map(String::as_str);
+    let description = matches.get_one::<String>("description").map(String::as_str);
+    let language = matches.get_one::<String>("language").map(String::as_str);
+
+    let queries: Vec<&str> = matches
+        .get_many::<String>("queries")
+        .unwrap_or_default()
+        .map(String::as_str)
+        .collect();
+    // Several queries are recorded together, e.g. in checkpoints.
+    let search_query = match queries.is_empty() {
+        false => queries.join("; "),
+        true => {
+            let keywords: Vec<&str> = matches
+                .get_many::<String>("query")
+                .unwrap_or_default()
+                .map(String::as_str)
+                .collect();
+            let mut search_query = SearchQuery::new();
+            if !keywords.is_empty() {
+                search_query = search_query.keywords(keywords.join(" "));
+            }
+            match (
+                matches.get_one::<String>("username"),
+                matches.get_one::<String>("org"),
+            ) {
+                // GitHub reads user: as org: for organizations, and
+                // ORs repeated ones.
+                _ if matches.get_flag("mine-and-orgs") => {
+                    let token = ctx.github_token()?.ok_or(
+                        "--mine-and-orgs needs to know who you are; set GITHUB_ACCESS_TOKEN or GH_TOKEN",
+                    )?;
+                    let owners = interrupt::or_exit(membership::mine_and_orgs(
+                        api,
+                        &token,
+                        Cache::default_dir().as_deref(),
+                        offline,
+                    ))
+                    .await?;
+                    owners
+                        .into_iter()
+                        .fold(search_query, |search_query, owner| search_query.user(owner))
+                }
+                (Some(username), _) => search_query.user(username),
+                (None, Some(org)) => search_query.org(org),
+                (None, None) if !keywords.is_empty() => search_query,
+                (None, None) => match gitremote::origin_repository() {
+                    Some(full_name) if forge.is_none() => search_query.repo(full_name),
+                    _ => crate::cli::command()
+                        .error(
+                            ErrorKind::MissingRequiredArgument,
+                            match forge {
+                                Some(_) => format!(
+                                    "pass --username, --org or a QUERY to search {}",
+                                    provider.name()
+                                ),
+                                None => "pass --username, --org or a QUERY, or run inside a clone of a GitHub repository".to_string(),
+                            },
+                        )
+                        .exit(),
+                },
+            }
+            .build()?
+        }
+    };
+
+    let limit = matches.get_one::<usize>("limit").copied();
+    let needs_full_page = title.is_some()
+        || description.is_some()
+        || language.is_some()
+        || matches.contains_id("where")
+        || matches.contains_id("description-lang")
+        || (exclude_archived && !matches.get_flag("include-archived"))
+        || matches.get_flag("all")
+        || matches.contains_id("rank")
+        || matches.contains_id("sort")
+        || default_sort.is_some()
+        || pipeline.is_some()
+        || matches.contains_id("grep-readme")
+        || matches.contains_id("random")
+        || matches.get_flag("heatmap");
+    // Quick lookups only transfer as many items as will be shown, unless
+    // client-side processing needs the whole page to pick from.
+    let per_page = match (matches.get_one::<u8>("per-page"), limit) {
+        (Some(per_page), _) => *per_page,
+        (None, Some(limit)) if !needs_full_page => {
+            limit.clamp(1, client::MAX_PER_PAGE as usize) as u8
+        }
+        (None, _) => client::MAX_PER_PAGE,
+    };
+
+    if matches.get_flag("interactive-pages") {
+        if queries.len() > 1 {
+            return Err("--interactive-pages pages through one --query at a time".into());
+        }
+        let per_page = matches
+            .get_one::<u8>("per-page")
+            .copied()
+            .unwrap_or(pages::PER_PAGE);
+        let redacted: Vec<redact::Field> = matches
+            .get_many::<redact::Field>("redact")
+            .unwrap_or_default()
+            .copied()
+            .collect();
+        lint(provider.lint(&search_query))?;
+        pages::browse(
+            provider,
+            &search_query,
+            per_page,
+            |repositories| filter_by_args(repositories, matches, exclude_archived),
+            |repo| {
+                output::plain(&redact::redact(
+                    &transform::apply(repo, transforms),
+                    &redacted,
+                ))
+            },
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let max_results = matches
+        .get_one::<u64>("max-results")
+        .map(|max| *max as usize);
+    let fetch_all = matches.get_flag("all") || max_results.is_some();
+    interrupt::keep_partial();
+    let (repositories, fetched_at) = match queries.as_slice() {
+        [] => {
+            fetch_repositories(
+                provider,
+                cache.as_ref(),
+                &search_query,
+                per_page,
+                fetch_all,
+                max_results,
+            )
+            .await?
+        }
+        queries => {
+            fetch_each(
+                provider,
+                cache.as_ref(),
+                queries,
+                per_page,
+                fetch_all,
+                max_results,
+            )
+            .await?
+        }
+    };
+    // Ctrl-C during the search: list what it fetched, without the
+    // stages that would fetch more, and exit 130 afterwards.
+    let interrupted_search = interrupt::is_interrupted();
+    if interrupted_search {
+        if repositories.items.is_empty() {
+            eprintln!("Interrupted before any results were fetched");
+            crate::exit(130);
+        }
+        // These would filter or act on the results, and doing so on
+        // part of them is no answer.
+        let unfinished = ["grep-readme", "pipeline", "exec"]
+            .into_iter()
+            .find(|id| matches.contains_id(id));
+        if let Some(id) = unfinished {
+            eprintln!("Interrupted before --{} could run", id);
+            crate::exit(130);
+        }
+        eprintln!(
+            "Interrupted; listing the {} repositories fetched so far",
+            repositories.items.len()
+        );
+    }
+    let truncated = repositories.total_count > repositories.items.len() as u64;
+    if !fetch_all && limit.is_none() && truncated {
+        eprintln!(
+            "Showing the first {} of {} matching repositories; pass --all to fetch every page",
+            repositories.items.len(),
+            repositories.total_count
+        );
+    } else if max_results.is_some() && truncated {
+        eprintln!(
+            "Fetched {} of {} matching repositories (--max-results)",
+            repositories.items.len(),
+            repositories.total_count
+        );
+    }
+
+    let mut filtered_repos = filter_by_args(repositories.items, matches, exclude_archived);
+
+    let smart = smart::Settings {
+        weights: smart_weights,
+        language: smart::wanted_language(language, &search_query),
+    };
+    let sort_key = matches.get_one::<sort::SortKey>("sort").copied();
+    if matches.contains_id("score") && !interrupted_search {
+        interrupt::or_exit(contrib::score(api, &mut filtered_repos)).await?;
+    } else if sort_key == Some(sort::SortKey::Score) {
+        return Err("--sort score needs a --score to sort by".into());
+    }
+    if let Some(expr) = matches.get_one::<rank::RankExpr>("rank") {
+        rank::rank_repositories(&mut filtered_repos, expr);
+    }
+    let sort_key = match sort_key {
+        None if matches.contains_id("rank") => None,
+        None if matches.contains_id("score") => Some(sort::SortKey::Score),
+        None => default_sort,
+        sort_key => sort_key,
+    };
+    if let Some(key) = &sort_key {
+        let descending = match (matches.get_flag("desc"), matches.get_flag("asc")) {
+            (true, _) => true,
+            (_, true) => false,
+            _ => key.descending_by_default(),
+        };
+        sort::sort_repositories(&mut filtered_repos, *key, descending, &smart);
+    }
+
+    let mut snippets = HashMap::new();
+    let mut interrupted = interrupted_search;
+    if let Some(term) = matches.get_one::<String>("grep-readme") {
+        let context = *matches.get_one::<usize>("grep-context").unwrap();
+        let checkpoint_path = matches.get_one::<String>("checkpoint").unwrap();
+
+        let progress = match matches.get_one::<String>("resume") {
+            Some(path) => {
+                let checkpoint = Checkpoint::load(path)?;
+                if checkpoint.query != search_query || checkpoint.grep_term != *term {
+                    return Err(
+                        format!("checkpoint {} was written for a different search", path).into(),
+                    );
+                }
+                checkpoint.progress
+            }
+            None => GrepProgress::default(),
+        };
+
+        let total = filtered_repos.len();
+        let (progress, was_interrupted) =
+            readme::grep_readmes(api, &filtered_repos, term, context, progress).await?;
+        filtered_repos.retain(|repo| progress.matches.contains_key(&repo.full_name));
+
+        if was_interrupted {
+            let checkpoint = Checkpoint {
+                query: search_query.clone(),
+                grep_term: term.clone(),
+                progress,
+            };
+            checkpoint.save(checkpoint_path)?;
+            eprintln!(
+                "Searched {} of {} READMEs; resume with --resume {}",
+                checkpoint.progress.completed.len(),
+                total,
+                checkpoint_path
+            );
+            snippets = checkpoint.progress.matches;
+            interrupted = true;
+        } else {
+            if progress.completed.len() < total {
+                let searched = progress.completed.len();
+                let checkpoint = Checkpoint {
+                    query: search_query.clone(),
+                    grep_term: term.clone(),
+                    progress,
+                };
+                checkpoint.save(checkpoint_path)?;
+                eprintln!(
+                    "Searched {} of {} READMEs; retry the rest with --resume {}",
+                    searched, total, checkpoint_path
+                );
+                snippets = checkpoint.progress.matches;
+            } else {
+                snippets = progress.matches;
+            }
+        }
+    }
+
+    if let Some(count) = matches.get_one::<usize>("random") {
+        let seed = matches
+            .get_one::<u64>("seed")
+            .copied()
+            .unwrap_or_else(sample::random_seed);
+        filtered_repos = sample::sample(filtered_repos, *count, seed);
+    }
+
+    if let Some(limit) = limit {
+        filtered_repos.truncate(limit);
+    }
+
+    if let Some(pipeline) = &pipeline {
+        filtered_repos = interrupt::or_exit(pipeline.run(api, filtered_repos, &smart)).await?;
+    }
+
+    if matches.get_flag("annotate-starred") && !interrupted_search {
+        if !api.has_token() {
+            return Err(
+                "--annotate-starred needs to know who you are; set GITHUB_ACCESS_TOKEN or GH_TOKEN"
+                    .into(),
+            );
+        }
+        interrupt::or_exit(starred::annotate(api, &mut filtered_repos)).await?;
+    }
+
+    if let Some(template) = matches.get_one::<String>("exec") {
+        let parallel = *matches.get_one::<usize>("parallel").unwrap();
+        let names: Vec<&str> = filtered_repos
+            .iter()
+            .map(|repo| repo.full_name.as_str())
+            .collect();
+        if !exec::confirm_bulk(
+            &format!("run `{}` for", template),
+            &names,
+            bulk_threshold,
+            matches.get_flag("yes"),
+        )? {
+            return Ok(());
+        }
+        let failures = exec::run(template, &filtered_repos, parallel).await?;
+        if failures > 0 {
+            return Err(format!("{} of {} commands failed", failures, filtered_repos.len()).into());
+        }
+        return Ok(());
+    }
+
+    if matches.get_flag("tui") || matches.get_flag("interactive") {
+        let mut session = Session {
+            query: search_query.clone(),
+            fetched_at: datetime::format_timestamp(fetched_at),
+            host: api.base_url().to_string(),
+            repositories: filtered_repos,
+            ..Session::default()
+        };
+        if let Some(name) = matches.get_one::<String>("session") {
+            session.save_as(name)?;
+        }
+        tui::run(session, matches.get_flag("interactive"))?;
+        return Ok(());
+    }
+
+    let provenance = Provenance::new(&search_query, fetched_at, api);
+    if matches.get_flag("refine") {
+        refine::run(filtered_repos, &provenance)?;
+        return Ok(());
+    }
+
+    let activity = if matches.get_flag("with-activity") && !interrupted_search {
+        Some(interrupt::or_exit(activity::fetch_activity(api, &filtered_repos)).await?)
+    } else {
+        None
+    };
+
+    let redacted: Vec<redact::Field> = matches
+        .get_many::<redact::Field>("redact")
+        .unwrap_or_default()
+        .copied()
+        .collect();
+    if let Some(mut sink) = output_sink(matches, pipeline.as_ref(), &provenance)? {
+        let started = Instant::now();
+        let repositories: Vec<Repository> = filtered_repos
+            .iter()
+            .map(|repo| redact::redact(&transform::apply(repo, transforms), &redacted))
+            .collect();
+        output::write_all(sink.as_mut(), &repositories).await?;
+        api.stats().timings.record_serialization(started.elapsed());
+    } else {
+        api.stats().timings.time_serialization(|| {
+            if matches.get_flag("heatmap") {
+                heatmap::print_heatmap(&filtered_repos);
+                return;
+            }
+            for repo in &filtered_repos {
+                print_repo(&redact::redact(
+                    &transform::apply(repo, transforms),
+                    &redacted,
+                ));
+                if let Some(activity) = &activity {
+                    activity::print_activity(activity.get(&repo.full_name));
+                }
+                if let Some(snippets) = snippets.get(&repo.full_name) {
+                    readme::print_snippets(snippets);
+                }
+            }
+        });
+    }
+
+    if matches.get_flag("open")
+        && (filtered_repos.len() <= 1
+            || matches.get_flag("yes")
+            || exec::confirm(&format!(
+                "Open {} repositories in the browser?",
+                filtered_repos.len()
+            ))?)
+    {
+        for url in filtered_repos
+            .iter()
+            .filter_map(|repo| repo.html_url.as_deref())
+        {
+            if let Err(err) = browser::open(url) {
+                eprintln!("warning: {}", err);
+            }
+        }
+    }
+
+    if interrupted {
+        print_stats(matches, api);
+        crate::exit(130);
+    }
+
+    if matches
+        .get_many::<String>("stats")
+        .unwrap_or_default()
+        .any(|kind| kind == "releases")
+    {
+        let cadences = interrupt::or_exit(cadence::fetch_cadences(api, &filtered_repos)).await?;
+        eprint!("{}", cadence::report(&cadences));
+    }
+    Ok(())
+}
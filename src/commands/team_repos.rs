@@ -0,0 +1,25 @@
+use super::{filter_by_args, output_sink, print_repo, Context};
+use crate::provenance::Provenance;
+use crate::{interrupt, output};
+use clap::ArgMatches;
+use ghs::datetime;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, team_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let exclude_archived = ctx.exclude_archived;
+    let org = team_matches.get_one::<String>("org").unwrap();
+    let team = team_matches.get_one::<String>("team").unwrap();
+    let repositories = interrupt::or_exit(api.team_repositories(org, team)).await?;
+    let repositories = filter_by_args(repositories, team_matches, exclude_archived);
+    let provenance = Provenance::new(
+        &format!("team-repos {}/{}", org, team),
+        datetime::now(),
+        api,
+    );
+    match output_sink(team_matches, None, &provenance)? {
+        Some(mut sink) => output::write_all(sink.as_mut(), &repositories).await?,
+        None => repositories.iter().for_each(print_repo),
+    }
+    Ok(())
+}
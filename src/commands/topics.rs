@@ -0,0 +1,77 @@
+use super::{filter_by_args, Context};
+use crate::fetch::fetch_repositories;
+use crate::{exec, interrupt, topics};
+use clap::ArgMatches;
+use ghs::client;
+use ghs::{BoxError, SearchQuery};
+use std::io;
+use std::io::IsTerminal;
+
+pub async fn run(ctx: &Context, topics_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let exclude_archived = ctx.exclude_archived;
+    match topics_matches.subcommand() {
+        Some(("suggest", suggest_matches)) => {
+            let search_query = match (
+                suggest_matches.get_one::<String>("user"),
+                suggest_matches.get_one::<String>("org"),
+            ) {
+                (_, Some(org)) => SearchQuery::new().org(org),
+                (Some(user), None) => SearchQuery::new().user(user),
+                (None, None) => unreachable!("clap requires --user or --org"),
+            }
+            .build()?;
+            // A cached listing is fine for suggestions, but not for
+            // changing repositories.
+            let apply = suggest_matches.get_flag("apply");
+            let (repositories, _) = fetch_repositories(
+                api,
+                if apply { None } else { cache.as_ref() },
+                &search_query,
+                client::MAX_PER_PAGE,
+                true,
+                None,
+            )
+            .await?;
+            let repositories =
+                filter_by_args(repositories.items, suggest_matches, exclude_archived);
+            let suggestions = topics::suggest(&repositories);
+            topics::print_suggestions(&suggestions, repositories.len());
+
+            if apply {
+                let ask = !suggest_matches.get_flag("yes");
+                if ask && !suggestions.is_empty() && !io::stdin().is_terminal() {
+                    return Err(
+                        "--apply asks before changing each repository, but there is no terminal; pass --yes to add every suggestion"
+                            .into(),
+                    );
+                }
+                let mut updated = 0;
+                for suggestion in &suggestions {
+                    let full_name = &suggestion.repo.full_name;
+                    if ask
+                        && !exec::confirm(&format!(
+                            "Add {} to {}?",
+                            suggestion.topics.join(", "),
+                            full_name
+                        ))?
+                    {
+                        continue;
+                    }
+                    // The topics are read again, in case they changed
+                    // since the search, rather than overwritten.
+                    let current = interrupt::or_exit(api.topics(full_name)).await?;
+                    interrupt::or_exit(
+                        api.replace_topics(full_name, &suggestion.combined(&current)),
+                    )
+                    .await?;
+                    updated += 1;
+                }
+                eprintln!("Updated the topics of {} repositories", updated);
+            }
+        }
+        _ => unreachable!("clap requires a topics subcommand"),
+    }
+    Ok(())
+}
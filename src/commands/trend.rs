@@ -0,0 +1,16 @@
+use crate::trend;
+use clap::ArgMatches;
+use ghs::BoxError;
+use std::path::Path;
+
+pub fn run(trend_matches: &ArgMatches) -> Result<(), BoxError> {
+    match trend_matches.subcommand() {
+        Some(("languages", languages_matches)) => {
+            let org = languages_matches.get_one::<String>("org").unwrap();
+            let db = languages_matches.get_one::<String>("db").unwrap();
+            print!("{}", trend::language_report(Path::new(db), org)?);
+        }
+        _ => unreachable!("clap requires a trend subcommand"),
+    }
+    Ok(())
+}
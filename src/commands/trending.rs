@@ -0,0 +1,38 @@
+use super::Context;
+use crate::fetch::fetch_repositories;
+use crate::trending;
+use clap::ArgMatches;
+use ghs::BoxError;
+use ghs::{client, datetime};
+
+pub async fn run(ctx: &Context, trending_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let language = trending_matches
+        .get_one::<String>("language")
+        .map(String::as_str);
+    let since = *trending_matches
+        .get_one::<trending::Since>("since")
+        .unwrap();
+    let now = datetime::now();
+    let mut found = Vec::new();
+    for query in trending::queries(language, since, now)? {
+        let (repositories, _) = fetch_repositories(
+            api,
+            cache.as_ref(),
+            &query,
+            client::MAX_PER_PAGE,
+            false,
+            None,
+        )
+        .await?;
+        found.extend(repositories.items);
+    }
+    let limit = *trending_matches.get_one::<usize>("limit").unwrap();
+    let ranked = trending::rank(found, since, now, limit);
+    if ranked.is_empty() {
+        return Err("no repository gained stars in that period".into());
+    }
+    print!("{}", trending::report(&ranked, since, language));
+    Ok(())
+}
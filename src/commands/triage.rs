@@ -0,0 +1,32 @@
+use super::Context;
+use crate::fetch::fetch_repositories;
+use crate::session::Session;
+use crate::{triage, tui};
+use clap::ArgMatches;
+use ghs::BoxError;
+use ghs::{client, datetime};
+
+pub async fn run(ctx: &Context, triage_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    let cache = &ctx.cache;
+    let name = triage_matches.get_one::<String>("session").unwrap();
+    let mut session = Session::load(name)?;
+    let (repositories, fetched_at) = fetch_repositories(
+        api,
+        cache.as_ref(),
+        &session.query,
+        client::MAX_PER_PAGE,
+        true,
+        None,
+    )
+    .await?;
+    let (changes, gone) = triage::diff(&session.repositories, &repositories.items);
+    eprintln!("{}", triage::summary(&changes, gone, &session.fetched_at));
+    session.repositories = repositories.items;
+    session.fetched_at = datetime::format_timestamp(fetched_at);
+    session.changes = changes;
+    (session.cursor, session.offset) = (0, 0);
+    // Saved on quit, so the next triage starts from this run.
+    tui::run(session, false)?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use super::{origin_repository, Context};
+use crate::codeowners::CodeOwners;
+use crate::interrupt;
+use clap::ArgMatches;
+use ghs::BoxError;
+
+pub async fn run(ctx: &Context, whoowns_matches: &ArgMatches) -> Result<(), BoxError> {
+    let api = &ctx.api;
+    // With a single argument it is the path, in the origin's repository.
+    let (full_name, path) = match (
+        whoowns_matches.get_one::<String>("repo"),
+        whoowns_matches.get_one::<String>("path"),
+    ) {
+        (Some(full_name), Some(path)) => (full_name.clone(), path),
+        (Some(path), None) => (origin_repository()?, path),
+        _ => return Err("whoowns needs a PATH".into()),
+    };
+    let Some(codeowners) = interrupt::or_exit(CodeOwners::fetch(api, &full_name)).await? else {
+        return Err(format!("{} has no CODEOWNERS file", full_name).into());
+    };
+
+    match codeowners.owner_of(path) {
+        Some(rule) if !rule.owners.is_empty() => {
+            println!("{}", rule.owners.join(" "));
+            eprintln!(
+                "(`{}` on line {} of {})",
+                rule.pattern, rule.line, codeowners.location
+            );
+        }
+        Some(rule) => println!(
+            "{} is explicitly unowned (`{}` on line {} of {})",
+            path, rule.pattern, rule.line, codeowners.location
+        ),
+        None => println!("No code owners for {}", path),
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::details::{self, RepoDetails};
+use crate::table;
+
+pub fn print_comparison(details: &[RepoDetails]) {
+    let mut rows = vec![std::iter::once(String::new())
+        .chain(details.iter().map(|details| details.repo.full_name.clone()))
+        .collect::<Vec<_>>()];
+
+    let mut add_row = |label: &str, value: &dyn Fn(&RepoDetails) -> String| {
+        rows.push(
+            std::iter::once(label.to_string())
+                .chain(details.iter().map(value))
+                .collect(),
+        );
+    };
+
+    add_row("Stars", &|details| {
+        details.repo.stargazers_count.to_string()
+    });
+    add_row("Forks", &|details| details.repo.forks_count.to_string());
+    add_row("Open issues", &|details| {
+        details.repo.open_issues_count.to_string()
+    });
+    add_row("Last push", &|details| {
+        details::date(details.repo.pushed_at.as_deref())
+    });
+    add_row("License", &|details| details::license(&details.repo));
+    add_row("Languages", &|details| {
+        details::language_breakdown(&details.languages)
+    });
+    add_row("Latest release", &|details| {
+        details::release(details.latest_release.as_ref())
+    });
+
+    print!("{}", table::render(&rows));
+}
@@ -0,0 +1,228 @@
+use ghs::BoxError;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// A value in the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Array(values) => {
+                let values: Vec<String> = values.iter().map(Value::to_string).collect();
+                write!(f, "{}", values.join(","))
+            }
+        }
+    }
+}
+
+pub type Table = BTreeMap<String, Value>;
+
+/// The `ghs` config file, a TOML subset: `[section]` headers (dotted names
+/// such as `[preset.oss-audit]` are kept whole), `key = value` lines with
+/// strings, integers, floats, booleans and one-line arrays, and `#` comments.
+#[derive(Debug, Default)]
+pub struct Config {
+    sections: BTreeMap<String, Table>,
+}
+
+impl Config {
+    /// `$GHS_CONFIG`, else `$XDG_CONFIG_HOME/ghs/config.toml`, else
+    /// `~/.config/ghs/config.toml`.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("GHS_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("ghs").join("config.toml"))
+    }
+
+    /// Reads the config file; a missing file is an empty config.
+    pub fn load() -> Result<Config, BoxError> {
+        let Some(path) = Config::path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => Config::parse(&contents)
+                .map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(format!("could not read {}: {}", path.display(), err).into()),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section = String::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let at_line = |err: String| format!("line {}: {}", index + 1, err);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let (name, rest) = header
+                    .split_once(']')
+                    .ok_or_else(|| at_line("unclosed section header".to_string()))?;
+                expect_end(rest).map_err(at_line)?;
+                section = name.trim().to_string();
+                config.sections.entry(section.clone()).or_default();
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| at_line(format!("expected `key = value`, got `{}`", line)))?;
+            let key = key.trim().trim_matches('"').to_string();
+            if key.is_empty() {
+                return Err(at_line("missing key".to_string()));
+            }
+            let (value, rest) = parse_value(value.trim()).map_err(at_line)?;
+            expect_end(rest).map_err(at_line)?;
+            config
+                .sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key, value);
+        }
+
+        Ok(config)
+    }
+
+    /// A top-level boolean setting; other types are an error.
+    pub fn boolean(&self, key: &str) -> Result<Option<bool>, String> {
+        match self.section("").and_then(|table| table.get(key)) {
+            None => Ok(None),
+            Some(Value::Boolean(value)) => Ok(Some(*value)),
+            Some(value) => Err(format!("`{}` must be true or false, not `{}`", key, value)),
+        }
+    }
+
+    /// A top-level string setting; other types are an error.
+    pub fn string(&self, key: &str) -> Result<Option<&str>, String> {
+        match self.section("").and_then(|table| table.get(key)) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(value) => Err(format!("`{}` must be a string, not `{}`", key, value)),
+        }
+    }
+
+    /// A top-level non-negative integer setting; other values are an error.
+    pub fn count(&self, key: &str) -> Result<Option<usize>, String> {
+        match self.section("").and_then(|table| table.get(key)) {
+            None => Ok(None),
+            Some(Value::Integer(value)) if *value >= 0 => Ok(Some(*value as usize)),
+            Some(value) => Err(format!(
+                "`{}` must be a whole number of at least 0, not `{}`",
+                key, value
+            )),
+        }
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Table> {
+        self.sections.get(name)
+    }
+
+    /// The names after `prefix.` of every `[prefix.name]` section.
+    pub fn subsections(&self, prefix: &str) -> Vec<&str> {
+        self.sections
+            .keys()
+            .filter_map(|name| name.strip_prefix(prefix)?.strip_prefix('.'))
+            .collect()
+    }
+}
+
+/// Only whitespace or a comment may follow a value or header.
+fn expect_end(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected `{}`", rest))
+    }
+}
+
+/// Parses the value at the start of `input`, returning it and the rest.
+fn parse_value(input: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = input.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &rest[index + 1..])),
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\')) => value.push(c),
+                    Some(c) => return Err(format!("unknown escape `\\{}`", c)),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        return Err("unterminated string".to_string());
+    }
+
+    if let Some(rest) = input.strip_prefix('\'') {
+        let (value, rest) = rest
+            .split_once('\'')
+            .ok_or_else(|| "unterminated string".to_string())?;
+        return Ok((Value::String(value.to_string()), rest));
+    }
+
+    if let Some(mut rest) = input.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(values), after));
+            }
+            let (value, after) = parse_value(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("expected `,` or `]` in array".to_string());
+            }
+        }
+    }
+
+    let end = input
+        .find(|c: char| c == ',' || c == ']' || c == '#' || c.is_whitespace())
+        .unwrap_or(input.len());
+    let (token, rest) = input.split_at(end);
+    let value = match token {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => {
+            let digits = token.replace('_', "");
+            if let Ok(integer) = digits.parse() {
+                Value::Integer(integer)
+            } else if let Ok(float) = digits.parse() {
+                Value::Float(float)
+            } else {
+                return Err(format!("`{}` is not a value", token));
+            }
+        }
+    };
+    Ok((value, rest))
+}
@@ -0,0 +1,93 @@
+//! Console setup for Windows, where escape sequences are only interpreted
+//! once virtual terminal processing is switched on, and output goes through
+//! the console's code page rather than UTF-8. Elsewhere this is a no-op.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ANSI: AtomicBool = AtomicBool::new(true);
+
+/// Whether the console on stdout interprets ANSI escape sequences. Only
+/// meaningful after [`init`].
+pub fn supports_ansi() -> bool {
+    ANSI.load(Ordering::Relaxed)
+}
+
+/// Prepares the console for UTF-8 text and ANSI escape sequences. Legacy
+/// consoles that cannot process them get no colors instead of garbage.
+#[cfg(windows)]
+pub fn init() {
+    // SAFETY: plain calls on the process's standard handles; a handle that
+    // is not a console makes GetConsoleMode fail, which is checked.
+    unsafe {
+        sys::SetConsoleOutputCP(sys::CP_UTF8);
+        sys::SetConsoleCP(sys::CP_UTF8);
+
+        let stdout = sys::GetStdHandle(sys::STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        if sys::GetConsoleMode(stdout, &mut mode) != 0 {
+            let enabled =
+                sys::SetConsoleMode(stdout, mode | sys::ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0;
+            ANSI.store(enabled, Ordering::Relaxed);
+        }
+
+        let stderr = sys::GetStdHandle(sys::STD_ERROR_HANDLE);
+        if sys::GetConsoleMode(stderr, &mut mode) != 0 {
+            sys::SetConsoleMode(stderr, mode | sys::ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn init() {}
+
+/// The few kernel32 console functions ghs needs.
+#[cfg(windows)]
+#[allow(non_snake_case)]
+pub mod sys {
+    use std::ffi::c_void;
+
+    pub type Handle = *mut c_void;
+
+    pub const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+    pub const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    pub const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+    pub const CP_UTF8: u32 = 65001;
+    pub const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+    pub const ENABLE_VIRTUAL_TERMINAL_INPUT: u32 = 0x0200;
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct Coord {
+        pub x: i16,
+        pub y: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct SmallRect {
+        pub left: i16,
+        pub top: i16,
+        pub right: i16,
+        pub bottom: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct ScreenBufferInfo {
+        pub size: Coord,
+        pub cursor_position: Coord,
+        pub attributes: u16,
+        pub window: SmallRect,
+        pub maximum_window_size: Coord,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn GetStdHandle(std_handle: u32) -> Handle;
+        pub fn GetConsoleMode(console: Handle, mode: *mut u32) -> i32;
+        pub fn SetConsoleMode(console: Handle, mode: u32) -> i32;
+        pub fn SetConsoleCP(code_page: u32) -> i32;
+        pub fn SetConsoleOutputCP(code_page: u32) -> i32;
+        pub fn GetConsoleScreenBufferInfo(console: Handle, info: *mut ScreenBufferInfo) -> i32;
+    }
+}
@@ -0,0 +1,172 @@
+//! `--score contrib`: how promising each repository is for someone looking
+//! for a place to contribute.
+
+use crate::circuit::CircuitBreaker;
+use ghs::model::Issue;
+use ghs::{datetime, BoxError, GhsClient, Repository};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories may be scored at once.
+const CONCURRENCY: usize = 8;
+/// How many of the latest issues are looked at for first responses.
+const RESPONSE_SAMPLE: u8 = 5;
+/// Comments read per sampled issue to find the first that is not the
+/// author's.
+const COMMENTS: u8 = 10;
+/// Labeled issues past this many add nothing more to the score.
+const ENOUGH_ISSUES: f64 = 20.0;
+/// A repository last pushed to this long ago counts as inactive.
+const STALE_DAYS: f64 = 365.0;
+/// The median first response, in hours, that halves responsiveness.
+const RESPONSE_HOURS: f64 = 48.0;
+
+/// What a repository's score is made of. Parts that could not be fetched
+/// are `None`.
+#[derive(Debug, Default)]
+struct Signals {
+    help_wanted: Option<usize>,
+    good_first_issues: Option<usize>,
+    /// Median hours from opening an issue to the first comment by someone
+    /// else, counting unanswered issues as waiting until now.
+    response_hours: Option<f64>,
+}
+
+impl Signals {
+    /// 40% open `help wanted` and `good first issue` issues, the latter
+    /// counting double, 30% how recently it was pushed to, and 30% how
+    /// soon issues get an answer. Unknown parts count as middling.
+    fn score(&self, repo: &Repository, now: i64) -> f64 {
+        let opportunity = match (self.help_wanted, self.good_first_issues) {
+            (None, None) => 0.5,
+            (help_wanted, good_first_issues) => {
+                let weighted =
+                    help_wanted.unwrap_or(0) as f64 + 2.0 * good_first_issues.unwrap_or(0) as f64;
+                (weighted.ln_1p() / ENOUGH_ISSUES.ln_1p()).min(1.0)
+            }
+        };
+        let activity = repo
+            .pushed_at
+            .as_deref()
+            .and_then(datetime::parse_timestamp)
+            .map_or(0.0, |pushed_at| {
+                let days = (now - pushed_at).max(0) as f64 / 86_400.0;
+                (1.0 - days / STALE_DAYS).max(0.0)
+            });
+        let responsiveness = self
+            .response_hours
+            .map_or(0.5, |hours| 1.0 / (1.0 + hours / RESPONSE_HOURS));
+        let score = 100.0 * (0.4 * opportunity + 0.3 * activity + 0.3 * responsiveness);
+        (score * 10.0).round() / 10.0
+    }
+}
+
+/// Sets `score` on each of `repositories`.
+pub async fn score(api: &GhsClient, repositories: &mut [Repository]) -> Result<(), BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories.iter() {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let signals = fetch_signals(&api, &breaker, &full_name).await?;
+            Ok::<_, BoxError>((full_name, signals))
+        });
+    }
+
+    let mut signals = HashMap::new();
+    while let Some(result) = tasks.join_next().await {
+        let (full_name, repo_signals) = result??;
+        signals.insert(full_name, repo_signals);
+    }
+    let now = datetime::now();
+    for repo in repositories {
+        let repo_signals = signals.remove(&repo.full_name).unwrap_or_default();
+        repo.score = Some(repo_signals.score(repo, now));
+    }
+    Ok(())
+}
+
+async fn fetch_signals(
+    api: &GhsClient,
+    breaker: &CircuitBreaker,
+    full_name: &str,
+) -> Result<Signals, BoxError> {
+    let help_wanted = breaker
+        .call(
+            &format!("help wanted issues of {}", full_name),
+            api.open_issues(full_name, &["help wanted"]),
+        )
+        .await?;
+    let good_first_issues = breaker
+        .call(
+            &format!("good first issues of {}", full_name),
+            api.open_issues(full_name, &["good first issue"]),
+        )
+        .await?;
+    let recent = breaker
+        .call(
+            &format!("recent issues of {}", full_name),
+            api.recent_issues(full_name, RESPONSE_SAMPLE),
+        )
+        .await?;
+
+    let now = datetime::now();
+    let mut response_hours = Vec::new();
+    for issue in recent.iter().flatten() {
+        if let Some(hours) = first_response_hours(api, breaker, full_name, issue, now).await? {
+            response_hours.push(hours);
+        }
+    }
+    response_hours.sort_by(f64::total_cmp);
+    Ok(Signals {
+        help_wanted: help_wanted.map(|issues| issues.len()),
+        good_first_issues: good_first_issues.map(|issues| issues.len()),
+        response_hours: response_hours.get(response_hours.len() / 2).copied(),
+    })
+}
+
+/// Hours until someone other than the author first commented on `issue`,
+/// or until now if nobody has. `None` if that cannot be told.
+async fn first_response_hours(
+    api: &GhsClient,
+    breaker: &CircuitBreaker,
+    full_name: &str,
+    issue: &Issue,
+    now: i64,
+) -> Result<Option<f64>, BoxError> {
+    let Some(opened) = issue
+        .created_at
+        .as_deref()
+        .and_then(datetime::parse_timestamp)
+    else {
+        return Ok(None);
+    };
+    let author = issue.user.as_ref().map(|user| user.login.as_str());
+    let answered = if issue.comments == 0 {
+        None
+    } else {
+        let comments = breaker
+            .call(
+                &format!("comments on {}#{}", full_name, issue.number),
+                api.issue_comments(full_name, issue.number, COMMENTS),
+            )
+            .await?;
+        let Some(comments) = comments else {
+            return Ok(None);
+        };
+        comments
+            .iter()
+            .find(|comment| comment.user.as_ref().map(|user| user.login.as_str()) != author)
+            .and_then(|comment| datetime::parse_timestamp(&comment.created_at))
+    };
+    Ok(Some(
+        (answered.unwrap_or(now) - opened).max(0) as f64 / 3600.0,
+    ))
+}
@@ -0,0 +1,233 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parses the `YYYY-MM-DDTHH:MM:SSZ` timestamps returned by the GitHub API
+/// into seconds since the Unix epoch.
+pub fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let bytes = timestamp.as_bytes();
+    if bytes.len() < 19 || bytes[4] != b'-' || bytes[7] != b'-' || bytes[10] != b'T' {
+        return None;
+    }
+
+    let field = |range: std::ops::Range<usize>| timestamp.get(range)?.parse::<i64>().ok();
+    let year = field(0..4)?;
+    let month = field(5..7)?;
+    let day = field(8..10)?;
+    let hour = field(11..13)?;
+    let minute = field(14..16)?;
+    let second = field(17..19)?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Parses an HTTP date such as `Sat, 31 Dec 2024 23:59:59 GMT`, the form
+/// used by the `Sunset` and `Deprecation` headers, into seconds since the
+/// Unix epoch.
+pub fn parse_http_date(date: &str) -> Option<i64> {
+    let (_, date) = date.split_once(',')?;
+    let mut fields = date.split_whitespace();
+    let day = fields.next()?.parse::<i64>().ok()?;
+    let month = fields.next()?;
+    let month = MONTHS.iter().position(|name| *name == month)? as i64 + 1;
+    let year = fields.next()?.parse::<i64>().ok()?;
+    let mut clock = fields
+        .next()?
+        .split(':')
+        .map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    if fields.next() != Some("GMT") || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Rewrites an RFC 3339 timestamp with an offset or fractional seconds, as
+/// other forges send them, into GitHub's UTC form: `2023-04-05T08:07:08+02:00`
+/// and `2023-04-05T06:07:08.000Z` both become `2023-04-05T06:07:08Z`.
+pub fn to_utc(timestamp: &str) -> Option<String> {
+    let local = parse_timestamp(timestamp)?;
+    let offset = match timestamp
+        .get(19..)?
+        .trim_start_matches(|c: char| c == '.' || c.is_ascii_digit())
+    {
+        "" | "Z" => 0,
+        offset => {
+            let sign = match offset.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            sign * (hours.parse::<i64>().ok()? * 3_600 + minutes.parse::<i64>().ok()? * 60)
+        }
+    };
+    Some(format_timestamp(local - offset))
+}
+
+/// Current time in seconds since the Unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Formats a day number (days since 1970-01-01) as `YYYY-MM-DD`.
+pub fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`, the form
+/// GitHub uses.
+pub fn format_timestamp(timestamp: i64) -> String {
+    let seconds = timestamp.rem_euclid(86_400);
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(timestamp.div_euclid(86_400)),
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// Formats seconds since the Unix epoch in UTC by a `strftime`-like
+/// `format`: `%Y`, `%y`, `%m`, `%d`, `%H`, `%M`, `%S`, `%b` (`Apr`) and
+/// `%%` are understood, and anything else after a `%` is an error.
+pub fn strftime(timestamp: i64, format: &str) -> Result<String, String> {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86_400));
+    let seconds = timestamp.rem_euclid(86_400);
+    let mut formatted = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => formatted.push_str(&format!("{:04}", year)),
+            Some('y') => formatted.push_str(&format!("{:02}", year.rem_euclid(100))),
+            Some('m') => formatted.push_str(&format!("{:02}", month)),
+            Some('d') => formatted.push_str(&format!("{:02}", day)),
+            Some('H') => formatted.push_str(&format!("{:02}", seconds / 3_600)),
+            Some('M') => formatted.push_str(&format!("{:02}", seconds % 3_600 / 60)),
+            Some('S') => formatted.push_str(&format!("{:02}", seconds % 60)),
+            Some('b') => formatted.push_str(MONTHS[month as usize - 1]),
+            Some('%') => formatted.push('%'),
+            Some(other) => return Err(format!("`%{}` in `{}` is not a date field", other, format)),
+            None => return Err(format!("`{}` ends in a lone `%`", format)),
+        }
+    }
+    Ok(formatted)
+}
+
+/// Inverse of [`days_from_civil`].
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_github_timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2023-04-05T06:07:08Z"), Some(1_680_674_828));
+        assert_eq!(parse_timestamp("2023-13-05T06:07:08Z"), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(
+            parse_http_date("Wed, 05 Apr 2023 06:07:08 GMT"),
+            Some(1_680_674_828)
+        );
+        assert_eq!(parse_http_date("Wed, 05 Avr 2023 06:07:08 GMT"), None);
+        assert_eq!(parse_http_date("2023-04-05T06:07:08Z"), None);
+    }
+
+    #[test]
+    fn converts_offsets_to_utc() {
+        for timestamp in [
+            "2023-04-05T08:07:08+02:00",
+            "2023-04-04T23:07:08-07:00",
+            "2023-04-05T06:07:08.000Z",
+            "2023-04-05T06:07:08Z",
+        ] {
+            assert_eq!(to_utc(timestamp).as_deref(), Some("2023-04-05T06:07:08Z"));
+        }
+        assert_eq!(to_utc("2023-04-05T06:07:08 GMT"), None);
+    }
+
+    #[test]
+    fn formats_dates_round_trip() {
+        for date in ["1970-01-01", "2000-02-29", "2008-02-08", "2024-12-31"] {
+            let days = parse_timestamp(&format!("{}T00:00:00Z", date)).unwrap() / 86_400;
+            assert_eq!(format_date(days), date);
+        }
+    }
+
+    #[test]
+    fn formats_timestamps_round_trip() {
+        for timestamp in [
+            "1970-01-01T00:00:00Z",
+            "2023-04-05T06:07:08Z",
+            "1969-12-31T23:59:59Z",
+        ] {
+            assert_eq!(
+                format_timestamp(parse_timestamp(timestamp).unwrap()),
+                timestamp
+            );
+        }
+    }
+
+    #[test]
+    fn formats_by_pattern() {
+        let timestamp = parse_timestamp("2023-04-05T06:07:08Z").unwrap();
+        assert_eq!(
+            strftime(timestamp, "%Y-%m-%d %H:%M:%S").unwrap(),
+            "2023-04-05 06:07:08"
+        );
+        assert_eq!(
+            strftime(timestamp, "%d %b '%y, 100%%").unwrap(),
+            "05 Apr '23, 100%"
+        );
+        assert!(strftime(timestamp, "%Q").is_err());
+        assert!(strftime(timestamp, "%Y%").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Deprecation and sunset notices GitHub attaches to API responses
+//! (RFC 9745's `Deprecation` and RFC 8594's `Sunset` headers, plus their
+//! `Link` relations), collected per endpoint.
+
+use crate::datetime;
+use crate::links;
+use reqwest::header::{self, HeaderMap};
+use reqwest::Method;
+use std::collections::{BTreeMap, BTreeSet};
+use url::Url;
+
+/// What GitHub said about retiring one endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    /// Method and path template, e.g. `GET /repos/{owner}/{repo}/readme`.
+    pub endpoint: String,
+    /// When the endpoint was (or will be) deprecated, if a date was given.
+    pub deprecated_at: Option<i64>,
+    /// When the endpoint stops working, if announced.
+    pub sunset_at: Option<i64>,
+    /// Where the change is documented.
+    pub link: Option<String>,
+}
+
+impl Notice {
+    /// Reads the notice, if any, from a response to `method url`.
+    pub fn from_headers(method: &Method, url: &str, headers: &HeaderMap) -> Option<Notice> {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let deprecation = header("deprecation");
+        let sunset = header("sunset");
+        if deprecation.is_none() && sunset.is_none() {
+            return None;
+        }
+
+        // Pagination may arrive in a separate `Link` header.
+        let link = headers
+            .get_all(header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(|link| {
+                links::target(link, "deprecation").or_else(|| links::target(link, "sunset"))
+            });
+        Some(Notice {
+            endpoint: endpoint(method, url),
+            deprecated_at: deprecation.and_then(parse_date),
+            sunset_at: sunset.and_then(datetime::parse_http_date),
+            link,
+        })
+    }
+}
+
+/// `@1688169599` (RFC 9745) or an HTTP date (earlier drafts); `true` says
+/// the endpoint is deprecated without saying since when.
+fn parse_date(value: &str) -> Option<i64> {
+    match value.trim().strip_prefix('@') {
+        Some(timestamp) => timestamp.parse().ok(),
+        None => datetime::parse_http_date(value),
+    }
+}
+
+/// The documented form of the endpoint behind `url`, with owners, names and
+/// paths replaced by placeholders so every repository reports the same one.
+pub fn endpoint(method: &Method, url: &str) -> String {
+    let path = Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| url.to_string());
+    let path = path.strip_prefix("/api/v3").unwrap_or(&path);
+
+    let mut template = Vec::new();
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    while let Some(segment) = segments.next() {
+        template.push(segment.to_string());
+        let placeholders: &[&str] = match segment {
+            "repos" => &["{owner}", "{repo}"],
+            "users" => &["{username}"],
+            "orgs" => &["{org}"],
+            "teams" => &["{team_slug}"],
+            "branches" => &["{branch}"],
+            "contents" => {
+                if segments.by_ref().count() > 0 {
+                    template.push("{path}".to_string());
+                }
+                break;
+            }
+            _ => &[],
+        };
+        for placeholder in placeholders {
+            if segments.next().is_some() {
+                template.push(placeholder.to_string());
+            }
+        }
+    }
+    format!("{} /{}", method, template.join("/"))
+}
+
+/// Every endpoint called during a run, and the notices that came back.
+#[derive(Debug, Default)]
+pub struct Deprecations {
+    notices: BTreeMap<String, Notice>,
+    /// Endpoints that answered without any notice.
+    clean: BTreeSet<String>,
+}
+
+impl Deprecations {
+    pub fn record(&mut self, method: &Method, url: &str, headers: &HeaderMap) {
+        match Notice::from_headers(method, url, headers) {
+            Some(notice) => {
+                self.clean.remove(&notice.endpoint);
+                self.notices.insert(notice.endpoint.clone(), notice);
+            }
+            None => {
+                let endpoint = endpoint(method, url);
+                if !self.notices.contains_key(&endpoint) {
+                    self.clean.insert(endpoint);
+                }
+            }
+        }
+    }
+
+    pub fn notices(&self) -> impl Iterator<Item = &Notice> {
+        self.notices.values()
+    }
+
+    /// Whether `endpoint` was called and answered without a notice, which
+    /// means an earlier one no longer applies.
+    pub fn is_clean(&self, endpoint: &str) -> bool {
+        self.clean.contains(endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn templates_endpoints() {
+        let cases = [
+            (
+                "https://api.github.com/search/repositories?q=user%3Aa&page=2",
+                "GET /search/repositories",
+            ),
+            (
+                "https://api.github.com/repos/a/b/contents/.github/CODEOWNERS",
+                "GET /repos/{owner}/{repo}/contents/{path}",
+            ),
+            (
+                "https://api.github.com/repos/a/b/branches/main/protection",
+                "GET /repos/{owner}/{repo}/branches/{branch}/protection",
+            ),
+            (
+                "https://ghe.example.com/api/v3/orgs/acme/teams/core/repos",
+                "GET /orgs/{org}/teams/{team_slug}/repos",
+            ),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(endpoint(&Method::GET, url), expected);
+        }
+    }
+
+    #[test]
+    fn reads_deprecation_and_sunset_headers() {
+        let notice = Notice::from_headers(
+            &Method::GET,
+            "https://api.github.com/repos/a/b/readme",
+            &headers(&[
+                ("deprecation", "@1688169599"),
+                ("sunset", "Wed, 05 Apr 2023 06:07:08 GMT"),
+                (
+                    "link",
+                    "<https://docs.github.com/changes>; rel=\"deprecation\"",
+                ),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(notice.endpoint, "GET /repos/{owner}/{repo}/readme");
+        assert_eq!(notice.deprecated_at, Some(1_688_169_599));
+        assert_eq!(notice.sunset_at, Some(1_680_674_828));
+        assert_eq!(
+            notice.link.as_deref(),
+            Some("https://docs.github.com/changes")
+        );
+    }
+
+    #[test]
+    fn ignores_responses_without_notices() {
+        let headers = headers(&[("link", "<https://x/?page=2>; rel=\"next\"")]);
+        assert_eq!(
+            Notice::from_headers(&Method::GET, "https://api.github.com/x", &headers),
+            None
+        );
+    }
+
+    #[test]
+    fn notices_outweigh_clean_responses() {
+        let mut deprecations = Deprecations::default();
+        let deprecated = headers(&[("deprecation", "true")]);
+        deprecations.record(
+            &Method::GET,
+            "https://api.github.com/repos/a/b",
+            &deprecated,
+        );
+        deprecations.record(
+            &Method::GET,
+            "https://api.github.com/repos/c/d",
+            &HeaderMap::new(),
+        );
+        deprecations.record(
+            &Method::GET,
+            "https://api.github.com/rate_limit",
+            &HeaderMap::new(),
+        );
+
+        assert_eq!(deprecations.notices().count(), 1);
+        assert!(!deprecations.is_clean("GET /repos/{owner}/{repo}"));
+        assert!(deprecations.is_clean("GET /rate_limit"));
+    }
+}
@@ -0,0 +1,171 @@
+use ghs::deprecation::Notice;
+use ghs::{datetime, GhsClient};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+/// File in the cache directory holding the notices seen on earlier runs,
+/// one tab-separated `seen endpoint deprecated sunset link` line each, with
+/// `-` for anything GitHub did not say.
+const STATE_FILE: &str = "api-deprecations";
+
+/// Notices not seen again for this long are dropped: either the endpoint is
+/// no longer retiring, or ghs no longer calls it.
+const MAX_AGE: i64 = 30 * 24 * 60 * 60;
+
+const ISSUES_URL: &str = "https://github.com/shinshin86/ghs/issues";
+
+struct Entry {
+    seen_at: i64,
+    notice: Notice,
+}
+
+fn read(path: &Path) -> Vec<Entry> {
+    let now = datetime::now();
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let seen_at = fields.next()?.parse::<i64>().ok()?;
+            let endpoint = fields.next()?.to_string();
+            let mut optional = || fields.next().filter(|field| *field != "-");
+            let deprecated_at = optional().and_then(|field| field.parse().ok());
+            let sunset_at = optional().and_then(|field| field.parse().ok());
+            let link = optional().map(str::to_string);
+            Some(Entry {
+                seen_at,
+                notice: Notice {
+                    endpoint,
+                    deprecated_at,
+                    sunset_at,
+                    link,
+                },
+            })
+        })
+        .filter(|entry| now - entry.seen_at < MAX_AGE)
+        .collect()
+}
+
+fn write(path: &Path, entries: &[Entry]) {
+    let text: String = entries
+        .iter()
+        .map(|entry| {
+            let notice = &entry.notice;
+            let number = |value: Option<i64>| value.map_or("-".to_string(), |n| n.to_string());
+            format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                entry.seen_at,
+                notice.endpoint,
+                number(notice.deprecated_at),
+                number(notice.sunset_at),
+                notice.link.as_deref().unwrap_or("-")
+            )
+        })
+        .collect();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, text);
+}
+
+fn warning(notice: &Notice) -> String {
+    let date = |timestamp: i64| datetime::format_date(timestamp.div_euclid(86_400));
+    let status = match (notice.sunset_at, notice.deprecated_at) {
+        (Some(sunset), _) if sunset <= datetime::now() => {
+            format!("was retired on {}", date(sunset))
+        }
+        (Some(sunset), _) => format!("will be retired on {}", date(sunset)),
+        (None, Some(deprecated)) => format!("has been deprecated since {}", date(deprecated)),
+        (None, None) => "has been deprecated".to_string(),
+    };
+    let link = notice
+        .link
+        .as_ref()
+        .map(|link| format!(" (see {})", link))
+        .unwrap_or_default();
+    format!(
+        "warning: GitHub says {} {}{}; run `ghs self-update`, and if that does not help, report it at {}",
+        notice.endpoint, status, link, ISSUES_URL
+    )
+}
+
+/// Prints one line for each endpoint GitHub flagged on recent runs. Only
+/// the cache is read, so this costs no requests.
+pub fn warn(cache_dir: &Path) {
+    for entry in read(&cache_dir.join(STATE_FILE)) {
+        eprintln!("{}", warning(&entry.notice));
+    }
+}
+
+/// The recorder [`record`] installed, until [`save`] takes it.
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+struct Recorder {
+    api: GhsClient,
+    path: PathBuf,
+}
+
+/// Starts recording the notices `api` collects, to be warned about on the
+/// next run. They are saved when the returned guard is dropped, on a normal
+/// return, or by [`save`], which every `process::exit` has to call first,
+/// since exiting runs no destructors.
+#[must_use]
+pub fn record(api: &GhsClient, cache_dir: &Path) -> SaveOnDrop {
+    *RECORDER.lock().unwrap_or_else(|err| err.into_inner()) = Some(Recorder {
+        api: api.clone(),
+        path: cache_dir.join(STATE_FILE),
+    });
+    SaveOnDrop
+}
+
+/// Saves the recorded notices, once; later calls do nothing.
+pub fn save() {
+    let recorder = RECORDER
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .take();
+    if let Some(recorder) = recorder {
+        recorder.save();
+    }
+}
+
+/// Calls [`save`] when dropped.
+pub struct SaveOnDrop;
+
+impl Drop for SaveOnDrop {
+    fn drop(&mut self) {
+        // A panic may have poisoned the stats; saving is not worth a second.
+        if !thread::panicking() {
+            save();
+        }
+    }
+}
+
+impl Recorder {
+    fn save(&self) {
+        let stats = self.api.stats();
+        let deprecations = &stats.deprecations;
+        let now = datetime::now();
+
+        let mut entries: Vec<Entry> = read(&self.path)
+            .into_iter()
+            .filter(|entry| {
+                !deprecations.is_clean(&entry.notice.endpoint)
+                    && deprecations
+                        .notices()
+                        .all(|notice| notice.endpoint != entry.notice.endpoint)
+            })
+            .collect();
+        entries.extend(deprecations.notices().map(|notice| Entry {
+            seen_at: now,
+            notice: notice.clone(),
+        }));
+        if entries.is_empty() && !self.path.exists() {
+            return;
+        }
+        entries.sort_by(|a, b| a.notice.endpoint.cmp(&b.notice.endpoint));
+        write(&self.path, &entries);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::circuit::CircuitBreaker;
+use ghs::model::Release;
+use ghs::{BoxError, GhsClient, Repository};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// How many languages to name in a breakdown before lumping the rest together.
+const TOP_LANGUAGES: usize = 3;
+
+/// A repository together with the extra data that needs separate requests.
+#[derive(Debug, Serialize)]
+pub struct RepoDetails {
+    pub repo: Repository,
+    pub languages: BTreeMap<String, u64>,
+    pub latest_release: Option<Release>,
+}
+
+/// Fetches a repository, its language breakdown and its latest release.
+///
+/// Only the repository itself is required: if the languages or the release
+/// cannot be fetched (or `breaker` has tripped) they are left empty.
+pub async fn fetch_details(
+    api: &GhsClient,
+    full_name: &str,
+    breaker: &CircuitBreaker,
+) -> Result<RepoDetails, BoxError> {
+    let languages_of = format!("languages of {}", full_name);
+    let release_of = format!("latest release of {}", full_name);
+    let (repo, languages, latest_release) = tokio::join!(
+        api.repository(full_name),
+        breaker.call(&languages_of, api.languages(full_name)),
+        breaker.call(&release_of, api.latest_release(full_name)),
+    );
+    let repo = repo?.ok_or_else(|| format!("repository {} not found", full_name))?;
+    let languages = languages?.unwrap_or_default();
+    let latest_release = latest_release?.flatten();
+
+    Ok(RepoDetails {
+        repo,
+        languages,
+        latest_release,
+    })
+}
+
+/// Fetches the details of every repository concurrently, keeping the order given.
+pub async fn fetch_all_details(
+    api: &GhsClient,
+    full_names: &[String],
+) -> Result<Vec<RepoDetails>, BoxError> {
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for (index, full_name) in full_names.iter().enumerate() {
+        let api = api.clone();
+        let breaker = Arc::clone(&breaker);
+        let full_name = full_name.clone();
+        tasks.spawn(async move {
+            let details = fetch_details(&api, &full_name, &breaker).await?;
+            Ok::<_, BoxError>((index, details))
+        });
+    }
+
+    let mut details = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        details.push(result??);
+    }
+    details.sort_by_key(|(index, _)| *index);
+    Ok(details.into_iter().map(|(_, details)| details).collect())
+}
+
+/// Prints every field of a repository as a `Label: value` card.
+pub fn print_card(details: &RepoDetails) {
+    let repo = &details.repo;
+    let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+
+    let fields = [
+        ("Repository", repo.full_name.clone()),
+        ("Description", or_dash(repo.description.as_deref())),
+        ("URL", or_dash(repo.html_url.as_deref())),
+        (
+            "Homepage",
+            or_dash(
+                repo.homepage
+                    .as_deref()
+                    .filter(|homepage| !homepage.is_empty()),
+            ),
+        ),
+        ("Language", or_dash(repo.language.as_deref())),
+        ("Languages", language_breakdown(&details.languages)),
+        (
+            "Topics",
+            if repo.topics.is_empty() {
+                "-".to_string()
+            } else {
+                repo.topics.join(", ")
+            },
+        ),
+        ("Stars", repo.stargazers_count.to_string()),
+        ("Forks", repo.forks_count.to_string()),
+        ("Watchers", repo.watchers_count.to_string()),
+        ("Open issues", repo.open_issues_count.to_string()),
+        ("License", license(repo)),
+        ("Default branch", or_dash(repo.default_branch.as_deref())),
+        ("Fork", yes_no(repo.fork)),
+        ("Archived", yes_no(repo.archived)),
+        ("Created", date(repo.created_at.as_deref())),
+        ("Updated", date(repo.updated_at.as_deref())),
+        ("Last push", date(repo.pushed_at.as_deref())),
+        ("Latest release", release(details.latest_release.as_ref())),
+    ];
+
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    for (label, value) in fields {
+        println!(
+            "{:<width$}  {}",
+            format!("{}:", label),
+            value,
+            width = width + 1
+        );
+    }
+}
+
+pub fn license(repo: &Repository) -> String {
+    repo.license
+        .as_ref()
+        .map(|license| {
+            license
+                .spdx_id
+                .clone()
+                .unwrap_or_else(|| license.name.clone())
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn release(release: Option<&Release>) -> String {
+    match release {
+        Some(release) => format!(
+            "{} ({})",
+            release.tag_name,
+            date(release.published_at.as_deref())
+        ),
+        None => "-".to_string(),
+    }
+}
+
+/// Summarizes bytes per language as percentages, e.g. `Rust 92%, Shell 8%`.
+pub fn language_breakdown(languages: &BTreeMap<String, u64>) -> String {
+    let total: u64 = languages.values().sum();
+    if total == 0 {
+        return "-".to_string();
+    }
+
+    let mut sorted: Vec<(&String, &u64)> = languages.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1));
+
+    let percent = |bytes: u64| (bytes as f64 / total as f64 * 100.0).round();
+    let mut parts: Vec<String> = sorted
+        .iter()
+        .take(TOP_LANGUAGES)
+        .map(|(language, bytes)| format!("{} {}%", language, percent(**bytes)))
+        .collect();
+    let other: u64 = sorted
+        .iter()
+        .skip(TOP_LANGUAGES)
+        .map(|(_, bytes)| **bytes)
+        .sum();
+    if other > 0 {
+        parts.push(format!("Other {}%", percent(other)));
+    }
+    parts.join(", ")
+}
+
+/// The `YYYY-MM-DD` part of a GitHub timestamp.
+pub fn date(timestamp: Option<&str>) -> String {
+    timestamp
+        .and_then(|timestamp| timestamp.get(..10))
+        .unwrap_or("-")
+        .to_string()
+}
@@ -0,0 +1,36 @@
+use crate::table;
+use ghs::model::Discussion;
+
+pub fn print_discussions(discussions: &[Discussion]) {
+    if discussions.is_empty() {
+        println!("No matching discussions");
+        return;
+    }
+
+    let mut rows = vec![vec![
+        "Title".to_string(),
+        "Category".to_string(),
+        "Repository".to_string(),
+        "Answered".to_string(),
+        "Link".to_string(),
+    ]];
+    for discussion in discussions {
+        rows.push(vec![
+            discussion.title.clone(),
+            discussion
+                .category
+                .as_ref()
+                .map(|category| category.name.clone())
+                .unwrap_or_default(),
+            discussion.repository.name_with_owner.clone(),
+            match discussion.is_answered {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "-",
+            }
+            .to_string(),
+            discussion.url.clone(),
+        ]);
+    }
+    print!("{}", table::render(&rows));
+}
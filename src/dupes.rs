@@ -0,0 +1,89 @@
+use ghs::Repository;
+use std::collections::BTreeSet;
+
+/// Two repositories whose names or descriptions look alike.
+#[derive(Debug)]
+pub struct DuplicatePair<'a> {
+    pub first: &'a Repository,
+    pub second: &'a Repository,
+    pub name_similarity: f64,
+    pub description_similarity: f64,
+}
+
+impl DuplicatePair<'_> {
+    fn score(&self) -> f64 {
+        self.name_similarity.max(self.description_similarity)
+    }
+}
+
+/// Compares every pair of repositories and returns those whose normalized
+/// name (Levenshtein) or description (Jaccard over words) similarity reaches
+/// `threshold`, most similar first.
+pub fn find_duplicates(repositories: &[Repository], threshold: f64) -> Vec<DuplicatePair<'_>> {
+    let names: Vec<String> = repositories
+        .iter()
+        .map(|repo| normalize_name(&repo.name))
+        .collect();
+    let words: Vec<BTreeSet<String>> = repositories
+        .iter()
+        .map(|repo| description_words(repo.description.as_deref()))
+        .collect();
+
+    let mut pairs = Vec::new();
+    for i in 0..repositories.len() {
+        for j in (i + 1)..repositories.len() {
+            let pair = DuplicatePair {
+                first: &repositories[i],
+                second: &repositories[j],
+                name_similarity: strsim::normalized_levenshtein(&names[i], &names[j]),
+                description_similarity: jaccard(&words[i], &words[j]),
+            };
+            if pair.score() >= threshold {
+                pairs.push(pair);
+            }
+        }
+    }
+
+    pairs.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    pairs
+}
+
+pub fn print_duplicates(pairs: &[DuplicatePair]) {
+    if pairs.is_empty() {
+        println!("No duplicate repositories found");
+        return;
+    }
+
+    for pair in pairs {
+        println!(
+            "{} <-> {}\nName similarity: {:.2}\nDescription similarity: {:.2}\n---",
+            pair.first.name, pair.second.name, pair.name_similarity, pair.description_similarity
+        );
+    }
+}
+
+/// Lowercases the name and drops separators so `foo-api` and `Foo_API` compare equal.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn description_words(description: Option<&str>) -> BTreeSet<String> {
+    description
+        .unwrap_or_default()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let intersection = a.intersection(b).count();
+    let union = a.union(b).count();
+    intersection as f64 / union as f64
+}
@@ -0,0 +1,262 @@
+//! `ghs repo edit`: changes the description, homepage and features of one
+//! repository, or of every one listed in a CSV file.
+
+use serde_json::{Map, Value};
+
+/// The CSV columns besides `full_name`, and the API fields they set.
+const COLUMNS: &[(&str, &str)] = &[
+    ("description", "description"),
+    ("homepage", "homepage"),
+    ("issues", "has_issues"),
+    ("wiki", "has_wiki"),
+    ("projects", "has_projects"),
+];
+
+/// The changes to make to a repository; `None` leaves a setting alone.
+#[derive(Debug, Default)]
+pub struct Edit {
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub has_issues: Option<bool>,
+    pub has_wiki: Option<bool>,
+    pub has_projects: Option<bool>,
+}
+
+impl Edit {
+    pub fn is_empty(&self) -> bool {
+        self.changes().is_empty()
+    }
+
+    /// The request body: only what changes.
+    pub fn to_json(&self) -> Value {
+        Value::Object(self.changes())
+    }
+
+    /// What changes, e.g. `description, wiki off`, for the report.
+    pub fn summary(&self) -> String {
+        let toggle = |name: &str, on: bool| format!("{} {}", name, if on { "on" } else { "off" });
+        let mut parts = Vec::new();
+        if self.description.is_some() {
+            parts.push("description".to_string());
+        }
+        if self.homepage.is_some() {
+            parts.push("homepage".to_string());
+        }
+        for (name, value) in [
+            ("issues", self.has_issues),
+            ("wiki", self.has_wiki),
+            ("projects", self.has_projects),
+        ] {
+            if let Some(on) = value {
+                parts.push(toggle(name, on));
+            }
+        }
+        parts.join(", ")
+    }
+
+    fn changes(&self) -> Map<String, Value> {
+        let mut changes = Map::new();
+        let texts = [
+            ("description", &self.description),
+            ("homepage", &self.homepage),
+        ];
+        for (field, value) in texts {
+            if let Some(value) = value {
+                changes.insert(field.to_string(), Value::String(value.clone()));
+            }
+        }
+        let toggles = [
+            ("has_issues", self.has_issues),
+            ("has_wiki", self.has_wiki),
+            ("has_projects", self.has_projects),
+        ];
+        for (field, value) in toggles {
+            if let Some(value) = value {
+                changes.insert(field.to_string(), Value::Bool(value));
+            }
+        }
+        changes
+    }
+
+    fn set(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "description" => self.description = Some(value.to_string()),
+            "homepage" => self.homepage = Some(value.to_string()),
+            "has_issues" => self.has_issues = Some(parse_toggle(value)?),
+            "has_wiki" => self.has_wiki = Some(parse_toggle(value)?),
+            "has_projects" => self.has_projects = Some(parse_toggle(value)?),
+            _ => unreachable!("COLUMNS names every field"),
+        }
+        Ok(())
+    }
+}
+
+/// `on`/`off`, as `--issues on` takes it, or `true`/`false` and `yes`/`no`
+/// as spreadsheets tend to write it.
+pub fn parse_toggle(value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "on" | "true" | "yes" => Ok(true),
+        "off" | "false" | "no" => Ok(false),
+        _ => Err(format!("`{}` is not on or off", value)),
+    }
+}
+
+/// The edits in a CSV file whose header names `full_name` and any of
+/// `description`, `homepage`, `issues`, `wiki` and `projects`. An empty
+/// cell leaves that setting alone; a description can still be cleared by
+/// editing the one repository with `--description ''`.
+pub fn from_csv(text: &str) -> Result<Vec<(String, Edit)>, String> {
+    let mut records = records(text)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err("the CSV file is empty".to_string());
+    };
+    let mut name_column = None;
+    let mut fields = Vec::with_capacity(header.len());
+    for (index, column) in header.iter().enumerate() {
+        let column = column.trim();
+        if column == "full_name" {
+            name_column = Some(index);
+            fields.push(None);
+            continue;
+        }
+        let field = COLUMNS
+            .iter()
+            .find(|(name, _)| *name == column)
+            .map(|(_, field)| *field)
+            .ok_or_else(|| {
+                format!(
+                    "unknown column `{}` (expected full_name, description, homepage, issues, wiki or projects)",
+                    column
+                )
+            })?;
+        fields.push(Some(field));
+    }
+    let name_column = name_column.ok_or("the CSV header has no full_name column")?;
+
+    let mut edits = Vec::new();
+    for (line, record) in records {
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let full_name = record
+            .get(name_column)
+            .map(|name| name.trim())
+            .unwrap_or("");
+        if !full_name.contains('/') {
+            return Err(format!("line {}: `{}` is not OWNER/NAME", line, full_name));
+        }
+        let mut edit = Edit::default();
+        for (cell, field) in record.iter().zip(&fields) {
+            if let (Some(field), false) = (field, cell.is_empty()) {
+                edit.set(field, cell)
+                    .map_err(|err| format!("line {}: {}", line, err))?;
+            }
+        }
+        edits.push((full_name.to_string(), edit));
+    }
+    Ok(edits)
+}
+
+/// The records of `text` with the line each starts on. Fields may be
+/// quoted, with `""` for a quote, and then hold commas and line breaks.
+fn records(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(format!("line {}: unclosed quote", start));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_hold_commas_quotes_and_line_breaks() {
+        let edits = from_csv(
+            "full_name,description,wiki\r\n\
+             octo/ghs,\"Search, \"\"fast\"\"\nand more\",off\r\n\
+             \n\
+             octo/ghr,,yes\n",
+        )
+        .unwrap();
+        assert_eq!(edits.len(), 2);
+        let (name, edit) = &edits[0];
+        assert_eq!(name, "octo/ghs");
+        assert_eq!(
+            edit.description.as_deref(),
+            Some("Search, \"fast\"\nand more")
+        );
+        assert_eq!(edit.summary(), "description, wiki off");
+
+        // An empty cell leaves its setting alone.
+        let (name, edit) = &edits[1];
+        assert_eq!(name, "octo/ghr");
+        assert_eq!(edit.to_json(), serde_json::json!({"has_wiki": true}));
+    }
+
+    #[test]
+    fn errors_name_the_line_a_record_starts_on() {
+        let text = "full_name,description,issues\n\
+                    octo/ghs,\"two\nlines\",on\n\
+                    octo/ghr,,maybe\n";
+        assert_eq!(
+            from_csv(text).unwrap_err(),
+            "line 4: `maybe` is not on or off"
+        );
+        assert_eq!(
+            from_csv("full_name\nocto/ghs\nghr\n").unwrap_err(),
+            "line 3: `ghr` is not OWNER/NAME"
+        );
+        assert_eq!(
+            from_csv("full_name,description\nocto/ghs,\"open\n").unwrap_err(),
+            "line 2: unclosed quote"
+        );
+    }
+
+    #[test]
+    fn the_header_needs_full_name_and_known_columns() {
+        assert_eq!(from_csv("").unwrap_err(), "the CSV file is empty");
+        assert_eq!(
+            from_csv("description\nA tool\n").unwrap_err(),
+            "the CSV header has no full_name column"
+        );
+        assert!(from_csv("full_name, topics\nocto/ghs,cli\n")
+            .unwrap_err()
+            .starts_with("unknown column `topics` (expected full_name,"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Failed API responses, told apart by what can be done about them: a
+//! rejected token, a token without the needed access, an exhausted rate
+//! limit, or a request the server refused to process.
+
+use crate::client::ApiResponse;
+use crate::datetime;
+use reqwest::StatusCode;
+use std::fmt;
+
+/// Exit status for errors without a more specific one.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit status for invalid arguments or queries, as clap uses.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit status when the token was rejected or lacks access.
+pub const EXIT_AUTH: i32 = 3;
+/// Exit status when the rate limit is exhausted.
+pub const EXIT_RATE_LIMITED: i32 = 4;
+
+/// Why an API request failed. Each variant keeps the URL and the server's
+/// own `message`, if it sent one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// 401: the token is missing, invalid, expired or revoked.
+    Unauthorized {
+        url: String,
+        message: Option<String>,
+    },
+    /// 403 for another reason than the rate limit, typically a token without
+    /// the scope or organization access the request needs.
+    Forbidden {
+        url: String,
+        message: Option<String>,
+    },
+    /// 403 or 429 with the rate limit used up. `reset` is when it resets, in
+    /// seconds since the Unix epoch, when the server said.
+    RateLimited {
+        url: String,
+        message: Option<String>,
+        reset: Option<i64>,
+    },
+    /// 422: the request was understood but refused, e.g. a search query
+    /// naming a user that does not exist.
+    Unprocessable {
+        url: String,
+        message: Option<String>,
+    },
+    /// Any other unsuccessful status.
+    Status {
+        url: String,
+        status: StatusCode,
+        message: Option<String>,
+    },
+}
+
+impl ApiError {
+    /// Classifies the unsuccessful `response` to a request for `url`.
+    pub fn from_response(url: &str, response: &ApiResponse) -> ApiError {
+        let url = url.to_string();
+        let message = message(&response.body);
+        let header = |name: &str| {
+            response
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i64>().ok())
+        };
+        let rate_limited = header("x-ratelimit-remaining") == Some(0)
+            || header("retry-after").is_some()
+            || response.status == StatusCode::TOO_MANY_REQUESTS;
+
+        match response.status {
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS if rate_limited => {
+                let reset = header("x-ratelimit-reset")
+                    .or_else(|| header("retry-after").map(|after| datetime::now() + after));
+                ApiError::RateLimited {
+                    url,
+                    message,
+                    reset,
+                }
+            }
+            StatusCode::UNAUTHORIZED => ApiError::Unauthorized { url, message },
+            StatusCode::FORBIDDEN => ApiError::Forbidden { url, message },
+            StatusCode::UNPROCESSABLE_ENTITY => ApiError::Unprocessable { url, message },
+            status => ApiError::Status {
+                url,
+                status,
+                message,
+            },
+        }
+    }
+
+    /// The process exit status to report this error with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ApiError::Unauthorized { .. } | ApiError::Forbidden { .. } => EXIT_AUTH,
+            ApiError::RateLimited { .. } => EXIT_RATE_LIMITED,
+            ApiError::Unprocessable { .. } => EXIT_USAGE,
+            ApiError::Status { .. } => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = |f: &mut fmt::Formatter<'_>, url: &str, status, message: &Option<String>| {
+            write!(f, "{} failed: {}", url, status)?;
+            match message {
+                Some(message) => write!(f, " ({})", message),
+                None => Ok(()),
+            }
+        };
+        match self {
+            ApiError::Unauthorized { url, message } => {
+                failed(f, url, StatusCode::UNAUTHORIZED, message)?;
+                write!(f, "; the access token is missing, invalid or expired")
+            }
+            ApiError::Forbidden { url, message } => {
+                failed(f, url, StatusCode::FORBIDDEN, message)?;
+                write!(
+                    f,
+                    "; the access token may lack a scope or access to the organization"
+                )
+            }
+            ApiError::RateLimited {
+                url,
+                message,
+                reset,
+            } => {
+                write!(f, "{} failed: the rate limit is exhausted", url)?;
+                if let Some(message) = message {
+                    write!(f, " ({})", message)?;
+                }
+                match reset {
+                    Some(reset) => {
+                        let reset = datetime::format_timestamp(*reset);
+                        write!(
+                            f,
+                            "; it resets at {} UTC",
+                            reset.replace('T', " ").trim_end_matches('Z')
+                        )
+                    }
+                    None => write!(f, "; try again later"),
+                }
+            }
+            ApiError::Unprocessable { url, message } => {
+                failed(f, url, StatusCode::UNPROCESSABLE_ENTITY, message)?;
+                write!(f, "; the server refused the request as given")
+            }
+            ApiError::Status {
+                url,
+                status,
+                message,
+            } => failed(f, url, *status, message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// The server's `message`, followed by the messages of any validation
+/// `errors` it listed, which say what was actually wrong.
+fn message(body: &[u8]) -> Option<String> {
+    let body = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+    let message = body.get("message")?.as_str()?.to_string();
+    let details: Vec<&str> = body
+        .get("errors")
+        .and_then(|errors| errors.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|error| error.get("message")?.as_str())
+        .collect();
+    match details.is_empty() {
+        true => Some(message),
+        false => Some(format!("{}: {}", message, details.join("; "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    fn response(status: u16, headers: &[(&'static str, &str)], body: &str) -> ApiResponse {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, value.parse().unwrap());
+        }
+        ApiResponse {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: map,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn tells_rate_limits_from_missing_access() {
+        let limited = response(
+            403,
+            &[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "86400"),
+            ],
+            r#"{"message": "API rate limit exceeded"}"#,
+        );
+        let error = ApiError::from_response("https://api.github.com/search", &limited);
+        assert_eq!(error.exit_code(), EXIT_RATE_LIMITED);
+        assert_eq!(
+            error.to_string(),
+            "https://api.github.com/search failed: the rate limit is exhausted \
+             (API rate limit exceeded); it resets at 1970-01-02 00:00:00 UTC"
+        );
+
+        let forbidden = response(403, &[("x-ratelimit-remaining", "4999")], "");
+        assert!(matches!(
+            ApiError::from_response("u", &forbidden),
+            ApiError::Forbidden { message: None, .. }
+        ));
+        let unauthorized = response(401, &[], r#"{"message": "Bad credentials"}"#);
+        assert_eq!(
+            ApiError::from_response("u", &unauthorized).to_string(),
+            "u failed: 401 Unauthorized (Bad credentials); \
+             the access token is missing, invalid or expired"
+        );
+    }
+
+    #[test]
+    fn includes_validation_errors() {
+        let rejected = response(
+            422,
+            &[],
+            r#"{"message": "Validation Failed", "errors": [{"message": "The listed users cannot be searched"}]}"#,
+        );
+        let error = ApiError::from_response("u", &rejected);
+        assert_eq!(error.exit_code(), EXIT_USAGE);
+        assert_eq!(
+            error,
+            ApiError::Unprocessable {
+                url: "u".to_string(),
+                message: Some("Validation Failed: The listed users cannot be searched".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::transform::Expr;
+use ghs::{BoxError, Repository};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::Arc;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Substitutes `{field}` placeholders in `template` with the repository's
+/// fields (any field of the JSON output, e.g. `{full_name}` or `{ssh_url}`),
+/// or with a transform of one such as `{lower(name)}`; `{{` and `}}` are
+/// literal braces. Values are quoted for the shell so names and
+/// descriptions cannot inject commands; on Windows that holds only in part,
+/// see [`shell_quote`]. Unknown placeholders are an error.
+pub fn render(template: &str, repo: &Repository) -> Result<String, String> {
+    substitute(template, repo, shell_quote)
+}
+
+/// Like [`render`], but for text rather than a command, e.g. the body of
+/// an issue: values go in as they are.
+pub fn fill(template: &str, repo: &Repository) -> Result<String, String> {
+    substitute(template, repo, str::to_string)
+}
+
+fn substitute(
+    template: &str,
+    repo: &Repository,
+    quote: impl Fn(&str) -> String,
+) -> Result<String, String> {
+    let fields = serde_json::to_value(repo).map_err(|err| err.to_string())?;
+    let mut command = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        command.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        // `{{` and `}}` are literal braces, as in `format!`; so is a lone `}`.
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            command.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if let Some(after) = tail.strip_prefix('}') {
+            command.push('}');
+            rest = after;
+            continue;
+        }
+        let after = &tail[1..];
+        let Some(end) = after.find('}') else {
+            return Err(format!("unclosed `{{` in `{}`", template));
+        };
+
+        let name = &after[..end];
+        let value = match fields.get(name) {
+            Some(serde_json::Value::String(value)) => Some(value.clone()),
+            // A field the repository lacks, e.g. a missing description.
+            Some(serde_json::Value::Null) => None,
+            // `None` too when the field it reads is missing.
+            None if name.contains('(') => Expr::parse(name)?.eval(repo),
+            Some(value) => Some(value.to_string()),
+            None => return Err(format!("unknown field `{{{}}}` in `{}`", name, template)),
+        };
+        // Missing values are filled in as empty text rather than failing the
+        // template, since most repositories lack one field or another.
+        command.push_str(&quote(&value.unwrap_or_default()));
+        rest = &after[end + 1..];
+    }
+    command.push_str(rest);
+    Ok(command)
+}
+
+/// Double quotes keep `&`, `|`, `<`, `>` and `^` from `cmd /C`, but `cmd`
+/// has no quoting that stops `%NAME%` from being expanded, so this is not
+/// injection-safe: a value holding `%NAME%` gets that variable's contents.
+#[cfg(windows)]
+fn shell_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Single quotes leave everything to `sh` as it is; a quote inside is
+/// closed, escaped and reopened.
+#[cfg(not(windows))]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Asks on the terminal whether to go ahead; anything but `y`/`yes` declines.
+/// Without a terminal there is nobody to ask, so the answer is no.
+pub fn confirm(prompt: &str) -> Result<bool, BoxError> {
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "{} -- refusing without a terminal; pass --yes to confirm",
+            prompt
+        );
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// How many repositories a bulk action may touch before it asks first,
+/// unless the config's `bulk_confirm_threshold` says otherwise.
+pub const BULK_CONFIRM_THRESHOLD: usize = 5;
+
+/// Affected repositories listed before the question; the rest are counted.
+const LISTED: usize = 20;
+
+/// Whether to go ahead with `action` on `repositories`: without asking if
+/// there are at most `threshold` of them or `yes` is set, else once the
+/// user confirms on the terminal after seeing which ones would be touched.
+pub fn confirm_bulk(
+    action: &str,
+    repositories: &[&str],
+    threshold: usize,
+    yes: bool,
+) -> Result<bool, BoxError> {
+    if yes || repositories.len() <= threshold {
+        return Ok(true);
+    }
+    eprintln!(
+        "This would {} {} repositories, more than {}:",
+        action,
+        repositories.len(),
+        threshold
+    );
+    for full_name in repositories.iter().take(LISTED) {
+        eprintln!("  {}", full_name);
+    }
+    if repositories.len() > LISTED {
+        eprintln!("  ... and {} more", repositories.len() - LISTED);
+    }
+    confirm("Go ahead?")
+}
+
+/// Runs the rendered template for every repository through the shell, at
+/// most `parallel` at a time. Returns how many commands failed.
+pub async fn run(
+    template: &str,
+    repositories: &[Repository],
+    parallel: usize,
+) -> Result<usize, BoxError> {
+    let commands = repositories
+        .iter()
+        .map(|repo| Ok((repo.full_name.clone(), render(template, repo)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let semaphore = Arc::new(Semaphore::new(parallel.max(1)));
+    let mut tasks = JoinSet::new();
+    for (full_name, command) in commands {
+        let semaphore = Arc::clone(&semaphore);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let status = shell(&command).status().await?;
+            if !status.success() {
+                eprintln!("{}: `{}` exited with {}", full_name, command, status);
+            }
+            Ok::<_, BoxError>(status.success())
+        });
+    }
+
+    let mut failures = 0;
+    while let Some(result) = tasks.join_next().await {
+        if !result?? {
+            failures += 1;
+        }
+    }
+    Ok(failures)
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": "ghs",
+            "full_name": "octo/ghs",
+            "stargazers_count": 7,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn fills_fields_and_keeps_escaped_braces() {
+        assert_eq!(
+            fill("{full_name} has {stargazers_count} stars", &repo()).unwrap(),
+            "octo/ghs has 7 stars"
+        );
+        assert_eq!(
+            fill("{{name}} is {name}; }} and } stay", &repo()).unwrap(),
+            "{name} is ghs; } and } stay"
+        );
+        assert!(fill("a lone { opens", &repo())
+            .unwrap_err()
+            .contains("unclosed"));
+        assert!(fill("{nme}", &repo())
+            .unwrap_err()
+            .contains("unknown field"));
+    }
+
+    #[test]
+    fn missing_fields_and_transforms_of_them_are_empty() {
+        assert_eq!(
+            fill(
+                "[{description}] [{upper(description)}] [{upper(name)}]",
+                &repo()
+            )
+            .unwrap(),
+            "[] [] [GHS]"
+        );
+        assert!(fill("{upper(nme)}", &repo()).is_err());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn quoted_values_reach_the_command_as_written() {
+        let payloads = [
+            "it's; touch pwned",
+            "$(touch pwned)",
+            "`touch pwned`",
+            "'$(touch pwned)'\\",
+            "a\nb && echo *",
+        ];
+        for payload in payloads {
+            let mut repo = repo();
+            repo.description = Some(payload.to_string());
+            let command = render("printf %s {description}", &repo).unwrap();
+            let output = std::process::Command::new("sh")
+                .args(["-c", &command])
+                .current_dir(std::env::temp_dir())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "`{}` failed", command);
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), payload);
+        }
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(id) `id`"), "'$(id) `id`'");
+    }
+}
@@ -0,0 +1,136 @@
+use crate::placeholder::{self, Field};
+use crate::provenance::{Export, Provenance};
+use ghs::{schema, BoxError, Repository};
+use std::fs;
+use std::path::Path;
+
+/// File formats results can be exported to, picked by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl Format {
+    /// `.csv` and `.md`/`.markdown` are recognized; anything else is JSON.
+    pub fn from_path(path: &Path) -> Format {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Format::Csv,
+            Some("md" | "markdown") => Format::Markdown,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Writes `repositories` to `path` in the format its extension names.
+pub fn write(
+    path: &Path,
+    repositories: &[&Repository],
+    provenance: &Provenance,
+) -> Result<(), BoxError> {
+    let contents = match Format::from_path(path) {
+        Format::Json => serde_json::to_string_pretty(&Export {
+            schema: schema::VERSION,
+            metadata: provenance,
+            repositories,
+        })?,
+        Format::Csv => csv(repositories, provenance),
+        Format::Markdown => markdown(repositories, provenance),
+    };
+    fs::write(path, contents)
+        .map_err(|err| format!("could not write {}: {}", path.display(), err).into())
+}
+
+/// The CSV columns describing a repository, shared with `--format csv`.
+pub const CSV_HEADER: &str =
+    "full_name,description,html_url,language,stargazers_count,forks_count,\
+                              open_issues_count,watchers_count,archived,topics,pushed_at";
+
+/// `repo`'s cells under [`CSV_HEADER`], not yet quoted.
+pub fn csv_fields(repo: &Repository) -> Vec<String> {
+    vec![
+        repo.full_name.clone(),
+        placeholder::or(repo.description.as_deref(), Field::Description, "").to_string(),
+        repo.html_url.clone().unwrap_or_default(),
+        placeholder::or(repo.language.as_deref(), Field::Language, "").to_string(),
+        repo.stargazers_count.to_string(),
+        repo.forks_count.to_string(),
+        repo.open_issues_count.to_string(),
+        repo.watchers_count.to_string(),
+        repo.archived.to_string(),
+        repo.topics.join(";"),
+        repo.pushed_at.clone().unwrap_or_default(),
+    ]
+}
+
+/// One row per repository; the provenance is repeated in trailing columns so
+/// rows from different runs can be concatenated and still told apart.
+fn csv(repositories: &[&Repository], provenance: &Provenance) -> String {
+    let mut out = format!("{},query,fetched_at,ghs_version,host\n", CSV_HEADER);
+    for repo in repositories {
+        let mut fields = csv_fields(repo);
+        fields.extend([
+            provenance.query.clone(),
+            provenance.fetched_at.clone(),
+            provenance.ghs_version.to_string(),
+            provenance.host.clone(),
+        ]);
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown(repositories: &[&Repository], provenance: &Provenance) -> String {
+    let mut out = String::from(
+        "| Repository | Stars | Language | Description |\n| --- | ---: | --- | --- |\n",
+    );
+    for repo in repositories {
+        let name = match &repo.html_url {
+            Some(url) => format!("[{}]({})", markdown_cell(&repo.full_name), url),
+            None => markdown_cell(&repo.full_name),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            name,
+            repo.stargazers_count,
+            markdown_cell(placeholder::or(
+                repo.language.as_deref(),
+                Field::Language,
+                ""
+            )),
+            markdown_cell(placeholder::or(
+                repo.description.as_deref(),
+                Field::Description,
+                ""
+            )),
+        ));
+    }
+    out.push_str(&format!(
+        "\n_`{}` on {}, fetched {} with ghs {}_\n",
+        provenance.query.replace('`', "'"),
+        provenance.host,
+        provenance.fetched_at,
+        provenance.ghs_version
+    ));
+    out
+}
+
+pub fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
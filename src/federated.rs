@@ -0,0 +1,292 @@
+//! One search across several forges at once, for `--provider all`.
+
+use crate::client::SearchPage;
+use crate::links::PageLinks;
+use crate::provider::{BoxFuture, SearchProvider};
+use crate::{BoxError, QueryError, Repositories, Repository};
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::future;
+use std::task::Poll;
+
+/// Sends each query to every provider concurrently and merges the results,
+/// most-starred first. Each repository records the forge it came from in
+/// [`Repository::provider`]; mirrors found on several forges are listed
+/// once, under all of their names.
+pub struct Federated {
+    providers: Vec<Box<dyn SearchProvider>>,
+    /// Every provider's base URL, so cached results are keyed by the set.
+    base_url: String,
+}
+
+impl Federated {
+    pub fn new(providers: Vec<Box<dyn SearchProvider>>) -> Federated {
+        let base_urls: Vec<&str> = providers
+            .iter()
+            .map(|provider| provider.base_url())
+            .collect();
+        Federated {
+            base_url: base_urls.join(" "),
+            providers,
+        }
+    }
+
+    async fn search_pages(
+        &self,
+        query: &str,
+        per_page: u8,
+        page: u32,
+    ) -> Result<SearchPage, BoxError> {
+        let pages = join_all(
+            self.providers
+                .iter()
+                .map(|provider| provider.search_page(query, per_page, page))
+                .collect(),
+        )
+        .await;
+        let mut results = Vec::new();
+        let mut next = None;
+        for (provider, page) in self.providers.iter().zip(pages) {
+            let page = page.map_err(|err| format!("{}: {}", provider.name(), err))?;
+            next = next.or(page.links.next);
+            results.push(page.repositories);
+        }
+        Ok(SearchPage {
+            etag: None,
+            repositories: self.merge(results),
+            // Only whether there is more; the URL is one forge's.
+            links: PageLinks {
+                next,
+                ..PageLinks::default()
+            },
+        })
+    }
+
+    async fn search_everywhere(
+        &self,
+        query: &str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &dyn Fn(usize, u64),
+    ) -> Result<Repositories, BoxError> {
+        // Each forge reports its own progress; the sum is passed on.
+        let progress = RefCell::new(vec![(0, 0); self.providers.len()]);
+        let reporters: Vec<_> = (0..self.providers.len())
+            .map(|index| {
+                let progress = &progress;
+                move |fetched: usize, total: u64| {
+                    let mut progress = progress.borrow_mut();
+                    progress[index] = (fetched, total);
+                    let fetched = progress.iter().map(|(fetched, _)| fetched).sum();
+                    let total = progress.iter().map(|(_, total)| total).sum();
+                    drop(progress);
+                    on_progress(fetched, total);
+                }
+            })
+            .collect();
+        let results = join_all(
+            self.providers
+                .iter()
+                .zip(&reporters)
+                .map(|(provider, report)| provider.search_all(query, per_page, max_results, report))
+                .collect(),
+        )
+        .await;
+
+        let mut merged = Vec::new();
+        for (provider, result) in self.providers.iter().zip(results) {
+            merged.push(result.map_err(|err| format!("{}: {}", provider.name(), err))?);
+        }
+        let mut repositories = self.merge(merged);
+        if let Some(max_results) = max_results {
+            repositories.items.truncate(max_results);
+        }
+        Ok(repositories)
+    }
+
+    /// Combines one result set per provider, in provider order.
+    fn merge(&self, results: Vec<Repositories>) -> Repositories {
+        let mut merged = Repositories {
+            total_count: 0,
+            incomplete_results: false,
+            items: Vec::new(),
+        };
+        for (provider, repositories) in self.providers.iter().zip(results) {
+            merged.total_count += repositories.total_count;
+            merged.incomplete_results |= repositories.incomplete_results;
+            merged
+                .items
+                .extend(repositories.items.into_iter().map(|mut repo| {
+                    repo.provider = Some(provider.name().to_string());
+                    repo
+                }));
+        }
+        let before = merged.items.len();
+        merged.items = dedupe_mirrors(merged.items);
+        // Totals count every copy; only the listed ones can be subtracted.
+        merged.total_count -= (before - merged.items.len()) as u64;
+        merged
+            .items
+            .sort_by_key(|repo| Reverse(repo.stargazers_count));
+        merged
+    }
+}
+
+/// What identifies a repository across forges: the path of its clone URL
+/// (or failing that its full name), without host, `.git` or case. A mirror
+/// of `github.com/rust-lang/rust` at `codeberg.org/rust-lang/rust.git`
+/// shares the key `rust-lang/rust`.
+fn mirror_key(repo: &Repository) -> String {
+    let url = repo
+        .clone_url
+        .as_deref()
+        .or(repo.ssh_url.as_deref())
+        .unwrap_or(&repo.full_name);
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        // `git@host:owner/name.git`
+        None => url.split_once(':').map_or(url, |(_, path)| path),
+    };
+    let path = path.trim_end_matches('/');
+    path.strip_suffix(".git").unwrap_or(path).to_lowercase()
+}
+
+/// Keeps one repository per [`mirror_key`]: the most-starred copy, which is
+/// usually the original, with every forge the key was found on.
+fn dedupe_mirrors(repositories: Vec<Repository>) -> Vec<Repository> {
+    let mut kept: Vec<Repository> = Vec::new();
+    let mut by_key = HashMap::new();
+    for repo in repositories {
+        let key = mirror_key(&repo);
+        let Some(&index) = by_key.get(&key) else {
+            by_key.insert(key, kept.len());
+            kept.push(repo);
+            continue;
+        };
+        let existing = &mut kept[index];
+        let mut providers = existing.provider.take().unwrap_or_default();
+        if let Some(provider) = &repo.provider {
+            if !providers.split(", ").any(|name| name == provider) {
+                providers = format!("{}, {}", providers, provider);
+            }
+        }
+        if repo.stargazers_count > existing.stargazers_count {
+            *existing = repo;
+        }
+        existing.provider = Some(providers);
+    }
+    kept
+}
+
+/// Runs every future concurrently on the current task and returns their
+/// outputs in order. Providers' futures are not `Send`, so they cannot be
+/// spawned.
+pub async fn join_all<'a, T>(futures: Vec<BoxFuture<'a, T>>) -> Vec<T> {
+    let mut futures: Vec<Option<BoxFuture<'a, T>>> = futures.into_iter().map(Some).collect();
+    let mut outputs: Vec<Option<T>> = futures.iter().map(|_| None).collect();
+    future::poll_fn(|cx| {
+        for (slot, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if let Some(future) = slot {
+                if let Poll::Ready(value) = future.as_mut().poll(cx) {
+                    *output = Some(value);
+                    *slot = None;
+                }
+            }
+        }
+        if futures.iter().all(Option::is_none) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+impl SearchProvider for Federated {
+    fn name(&self) -> &'static str {
+        "every configured forge"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn search_page<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        page: u32,
+    ) -> BoxFuture<'a, Result<SearchPage, BoxError>> {
+        Box::pin(self.search_pages(query, per_page, page))
+    }
+
+    fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &'a dyn Fn(usize, u64),
+    ) -> BoxFuture<'a, Result<Repositories, BoxError>> {
+        Box::pin(self.search_everywhere(query, per_page, max_results, on_progress))
+    }
+
+    /// The lowest of the providers' caps, since a page past any of them
+    /// fails the whole search.
+    fn result_cap(&self) -> Option<u64> {
+        self.providers
+            .iter()
+            .filter_map(|provider| provider.result_cap())
+            .min()
+    }
+
+    fn lint(&self, query: &str) -> Result<Vec<String>, QueryError> {
+        let mut warnings = Vec::new();
+        for provider in &self.providers {
+            for warning in provider.lint(query)? {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(provider: &str, clone_url: &str, stars: u64) -> Repository {
+        let mut repo: Repository = serde_json::from_str(r#"{"name": "rust"}"#).unwrap();
+        repo.provider = Some(provider.to_string());
+        repo.clone_url = Some(clone_url.to_string());
+        repo.stargazers_count = stars;
+        repo
+    }
+
+    #[test]
+    fn keys_mirrors_by_clone_url_path() {
+        let github = repo("GitHub", "https://github.com/rust-lang/rust.git", 0);
+        let mut codeberg = repo("Gitea", "https://codeberg.org/Rust-Lang/rust/", 0);
+        assert_eq!(mirror_key(&github), "rust-lang/rust");
+        assert_eq!(mirror_key(&codeberg), "rust-lang/rust");
+        codeberg.clone_url = None;
+        codeberg.ssh_url = Some("git@codeberg.org:rust-lang/rust.git".to_string());
+        assert_eq!(mirror_key(&codeberg), "rust-lang/rust");
+    }
+
+    #[test]
+    fn keeps_the_most_starred_copy_of_a_mirror() {
+        let repositories = dedupe_mirrors(vec![
+            repo("GitHub", "https://github.com/rust-lang/rust.git", 90),
+            repo("Gitea", "https://codeberg.org/alice/tool.git", 5),
+            repo("GitLab", "https://gitlab.com/rust-lang/rust.git", 1),
+        ]);
+        assert_eq!(repositories.len(), 2);
+        assert_eq!(repositories[0].stargazers_count, 90);
+        assert_eq!(repositories[0].provider.as_deref(), Some("GitHub, GitLab"));
+        assert_eq!(repositories[1].provider.as_deref(), Some("Gitea"));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::cache::Cache;
+use crate::commands::lint;
+use crate::{interrupt, plain};
+use ghs::provider::BoxFuture;
+use ghs::{client, datetime, federated};
+use ghs::{BoxError, Repositories, SearchProvider};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+
+/// Fetches the first page of results, or with `all` every result (slicing
+/// around GitHub's search cap), or up to `max_results` of them, with a
+/// progress line on stderr. Also returns when the results were fetched,
+/// which is earlier for cached ones.
+pub async fn fetch_repositories(
+    provider: &dyn SearchProvider,
+    cache: Option<&Cache>,
+    query: &str,
+    per_page: u8,
+    all: bool,
+    max_results: Option<usize>,
+) -> Result<(Repositories, i64), BoxError> {
+    lint(provider.lint(query))?;
+    let Some(cache) = cache else {
+        let repositories = search(provider, query, per_page, all, max_results).await?;
+        return Ok((repositories, datetime::now()));
+    };
+
+    // Results from other servers are kept apart from github.com's, and
+    // capped result sets from complete ones.
+    let mut key = match provider.base_url() {
+        client::DEFAULT_API_URL => query.to_string(),
+        base_url => format!("{} {}", base_url, query),
+    };
+    if let Some(max_results) = max_results {
+        key.push_str(&format!(" max-results={}", max_results));
+    }
+    let cached = cache.get(&key, per_page, all).await;
+    let (repositories, etag) = match cached {
+        Some(entry) if entry.is_fresh() || cache.is_offline() => {
+            return Ok((entry.repositories, entry.fetched_at))
+        }
+        None if cache.is_offline() => {
+            return Err("no cached results for this search; run it once without --offline".into())
+        }
+        // A single page can be revalidated cheaply; an unchanged first page of
+        // a multi-page search says nothing about the others.
+        Some(entry) if !all && entry.etag.is_some() => {
+            let etag = entry.etag.unwrap();
+            match interrupt::or_exit(provider.search_page_if_changed(query, per_page, &etag))
+                .await?
+            {
+                None => (entry.repositories, Some(etag)),
+                Some(page) => (page.repositories, page.etag),
+            }
+        }
+        _ if !all => {
+            let page = interrupt::or_exit(provider.search_page(query, per_page, 1)).await?;
+            (page.repositories, page.etag)
+        }
+        _ => (
+            search(provider, query, per_page, all, max_results).await?,
+            None,
+        ),
+    };
+
+    // What an interrupted search fetched is not the whole answer, and must
+    // not be served as one later.
+    if interrupt::is_interrupted() {
+        return Ok((repositories, datetime::now()));
+    }
+    if let Err(err) = cache.put(&key, per_page, all, etag, &repositories).await {
+        eprintln!("warning: {}", err);
+    }
+    Ok((repositories, datetime::now()))
+}
+
+/// Runs every one of `queries` concurrently, each as [`fetch_repositories`]
+/// runs one, and merges their results in the order the queries were given.
+/// Repositories found by several queries are listed once, and each records
+/// the queries that found it in [`Repository::query`].
+pub async fn fetch_each(
+    provider: &dyn SearchProvider,
+    cache: Option<&Cache>,
+    queries: &[&str],
+    per_page: u8,
+    all: bool,
+    max_results: Option<usize>,
+) -> Result<(Repositories, i64), BoxError> {
+    let results = federated::join_all(
+        queries
+            .iter()
+            .map(|query| -> BoxFuture<'_, _> {
+                Box::pin(fetch_repositories(
+                    provider,
+                    cache,
+                    query,
+                    per_page,
+                    all,
+                    max_results,
+                ))
+            })
+            .collect(),
+    )
+    .await;
+
+    let mut merged = Repositories {
+        total_count: 0,
+        incomplete_results: false,
+        items: Vec::new(),
+    };
+    let mut fetched_at = datetime::now();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    for (query, result) in queries.iter().zip(results) {
+        let (repositories, query_fetched_at) =
+            result.map_err(|err| format!("--query {}: {}", query, err))?;
+        fetched_at = fetched_at.min(query_fetched_at);
+        merged.total_count += repositories.total_count;
+        merged.incomplete_results |= repositories.incomplete_results;
+        for mut repo in repositories.items {
+            match by_name.get(&repo.full_name) {
+                Some(&index) => {
+                    // Totals count every copy; only the listed ones can be
+                    // subtracted.
+                    merged.total_count -= 1;
+                    let found_by = merged.items[index].query.get_or_insert_with(String::new);
+                    found_by.push_str(", ");
+                    found_by.push_str(query);
+                }
+                None => {
+                    by_name.insert(repo.full_name.clone(), merged.items.len());
+                    repo.query = Some(query.to_string());
+                    merged.items.push(repo);
+                }
+            }
+        }
+    }
+    Ok((merged, fetched_at))
+}
+
+pub async fn search(
+    provider: &dyn SearchProvider,
+    query: &str,
+    per_page: u8,
+    all: bool,
+    max_results: Option<usize>,
+) -> Result<Repositories, BoxError> {
+    if !all {
+        let page = interrupt::or_exit(provider.search_page(query, per_page, 1)).await?;
+        return Ok(page.repositories);
+    }
+
+    let show_progress = !plain::enabled() && io::stderr().is_terminal();
+    let on_progress = |fetched, total| {
+        if show_progress {
+            eprint!("\rFetched {}/{} repositories", fetched, total);
+        }
+    };
+    // Ctrl-C stops the paging, leaving the pages fetched so far.
+    let repositories = provider
+        .search_all(query, per_page, max_results, &on_progress)
+        .await?;
+    if show_progress {
+        eprintln!();
+    }
+    if interrupt::is_interrupted() {
+        if !interrupt::keeps_partial() {
+            eprintln!("Interrupted");
+            crate::exit(130);
+        }
+        return Ok(repositories);
+    }
+
+    let capped = max_results.is_some_and(|max| repositories.items.len() >= max);
+    if let Some(cap) = provider.result_cap() {
+        if !capped && repositories.total_count > repositories.items.len() as u64 {
+            eprintln!(
+                "warning: {} repositories match, but only {} could be retrieved within {}'s search cap of {} per query",
+                repositories.total_count,
+                repositories.items.len(),
+                provider.name(),
+                cap
+            );
+        }
+    }
+    Ok(repositories)
+}
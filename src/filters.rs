@@ -0,0 +1,201 @@
+//! Narrowing fetched repositories down locally, without further requests.
+
+use crate::Repository;
+
+/// How `--title` and `--description` compare their text against a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    /// Case-insensitive substring match: `go` matches `django`.
+    #[default]
+    Substring,
+    /// Every word must appear as a whole word, in any order.
+    Word,
+    /// The words must appear as whole words, in order and next to each other.
+    Phrase,
+}
+
+impl MatchMode {
+    pub fn parse(mode: &str) -> Result<MatchMode, String> {
+        match mode {
+            "substring" => Ok(MatchMode::Substring),
+            "word" => Ok(MatchMode::Word),
+            "phrase" => Ok(MatchMode::Phrase),
+            _ => Err(format!(
+                "`{}` is not a match mode (expected substring, word or phrase)",
+                mode
+            )),
+        }
+    }
+
+    pub fn matches(self, text: &str, needle: &str) -> bool {
+        match self {
+            MatchMode::Substring => text.to_lowercase().contains(&needle.to_lowercase()),
+            MatchMode::Word => {
+                let text = words(text);
+                words(needle).iter().all(|word| text.contains(word))
+            }
+            MatchMode::Phrase => {
+                let phrase = words(needle);
+                !phrase.is_empty()
+                    && words(text)
+                        .windows(phrase.len())
+                        .any(|window| window == phrase.as_slice())
+            }
+        }
+    }
+}
+
+/// Criteria a repository must meet to be kept; unset ones accept anything.
+///
+/// ```
+/// use ghs::filters::{MatchMode, RepositoryFilter};
+///
+/// let filter = RepositoryFilter {
+///     description: Some("command line".to_string()),
+///     language: Some("rust".to_string()),
+///     match_mode: MatchMode::Phrase,
+///     ..RepositoryFilter::default()
+/// };
+/// # let repositories: Vec<ghs::Repository> = Vec::new();
+/// let kept = filter.apply(repositories);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryFilter {
+    /// Text the repository name must match.
+    pub title: Option<String>,
+    /// Text the description must match; repositories without one are dropped.
+    pub description: Option<String>,
+    /// The primary language, compared case-insensitively.
+    pub language: Option<String>,
+    /// How `title` and `description` are matched.
+    pub match_mode: MatchMode,
+    pub exclude_archived: bool,
+}
+
+impl RepositoryFilter {
+    pub fn matches(&self, repo: &Repository) -> bool {
+        let mode = self.match_mode;
+        self.title
+            .as_ref()
+            .is_none_or(|title| mode.matches(&repo.name, title))
+            && self.description.as_ref().is_none_or(|description| {
+                repo.description
+                    .as_ref()
+                    .is_some_and(|text| mode.matches(text, description))
+            })
+            && self.language.as_ref().is_none_or(|language| {
+                repo.language.as_ref().is_some_and(|repo_language| {
+                    repo_language.to_lowercase() == language.to_lowercase()
+                })
+            })
+            && !(self.exclude_archived && repo.archived)
+    }
+
+    /// Keeps the repositories that match, in order.
+    pub fn apply(&self, mut repositories: Vec<Repository>) -> Vec<Repository> {
+        repositories.retain(|repo| self.matches(repo));
+        repositories
+    }
+}
+
+/// Lowercased words, taken as runs of Unicode letters and digits.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How well `pattern` matches `text` fuzzily, ignoring case: its characters
+/// must all appear in `text` in order, though not necessarily next to each
+/// other. Runs of adjacent characters and matches at the start of a word
+/// score higher. `None` if `pattern` does not match at all.
+pub fn fuzzy_score(text: &str, pattern: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let first = *pattern.first()?;
+    // Matching greedily from each place the first character occurs, and
+    // keeping the best, finds `cli` in `cool-cli` as a run.
+    (0..text.len())
+        .filter(|start| text[*start] == first)
+        .filter_map(|start| {
+            let mut score = 0;
+            let mut position = start;
+            let mut previous: Option<usize> = None;
+            for wanted in &pattern {
+                let found = (position..text.len()).find(|index| text[*index] == *wanted)?;
+                score += 1;
+                if previous.is_some_and(|previous| previous + 1 == found) {
+                    score += 4;
+                }
+                if found == 0 || !text[found - 1].is_alphanumeric() {
+                    score += 2;
+                }
+                previous = Some(found);
+                position = found + 1;
+            }
+            Some(score)
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(json: &str) -> Repository {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn match_modes() {
+        let text = "A fast command-line tool";
+        assert!(MatchMode::Substring.matches(text, "LINE TO"));
+        assert!(!MatchMode::Word.matches(text, "line to"));
+        assert!(MatchMode::Word.matches(text, "tool fast"));
+        assert!(MatchMode::Phrase.matches(text, "command line"));
+        assert!(!MatchMode::Phrase.matches(text, "line command"));
+        assert!(!MatchMode::Phrase.matches(text, ""));
+    }
+
+    #[test]
+    fn filter_needs_every_criterion() {
+        let repositories = vec![
+            repo(r#"{"name": "ghs", "description": "GitHub search", "language": "Rust"}"#),
+            repo(r#"{"name": "ghs-web", "language": "TypeScript"}"#),
+            repo(
+                r#"{"name": "old-ghs", "description": "GitHub search", "language": "rust", "archived": true}"#,
+            ),
+        ];
+        let filter = RepositoryFilter {
+            title: Some("ghs".to_string()),
+            language: Some("RUST".to_string()),
+            ..RepositoryFilter::default()
+        };
+        let names = |kept: Vec<Repository>| -> Vec<String> {
+            kept.into_iter().map(|repo| repo.name).collect()
+        };
+        assert_eq!(
+            names(filter.apply(repositories.clone())),
+            ["ghs", "old-ghs"]
+        );
+
+        let filter = RepositoryFilter {
+            exclude_archived: true,
+            description: Some("search".to_string()),
+            ..filter
+        };
+        assert_eq!(names(filter.apply(repositories.clone())), ["ghs"]);
+        assert_eq!(RepositoryFilter::default().apply(repositories).len(), 3);
+    }
+
+    #[test]
+    fn fuzzy_scores_prefer_runs_and_word_starts() {
+        assert_eq!(fuzzy_score("kubernetes", "ktb"), None);
+        assert_eq!(fuzzy_score("anything", ""), None);
+        let run = fuzzy_score("cool-cli", "cli").unwrap();
+        let scattered = fuzzy_score("cool-helix", "cli").unwrap();
+        assert!(run > scattered);
+        assert!(fuzzy_score("golang", "go").unwrap() > fuzzy_score("django", "go").unwrap());
+    }
+}
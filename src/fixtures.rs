@@ -0,0 +1,285 @@
+//! Recorded API responses, for tests that replay them instead of talking
+//! to GitHub.
+//!
+//! `ghs fixtures generate` records them and `--replay DIR` (or
+//! [`GhsClientBuilder::replay_fixtures`](crate::GhsClientBuilder::replay_fixtures))
+//! serves them. A fixture directory holds one JSON file per request,
+//! named after the method and path plus a hash of the whole request line,
+//! e.g. `get-search-repositories-0d1f5e2a9c.json`:
+//!
+//! ```json
+//! {
+//!   "method": "GET",
+//!   "path": "/search/repositories?q=user%3Aoctocat&per_page=5",
+//!   "status": 200,
+//!   "headers": { "content-type": "application/json; charset=utf-8" },
+//!   "body": { "total_count": 8, "incomplete_results": false, "items": [] }
+//! }
+//! ```
+//!
+//! `path` is below the API base URL, and so are the URLs in `headers`,
+//! which start with `{base}` in its place. `body` is the JSON the server
+//! sent, or with `"raw": true` the text of a body that was not JSON, such
+//! as a raw README. Only headers the client reads are kept.
+
+use crate::BoxError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What the base URL becomes in recorded headers.
+const BASE: &str = "{base}";
+
+/// The response headers worth recording: the rest identify the request or
+/// the server and would only make fixtures differ between recordings.
+const KEPT_HEADERS: &[&str] = &[
+    "content-type",
+    "etag",
+    "link",
+    "retry-after",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "x-ratelimit-used",
+    "x-ratelimit-resource",
+    "deprecation",
+    "sunset",
+];
+
+/// Prefixes of GitHub's token formats.
+const TOKEN_PREFIXES: &[&str] = &["ghp_", "gho_", "ghu_", "ghs_", "ghr_", "github_pat_"];
+
+/// What a client does with the fixtures in a directory.
+#[derive(Debug)]
+pub(crate) enum Mode {
+    /// Writes every response it gets there.
+    Record { dir: PathBuf, anonymize: bool },
+    /// Answers every request from there, without the network.
+    Replay(PathBuf),
+}
+
+/// One recorded request and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub method: String,
+    /// Path and query string below the API base URL.
+    pub path: String,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+    /// Whether `body` is the text of the response rather than its JSON.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub raw: bool,
+}
+
+impl Fixture {
+    /// Records a response to `method` `url`, a URL below `base_url`.
+    pub fn new(
+        base_url: &str,
+        method: &str,
+        url: &str,
+        status: u16,
+        headers: impl IntoIterator<Item = (String, String)>,
+        body: &[u8],
+    ) -> Fixture {
+        let headers = headers
+            .into_iter()
+            .filter(|(name, _)| KEPT_HEADERS.contains(&name.as_str()))
+            .map(|(name, value)| (name, value.replace(base_url, BASE)))
+            .collect();
+        let (body, raw) = match serde_json::from_slice(body) {
+            Ok(json) => (json, false),
+            Err(_) => (
+                Value::String(String::from_utf8_lossy(body).into_owned()),
+                true,
+            ),
+        };
+        Fixture {
+            method: method.to_string(),
+            path: url.strip_prefix(base_url).unwrap_or(url).to_string(),
+            status,
+            headers,
+            body,
+            raw,
+        }
+    }
+
+    /// The headers as they would come from `base_url`.
+    pub fn headers_for<'a>(
+        &'a self,
+        base_url: &'a str,
+    ) -> impl Iterator<Item = (&'a str, String)> + 'a {
+        self.headers
+            .iter()
+            .map(move |(name, value)| (name.as_str(), value.replace(BASE, base_url)))
+    }
+
+    /// The body as the server sent it, give or take JSON formatting.
+    pub fn body_bytes(&self) -> Vec<u8> {
+        match (&self.body, self.raw) {
+            (Value::String(text), true) => text.clone().into_bytes(),
+            (body, _) => body.to_string().into_bytes(),
+        }
+    }
+
+    /// Replaces e-mail addresses with `user@example.com` and anything
+    /// shaped like a GitHub token with `[REDACTED]`. Logins and repository
+    /// names are public and kept, as tests tend to look for them.
+    pub fn anonymize(&mut self) {
+        scrub(&mut self.body);
+        for value in self.headers.values_mut() {
+            if looks_like_token(value) {
+                *value = "[REDACTED]".to_string();
+            }
+        }
+    }
+}
+
+fn scrub(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    Value::String(email) if key.ends_with("email") && email.contains('@') => {
+                        *email = "user@example.com".to_string()
+                    }
+                    value => scrub(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub),
+        Value::String(text) if looks_like_token(text) => *text = "[REDACTED]".to_string(),
+        _ => {}
+    }
+}
+
+fn looks_like_token(text: &str) -> bool {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|word| TOKEN_PREFIXES.iter().any(|prefix| word.starts_with(prefix)))
+}
+
+/// The file a request for `method` `path` is recorded in.
+pub fn file_name(method: &str, path: &str) -> String {
+    let route = path.split('?').next().unwrap_or_default();
+    let slug: Vec<String> = route
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() {
+                    true => c.to_ascii_lowercase(),
+                    false => '-',
+                })
+                .collect()
+        })
+        .collect();
+    let hash = format!(
+        "{:x}",
+        Sha256::digest(format!("{} {}", method, path).as_bytes())
+    );
+    format!(
+        "{}-{}-{}.json",
+        method.to_lowercase(),
+        slug.join("-"),
+        &hash[..10]
+    )
+}
+
+/// Writes `fixture` to its file in `dir`, creating the directory.
+pub fn save(dir: &Path, fixture: &Fixture) -> Result<(), BoxError> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(file_name(&fixture.method, &fixture.path));
+    fs::write(&path, serde_json::to_string_pretty(fixture)? + "\n")
+        .map_err(|err| format!("could not write fixture {}: {}", path.display(), err))?;
+    Ok(())
+}
+
+/// The fixture recorded in `dir` for `method` `path`.
+pub fn load(dir: &Path, method: &str, path: &str) -> Result<Fixture, BoxError> {
+    let file = dir.join(file_name(method, path));
+    let contents = fs::read_to_string(&file).map_err(|err| {
+        format!(
+            "no fixture for {} {} ({}: {})",
+            method,
+            path,
+            file.display(),
+            err
+        )
+    })?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn names_files_after_the_route() {
+        let name = file_name("GET", "/search/repositories?q=user%3Aocto&per_page=5");
+        assert!(name.starts_with("get-search-repositories-"), "{}", name);
+        assert_ne!(name, file_name("GET", "/search/repositories?q=rust"));
+        assert_eq!(
+            file_name("PUT", "/repos/octo/ghs.rs/topics").len(),
+            "put-repos-octo-ghs-rs-topics-".len() + 10 + ".json".len()
+        );
+    }
+
+    #[test]
+    fn keeps_paths_and_links_below_the_base_url() {
+        let headers = [
+            (
+                "link".to_string(),
+                "<https://api.test/x?page=2>; rel=\"next\"".to_string(),
+            ),
+            ("x-github-request-id".to_string(), "ABCD:1234".to_string()),
+        ];
+        let fixture = Fixture::new(
+            "https://api.test",
+            "GET",
+            "https://api.test/x",
+            200,
+            headers,
+            b"{}",
+        );
+        assert_eq!(fixture.path, "/x");
+        assert_eq!(
+            fixture.headers.keys().collect::<Vec<_>>(),
+            ["link"],
+            "only headers the client reads are kept"
+        );
+        let (_, link) = fixture.headers_for("http://localhost:1").next().unwrap();
+        assert_eq!(link, "<http://localhost:1/x?page=2>; rel=\"next\"");
+    }
+
+    #[test]
+    fn keeps_bodies_that_are_not_json_as_text() {
+        let fixture = Fixture::new("", "GET", "/readme", 200, [], b"# ghs\n");
+        assert!(fixture.raw);
+        assert_eq!(fixture.body_bytes(), b"# ghs\n");
+    }
+
+    #[test]
+    fn anonymizes_emails_and_tokens() {
+        let body = json!({
+            "owner": {"login": "octo", "email": "octo@github.com"},
+            "commit": {"author": {"email": "octo@users.noreply.github.com"}},
+            "notes": ["token ghp_abc123 leaked", "fine"],
+        });
+        let mut fixture = Fixture::new("", "GET", "/x", 200, [], body.to_string().as_bytes());
+        fixture.anonymize();
+        assert_eq!(
+            fixture.body,
+            json!({
+                "owner": {"login": "octo", "email": "user@example.com"},
+                "commit": {"author": {"email": "user@example.com"}},
+                "notes": ["[REDACTED]", "fine"],
+            })
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Repository search on Gitea and Forgejo, mapped onto the same
+//! [`Repository`] model as GitHub's results.
+
+use crate::client::{self, SearchPage};
+use crate::links::PageLinks;
+use crate::model::{License, User};
+use crate::provider::{BoxFuture, Criteria, SearchProvider};
+use crate::{datetime, BoxError, GhsClient, GhsClientBuilder, Repositories, Repository};
+use reqwest::header;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// Client for a Gitea or Forgejo instance's `/api/v1`. Requests go through a
+/// [`GhsClient`] pointed at that API, so retries, rate-limit handling and
+/// `--stats` work the same as on GitHub.
+#[derive(Debug, Clone)]
+pub struct GiteaClient {
+    api: GhsClient,
+    /// Owner logins resolved to the numeric ids the search API filters by.
+    owner_ids: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+/// A repository as Gitea serializes it.
+#[derive(Debug, Deserialize)]
+struct GiteaRepository {
+    name: String,
+    full_name: String,
+    owner: Option<GiteaUser>,
+    #[serde(default)]
+    description: String,
+    html_url: Option<String>,
+    clone_url: Option<String>,
+    ssh_url: Option<String>,
+    #[serde(default)]
+    website: String,
+    #[serde(default)]
+    language: String,
+    topics: Option<Vec<String>>,
+    default_branch: Option<String>,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    stars_count: u64,
+    #[serde(default)]
+    forks_count: u64,
+    #[serde(default)]
+    open_issues_count: u64,
+    #[serde(default)]
+    watchers_count: u64,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    /// SPDX identifiers, on Gitea 1.22 and later.
+    licenses: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+    id: u64,
+    login: String,
+    avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    data: Vec<GiteaRepository>,
+}
+
+impl From<GiteaRepository> for Repository {
+    fn from(repo: GiteaRepository) -> Repository {
+        let non_empty = |value: String| (!value.is_empty()).then_some(value);
+        let updated_at = repo.updated_at.as_deref().and_then(datetime::to_utc);
+        Repository {
+            name: repo.name,
+            full_name: repo.full_name,
+            owner: repo.owner.map(|owner| User {
+                login: owner.login,
+                avatar_url: owner.avatar_url,
+            }),
+            description: non_empty(repo.description),
+            html_url: repo.html_url,
+            clone_url: repo.clone_url,
+            ssh_url: repo.ssh_url,
+            homepage: non_empty(repo.website),
+            language: non_empty(repo.language),
+            topics: repo.topics.unwrap_or_default(),
+            default_branch: repo.default_branch,
+            fork: repo.fork,
+            archived: repo.archived,
+            stargazers_count: repo.stars_count,
+            forks_count: repo.forks_count,
+            open_issues_count: repo.open_issues_count,
+            watchers_count: repo.watchers_count,
+            created_at: repo.created_at.as_deref().and_then(datetime::to_utc),
+            // Gitea has no separate push time; pushes bump `updated_at`.
+            pushed_at: updated_at.clone(),
+            updated_at,
+            license: repo
+                .licenses
+                .and_then(|licenses| licenses.into_iter().next())
+                .map(|spdx_id| License {
+                    name: spdx_id.clone(),
+                    spdx_id: Some(spdx_id),
+                }),
+            provider: None,
+            query: None,
+            starred: None,
+            score: None,
+            languages: None,
+        }
+    }
+}
+
+impl GiteaClient {
+    /// `host` is a hostname such as `codeberg.org`, or a URL such as
+    /// `http://localhost:3000` for instances not served over HTTPS. The
+    /// builder's own host setting is replaced.
+    pub fn new(host: &str, builder: GhsClientBuilder) -> Result<GiteaClient, BoxError> {
+        let site = if host.contains("://") {
+            host.trim_end_matches('/').to_string()
+        } else {
+            format!("https://{}", host.trim_end_matches('/'))
+        };
+        Ok(GiteaClient {
+            api: builder.host(format!("{}/api/v1", site)).build()?,
+            owner_ids: Arc::default(),
+        })
+    }
+
+    /// The underlying client, for its stats.
+    pub fn api(&self) -> &GhsClient {
+        &self.api
+    }
+
+    async fn owner_id(&self, login: &str) -> Result<u64, BoxError> {
+        if let Some(id) = self.owner_ids.lock().unwrap().get(login) {
+            return Ok(*id);
+        }
+        let url = format!("{}/users/{}", self.api.base_url(), login);
+        let owner: GiteaUser = self
+            .api
+            .get_json(&url)
+            .await?
+            .ok_or_else(|| format!("no user or organization named {}", login))?;
+        self.owner_ids
+            .lock()
+            .unwrap()
+            .insert(login.to_string(), owner.id);
+        Ok(owner.id)
+    }
+
+    async fn search_repositories_page(
+        &self,
+        query: &str,
+        per_page: u8,
+        page: u32,
+    ) -> Result<SearchPage, BoxError> {
+        let search = Criteria::parse(query, "Gitea")?;
+        // Gitea matches either names or topics against `q`, never both.
+        if search.topic.is_some() && !search.keywords.is_empty() {
+            return Err("Gitea search cannot combine keywords with topic:".into());
+        }
+        if let Some(full_name) = &search.repo {
+            return self.repository_page(full_name, page).await;
+        }
+
+        let mut params = vec![
+            ("q", search.keywords.join(" ")),
+            ("limit", per_page.to_string()),
+            ("page", page.to_string()),
+        ];
+        if let Some(owner) = &search.owner {
+            params.push(("uid", self.owner_id(owner).await?.to_string()));
+            params.push(("exclusive", "true".to_string()));
+        }
+        if let Some(topic) = &search.topic {
+            params[0].1 = topic.clone();
+            params.push(("topic", "true".to_string()));
+        }
+        if let Some(archived) = search.archived {
+            params.push(("archived", archived.to_string()));
+        }
+        let url =
+            Url::parse_with_params(&format!("{}/repos/search", self.api.base_url()), &params)?;
+
+        let response = self.api.get(url.as_str(), "application/json").await?;
+        if !response.status.is_success() {
+            return Err(client::request_error(url.as_str(), &response));
+        }
+        let results: SearchResults = self
+            .api
+            .stats()
+            .timings
+            .time_serialization(|| serde_json::from_slice(&response.body))?;
+        let items: Vec<Repository> = results.data.into_iter().map(Repository::from).collect();
+        let total_count = response
+            .headers
+            .get("x-total-count")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(items.len() as u64);
+        let links = response
+            .headers
+            .get(header::LINK)
+            .and_then(|value| value.to_str().ok())
+            .map(PageLinks::parse)
+            .unwrap_or_default();
+
+        Ok(SearchPage {
+            etag: None,
+            repositories: Repositories {
+                total_count,
+                incomplete_results: false,
+                items,
+            },
+            links,
+        })
+    }
+
+    /// `repo:owner/name` as a one-result page.
+    async fn repository_page(&self, full_name: &str, page: u32) -> Result<SearchPage, BoxError> {
+        let url = format!("{}/repos/{}", self.api.base_url(), full_name);
+        let repository = match page {
+            1 => self.api.get_json::<GiteaRepository>(&url).await?,
+            _ => None,
+        };
+        let items: Vec<Repository> = repository.into_iter().map(Repository::from).collect();
+        Ok(SearchPage {
+            etag: None,
+            repositories: Repositories {
+                total_count: items.len() as u64,
+                incomplete_results: false,
+                items,
+            },
+            links: PageLinks::default(),
+        })
+    }
+
+    /// Pages through every result, or until `max_results`. Instances cap the page size (at 50 by
+    /// default) whatever was asked for, so this stops on an empty page or
+    /// once the advertised total is reached rather than counting pages.
+    async fn search_all_repositories(
+        &self,
+        query: &str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &dyn Fn(usize, u64),
+    ) -> Result<Repositories, BoxError> {
+        let Some(first) = self
+            .api
+            .unless_cancelled(self.search_repositories_page(query, per_page, 1))
+            .await
+            .transpose()?
+        else {
+            return Ok(Repositories::cancelled());
+        };
+        let mut repositories = first.repositories;
+        on_progress(repositories.items.len(), repositories.total_count);
+
+        let wanted = max_results.map_or(repositories.total_count, |max| {
+            repositories.total_count.min(max as u64)
+        });
+        let mut page = 1;
+        while (repositories.items.len() as u64) < wanted {
+            page += 1;
+            let Some(next) = self
+                .api
+                .unless_cancelled(self.search_repositories_page(query, per_page, page))
+                .await
+                .transpose()?
+            else {
+                repositories.incomplete_results = true;
+                break;
+            };
+            if next.repositories.items.is_empty() {
+                break;
+            }
+            repositories.items.extend(next.repositories.items);
+            on_progress(repositories.items.len(), repositories.total_count);
+        }
+        if let Some(max_results) = max_results {
+            repositories.items.truncate(max_results);
+        }
+        Ok(repositories)
+    }
+}
+
+impl SearchProvider for GiteaClient {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn base_url(&self) -> &str {
+        self.api.base_url()
+    }
+
+    fn search_page<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        page: u32,
+    ) -> BoxFuture<'a, Result<SearchPage, BoxError>> {
+        Box::pin(self.search_repositories_page(query, per_page, page))
+    }
+
+    fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &'a dyn Fn(usize, u64),
+    ) -> BoxFuture<'a, Result<Repositories, BoxError>> {
+        Box::pin(self.search_all_repositories(query, per_page, max_results, on_progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_gitea_repositories() {
+        let repo: GiteaRepository = serde_json::from_str(
+            r#"{
+                "id": 1, "name": "forgejo", "full_name": "forgejo/forgejo",
+                "owner": {"id": 2, "login": "forgejo", "avatar_url": "https://x/a.png"},
+                "description": "", "website": "https://forgejo.org", "language": "Go",
+                "topics": null, "stars_count": 3, "archived": false,
+                "updated_at": "2023-04-05T08:07:08+02:00", "licenses": ["GPL-3.0-or-later"]
+            }"#,
+        )
+        .unwrap();
+        let repo = Repository::from(repo);
+        assert_eq!(repo.description, None);
+        assert_eq!(repo.homepage.as_deref(), Some("https://forgejo.org"));
+        assert_eq!(repo.stargazers_count, 3);
+        assert!(repo.topics.is_empty());
+        assert_eq!(repo.pushed_at.as_deref(), Some("2023-04-05T06:07:08Z"));
+        assert_eq!(
+            repo.license.and_then(|license| license.spdx_id).as_deref(),
+            Some("GPL-3.0-or-later")
+        );
+    }
+}
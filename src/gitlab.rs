@@ -0,0 +1,373 @@
+//! Project search on GitLab, mapped onto the same [`Repository`] model as
+//! GitHub's results.
+
+use crate::client::{self, SearchPage};
+use crate::links::PageLinks;
+use crate::model::User;
+use crate::provider::{BoxFuture, Criteria, SearchProvider};
+use crate::{datetime, BoxError, GhsClient, GhsClientBuilder, Repositories, Repository};
+use reqwest::header;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use url::{form_urlencoded, Url};
+
+/// Largest page GitLab serves.
+const MAX_PER_PAGE: u8 = 100;
+
+/// Client for a GitLab instance's `/api/v4`. Requests go through a
+/// [`GhsClient`] pointed at that API, so retries, rate-limit handling and
+/// `--stats` work the same as on GitHub. GitLab accepts personal access
+/// tokens as bearer tokens.
+#[derive(Debug, Clone)]
+pub struct GitLabClient {
+    api: GhsClient,
+    /// Where each owner's projects are listed: `users/...` or `groups/...`.
+    owner_paths: Arc<Mutex<HashMap<String, String>>>,
+}
+
+/// A project as GitLab serializes it.
+#[derive(Debug, Deserialize)]
+struct Project {
+    /// The URL slug; `name` is a display name that may contain spaces.
+    path: String,
+    path_with_namespace: String,
+    namespace: Option<Namespace>,
+    description: Option<String>,
+    web_url: Option<String>,
+    http_url_to_repo: Option<String>,
+    ssh_url_to_repo: Option<String>,
+    /// `tag_list` before GitLab 14.
+    #[serde(default, alias = "tag_list")]
+    topics: Vec<String>,
+    default_branch: Option<String>,
+    forked_from_project: Option<serde_json::Value>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    star_count: u64,
+    #[serde(default)]
+    forks_count: u64,
+    /// Missing when the project has issues turned off.
+    #[serde(default)]
+    open_issues_count: u64,
+    created_at: Option<String>,
+    last_activity_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Namespace {
+    full_path: String,
+    avatar_url: Option<String>,
+}
+
+impl From<Project> for Repository {
+    fn from(project: Project) -> Repository {
+        let last_activity_at = project
+            .last_activity_at
+            .as_deref()
+            .and_then(datetime::to_utc);
+        Repository {
+            name: project.path,
+            full_name: project.path_with_namespace,
+            owner: project.namespace.map(|namespace| User {
+                login: namespace.full_path,
+                avatar_url: namespace.avatar_url,
+            }),
+            description: project
+                .description
+                .filter(|description| !description.is_empty()),
+            html_url: project.web_url,
+            clone_url: project.http_url_to_repo,
+            ssh_url: project.ssh_url_to_repo,
+            homepage: None,
+            // Only available per project, from /projects/:id/languages.
+            language: None,
+            topics: project.topics,
+            default_branch: project.default_branch,
+            fork: project.forked_from_project.is_some(),
+            archived: project.archived,
+            stargazers_count: project.star_count,
+            forks_count: project.forks_count,
+            open_issues_count: project.open_issues_count,
+            watchers_count: 0,
+            created_at: project.created_at.as_deref().and_then(datetime::to_utc),
+            // GitLab's activity time covers pushes as well as issues and
+            // merge requests; it is the nearest thing to `pushed_at`.
+            pushed_at: last_activity_at.clone(),
+            updated_at: last_activity_at,
+            license: None,
+            provider: None,
+            query: None,
+            starred: None,
+            score: None,
+            languages: None,
+        }
+    }
+}
+
+/// `group/subgroup` as the single path segment GitLab expects.
+fn encode_path(path: &str) -> String {
+    form_urlencoded::byte_serialize(path.as_bytes()).collect()
+}
+
+impl GitLabClient {
+    /// `host` is a hostname such as `gitlab.com`, or a URL such as
+    /// `http://localhost:8080` for instances not served over HTTPS. The
+    /// builder's own host setting is replaced.
+    pub fn new(host: &str, builder: GhsClientBuilder) -> Result<GitLabClient, BoxError> {
+        let site = if host.contains("://") {
+            host.trim_end_matches('/').to_string()
+        } else {
+            format!("https://{}", host.trim_end_matches('/'))
+        };
+        Ok(GitLabClient {
+            api: builder.host(format!("{}/api/v4", site)).build()?,
+            owner_paths: Arc::default(),
+        })
+    }
+
+    /// The underlying client, for its stats.
+    pub fn api(&self) -> &GhsClient {
+        &self.api
+    }
+
+    /// The projects listing for `owner`, which may be a user or a group.
+    async fn owner_path(&self, owner: &str) -> Result<String, BoxError> {
+        if let Some(path) = self.owner_paths.lock().unwrap().get(owner) {
+            return Ok(path.clone());
+        }
+        let base_url = self.api.base_url();
+        let url = Url::parse_with_params(&format!("{}/users", base_url), [("username", owner)])?;
+        let users: Vec<serde_json::Value> =
+            self.api.get_json(url.as_str()).await?.unwrap_or_default();
+        let path = if users.is_empty() {
+            let group = format!("groups/{}", encode_path(owner));
+            self.api
+                .get_json::<serde_json::Value>(&format!("{}/{}", base_url, group))
+                .await?
+                .ok_or_else(|| format!("no user or group named {}", owner))?;
+            group
+        } else {
+            format!("users/{}", encode_path(owner))
+        };
+        self.owner_paths
+            .lock()
+            .unwrap()
+            .insert(owner.to_string(), path.clone());
+        Ok(path)
+    }
+
+    async fn search_projects_page(
+        &self,
+        query: &str,
+        per_page: u8,
+        page: u32,
+    ) -> Result<SearchPage, BoxError> {
+        let criteria = Criteria::parse(query, "GitLab")?;
+        if let Some(full_name) = &criteria.repo {
+            return self.project_page(full_name, page).await;
+        }
+
+        let mut params = vec![
+            ("per_page", per_page.min(MAX_PER_PAGE).to_string()),
+            ("page", page.to_string()),
+        ];
+        if !criteria.keywords.is_empty() {
+            params.push(("search", criteria.keywords.join(" ")));
+        }
+        if let Some(topic) = &criteria.topic {
+            params.push(("topic", topic.clone()));
+        }
+        if let Some(archived) = criteria.archived {
+            params.push(("archived", archived.to_string()));
+        }
+        let listing = match &criteria.owner {
+            Some(owner) => {
+                let path = self.owner_path(owner).await?;
+                if path.starts_with("groups/") {
+                    params.push(("include_subgroups", "true".to_string()));
+                }
+                format!("{}/projects", path)
+            }
+            None => "projects".to_string(),
+        };
+        let url = Url::parse_with_params(&format!("{}/{}", self.api.base_url(), listing), &params)?;
+
+        let response = self.api.get(url.as_str(), "application/json").await?;
+        if !response.status.is_success() {
+            return Err(client::request_error(url.as_str(), &response));
+        }
+        let projects: Vec<Project> = self
+            .api
+            .stats()
+            .timings
+            .time_serialization(|| serde_json::from_slice(&response.body))?;
+        let items: Vec<Repository> = projects.into_iter().map(Repository::from).collect();
+        // GitLab leaves out the total for very large result sets.
+        let total_count = response
+            .headers
+            .get("x-total")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(items.len() as u64);
+        let links = response
+            .headers
+            .get(header::LINK)
+            .and_then(|value| value.to_str().ok())
+            .map(PageLinks::parse)
+            .unwrap_or_default();
+
+        Ok(SearchPage {
+            etag: None,
+            repositories: Repositories {
+                total_count,
+                incomplete_results: false,
+                items,
+            },
+            links,
+        })
+    }
+
+    /// `repo:namespace/path` as a one-result page.
+    async fn project_page(&self, full_name: &str, page: u32) -> Result<SearchPage, BoxError> {
+        let url = format!(
+            "{}/projects/{}",
+            self.api.base_url(),
+            encode_path(full_name)
+        );
+        let project = match page {
+            1 => self.api.get_json::<Project>(&url).await?,
+            _ => None,
+        };
+        let items: Vec<Repository> = project.into_iter().map(Repository::from).collect();
+        Ok(SearchPage {
+            etag: None,
+            repositories: Repositories {
+                total_count: items.len() as u64,
+                incomplete_results: false,
+                items,
+            },
+            links: PageLinks::default(),
+        })
+    }
+
+    /// Follows `next` links until the last page, or until `max_results`.
+    async fn search_all_projects(
+        &self,
+        query: &str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &dyn Fn(usize, u64),
+    ) -> Result<Repositories, BoxError> {
+        let Some(mut page) = self
+            .api
+            .unless_cancelled(self.search_projects_page(query, per_page, 1))
+            .await
+            .transpose()?
+        else {
+            return Ok(Repositories::cancelled());
+        };
+        let mut repositories = Repositories {
+            total_count: page.repositories.total_count,
+            incomplete_results: false,
+            items: Vec::new(),
+        };
+        let mut number = 1;
+        loop {
+            repositories.items.extend(page.repositories.items);
+            let total_count = repositories
+                .total_count
+                .max(repositories.items.len() as u64);
+            on_progress(repositories.items.len(), total_count);
+            let enough = max_results.is_some_and(|max| repositories.items.len() >= max);
+            if enough || page.links.next.is_none() {
+                break;
+            }
+            number += 1;
+            match self
+                .api
+                .unless_cancelled(self.search_projects_page(query, per_page, number))
+                .await
+            {
+                Some(next) => page = next?,
+                None => {
+                    repositories.incomplete_results = true;
+                    break;
+                }
+            }
+        }
+        repositories.total_count = repositories
+            .total_count
+            .max(repositories.items.len() as u64);
+        if let Some(max_results) = max_results {
+            repositories.items.truncate(max_results);
+        }
+        Ok(repositories)
+    }
+}
+
+impl SearchProvider for GitLabClient {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn base_url(&self) -> &str {
+        self.api.base_url()
+    }
+
+    fn search_page<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        page: u32,
+    ) -> BoxFuture<'a, Result<SearchPage, BoxError>> {
+        Box::pin(self.search_projects_page(query, per_page, page))
+    }
+
+    fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &'a dyn Fn(usize, u64),
+    ) -> BoxFuture<'a, Result<Repositories, BoxError>> {
+        Box::pin(self.search_all_projects(query, per_page, max_results, on_progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_gitlab_projects() {
+        let project: Project = serde_json::from_str(
+            r#"{
+                "id": 1, "name": "GitLab FOSS", "path": "gitlab-foss",
+                "path_with_namespace": "gitlab-org/gitlab-foss",
+                "namespace": {"id": 9, "path": "gitlab-org", "full_path": "gitlab-org", "avatar_url": null},
+                "description": "", "web_url": "https://gitlab.com/gitlab-org/gitlab-foss",
+                "tag_list": ["ruby"], "star_count": 5, "archived": true,
+                "created_at": "2013-09-30T13:46:02.000Z",
+                "last_activity_at": "2023-04-05T08:07:08.123+02:00"
+            }"#,
+        )
+        .unwrap();
+        let repo = Repository::from(project);
+        assert_eq!(repo.name, "gitlab-foss");
+        assert_eq!(repo.full_name, "gitlab-org/gitlab-foss");
+        assert_eq!(repo.owner.unwrap().login, "gitlab-org");
+        assert_eq!(repo.description, None);
+        assert_eq!(repo.topics, ["ruby"]);
+        assert_eq!(repo.stargazers_count, 5);
+        assert!(repo.archived && !repo.fork);
+        assert_eq!(repo.pushed_at.as_deref(), Some("2023-04-05T06:07:08Z"));
+        assert_eq!(repo.created_at.as_deref(), Some("2013-09-30T13:46:02Z"));
+    }
+
+    #[test]
+    fn encodes_nested_namespaces() {
+        assert_eq!(encode_path("gitlab-org/gitlab"), "gitlab-org%2Fgitlab");
+    }
+}
@@ -0,0 +1,62 @@
+use crate::color;
+use crate::plain;
+use ghs::datetime;
+use ghs::Repository;
+
+const DAY: i64 = 86_400;
+const BAR_WIDTH: usize = 40;
+
+/// Push-age buckets, newest first: label, maximum age in seconds and bar color.
+const BUCKETS: &[(&str, i64, &str)] = &[
+    ("This week", 7 * DAY, color::GREEN),
+    ("This month", 30 * DAY, color::GREEN),
+    ("This quarter", 91 * DAY, color::YELLOW),
+    ("This year", 365 * DAY, color::YELLOW),
+    ("Older", i64::MAX, color::RED),
+];
+
+/// Counts repositories per push-age bucket; the extra trailing slot holds
+/// repositories that were never pushed to.
+fn bucket_counts(repositories: &[Repository], now: i64) -> Vec<usize> {
+    let mut counts = vec![0; BUCKETS.len() + 1];
+    for repo in repositories {
+        let pushed = repo
+            .pushed_at
+            .as_deref()
+            .and_then(datetime::parse_timestamp);
+        let index = match pushed {
+            Some(pushed) => BUCKETS
+                .iter()
+                .position(|(_, max_age, _)| now - pushed <= *max_age)
+                .unwrap_or(BUCKETS.len() - 1),
+            None => BUCKETS.len(),
+        };
+        counts[index] += 1;
+    }
+    counts
+}
+
+pub fn print_heatmap(repositories: &[Repository]) {
+    let counts = bucket_counts(repositories, datetime::now());
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    let rows = BUCKETS
+        .iter()
+        .map(|(label, _, code)| (*label, *code))
+        .chain(std::iter::once(("Never pushed", color::DIM)));
+
+    for ((label, code), count) in rows.zip(counts) {
+        if plain::enabled() {
+            println!("{}: {} repositories", label, count);
+            continue;
+        }
+
+        let width = (count * BAR_WIDTH).div_ceil(max);
+        println!(
+            "{:<12} {} {}",
+            label,
+            color::paint(&"█".repeat(width), code),
+            count
+        );
+    }
+}
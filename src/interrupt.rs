@@ -0,0 +1,59 @@
+use ghs::cancel::Cancel;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+static CANCEL: OnceLock<Cancel> = OnceLock::new();
+static KEEP_PARTIAL: AtomicBool = AtomicBool::new(false);
+
+/// What Ctrl-C cancels. Clients built with it stop paging on the first
+/// Ctrl-C and return the results fetched so far.
+pub fn cancel() -> Cancel {
+    CANCEL.get_or_init(Cancel::new).clone()
+}
+
+/// Installs the Ctrl-C handler. The first Ctrl-C asks long-running work to
+/// stop and flush what it has; a second one exits immediately.
+pub fn install() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if is_interrupted() {
+                crate::exit(130);
+            }
+            eprintln!("\nInterrupted, finishing up (press Ctrl-C again to abort)...");
+            cancel().cancel();
+        }
+    });
+}
+
+pub fn is_interrupted() -> bool {
+    cancel().is_cancelled()
+}
+
+/// Lets an interrupted search return the pages it fetched, for a listing
+/// that will flush them, rather than exit. Anything acting on the results
+/// leaves this off, so that it never acts on part of them.
+pub fn keep_partial() {
+    KEEP_PARTIAL.store(true, Ordering::SeqCst);
+}
+
+pub fn keeps_partial() -> bool {
+    KEEP_PARTIAL.load(Ordering::SeqCst)
+}
+
+/// Resolves once Ctrl-C has been pressed.
+pub async fn wait() {
+    cancel().cancelled().await
+}
+
+/// Runs `future` to completion unless Ctrl-C comes first. Used around fetches
+/// that have nothing to flush, so an interruption simply exits.
+pub async fn or_exit<T>(future: impl Future<Output = T>) -> T {
+    tokio::select! {
+        value = future => value,
+        _ = wait() => {
+            eprintln!("Interrupted");
+            crate::exit(130);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::circuit::CircuitBreaker;
+use crate::table;
+use ghs::{BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use url::Url;
+
+/// How many repositories' issues may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+/// Counts the open issues carrying all of `labels` in each repository and
+/// returns the repositories that have any, most issues first.
+pub async fn count_labeled_issues(
+    api: &GhsClient,
+    repositories: Vec<Repository>,
+    labels: &[String],
+) -> Result<Vec<(Repository, usize)>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let labels: Arc<[String]> = labels.into();
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let labels = Arc::clone(&labels);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+            let issues = breaker
+                .call(
+                    &format!("issues of {}", repo.full_name),
+                    api.open_issues(&repo.full_name, &labels),
+                )
+                .await?;
+            Ok::<_, BoxError>((repo, issues.map_or(0, |issues| issues.len())))
+        });
+    }
+
+    let mut counts = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        let (repo, count) = result??;
+        if count > 0 {
+            counts.push((repo, count));
+        }
+    }
+    counts.sort_by(|(a, a_count), (b, b_count)| {
+        b_count
+            .cmp(a_count)
+            .then_with(|| a.full_name.cmp(&b.full_name))
+    });
+    Ok(counts)
+}
+
+pub fn print_ranking(counts: &[(Repository, usize)], labels: &[String]) {
+    let joined = labels.join("`, `");
+    if counts.is_empty() {
+        println!("No open issues labeled `{}`", joined);
+        return;
+    }
+
+    let mut rows = vec![vec![
+        "Issues".to_string(),
+        "Repository".to_string(),
+        "Link".to_string(),
+    ]];
+    for (repo, count) in counts {
+        rows.push(vec![
+            count.to_string(),
+            repo.full_name.clone(),
+            issues_link(repo, &labels[0]).unwrap_or_default(),
+        ]);
+    }
+    print!("{}", table::render(&rows));
+
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    println!(
+        "{} open issues labeled `{}` across {} repositories",
+        total,
+        joined,
+        counts.len()
+    );
+}
+
+/// The repository's page listing the issues labeled `label`.
+fn issues_link(repo: &Repository, label: &str) -> Option<String> {
+    let mut url = Url::parse(repo.html_url.as_deref()?).ok()?;
+    url.path_segments_mut().ok()?.push("labels").push(label);
+    Some(url.to_string())
+}
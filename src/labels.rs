@@ -0,0 +1,159 @@
+use crate::circuit::CircuitBreaker;
+use crate::table;
+use ghs::model::Label;
+use ghs::{BoxError, GhsClient, Repository};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories' labels may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+/// Reduces a label name to the concept it stands for, so that
+/// `good first issue`, `Good-First-Issue` and `good_first_issue` group
+/// together.
+pub fn concept(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// One label concept and every way the repositories spell and color it.
+#[derive(Debug, Default)]
+pub struct LabelUsage {
+    /// Spelling → repositories using it.
+    pub spellings: BTreeMap<String, BTreeSet<String>>,
+    pub colors: BTreeSet<String>,
+}
+
+impl LabelUsage {
+    pub fn repositories(&self) -> usize {
+        self.spellings.values().map(BTreeSet::len).sum()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.spellings.len() <= 1 && self.colors.len() <= 1
+    }
+
+    /// The spelling used by the most repositories.
+    pub fn preferred(&self) -> &str {
+        self.spellings
+            .iter()
+            .max_by_key(|(_, repositories)| repositories.len())
+            .map_or("", |(spelling, _)| spelling)
+    }
+}
+
+/// The labels of every repository, keyed by full name. Repositories whose
+/// labels could not be fetched are left out.
+pub async fn fetch_labels(
+    api: &GhsClient,
+    repositories: &[Repository],
+) -> Result<BTreeMap<String, Vec<Label>>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let labels = breaker
+                .call(&format!("labels of {}", full_name), api.labels(&full_name))
+                .await?;
+            Ok::<_, BoxError>((full_name, labels))
+        });
+    }
+
+    let mut labels = BTreeMap::new();
+    while let Some(result) = tasks.join_next().await {
+        if let (full_name, Some(repo_labels)) = result?? {
+            labels.insert(full_name, repo_labels);
+        }
+    }
+    Ok(labels)
+}
+
+/// Groups the labels of all repositories by concept.
+pub fn inventory(labels: &BTreeMap<String, Vec<Label>>) -> BTreeMap<String, LabelUsage> {
+    let mut usages: BTreeMap<String, LabelUsage> = BTreeMap::new();
+    for (full_name, repo_labels) in labels {
+        for label in repo_labels {
+            let usage = usages.entry(concept(&label.name)).or_default();
+            usage
+                .spellings
+                .entry(label.name.clone())
+                .or_default()
+                .insert(full_name.clone());
+            if let Some(color) = &label.color {
+                usage.colors.insert(color.to_lowercase());
+            }
+        }
+    }
+    usages
+}
+
+/// Prints every label concept, most widely used first, flagging those that
+/// are spelled or colored differently across repositories.
+pub fn print_inventory(usages: &BTreeMap<String, LabelUsage>, repositories: usize) {
+    let mut usages: Vec<&LabelUsage> = usages.values().collect();
+    usages.sort_by(|a, b| {
+        b.repositories()
+            .cmp(&a.repositories())
+            .then_with(|| a.preferred().cmp(b.preferred()))
+    });
+
+    let mut rows = vec![vec![
+        "Label".to_string(),
+        "Repositories".to_string(),
+        "Spellings".to_string(),
+        "Colors".to_string(),
+        "Consistent".to_string(),
+    ]];
+    for usage in &usages {
+        let spellings: Vec<String> = usage
+            .spellings
+            .iter()
+            .map(|(spelling, repositories)| format!("{} ({})", spelling, repositories.len()))
+            .collect();
+        let colors: Vec<String> = usage
+            .colors
+            .iter()
+            .map(|color| format!("#{}", color))
+            .collect();
+        rows.push(vec![
+            usage.preferred().to_string(),
+            format!("{}/{}", usage.repositories(), repositories),
+            spellings.join(", "),
+            colors.join(", "),
+            if usage.is_consistent() { "yes" } else { "NO" }.to_string(),
+        ]);
+    }
+    print!("{}", table::render(&rows));
+
+    let inconsistent = usages.iter().filter(|usage| !usage.is_consistent()).count();
+    println!(
+        "{} labels across {} repositories, {} spelled or colored inconsistently",
+        usages.len(),
+        repositories,
+        inconsistent
+    );
+}
+
+/// The repositories that have no label for the concept of `label`.
+pub fn missing<'a>(labels: &'a BTreeMap<String, Vec<Label>>, label: &str) -> Vec<&'a str> {
+    let wanted = concept(label);
+    labels
+        .iter()
+        .filter(|(_, repo_labels)| {
+            !repo_labels
+                .iter()
+                .any(|repo_label| concept(&repo_label.name) == wanted)
+        })
+        .map(|(full_name, _)| full_name.as_str())
+        .collect()
+}
@@ -0,0 +1,104 @@
+//! Language detection for short texts such as repository descriptions,
+//! by the `whatlang` crate: the script first, then trigram statistics for
+//! languages that share one.
+
+use std::sync::OnceLock;
+use whatlang::{Detector, Lang};
+
+/// Languages `detect` can report, as ISO 639-1 codes, and the `whatlang`
+/// language each one stands for.
+const CODES: &[(&str, Lang)] = &[
+    ("ja", Lang::Jpn),
+    ("zh", Lang::Cmn),
+    ("ko", Lang::Kor),
+    ("ru", Lang::Rus),
+    ("ar", Lang::Ara),
+    ("he", Lang::Heb),
+    ("el", Lang::Ell),
+    ("th", Lang::Tha),
+    ("hi", Lang::Hin),
+    ("en", Lang::Eng),
+    ("de", Lang::Deu),
+    ("es", Lang::Spa),
+    ("fr", Lang::Fra),
+    ("it", Lang::Ita),
+    ("pt", Lang::Por),
+];
+
+/// Languages `detect` can report, as ISO 639-1 codes.
+pub const LANGUAGES: &[&str] = &[
+    "ja", "zh", "ko", "ru", "ar", "he", "el", "th", "hi", "en", "de", "es", "fr", "it", "pt",
+];
+
+/// Guesses the language of `text`, always one of [`LANGUAGES`]: the
+/// detector only weighs those, so a short description is not put down to
+/// some language `--description-lang` cannot ask for. Text without letters
+/// is `None`.
+pub fn detect(text: &str) -> Option<&'static str> {
+    static DETECTOR: OnceLock<Detector> = OnceLock::new();
+    let detector = DETECTOR
+        .get_or_init(|| Detector::with_allowlist(CODES.iter().map(|(_, lang)| *lang).collect()));
+    let lang = detector.detect_lang(text)?;
+    CODES
+        .iter()
+        .find(|(_, known)| *known == lang)
+        .map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_language_has_a_code() {
+        let codes: Vec<&str> = CODES.iter().map(|(code, _)| *code).collect();
+        assert_eq!(codes, LANGUAGES);
+    }
+
+    #[test]
+    fn tells_scripts_apart() {
+        assert_eq!(detect("GitHub のリポジトリを検索するツール"), Some("ja"));
+        assert_eq!(detect("一个用于搜索代码仓库的命令行工具"), Some("zh"));
+        assert_eq!(detect("저장소를 검색하는 명령줄 도구"), Some("ko"));
+        assert_eq!(detect("Утилита для поиска репозиториев"), Some("ru"));
+        assert_eq!(detect("أداة للبحث في المستودعات"), Some("ar"));
+        assert_eq!(detect("כלי לחיפוש מאגרים"), Some("he"));
+        assert_eq!(detect("Εργαλείο για αναζήτηση αποθετηρίων"), Some("el"));
+        assert_eq!(detect("เครื่องมือสำหรับค้นหาที่เก็บ"), Some("th"));
+        assert_eq!(detect("रिपॉजिटरी खोजने के लिए एक उपकरण"), Some("hi"));
+    }
+
+    #[test]
+    fn tells_latin_script_languages_apart() {
+        assert_eq!(
+            detect("A command line tool for searching repositories on GitHub"),
+            Some("en")
+        );
+        assert_eq!(
+            detect("Ein Werkzeug für die Suche nach Repositories auf GitHub"),
+            Some("de")
+        );
+        assert_eq!(
+            detect("Una herramienta para buscar repositorios en GitHub desde la terminal"),
+            Some("es")
+        );
+        assert_eq!(
+            detect("Un outil pour chercher des dépôts sur GitHub depuis le terminal"),
+            Some("fr")
+        );
+        assert_eq!(
+            detect("Uno strumento per cercare i repository su GitHub dal terminale"),
+            Some("it")
+        );
+        assert_eq!(
+            detect("Uma ferramenta para procurar repositórios no GitHub pelo terminal"),
+            Some("pt")
+        );
+    }
+
+    #[test]
+    fn text_without_letters_has_no_language() {
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("1.2.3 -- 42 %"), None);
+    }
+}
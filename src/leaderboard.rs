@@ -0,0 +1,196 @@
+use crate::{export, table};
+use ghs::sink::Sqlite;
+use ghs::{datetime, BoxError};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What `ghs leaderboard` ranks repositories by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Stars,
+    Forks,
+}
+
+impl Metric {
+    pub fn parse(metric: &str) -> Result<Metric, String> {
+        match metric {
+            "stars" => Ok(Metric::Stars),
+            "forks" => Ok(Metric::Forks),
+            _ => Err(format!(
+                "`{}` is not a metric (expected stars or forks)",
+                metric
+            )),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Metric::Stars => "stargazers_count",
+            Metric::Forks => "forks_count",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Stars => "stars",
+            Metric::Forks => "forks",
+        }
+    }
+}
+
+/// Parses a period such as `30d`, `2w` or `12h` into seconds.
+pub fn parse_period(period: &str) -> Result<i64, String> {
+    let invalid = || {
+        format!(
+            "`{}` is not a period (expected a number of hours, days or weeks, e.g. 30d)",
+            period
+        )
+    };
+    let (count, unit) = period.split_at(period.len().saturating_sub(1));
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    let unit = match unit {
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(invalid()),
+    };
+    Ok(count * unit)
+}
+
+/// One repository's growth over the period.
+struct Gain {
+    full_name: String,
+    html_url: Option<String>,
+    value: u64,
+    gained: u64,
+}
+
+/// The repositories in the SQLite store at `db` (of `owner` only, if given)
+/// that gained the most `metric` over the last `period` seconds, as a table
+/// or, for a newsletter, as Markdown.
+///
+/// Each repository is measured from its last snapshot before the period
+/// began, or its first one within it, to its latest.
+pub fn report(
+    db: &Path,
+    metric: Metric,
+    period: i64,
+    owner: Option<&str>,
+    limit: usize,
+    markdown: bool,
+) -> Result<String, BoxError> {
+    let filter = match owner {
+        Some(_) => "WHERE lower(snapshots.owner) = lower(?1) ",
+        None => "",
+    };
+    let rows = Sqlite::query(
+        db,
+        &format!(
+            "SELECT snapshots.full_name, taken_at, snapshots.{}, repositories.html_url \
+             FROM snapshots LEFT JOIN repositories USING (full_name) {}ORDER BY taken_at",
+            metric.column(),
+            filter
+        ),
+        owner.as_slice(),
+    )?;
+
+    // Each repository's snapshots, oldest first, and web page.
+    let mut snapshots: BTreeMap<&str, Vec<(i64, u64)>> = BTreeMap::new();
+    let mut html_urls: BTreeMap<&str, &str> = BTreeMap::new();
+    for row in &rows {
+        let [full_name, taken_at, value, html_url] = row.as_slice() else {
+            continue;
+        };
+        if let (Ok(taken_at), Ok(value)) = (taken_at.parse::<i64>(), value.parse::<u64>()) {
+            snapshots
+                .entry(full_name)
+                .or_default()
+                .push((taken_at, value));
+            html_urls.insert(full_name, html_url);
+        }
+    }
+
+    let now = datetime::now();
+    let since = now - period;
+    let mut gains: Vec<Gain> = Vec::new();
+    for (full_name, snapshots) in snapshots {
+        let start = snapshots
+            .iter()
+            .rev()
+            .find(|(taken_at, _)| *taken_at <= since)
+            .or_else(|| snapshots.first());
+        let (Some(&(started_at, start)), Some(&(ended_at, end))) = (start, snapshots.last()) else {
+            continue;
+        };
+        if ended_at > since && ended_at > started_at && end > start {
+            gains.push(Gain {
+                full_name: full_name.to_string(),
+                html_url: html_urls
+                    .get(full_name)
+                    .filter(|url| !url.is_empty())
+                    .map(|url| url.to_string()),
+                value: end,
+                gained: end - start,
+            });
+        }
+    }
+    if gains.is_empty() {
+        return Err(format!(
+            "no repository in {} gained {} since {}; every `--output sqlite:{}` run adds a snapshot",
+            db.display(),
+            metric.name(),
+            datetime::format_date(since.div_euclid(86_400)),
+            db.display()
+        )
+        .into());
+    }
+    // Stable, so ties stay in name order.
+    gains.sort_by_key(|gain| std::cmp::Reverse(gain.gained));
+    gains.truncate(limit);
+
+    let title = format!(
+        "Most {} gained, {} to {}",
+        metric.name(),
+        datetime::format_date(since.div_euclid(86_400)),
+        datetime::format_date(now.div_euclid(86_400))
+    );
+    let metric_header = match metric {
+        Metric::Stars => "Stars",
+        Metric::Forks => "Forks",
+    };
+    if markdown {
+        let mut out = format!(
+            "## {}\n\n| # | Repository | {} | Gained |\n| ---: | --- | ---: | ---: |\n",
+            title, metric_header
+        );
+        for (rank, gain) in gains.iter().enumerate() {
+            let name = match &gain.html_url {
+                Some(url) => format!("[{}]({})", export::markdown_cell(&gain.full_name), url),
+                None => export::markdown_cell(&gain.full_name),
+            };
+            out.push_str(&format!(
+                "| {} | {} | {} | +{} |\n",
+                rank + 1,
+                name,
+                gain.value,
+                gain.gained
+            ));
+        }
+        return Ok(out);
+    }
+    let mut rows = vec![vec![
+        "#".to_string(),
+        "Repository".to_string(),
+        metric_header.to_string(),
+        "Gained".to_string(),
+    ]];
+    rows.extend(gains.iter().enumerate().map(|(rank, gain)| {
+        vec![
+            (rank + 1).to_string(),
+            gain.full_name.clone(),
+            gain.value.to_string(),
+            format!("+{}", gain.gained),
+        ]
+    }));
+    Ok(format!("{}:\n{}", title, table::render(&rows)))
+}
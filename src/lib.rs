@@ -0,0 +1,36 @@
+//! The GitHub client and repository model behind the `ghs` command line tool.
+
+pub mod auth;
+pub mod cancel;
+pub mod client;
+pub mod datetime;
+pub mod deprecation;
+pub mod error;
+pub mod federated;
+pub mod filters;
+pub mod fixtures;
+pub mod gitea;
+pub mod gitlab;
+pub mod links;
+pub mod locale;
+pub mod model;
+pub mod parquet;
+pub mod progress;
+pub mod provider;
+pub mod query;
+pub mod retry;
+pub mod schema;
+pub mod sink;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use auth::AuthProvider;
+pub use client::{GhsClient, GhsClientBuilder};
+pub use error::ApiError;
+pub use model::{Repositories, Repository};
+pub use provider::SearchProvider;
+pub use query::{QueryError, SearchQuery};
+pub use sink::OutputSink;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
@@ -0,0 +1,110 @@
+use url::Url;
+
+/// The pagination targets advertised in a `Link` response header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageLinks {
+    pub first: Option<String>,
+    pub prev: Option<String>,
+    pub next: Option<String>,
+    pub last: Option<String>,
+}
+
+impl PageLinks {
+    /// Parses a header such as
+    /// `<https://api.github.com/...&page=2>; rel="next", <...&page=5>; rel="last"`.
+    /// Unknown relations and malformed entries are ignored.
+    pub fn parse(header: &str) -> PageLinks {
+        let mut links = PageLinks::default();
+        for (url, rel) in entries(header) {
+            let slot = match rel {
+                "first" => &mut links.first,
+                "prev" => &mut links.prev,
+                "next" => &mut links.next,
+                "last" => &mut links.last,
+                _ => continue,
+            };
+            *slot = Some(url.to_string());
+        }
+        links
+    }
+
+    /// Number of the last page, if the response advertised one. A response
+    /// without a `last` link is the only (or final) page.
+    pub fn last_page(&self) -> Option<u32> {
+        self.last.as_deref().and_then(page_number)
+    }
+}
+
+/// The target of the first link with relation `rel`, such as the
+/// `deprecation` and `sunset` links that announce an endpoint's retirement.
+pub fn target(header: &str, rel: &str) -> Option<String> {
+    entries(header)
+        .find(|(_, candidate)| *candidate == rel)
+        .map(|(url, _)| url.to_string())
+}
+
+/// Every `(url, relation)` pair in a `Link` header; an entry listing several
+/// relations yields one pair for each.
+fn entries(header: &str) -> impl Iterator<Item = (&str, &str)> {
+    header.split(',').flat_map(|entry| {
+        let mut parts = entry.split(';');
+        let url = parts
+            .next()
+            .map(str::trim)
+            .and_then(|target| target.strip_prefix('<'))
+            .and_then(|target| target.strip_suffix('>'));
+        parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                (key.trim() == "rel").then_some(value)
+            })
+            .flat_map(|value| value.trim().trim_matches('"').split_whitespace())
+            .filter_map(move |rel| Some((url?, rel)))
+    })
+}
+
+/// Extracts the `page` query parameter from a pagination URL.
+pub fn page_number(url: &str) -> Option<u32> {
+    Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "page")
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_github_link_header() {
+        let links = PageLinks::parse(
+            "<https://api.github.com/search/repositories?q=user%3Aa&page=2>; rel=\"next\", \
+             <https://api.github.com/search/repositories?q=user%3Aa&page=7>; rel=\"last\"",
+        );
+        assert_eq!(
+            links.next.as_deref(),
+            Some("https://api.github.com/search/repositories?q=user%3Aa&page=2")
+        );
+        assert_eq!(links.last_page(), Some(7));
+        assert_eq!(links.prev, None);
+    }
+
+    #[test]
+    fn ignores_malformed_entries() {
+        let links = PageLinks::parse("garbage, <https://x/?page=3>; rel=\"prev\"; title=\"x\"");
+        assert_eq!(links.prev.as_deref(), Some("https://x/?page=3"));
+        assert_eq!(links.last_page(), None);
+    }
+
+    #[test]
+    fn finds_links_by_relation() {
+        let header = "<https://docs.github.com/changes>; rel=\"deprecation\"; type=\"text/html\", \
+                      <https://api.github.com/x?page=2>; rel=\"next\"";
+        assert_eq!(
+            target(header, "deprecation").as_deref(),
+            Some("https://docs.github.com/changes")
+        );
+        assert_eq!(target(header, "sunset"), None);
+    }
+}
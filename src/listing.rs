@@ -0,0 +1,298 @@
+//! Code and issue search results and notifications, written in the same
+//! [`Format`]s as repositories.
+
+use crate::output::{self, Format};
+use crate::{export, plain, table};
+use ghs::model::{CodeResult, IssueResult, Notification};
+use ghs::{schema, BoxError};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// How many lines of each matched fragment the listing shows.
+const FRAGMENT_LINES: usize = 3;
+
+/// A kind of search result that can be listed.
+pub trait Listing: Serialize {
+    /// What the results are called, e.g. in "No matching code".
+    const NOUN: &'static str;
+    const CSV_HEADER: &'static str;
+    const TABLE_HEADER: &'static [&'static str];
+
+    /// Cells under [`Listing::CSV_HEADER`], not yet quoted.
+    fn csv_fields(&self) -> Vec<String>;
+    /// Cells under [`Listing::TABLE_HEADER`].
+    fn table_row(&self) -> Vec<String>;
+    /// The default listing: a few labelled lines, or one in plain mode.
+    fn plain(&self) -> String;
+}
+
+impl Listing for CodeResult {
+    const NOUN: &'static str = "code";
+    const CSV_HEADER: &'static str = "repository,path,html_url,fragment";
+    const TABLE_HEADER: &'static [&'static str] = &["Repository", "Path", "Link"];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.repository.full_name.clone(),
+            self.path.clone(),
+            self.html_url.clone(),
+            self.text_matches
+                .first()
+                .map(|text_match| text_match.fragment.clone())
+                .unwrap_or_default(),
+        ]
+    }
+
+    fn table_row(&self) -> Vec<String> {
+        vec![
+            self.repository.full_name.clone(),
+            self.path.clone(),
+            self.html_url.clone(),
+        ]
+    }
+
+    fn plain(&self) -> String {
+        if plain::enabled() {
+            return format!(
+                "File {} in {}. {}\n",
+                self.path, self.repository.full_name, self.html_url
+            );
+        }
+        let mut out = format!(
+            "Repository: {}\nPath: {}\nURL: {}\n",
+            self.repository.full_name, self.path, self.html_url
+        );
+        if let Some(text_match) = self.text_matches.first() {
+            for line in text_match
+                .fragment
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .take(FRAGMENT_LINES)
+            {
+                out.push_str(&format!("  {}\n", line.trim_end()));
+            }
+        }
+        out.push_str("---\n");
+        out
+    }
+}
+
+impl Listing for IssueResult {
+    const NOUN: &'static str = "issues";
+    const CSV_HEADER: &'static str =
+        "repository,number,kind,title,state,author,labels,comments,created_at,updated_at,html_url";
+    const TABLE_HEADER: &'static [&'static str] =
+        &["Repository", "Number", "Kind", "State", "Updated", "Title"];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.repository().to_string(),
+            self.number.to_string(),
+            kind(self).to_string(),
+            self.title.clone(),
+            self.state.clone(),
+            author(self).to_string(),
+            labels(self).join(";"),
+            self.comments.to_string(),
+            self.created_at.clone().unwrap_or_default(),
+            self.updated_at.clone().unwrap_or_default(),
+            self.html_url.clone(),
+        ]
+    }
+
+    fn table_row(&self) -> Vec<String> {
+        vec![
+            self.repository().to_string(),
+            format!("#{}", self.number),
+            kind(self).to_string(),
+            self.state.clone(),
+            self.updated_at
+                .as_deref()
+                .map(output::date)
+                .unwrap_or_default(),
+            output::shorten(&self.title),
+        ]
+    }
+
+    fn plain(&self) -> String {
+        let labels = labels(self);
+        if plain::enabled() {
+            let labels = match labels.is_empty() {
+                true => String::new(),
+                false => format!(" Labels: {}.", labels.join(", ")),
+            };
+            return format!(
+                "{} {}#{}: {}. State: {}.{} {}\n",
+                capitalized(kind(self)),
+                self.repository(),
+                self.number,
+                self.title.trim_end_matches('.'),
+                self.state,
+                labels,
+                self.html_url
+            );
+        }
+        let labels = match labels.is_empty() {
+            true => String::new(),
+            false => format!("Labels: {}\n", labels.join(", ")),
+        };
+        format!(
+            "{} #{}: {}\nRepository: {}\nState: {}\nAuthor: {}\n{}URL: {}\n---\n",
+            capitalized(kind(self)),
+            self.number,
+            self.title,
+            self.repository(),
+            self.state,
+            author(self),
+            labels,
+            self.html_url
+        )
+    }
+}
+
+impl Listing for Notification {
+    const NOUN: &'static str = "notifications";
+    const CSV_HEADER: &'static str = "id,repository,type,title,reason,unread,updated_at,html_url";
+    const TABLE_HEADER: &'static [&'static str] =
+        &["", "Repository", "Type", "Reason", "Updated", "Title"];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.repository.full_name.clone(),
+            self.subject.kind.clone(),
+            self.subject.title.clone(),
+            self.reason.clone(),
+            self.unread.to_string(),
+            self.updated_at.clone().unwrap_or_default(),
+            self.html_url().unwrap_or_default(),
+        ]
+    }
+
+    fn table_row(&self) -> Vec<String> {
+        vec![
+            if self.unread { "*" } else { "" }.to_string(),
+            self.repository.full_name.clone(),
+            self.subject.kind.clone(),
+            self.reason.clone(),
+            self.updated_at
+                .as_deref()
+                .map(output::date)
+                .unwrap_or_default(),
+            output::shorten(&self.subject.title),
+        ]
+    }
+
+    fn plain(&self) -> String {
+        let state = if self.unread { "unread" } else { "read" };
+        let url = self.html_url();
+        if plain::enabled() {
+            return format!(
+                "{} in {}: {}. Reason: {}, {}.{}\n",
+                self.subject.kind,
+                self.repository.full_name,
+                self.subject.title.trim_end_matches('.'),
+                self.reason,
+                state,
+                url.map(|url| format!(" {}", url)).unwrap_or_default()
+            );
+        }
+        format!(
+            "{}: {}\nRepository: {}\nReason: {} ({})\n{}---\n",
+            self.subject.kind,
+            self.subject.title,
+            self.repository.full_name,
+            self.reason,
+            state,
+            url.map(|url| format!("URL: {}\n", url)).unwrap_or_default()
+        )
+    }
+}
+
+fn kind(issue: &IssueResult) -> &'static str {
+    match issue.pull_request {
+        Some(_) => "pull request",
+        None => "issue",
+    }
+}
+
+fn capitalized(kind: &str) -> String {
+    let mut chars = kind.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn author(issue: &IssueResult) -> &str {
+    issue.user.as_ref().map_or("", |user| user.login.as_str())
+}
+
+fn labels(issue: &IssueResult) -> Vec<&str> {
+    issue
+        .labels
+        .iter()
+        .map(|label| label.name.as_str())
+        .collect()
+}
+
+/// Writes `items` in `format` to `output`, a file path or `-` for stdout,
+/// which is also the default.
+pub fn write<T: Listing>(
+    items: &[T],
+    format: Option<Format>,
+    output: Option<&str>,
+) -> Result<(), BoxError> {
+    let mut writer: Box<dyn Write> = match output {
+        None | Some("-") => Box::new(io::stdout()),
+        Some(output) if output.starts_with("sqlite:") || output.contains("://") => {
+            return Err(format!(
+                "--output takes a file for {} results, not {}",
+                T::NOUN,
+                output
+            )
+            .into())
+        }
+        Some(output) => {
+            Box::new(BufWriter::new(File::create(output).map_err(|err| {
+                format!("could not create {}: {}", output, err)
+            })?))
+        }
+    };
+    match format.unwrap_or_default() {
+        Format::Plain if items.is_empty() => writeln!(writer, "No matching {}", T::NOUN)?,
+        Format::Plain => {
+            for item in items {
+                writer.write_all(item.plain().as_bytes())?;
+            }
+        }
+        Format::Json => writeln!(
+            writer,
+            "{{\n  \"schema\": \"{}\",\n  \"{}\": {}\n}}",
+            schema::VERSION,
+            T::NOUN,
+            serde_json::to_string_pretty(items)?.replace('\n', "\n  ")
+        )?,
+        Format::Csv => {
+            writeln!(writer, "{}", T::CSV_HEADER)?;
+            for item in items {
+                let fields: Vec<String> = item
+                    .csv_fields()
+                    .iter()
+                    .map(|field| export::csv_field(field))
+                    .collect();
+                writeln!(writer, "{}", fields.join(","))?;
+            }
+        }
+        Format::Table => {
+            let mut rows = vec![T::TABLE_HEADER
+                .iter()
+                .map(|cell| cell.to_string())
+                .collect()];
+            rows.extend(items.iter().map(Listing::table_row));
+            writer.write_all(table::render(&rows).as_bytes())?;
+        }
+    }
+    Ok(writer.flush()?)
+}
@@ -0,0 +1,268 @@
+//! How counts and dates read in a language: `12,345` or `1.2万`,
+//! `Oct 14, 2026` or `2026年10月14日`. Only the human-readable listings use
+//! this; JSON and CSV keep plain numbers and ISO timestamps.
+
+use crate::datetime;
+use std::env;
+
+/// The languages ghs can format for.
+pub const LANGUAGES: &[&str] = &[
+    "en", "ja", "zh", "ko", "de", "fr", "es", "it", "pt", "nl", "ru",
+];
+
+/// A locale to format counts and dates for. The default is `C`: digits
+/// without separators and `YYYY-MM-DD` dates, as ghs has always printed
+/// them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Locale(Style);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Style {
+    #[default]
+    C,
+    /// English with the month first, as in the United States.
+    EnUs,
+    /// English with the day first, as almost everywhere else.
+    En,
+    Ja,
+    /// Simplified Chinese.
+    Zh,
+    /// Traditional Chinese, as in Taiwan and Hong Kong.
+    ZhHant,
+    Ko,
+    De,
+    Fr,
+    Es,
+    It,
+    Pt,
+    Nl,
+    Ru,
+}
+
+impl Locale {
+    /// Parses a tag such as `ja-JP`, `en_GB.UTF-8`, `zh-Hant` or `C`; only
+    /// the language, and the region or script where they matter, are read.
+    pub fn parse(tag: &str) -> Result<Locale, String> {
+        let unsupported = || {
+            format!(
+                "`{}` is not a supported locale (expected C or one of {}, e.g. ja-JP)",
+                tag,
+                LANGUAGES.join(", ")
+            )
+        };
+        // `ja_JP.UTF-8@euro` is `ja` and `JP`.
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_lowercase();
+        let rest: Vec<String> = subtags.map(str::to_uppercase).collect();
+        let has = |subtag: &str| rest.iter().any(|rest| rest == subtag);
+        let style = match language.as_str() {
+            "c" | "posix" if rest.is_empty() => Style::C,
+            "en" if rest.is_empty() || ["US", "CA", "PH"].iter().any(|region| has(region)) => {
+                Style::EnUs
+            }
+            "en" => Style::En,
+            "ja" => Style::Ja,
+            "zh" if ["HANT", "TW", "HK", "MO"].iter().any(|subtag| has(subtag)) => Style::ZhHant,
+            "zh" => Style::Zh,
+            "ko" => Style::Ko,
+            "de" => Style::De,
+            "fr" => Style::Fr,
+            "es" => Style::Es,
+            "it" => Style::It,
+            "pt" => Style::Pt,
+            "nl" => Style::Nl,
+            "ru" => Style::Ru,
+            _ => return Err(unsupported()),
+        };
+        Ok(Locale(style))
+    }
+
+    /// The locale of the environment, from `LC_ALL` or else `LANG`; one
+    /// that is unset or not supported is `C`.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|tag| !tag.is_empty())
+            .and_then(|tag| Locale::parse(&tag).ok())
+            .unwrap_or_default()
+    }
+
+    /// `count` with the locale's digit grouping. Chinese, Japanese and
+    /// Korean count in myriads instead, from `1.2万` up; the figure is
+    /// truncated rather than rounded, so it never overstates.
+    pub fn format_count(&self, count: u64) -> String {
+        let (separator, min_grouping) = match self.0 {
+            Style::C => return count.to_string(),
+            Style::Ja | Style::Zh | Style::ZhHant | Style::Ko => {
+                if let Some(compact) = self.myriads(count) {
+                    return compact;
+                }
+                (",", 1)
+            }
+            Style::EnUs | Style::En => (",", 1),
+            Style::De | Style::It | Style::Pt | Style::Nl => (".", 1),
+            // Spanish leaves four-digit numbers alone.
+            Style::Es => (".", 2),
+            Style::Fr => ("\u{202f}", 1),
+            Style::Ru => ("\u{a0}", 1),
+        };
+        let digits = count.to_string();
+        if digits.len() < 3 + min_grouping {
+            return digits;
+        }
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push_str(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// `count` in units of 万 (10⁴) or 億 (10⁸), with one decimal below ten
+    /// units, or `None` below 10,000.
+    fn myriads(&self, count: u64) -> Option<String> {
+        let (ten_thousand, hundred_million) = match self.0 {
+            Style::Ja => ("万", "億"),
+            Style::Zh => ("万", "亿"),
+            Style::ZhHant => ("萬", "億"),
+            Style::Ko => ("만", "억"),
+            _ => return None,
+        };
+        let (unit, name) = match count {
+            0..=9_999 => return None,
+            10_000..=99_999_999 => (10_000, ten_thousand),
+            _ => (100_000_000, hundred_million),
+        };
+        let whole = count / unit;
+        let tenth = count % unit * 10 / unit;
+        Some(match (whole, tenth) {
+            (1..=9, 1..) => format!("{}.{}{}", whole, tenth, name),
+            _ => format!("{}{}", whole, name),
+        })
+    }
+
+    /// The date of a `YYYY-MM-DDTHH:MM:SSZ` timestamp, in UTC, with the
+    /// month named, or `None` when `timestamp` is not one.
+    pub fn format_date(&self, timestamp: &str) -> Option<String> {
+        let seconds = datetime::parse_timestamp(timestamp)?;
+        let (year, month, day) = datetime::civil_from_days(seconds.div_euclid(86_400));
+        let month = month as usize - 1;
+        Some(match self.0 {
+            Style::C => format!("{:04}-{:02}-{:02}", year, month + 1, day),
+            Style::EnUs => format!("{} {}, {}", EN[month], day, year),
+            Style::En => format!("{} {} {}", day, EN[month], year),
+            Style::Ja | Style::Zh | Style::ZhHant => format!("{}年{}月{}日", year, month + 1, day),
+            Style::Ko => format!("{}년 {}월 {}일", year, month + 1, day),
+            Style::De => format!("{}. {} {}", day, DE[month], year),
+            Style::Fr => format!("{} {} {}", day, FR[month], year),
+            Style::Es => format!("{} {} {}", day, ES[month], year),
+            Style::It => format!("{} {} {}", day, IT[month], year),
+            Style::Pt => format!("{} de {} de {}", day, PT[month], year),
+            Style::Nl => format!("{} {} {}", day, NL[month], year),
+            Style::Ru => format!("{} {} {} г.", day, RU[month], year),
+        })
+    }
+}
+
+// Abbreviated month names as CLDR writes them in a date, e.g. the Russian
+// ones in the genitive.
+const EN: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const DE: [&str; 12] = [
+    "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez.",
+];
+const FR: [&str; 12] = [
+    "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+    "déc.",
+];
+const ES: [&str; 12] = [
+    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+];
+const IT: [&str; 12] = [
+    "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+];
+const PT: [&str; 12] = [
+    "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.", "dez.",
+];
+const NL: [&str; 12] = [
+    "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+];
+const RU: [&str; 12] = [
+    "янв.",
+    "февр.",
+    "мар.",
+    "апр.",
+    "мая",
+    "июн.",
+    "июл.",
+    "авг.",
+    "сент.",
+    "окт.",
+    "нояб.",
+    "дек.",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(tag: &str) -> Locale {
+        Locale::parse(tag).unwrap()
+    }
+
+    #[test]
+    fn parses_tags_and_posix_names() {
+        assert_eq!(locale("ja-JP"), locale("ja_JP.UTF-8"));
+        assert_eq!(locale("C.UTF-8"), Locale::default());
+        assert_eq!(locale("en"), locale("en-US"));
+        assert_ne!(locale("en-GB"), locale("en-US"));
+        assert_eq!(locale("zh-Hant"), locale("zh_TW"));
+        assert_ne!(locale("zh-CN"), locale("zh-TW"));
+        assert!(Locale::parse("tlh").unwrap_err().contains("tlh"));
+    }
+
+    #[test]
+    fn groups_digits_by_language() {
+        assert_eq!(locale("C").format_count(1_234_567), "1234567");
+        assert_eq!(locale("en-US").format_count(1_234_567), "1,234,567");
+        assert_eq!(locale("en-US").format_count(999), "999");
+        assert_eq!(locale("de").format_count(1_234_567), "1.234.567");
+        assert_eq!(locale("fr").format_count(12_345), "12\u{202f}345");
+        assert_eq!(locale("es").format_count(1_234), "1234");
+        assert_eq!(locale("es").format_count(12_345), "12.345");
+    }
+
+    #[test]
+    fn counts_in_myriads_from_ten_thousand() {
+        let ja = locale("ja-JP");
+        assert_eq!(ja.format_count(9_999), "9,999");
+        assert_eq!(ja.format_count(10_000), "1万");
+        assert_eq!(ja.format_count(12_345), "1.2万");
+        assert_eq!(ja.format_count(19_999), "1.9万");
+        assert_eq!(ja.format_count(123_456), "12万");
+        assert_eq!(ja.format_count(99_999_999), "9999万");
+        assert_eq!(ja.format_count(123_456_789), "1.2億");
+        assert_eq!(locale("zh-CN").format_count(123_456_789), "1.2亿");
+        assert_eq!(locale("zh-TW").format_count(12_345), "1.2萬");
+        assert_eq!(locale("ko").format_count(12_345), "1.2만");
+    }
+
+    #[test]
+    fn names_the_month_in_dates() {
+        let date = |tag: &str| locale(tag).format_date("2026-10-04T23:59:59Z").unwrap();
+        assert_eq!(date("C"), "2026-10-04");
+        assert_eq!(date("en-US"), "Oct 4, 2026");
+        assert_eq!(date("en-GB"), "4 Oct 2026");
+        assert_eq!(date("ja-JP"), "2026年10月4日");
+        assert_eq!(date("ko-KR"), "2026년 10월 4일");
+        assert_eq!(date("de-DE"), "4. Okt. 2026");
+        assert_eq!(date("fr-FR"), "4 oct. 2026");
+        assert_eq!(date("ru-RU"), "4 окт. 2026 г.");
+        assert_eq!(locale("ja").format_date("last week"), None);
+    }
+}
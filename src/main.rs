@@ -1,143 +1,248 @@
-use reqwest::{header, Client};
-use serde::{Deserialize, Serialize};
-use clap::{Arg, Command};
+mod activity;
+mod audit;
+mod avatar;
+mod bench;
+mod browser;
+mod cache;
+mod cadence;
+mod checkpoint;
+mod ci;
+mod circuit;
+mod cli;
+mod codeowners;
+mod color;
+mod commands;
+mod compare;
+mod config;
+mod console;
+mod contrib;
+mod deprecations;
+mod details;
+mod discussions;
+mod dupes;
+mod edit;
+mod exec;
+mod export;
+mod fetch;
+mod gitremote;
+mod heatmap;
+mod interrupt;
+mod issues;
+mod labels;
+mod langdetect;
+mod leaderboard;
+mod listing;
+mod members;
+mod membership;
+mod milestones;
+mod output;
+mod pages;
+mod pipeline;
+mod placeholder;
+mod plain;
+mod predicate;
+mod preset;
+mod profile;
+mod provenance;
+mod rank;
+mod readme;
+mod redact;
+mod refine;
+mod rename;
+mod sample;
+#[cfg(feature = "testing")]
+mod selftest;
+mod session;
+mod smart;
+mod sort;
+mod starred;
+mod store;
+mod table;
+mod terminal;
+mod topics;
+mod transfer;
+mod transform;
+mod trend;
+mod trending;
+mod triage;
+mod tui;
+mod update;
+
+use cache::Cache;
+use commands::Context;
+use config::Config;
+use ghs::error::{self, ApiError};
+use ghs::locale::Locale;
+use ghs::schema;
+use ghs::{BoxError, QueryError};
+use placeholder::Placeholders;
+use profile::Profile;
+use session::Session;
 use std::env;
+use std::io::{self, IsTerminal};
+use std::process;
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Repository {
-    name: String,
-    description: Option<String>,
-    language: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct Repositories {
-    items: Vec<Repository>,
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("error: {}", err);
+        exit(exit_code(&err));
+    }
 }
 
-async fn search_github_repositories(query: &str, access_token: &str) -> Result<Repositories, reqwest::Error> {
-    let client = Client::new();
-    let url = format!("https://api.github.com/search/repositories?q={}&per_page=100", query);
-
-    let user_agent = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-
-    let request = client
-        .get(url)
-        .header(header::ACCEPT, "application/vnd.github+json")
-        .header(header::AUTHORIZATION, format!("Bearer {}", access_token))
-        .header(header::USER_AGENT, user_agent)
-        .header("X-GitHub-Api-Version", "2022-11-28")
-        .send()
-        .await?;
-
-    let repositories: Repositories = request.json().await?;
-    Ok(repositories)
+/// Exits with `code`, first saving what would otherwise be saved on the way
+/// out of [`run`], since exiting runs no destructors.
+fn exit(code: i32) -> ! {
+    deprecations::save();
+    process::exit(code)
 }
 
-fn filter_repositories(
-    repositories: Repositories,
-    title: Option<&str>,
-    description: Option<&str>,
-    language: Option<&str>,
-) -> Vec<Repository> {
-    repositories
-        .items
-        .into_iter()
-        .filter(|repo| {
-            title.map_or(true, |title| {
-                repo.name.to_lowercase().contains(&title.to_lowercase())
-            })
-        })
-        .filter(|repo| {
-            description.map_or(true, |description| {
-                repo.description
-                    .as_ref()
-                    .map_or(false, |repo_description| {
-                        repo_description
-                            .to_lowercase()
-                            .contains(&description.to_lowercase())
-                    })
-            })
-        })
-        .filter(|repo| {
-            language.map_or(true, |language| {
-                repo.language
-                    .as_ref()
-                    .map_or(false, |repo_language| {
-                        repo_language.to_lowercase() == language.to_lowercase()
-                    })
-            })
-        })
-        .collect()
+/// Scripts can tell a rejected token, an exhausted rate limit and a bad
+/// query apart from other failures by the exit status.
+fn exit_code(err: &BoxError) -> i32 {
+    if let Some(err) = err.downcast_ref::<ApiError>() {
+        err.exit_code()
+    } else if err.is::<QueryError>() {
+        error::EXIT_USAGE
+    } else {
+        error::EXIT_FAILURE
+    }
 }
 
-fn print_repo(repo: Repository) {
-    let description = repo.description.unwrap_or_else(|| "No description".to_string());
-    let language = repo.language.unwrap_or_else(|| "No language specified".to_string());
-
-    println!(
-        "Repository Name: {}\nDescription: {}\nLanguage: {}\n---",
-        repo.name, description, language
+async fn run() -> Result<(), BoxError> {
+    let config = Config::load()?;
+    let update_check = config.boolean("update_check")?.unwrap_or(true)
+        && env::var_os("GHS_NO_UPDATE_CHECK").is_none();
+    let deprecation_warnings = config.boolean("deprecation_warnings")?.unwrap_or(true);
+    let args = preset::expand(&config, cli::repos_alias(env::args_os()))?;
+    let profile = Profile::select(&config, &args)?;
+    let args = match &profile {
+        Some(profile) => profile.apply(args),
+        None => args,
+    };
+    let matches = cli::command()
+        .try_get_matches_from(args)
+        .unwrap_or_else(|err| err.exit());
+
+    if matches.get_flag("plain") {
+        plain::enable();
+    }
+    output::set_locale(
+        matches
+            .get_one::<Locale>("locale")
+            .copied()
+            .unwrap_or_else(Locale::from_env),
     );
-}
-
-#[tokio::main]
-async fn main() -> Result<(), reqwest::Error> {
-    let matches = Command::new("GitHub Repository Search")
-        .arg(
-            Arg::new("username")
-                .short('u')
-                .long("username")
-                .value_name("USERNAME")
-                .help("GitHub username")
-                .required(true),
-        )
-        .arg(
-            Arg::new("repositories")
-                .short('r')
-                .long("repositories")
-                .value_name("REPOSITORIES")
-                .help("Filter by the specified repository name"),
-        )
-        .arg(
-            Arg::new("title")
-                .short('t')
-                .long("title")
-                .value_name("TITLE")
-                .help("Filter by the specified title"),
-        )
-        .arg(
-            Arg::new("description")
-                .short('d')
-                .long("description")
-                .value_name("DESCRIPTION")
-                .help("Filter by the specified repository description"),
-        )
-        .arg(
-            Arg::new("language")
-                .short('l')
-                .long("language")
-                .value_name("LANGUAGE")
-                .help("Filter by the specified programming language"),
-        )
-        .get_matches();
-
-    let access_token = env::var("GITHUB_ACCESS_TOKEN").expect("GITHUB_ACCESS_TOKEN must be set");
-    let github_username = matches.get_one::<String>("username").unwrap();
-
-    let title = matches.get_one::<String>("title").map(String::as_str);
-    let description = matches.get_one::<String>("description").map(String::as_str);
-    let language = matches.get_one::<String>("language").map(String::as_str);
-
-    let search_query = format!("user:{}", github_username);
-
-    let repositories = search_github_repositories(&search_query, &access_token).await?;
+    if matches.get_flag("strict") {
+        circuit::enable_strict();
+    }
+    let mut placeholders = Placeholders::from_config(&config)?;
+    for (field, text) in matches
+        .get_many::<(placeholder::Field, String)>("null-placeholder")
+        .into_iter()
+        .flatten()
+    {
+        placeholders.set(*field, text.clone());
+    }
+    placeholders.install();
+    console::init();
+    interrupt::install();
+    // It brings its own server, so no provider, token or cache applies.
+    if matches.subcommand_name() == Some("selftest") {
+        #[cfg(feature = "testing")]
+        return selftest::run().await;
+        #[cfg(not(feature = "testing"))]
+        return Err(
+            "this build of ghs was made without the `testing` feature that selftest needs".into(),
+        );
+    }
 
-    let filtered_repos = filter_repositories(repositories, title, description, language);
+    let ctx = Context::new(&config, matches, profile)?;
+    let matches = &ctx.matches;
+
+    // Scripts neither want the notice nor the extra request, and ghs is
+    // released on github.com only.
+    if update_check
+        && ctx.reaches_github_com
+        && io::stderr().is_terminal()
+        && matches.subcommand_name() != Some("self-update")
+    {
+        if let Some(dir) = Cache::default_dir() {
+            update::notify_if_outdated(&ctx.api, &dir).await;
+        }
+    }
 
-    for repo in filtered_repos {
-        print_repo(repo);
+    // Notices are recorded on every run but only shown to people.
+    let _deprecations = Cache::default_dir().map(|dir| {
+        if deprecation_warnings && io::stderr().is_terminal() {
+            deprecations::warn(&dir);
+        }
+        deprecations::record(&ctx.api, &dir)
+    });
+
+    match matches.subcommand() {
+        Some(("compare-repos", compare_matches)) => {
+            commands::compare_repos::run(&ctx, compare_matches).await?
+        }
+        Some(("repo", repo_matches)) => commands::repo::run(&ctx, repo_matches).await?,
+        Some(("issue", issue_matches)) => commands::issue::run(&ctx, issue_matches).await?,
+        Some(("org-members-repos", members_matches)) => {
+            commands::org_members_repos::run(&ctx, members_matches).await?
+        }
+        Some(("team-repos", team_matches)) => commands::team_repos::run(&ctx, team_matches).await?,
+        Some(("issues-of-results", issues_matches)) => {
+            commands::issues_of_results::run(&ctx, issues_matches).await?
+        }
+        Some(("topics", topics_matches)) => commands::topics::run(&ctx, topics_matches).await?,
+        Some(("audit-files", audit_matches)) => {
+            commands::audit_files::run(&ctx, audit_matches).await?
+        }
+        Some(("audit-protection", audit_matches)) => {
+            commands::audit_protection::run(&ctx, audit_matches).await?
+        }
+        Some(("ci-status", ci_matches)) => commands::ci_status::run(&ctx, ci_matches).await?,
+        Some(("discussions", discussions_matches)) => {
+            commands::discussions::run(&ctx, discussions_matches).await?
+        }
+        Some(("code", code_matches)) => commands::code::run(&ctx, code_matches).await?,
+        Some(("issues", issues_matches)) => commands::issues::run(&ctx, issues_matches).await?,
+        Some(("labels", labels_matches)) => commands::labels::run(&ctx, labels_matches).await?,
+        Some(("milestones", milestones_matches)) => {
+            commands::milestones::run(&ctx, milestones_matches).await?
+        }
+        Some(("notifications", notifications_matches)) => {
+            commands::notifications::run(&ctx, notifications_matches).await?
+        }
+        Some(("whoowns", whoowns_matches)) => commands::whoowns::run(&ctx, whoowns_matches).await?,
+        Some(("self-update", update_matches)) => {
+            update::self_update(&ctx.api, update_matches.get_flag("yes")).await?;
+        }
+        Some(("tui", tui_matches)) => {
+            let name = tui_matches.get_one::<String>("session").unwrap();
+            tui::run(Session::load(name)?, false)?;
+        }
+        Some(("triage", triage_matches)) => commands::triage::run(&ctx, triage_matches).await?,
+        Some(("export", export_matches)) => commands::export::run(export_matches)?,
+        Some(("leaderboard", leaderboard_matches)) => {
+            commands::leaderboard::run(leaderboard_matches)?
+        }
+        Some(("trending", trending_matches)) => {
+            commands::trending::run(&ctx, trending_matches).await?
+        }
+        Some(("bench", bench_matches)) => commands::bench::run(&ctx, bench_matches).await?,
+        Some(("cache", cache_matches)) => commands::cache::run(&ctx, cache_matches).await?,
+        Some(("schema", _)) => print!("{}", schema::DOCUMENT),
+        Some(("fixtures", fixtures_matches)) => {
+            commands::fixtures::run(&ctx, fixtures_matches).await?
+        }
+        Some(("trend", trend_matches)) => commands::trend::run(trend_matches)?,
+        Some(("rename-branch", rename_matches)) => {
+            commands::rename_branch::run(&ctx, rename_matches).await?
+        }
+        Some(("dupes", dupes_matches)) => commands::dupes::run(&ctx, dupes_matches).await?,
+        _ => commands::search::run(&ctx).await?,
     }
 
+    commands::print_stats(matches, &ctx.api);
     Ok(())
 }
@@ -0,0 +1,57 @@
+use crate::circuit::CircuitBreaker;
+use crate::commands::print_repo;
+use ghs::model::User;
+use ghs::{BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many members' repository lists may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+/// Lists the members of `org` and fetches each member's own repositories
+/// concurrently. Members come back sorted by login; members whose
+/// repositories could not be fetched are left out with a warning.
+pub async fn member_repositories(
+    api: &GhsClient,
+    org: &str,
+) -> Result<Vec<(User, Vec<Repository>)>, BoxError> {
+    let members = api.org_members(org).await?;
+
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for member in members {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let repositories = breaker
+                .call(
+                    &format!("repositories of {}", member.login),
+                    api.user_repositories(&member.login),
+                )
+                .await?;
+            Ok::<_, BoxError>(repositories.map(|repositories| (member, repositories)))
+        });
+    }
+
+    let mut rollup = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        if let Some(entry) = result?? {
+            rollup.push(entry);
+        }
+    }
+    rollup.sort_by_key(|(member, _)| member.login.to_lowercase());
+    Ok(rollup)
+}
+
+pub fn print_rollup(rollup: &[(User, Vec<Repository>)]) {
+    for (member, repositories) in rollup {
+        println!("== {} ({} repositories)", member.login, repositories.len());
+        for repo in repositories {
+            print_repo(repo);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! `--mine-and-orgs`: the authenticated user and every organization they
+//! belong to, remembered for a day so that the search costs no extra
+//! requests most of the time.
+
+use ghs::{datetime, BoxError, GhsClient};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How long memberships are reused before they are looked up again, in
+/// seconds; joining or leaving an organization shows up after this.
+const MAX_AGE: i64 = 24 * 60 * 60;
+
+/// What is cached, per access token.
+#[derive(Debug, Serialize, Deserialize)]
+struct Memberships {
+    fetched_at: i64,
+    login: String,
+    orgs: Vec<String>,
+}
+
+/// The file for the token whose hash is `token_hash`; tokens of different
+/// accounts must not share it, and the token itself is not written down.
+fn path(cache_dir: &Path, token: &str) -> PathBuf {
+    let hash = format!("{:x}", Sha256::digest(token.as_bytes()));
+    cache_dir.join(format!("memberships-{}.json", &hash[..16]))
+}
+
+/// The login of the user `token` belongs to, followed by their
+/// organizations. They are read from `cache_dir` while less than a day old,
+/// or however old when `offline`, and looked up and saved otherwise.
+pub async fn mine_and_orgs(
+    api: &GhsClient,
+    token: &str,
+    cache_dir: Option<&Path>,
+    offline: bool,
+) -> Result<Vec<String>, BoxError> {
+    let path = cache_dir.map(|dir| path(dir, token));
+    let cached: Option<Memberships> = path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok());
+    let memberships = match cached {
+        Some(cached) if offline || datetime::now() - cached.fetched_at < MAX_AGE => cached,
+        None if offline => {
+            return Err(
+                "--mine-and-orgs has no cached memberships; run it once without --offline".into(),
+            )
+        }
+        _ => {
+            let login = api.authenticated_user().await?.login;
+            let orgs = api
+                .user_orgs()
+                .await?
+                .into_iter()
+                .map(|org| org.login)
+                .collect();
+            let memberships = Memberships {
+                fetched_at: datetime::now(),
+                login,
+                orgs,
+            };
+            // A cache that cannot be written only costs the lookup next time.
+            if let Some(path) = &path {
+                let _ = path.parent().map(fs::create_dir_all);
+                let _ = fs::write(path, serde_json::to_string(&memberships)?);
+            }
+            memberships
+        }
+    };
+    Ok(std::iter::once(memberships.login)
+        .chain(memberships.orgs)
+        .collect())
+}
@@ -0,0 +1,124 @@
+use crate::circuit::CircuitBreaker;
+use crate::color;
+use crate::table;
+use ghs::model::Milestone;
+use ghs::{datetime, BoxError, GhsClient, Repository};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories' milestones may be fetched at once.
+const CONCURRENCY: usize = 8;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Fetches the milestones in `state` of every repository and returns them
+/// as `(repository, milestone)` pairs, soonest due first; milestones without
+/// a due date come last.
+pub async fn fetch_milestones(
+    api: &GhsClient,
+    repositories: &[Repository],
+    state: &str,
+) -> Result<Vec<(String, Milestone)>, BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let state: Arc<str> = state.into();
+    let mut tasks = JoinSet::new();
+    for repo in repositories {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let state = Arc::clone(&state);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let milestones = breaker
+                .call(
+                    &format!("milestones of {}", full_name),
+                    api.milestones(&full_name, &state),
+                )
+                .await?;
+            Ok::<_, BoxError>((full_name, milestones.unwrap_or_default()))
+        });
+    }
+
+    let mut milestones = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        let (full_name, repo_milestones) = result??;
+        milestones.extend(
+            repo_milestones
+                .into_iter()
+                .map(|milestone| (full_name.clone(), milestone)),
+        );
+    }
+    milestones.sort_by(|(a_repo, a), (b_repo, b)| {
+        let due = |milestone: &Milestone| {
+            milestone
+                .due_on
+                .as_deref()
+                .and_then(datetime::parse_timestamp)
+                .unwrap_or(i64::MAX)
+        };
+        due(a)
+            .cmp(&due(b))
+            .then_with(|| a_repo.cmp(b_repo))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    Ok(milestones)
+}
+
+/// The share of the milestone's issues that are closed, in percent. An
+/// empty milestone counts as not started.
+pub fn completion(milestone: &Milestone) -> u64 {
+    let total = milestone.open_issues + milestone.closed_issues;
+    (milestone.closed_issues * 100)
+        .checked_div(total)
+        .unwrap_or(0)
+}
+
+pub fn print_milestones(milestones: &[(String, Milestone)]) {
+    if milestones.is_empty() {
+        println!("No milestones");
+        return;
+    }
+
+    let now = datetime::now();
+    let mut rows = vec![vec![
+        "Due".to_string(),
+        "Repository".to_string(),
+        "Milestone".to_string(),
+        "Done".to_string(),
+        "Issues".to_string(),
+        "Link".to_string(),
+    ]];
+    for (full_name, milestone) in milestones {
+        let due = milestone
+            .due_on
+            .as_deref()
+            .and_then(datetime::parse_timestamp);
+        let due = match due {
+            Some(due) => {
+                let date = datetime::format_date(due.div_euclid(SECONDS_PER_DAY));
+                if due < now && milestone.state == "open" {
+                    color::paint(&format!("{} overdue", date), color::RED)
+                } else {
+                    date
+                }
+            }
+            None => "-".to_string(),
+        };
+        rows.push(vec![
+            due,
+            full_name.clone(),
+            milestone.title.clone(),
+            format!("{}%", completion(milestone)),
+            format!(
+                "{}/{}",
+                milestone.closed_issues,
+                milestone.open_issues + milestone.closed_issues
+            ),
+            milestone.html_url.clone(),
+        ]);
+    }
+    print!("{}", table::render(&rows));
+}
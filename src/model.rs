@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Repository {
+    pub name: String,
+    #[serde(default)]
+    pub full_name: String,
+    pub owner: Option<User>,
+    pub description: Option<String>,
+    pub html_url: Option<String>,
+    pub clone_url: Option<String>,
+    pub ssh_url: Option<String>,
+    pub homepage: Option<String>,
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub stargazers_count: u64,
+    #[serde(default)]
+    pub forks_count: u64,
+    #[serde(default)]
+    pub open_issues_count: u64,
+    #[serde(default)]
+    pub watchers_count: u64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub pushed_at: Option<String>,
+    pub license: Option<License>,
+    /// The forge the repository was found on, when several were searched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The queries that found the repository, when several were run with
+    /// `--query`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Whether the authenticated user has starred the repository, when
+    /// `--annotate-starred` checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starred: Option<bool>,
+    /// A 0-100 score computed by `--score`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Bytes of code per language, when a pipeline's `enrich` stage
+    /// fetched them; search results only carry the main `language`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub languages: Option<BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct License {
+    pub name: String,
+    pub spdx_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct User {
+    pub login: String,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// One week of `/stats/commit_activity`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WeeklyCommits {
+    /// Start of the week, in seconds since the Unix epoch.
+    pub week: i64,
+    pub total: u64,
+    /// Commits per day, Sunday first.
+    #[serde(default)]
+    pub days: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Label {
+    pub name: String,
+    /// Hex color without the leading `#`.
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    pub user: Option<User>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub comments: u64,
+    pub created_at: Option<String>,
+    /// Set when the "issue" is a pull request, which the issues API also lists.
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IssueComment {
+    pub user: Option<User>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Milestone {
+    pub title: String,
+    pub html_url: String,
+    pub state: String,
+    #[serde(default)]
+    pub open_issues: u64,
+    #[serde(default)]
+    pub closed_issues: u64,
+    pub due_on: Option<String>,
+}
+
+/// A discussion as returned by the GraphQL search.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discussion {
+    pub title: String,
+    pub url: String,
+    pub category: Option<Named>,
+    pub repository: RepositoryName,
+    /// Only set in categories that accept answers.
+    pub is_answered: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Named {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryName {
+    pub name_with_owner: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkflowRun {
+    pub name: Option<String>,
+    pub head_branch: Option<String>,
+    /// `queued`, `in_progress` or `completed`.
+    pub status: Option<String>,
+    /// Set once the run has completed: `success`, `failure`, `cancelled`, ...
+    pub conclusion: Option<String>,
+    pub html_url: String,
+    pub updated_at: Option<String>,
+}
+
+/// A repository's topics, as `/repos/{owner}/{repo}/topics` has them.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Topics {
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WorkflowRuns {
+    pub workflow_runs: Vec<WorkflowRun>,
+}
+
+/// The subset of a branch's protection rules that audits look at.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BranchProtection {
+    pub required_pull_request_reviews: Option<RequiredReviews>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RequiredReviews {
+    #[serde(default)]
+    pub required_approving_review_count: u32,
+}
+
+/// What `/branches/{branch}/protection` revealed about a branch.
+#[derive(Debug, Clone)]
+pub enum Protection {
+    Protected(BranchProtection),
+    Unprotected,
+    /// Reading protection rules needs admin access to the repository.
+    NoAccess,
+}
+
+/// Results of a code or issue search, as [`Repositories`] are of a
+/// repository search.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SearchResults<T> {
+    #[serde(default)]
+    pub total_count: u64,
+    #[serde(default)]
+    pub incomplete_results: bool,
+    pub items: Vec<T>,
+}
+
+/// A file matched by a code search.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CodeResult {
+    pub name: String,
+    pub path: String,
+    pub html_url: String,
+    /// Only the repository's names and URLs; search results leave out its
+    /// counts.
+    pub repository: Repository,
+    /// The matched parts of the file.
+    #[serde(default)]
+    pub text_matches: Vec<TextMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextMatch {
+    #[serde(default)]
+    pub fragment: String,
+}
+
+/// An issue or pull request matched by an issue search.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IssueResult {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    /// `open` or `closed`.
+    pub state: String,
+    pub user: Option<User>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub comments: u64,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// API URL of the repository, ending in `/repos/OWNER/NAME`.
+    pub repository_url: String,
+    /// Set for pull requests.
+    pub pull_request: Option<serde_json::Value>,
+}
+
+impl IssueResult {
+    /// The repository's `owner/name`.
+    pub fn repository(&self) -> &str {
+        self.repository_url
+            .rsplit_once("/repos/")
+            .map_or(&self.repository_url, |(_, full_name)| full_name)
+    }
+}
+
+/// A thread in the authenticated user's notifications inbox.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Notification {
+    /// The thread's id, by which it is marked read.
+    pub id: String,
+    pub unread: bool,
+    /// Why it was sent, e.g. `mention`, `review_requested` or `subscribed`.
+    pub reason: String,
+    pub updated_at: Option<String>,
+    pub subject: NotificationSubject,
+    /// Only the repository's names and URLs.
+    pub repository: Repository,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationSubject {
+    pub title: String,
+    /// `Issue`, `PullRequest`, `Release`, `Discussion` and so on.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// API URL of the subject; not set for some kinds, e.g. discussions.
+    pub url: Option<String>,
+}
+
+impl Notification {
+    /// The web page of the issue or pull request the thread is about; for
+    /// other subjects the API gives no link to one.
+    pub fn html_url(&self) -> Option<String> {
+        let url = self.subject.url.as_deref()?;
+        let (page, number) = match url.rsplit_once("/pulls/") {
+            Some((_, number)) => ("pull", number),
+            None => ("issues", url.rsplit_once("/issues/")?.1),
+        };
+        let repository = self.repository.html_url.as_deref()?;
+        Some(format!("{}/{}/{}", repository, page, number))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Repositories {
+    /// How many repositories matched the query on GitHub's side, which may be
+    /// more than were returned.
+    #[serde(default)]
+    pub total_count: u64,
+    #[serde(default)]
+    pub incomplete_results: bool,
+    pub items: Vec<Repository>,
+}
+
+impl Repositories {
+    /// What a search cancelled before its first page returns.
+    pub fn cancelled() -> Repositories {
+        Repositories {
+            total_count: 0,
+            incomplete_results: true,
+            items: Vec::new(),
+        }
+    }
+}
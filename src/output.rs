@@ -0,0 +1,551 @@
+use crate::placeholder::{self, Field};
+use crate::provenance::Provenance;
+use crate::{details, export, plain, table};
+use ghs::locale::Locale;
+use ghs::provider::BoxFuture;
+use ghs::sink::{OutputSink, Sqlite, SqliteProvenance, Webhook};
+use ghs::{schema, BoxError, Repository};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What `--locale` or the environment picked, for the rest of the run.
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Descriptions longer than this are cut short in the table.
+const TABLE_DESCRIPTION_WIDTH: usize = 50;
+
+/// How listed repositories are written out, picked with `--format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The multi-line listing, one line per repository with `--plain`.
+    #[default]
+    Plain,
+    /// Every field of every repository, as a JSON array.
+    Json,
+    Csv,
+    /// One aligned row per repository.
+    Table,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Format, String> {
+        match format {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "table" => Ok(Format::Table),
+            _ => Err(format!(
+                "`{}` is not an output format (expected plain, json, csv or table)",
+                format
+            )),
+        }
+    }
+}
+
+/// Writes results in one [`Format`] to stdout or a file. JSON and CSV are
+/// streamed batch by batch; the table needs every row to align its columns
+/// and is written by [`OutputSink::finish`].
+pub struct Formatter<W> {
+    format: Format,
+    writer: W,
+    /// Whether anything was written yet, i.e. the header or opening bracket.
+    started: bool,
+    /// Which extra columns rows carry, decided by the first batch.
+    columns: Columns,
+    table: Vec<Repository>,
+    provenance: Option<Provenance>,
+}
+
+impl<W: Write> Formatter<W> {
+    pub fn new(format: Format, writer: W) -> Formatter<W> {
+        Formatter {
+            format,
+            writer,
+            started: false,
+            columns: Columns::default(),
+            table: Vec::new(),
+            provenance: None,
+        }
+    }
+
+    /// Records where the results came from: as the JSON document's
+    /// `metadata`, and in trailing CSV columns, as exports do.
+    pub fn provenance(mut self, provenance: Provenance) -> Formatter<W> {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    fn json_opening(&self) -> Result<String, BoxError> {
+        let mut opening = format!("{{\n  \"schema\": \"{}\",\n", schema::VERSION);
+        if let Some(provenance) = &self.provenance {
+            let metadata = serde_json::to_string_pretty(provenance)?.replace('\n', "\n  ");
+            opening.push_str(&format!("  \"metadata\": {},\n", metadata));
+        }
+        opening.push_str("  \"repositories\": ");
+        Ok(opening)
+    }
+
+    fn write_batch(&mut self, repositories: &[Repository]) -> Result<(), BoxError> {
+        if !self.started {
+            self.columns = Columns::of(repositories);
+            // Every row says which query found it, with the provenance.
+            self.columns.query |= self.provenance.is_some();
+        }
+        match self.format {
+            Format::Plain => {
+                for repo in repositories {
+                    self.writer.write_all(plain(repo).as_bytes())?;
+                }
+            }
+            Format::Json => {
+                for repo in repositories {
+                    // The same layout as pretty-printing the whole document.
+                    let item = serde_json::to_string_pretty(repo)?.replace('\n', "\n    ");
+                    if !self.started {
+                        write!(self.writer, "{}", self.json_opening()?)?;
+                    }
+                    let separator = if self.started { ",\n    " } else { "[\n    " };
+                    write!(self.writer, "{}{}", separator, item)?;
+                    self.started = true;
+                }
+            }
+            Format::Csv => {
+                if !self.started {
+                    let header = csv_header(self.columns, self.provenance.as_ref());
+                    writeln!(self.writer, "{}", header)?;
+                }
+                for repo in repositories {
+                    let row = csv_row(repo, self.columns, self.provenance.as_ref());
+                    writeln!(self.writer, "{}", row)?;
+                }
+            }
+            Format::Table => self.table.extend_from_slice(repositories),
+        }
+        self.started = true;
+        Ok(())
+    }
+
+    fn finish_output(&mut self) -> Result<(), BoxError> {
+        match self.format {
+            Format::Json if self.started => writeln!(self.writer, "\n  ]\n}}")?,
+            Format::Json => writeln!(self.writer, "{}[]\n}}", self.json_opening()?)?,
+            Format::Csv if !self.started => {
+                let columns = Columns {
+                    query: self.provenance.is_some(),
+                    ..Columns::default()
+                };
+                let header = csv_header(columns, self.provenance.as_ref());
+                writeln!(self.writer, "{}", header)?
+            }
+            Format::Table => self
+                .writer
+                .write_all(table(&self.table, self.columns).as_bytes())?,
+            _ => {}
+        }
+        Ok(self.writer.flush()?)
+    }
+}
+
+impl<W: Write> OutputSink for Formatter<W> {
+    fn write<'a>(
+        &'a mut self,
+        repositories: &'a [Repository],
+    ) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move { self.write_batch(repositories) })
+    }
+
+    fn finish(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move { self.finish_output() })
+    }
+}
+
+/// The sink `--output` names: `-` for stdout, `sqlite:PATH` for a database,
+/// an `http(s)://` webhook URL, or else a file path. Only stdout and files
+/// take a `--format`. All but webhooks record `provenance`.
+pub fn open(
+    output: &str,
+    format: Option<Format>,
+    provenance: &Provenance,
+) -> Result<Box<dyn OutputSink>, BoxError> {
+    let format_required = |kind: &str| match format {
+        Some(_) => Err(format!("--format does not apply to {} outputs", kind)),
+        None => Ok(()),
+    };
+    if let Some(path) = output.strip_prefix("sqlite:") {
+        format_required("sqlite:")?;
+        let provenance = SqliteProvenance {
+            query: provenance.query.clone(),
+            host: provenance.host.clone(),
+        };
+        return Ok(Box::new(Sqlite::open(Path::new(path), provenance)?));
+    }
+    if output.starts_with("http://") || output.starts_with("https://") {
+        format_required("webhook")?;
+        return Ok(Box::new(Webhook::new(output)));
+    }
+    let format = format.unwrap_or_default();
+    if output == "-" {
+        return Ok(Box::new(
+            Formatter::new(format, io::stdout()).provenance(provenance.clone()),
+        ));
+    }
+    let file =
+        File::create(output).map_err(|err| format!("could not create {}: {}", output, err))?;
+    Ok(Box::new(
+        Formatter::new(format, BufWriter::new(file)).provenance(provenance.clone()),
+    ))
+}
+
+/// Writes `repositories` to `sink` as one batch and finishes it.
+pub async fn write_all(
+    sink: &mut dyn OutputSink,
+    repositories: &[Repository],
+) -> Result<(), BoxError> {
+    if !repositories.is_empty() {
+        sink.write(repositories).await?;
+    }
+    sink.finish().await
+}
+
+/// The listing `ghs` prints by default: a few labelled lines per repository,
+/// or a single sentence in plain mode.
+pub fn plain(repo: &Repository) -> String {
+    let description = placeholder::or(
+        repo.description.as_deref(),
+        Field::Description,
+        "No description",
+    );
+    let language = placeholder::or(
+        repo.language.as_deref(),
+        Field::Language,
+        "No language specified",
+    );
+
+    if plain::enabled() {
+        let provider = repo
+            .provider
+            .as_ref()
+            .map(|provider| format!(" Found on: {}.", provider))
+            .unwrap_or_default();
+        let query = repo
+            .query
+            .as_ref()
+            .map(|query| format!(" Query: {}.", query))
+            .unwrap_or_default();
+        let starred = match repo.starred {
+            Some(true) => " Starred by you.",
+            _ => "",
+        };
+        let score = repo
+            .score
+            .map(|score| format!(" Score: {}.", score))
+            .unwrap_or_default();
+        let languages = repo
+            .languages
+            .as_ref()
+            .map(|languages| format!(" Languages: {}.", details::language_breakdown(languages)))
+            .unwrap_or_default();
+        let url = repo
+            .html_url
+            .as_ref()
+            // No full stop, so the URL can be copied as is.
+            .map(|url| format!(" {}", url))
+            .unwrap_or_default();
+        return format!(
+            "Repository {}: {}. Language: {}.{} Stars: {}.{}{}{}{}{}\n",
+            repo.name,
+            description.trim_end_matches('.'),
+            language,
+            languages,
+            count(repo.stargazers_count),
+            starred,
+            score,
+            url,
+            provider,
+            query
+        );
+    }
+    let provider = repo
+        .provider
+        .as_ref()
+        .map(|provider| format!("Found on: {}\n", provider))
+        .unwrap_or_default();
+    let query = repo
+        .query
+        .as_ref()
+        .map(|query| format!("Query: {}\n", query))
+        .unwrap_or_default();
+    let starred = match repo.starred {
+        Some(starred) => format!("Starred by you: {}\n", if starred { "yes" } else { "no" }),
+        None => String::new(),
+    };
+    let score = repo
+        .score
+        .map(|score| format!("Score: {}\n", score))
+        .unwrap_or_default();
+    let languages = repo
+        .languages
+        .as_ref()
+        .map(|languages| format!("Languages: {}\n", details::language_breakdown(languages)))
+        .unwrap_or_default();
+    let url = repo
+        .html_url
+        .as_ref()
+        .map(|url| format!("URL: {}\n", url))
+        .unwrap_or_default();
+    format!(
+        "Repository Name: {}\nDescription: {}\nLanguage: {}\n{}Stars: {}\n{}{}{}{}{}---\n",
+        repo.name,
+        description,
+        language,
+        languages,
+        count(repo.stargazers_count),
+        starred,
+        score,
+        url,
+        provider,
+        query
+    )
+}
+
+/// Columns beyond the usual ones: which forge each result came from, when
+/// several were searched, which queries found it, when several were run,
+/// whether it is starred, when that was checked, and its `--score`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Columns {
+    provider: bool,
+    query: bool,
+    starred: bool,
+    score: bool,
+}
+
+impl Columns {
+    fn of(repositories: &[Repository]) -> Columns {
+        Columns {
+            provider: repositories.iter().any(|repo| repo.provider.is_some()),
+            query: repositories.iter().any(|repo| repo.query.is_some()),
+            starred: repositories.iter().any(|repo| repo.starred.is_some()),
+            score: repositories.iter().any(|repo| repo.score.is_some()),
+        }
+    }
+}
+
+/// The header row; with a provenance, the `query` column is always there,
+/// followed by the rest of it.
+fn csv_header(columns: Columns, provenance: Option<&Provenance>) -> String {
+    let mut header = export::CSV_HEADER.to_string();
+    if columns.provider {
+        header.push_str(",provider");
+    }
+    if columns.query {
+        header.push_str(",query");
+    }
+    if columns.starred {
+        header.push_str(",starred");
+    }
+    if columns.score {
+        header.push_str(",score");
+    }
+    if provenance.is_some() {
+        header.push_str(",fetched_at,ghs_version,host");
+    }
+    header
+}
+
+fn csv_row(repo: &Repository, columns: Columns, provenance: Option<&Provenance>) -> String {
+    let mut fields = export::csv_fields(repo);
+    if columns.provider {
+        fields.push(repo.provider.clone().unwrap_or_default());
+    }
+    if columns.query {
+        // The queries that found it, when several were run.
+        let query = repo.query.as_ref();
+        let query = query.or(provenance.map(|provenance| &provenance.query));
+        fields.push(query.cloned().unwrap_or_default());
+    }
+    if columns.starred {
+        fields.push(
+            repo.starred
+                .map(|starred| starred.to_string())
+                .unwrap_or_default(),
+        );
+    }
+    if columns.score {
+        fields.push(
+            repo.score
+                .map(|score| score.to_string())
+                .unwrap_or_default(),
+        );
+    }
+    if let Some(provenance) = provenance {
+        fields.extend([
+            provenance.fetched_at.clone(),
+            provenance.ghs_version.to_string(),
+            provenance.host.clone(),
+        ]);
+    }
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| export::csv_field(field))
+        .collect();
+    fields.join(",")
+}
+
+fn table(repositories: &[Repository], columns: Columns) -> String {
+    let mut rows = vec![vec![
+        "Repository".to_string(),
+        "Stars".to_string(),
+        "Forks".to_string(),
+        "Language".to_string(),
+        "Pushed".to_string(),
+        "Description".to_string(),
+    ]];
+    if columns.score {
+        rows[0].insert(1, "Score".to_string());
+    }
+    if columns.starred {
+        rows[0].insert(1, "Starred".to_string());
+    }
+    if columns.query {
+        rows[0].insert(1, "Query".to_string());
+    }
+    if columns.provider {
+        rows[0].insert(1, "Provider".to_string());
+    }
+    rows.extend(repositories.iter().map(|repo| {
+        let mut row = vec![
+            repo.full_name.clone(),
+            count(repo.stargazers_count),
+            count(repo.forks_count),
+            placeholder::or(repo.language.as_deref(), Field::Language, "").to_string(),
+            // The date is enough to tell recent pushes from old ones.
+            repo.pushed_at.as_deref().map(date).unwrap_or_default(),
+            shorten(placeholder::or(
+                repo.description.as_deref(),
+                Field::Description,
+                "",
+            )),
+        ];
+        if columns.score {
+            row.insert(
+                1,
+                repo.score
+                    .map(|score| score.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        if columns.starred {
+            let starred = if repo.starred == Some(true) {
+                "yes"
+            } else {
+                ""
+            };
+            row.insert(1, starred.to_string());
+        }
+        if columns.query {
+            row.insert(1, repo.query.clone().unwrap_or_default());
+        }
+        if columns.provider {
+            row.insert(1, repo.provider.clone().unwrap_or_default());
+        }
+        row
+    }));
+    table::render(&rows)
+}
+
+/// Formats counts and dates in the listing and the table for `locale` for
+/// the rest of the run; until then, and in JSON and CSV, they stay plain.
+pub fn set_locale(locale: Locale) {
+    // Only `run` sets it, once.
+    let _ = LOCALE.set(locale);
+}
+
+/// `count`, e.g. stars, for people to read.
+pub fn count(count: u64) -> String {
+    LOCALE
+        .get()
+        .copied()
+        .unwrap_or_default()
+        .format_count(count)
+}
+
+/// The date of `timestamp` for people to read, or `timestamp` as it is when
+/// it is not one, e.g. because a `--transform` already formatted it.
+pub fn date(timestamp: &str) -> String {
+    LOCALE
+        .get()
+        .copied()
+        .unwrap_or_default()
+        .format_date(timestamp)
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// The first line of `text`, cut to [`TABLE_DESCRIPTION_WIDTH`] characters.
+pub fn shorten(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= TABLE_DESCRIPTION_WIDTH {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(TABLE_DESCRIPTION_WIDTH - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provenance() -> Provenance {
+        Provenance {
+            query: "org:octo".to_string(),
+            fetched_at: "2026-10-14T00:00:00Z".to_string(),
+            ghs_version: "0.0.1",
+            host: "https://api.github.com".to_string(),
+        }
+    }
+
+    fn written(format: Format, provenance: Option<Provenance>) -> String {
+        let repo: Repository =
+            serde_json::from_value(serde_json::json!({"name": "ghs", "full_name": "octo/ghs"}))
+                .unwrap();
+        let mut written = Vec::new();
+        let mut formatter = Formatter::new(format, &mut written);
+        if let Some(provenance) = provenance {
+            formatter = formatter.provenance(provenance);
+        }
+        formatter.write_batch(&[repo]).unwrap();
+        formatter.finish_output().unwrap();
+        String::from_utf8(written).unwrap()
+    }
+
+    #[test]
+    fn json_documents_carry_the_provenance() {
+        let document: serde_json::Value =
+            serde_json::from_str(&written(Format::Json, Some(provenance()))).unwrap();
+        assert_eq!(document["schema"], schema::VERSION);
+        assert_eq!(document["metadata"]["query"], "org:octo");
+        assert_eq!(document["metadata"]["ghs_version"], "0.0.1");
+        assert_eq!(document["repositories"][0]["full_name"], "octo/ghs");
+
+        let document: serde_json::Value =
+            serde_json::from_str(&written(Format::Json, None)).unwrap();
+        assert!(document.get("metadata").is_none());
+    }
+
+    #[test]
+    fn csv_rows_end_in_the_provenance() {
+        let csv = written(Format::Csv, Some(provenance()));
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap();
+        assert!(header.ends_with(",pushed_at,query,fetched_at,ghs_version,host"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .ends_with(",org:octo,2026-10-14T00:00:00Z,0.0.1,https://api.github.com"));
+        assert_eq!(
+            written(Format::Csv, None).lines().next().unwrap(),
+            export::CSV_HEADER
+        );
+    }
+}
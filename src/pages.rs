@@ -0,0 +1,120 @@
+//! `--interactive-pages`: the results one search page at a time, fetched
+//! as they are asked for, with a prompt between pages.
+
+use crate::{browser, interrupt};
+use ghs::client::SEARCH_RESULT_CAP;
+use ghs::provider::SearchProvider;
+use ghs::{BoxError, Repository};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Results per page unless `--per-page` says otherwise; about a screenful
+/// of the listing.
+pub const PER_PAGE: u8 = 10;
+
+/// What was typed at the prompt.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Next,
+    Previous,
+    Quit,
+    /// Open the result with this number.
+    Open(usize),
+}
+
+impl Command {
+    /// Enter alone moves on, as in a pager.
+    fn parse(input: &str) -> Result<Command, String> {
+        let input = input.trim();
+        match input {
+            "" | "n" | "next" => return Ok(Command::Next),
+            "p" | "prev" => return Ok(Command::Previous),
+            "q" | "quit" => return Ok(Command::Quit),
+            _ => {}
+        }
+        input
+            .strip_prefix('o')
+            .map(|number| number.trim().trim_start_matches('#'))
+            .and_then(|number| number.parse().ok())
+            .map(Command::Open)
+            .ok_or_else(|| format!("`{}` is not n, p, q or o followed by a number", input))
+    }
+}
+
+/// Prints the results of `query` page by page, `per_page` at a time, each
+/// page passed through `filter` and every result through `render`. Results
+/// are numbered across pages so that `o 12` opens the twelfth one. Pages
+/// already seen are not fetched again.
+pub async fn browse(
+    provider: &dyn SearchProvider,
+    query: &str,
+    per_page: u8,
+    filter: impl Fn(Vec<Repository>) -> Vec<Repository>,
+    render: impl Fn(&Repository) -> String,
+) -> Result<(), BoxError> {
+    if !io::stdin().is_terminal() {
+        return Err("--interactive-pages needs a terminal to read n, p, q or o from".into());
+    }
+    let mut pages: Vec<Vec<Repository>> = Vec::new();
+    let mut last_page = 1;
+    let mut current = 0;
+    loop {
+        if current == pages.len() {
+            let page =
+                interrupt::or_exit(provider.search_page(query, per_page, current as u32 + 1))
+                    .await?;
+            let total = page.repositories.total_count.min(SEARCH_RESULT_CAP);
+            last_page = total.div_ceil(u64::from(per_page)).max(1) as usize;
+            pages.push(filter(page.repositories.items));
+        }
+
+        let first = pages[..current].iter().map(Vec::len).sum::<usize>() + 1;
+        let mut stdout = io::stdout().lock();
+        for (number, repo) in (first..).zip(&pages[current]) {
+            write!(stdout, "#{} {}", number, render(repo))?;
+        }
+        if pages[current].is_empty() {
+            writeln!(stdout, "No results on this page")?;
+        }
+        stdout.flush()?;
+        drop(stdout);
+
+        loop {
+            eprint!(
+                "Page {} of {}: [n]ext / [p]rev / [q]uit / [o]pen # ",
+                current + 1,
+                last_page
+            );
+            io::stderr().flush()?;
+            let mut input = String::new();
+            if io::stdin().lock().read_line(&mut input)? == 0 {
+                eprintln!();
+                return Ok(());
+            }
+            match Command::parse(&input) {
+                Ok(Command::Next) if current + 1 < last_page => {
+                    current += 1;
+                    break;
+                }
+                Ok(Command::Next) => eprintln!("This is the last page"),
+                Ok(Command::Previous) if current > 0 => {
+                    current -= 1;
+                    break;
+                }
+                Ok(Command::Previous) => eprintln!("This is the first page"),
+                Ok(Command::Quit) => return Ok(()),
+                Ok(Command::Open(number)) => {
+                    let repo = pages.iter().flatten().nth(number.wrapping_sub(1));
+                    match repo.and_then(|repo| repo.html_url.as_deref()) {
+                        Some(url) => {
+                            if let Err(err) = browser::open(url) {
+                                eprintln!("warning: {}", err);
+                            }
+                        }
+                        None => eprintln!("There is no result #{} to open", number),
+                    }
+                }
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! A small Parquet writer: flat, nullable columns in a single row group,
+//! PLAIN-encoded and uncompressed. That is as much of the format as tables
+//! of repository data need, and DuckDB, pandas and Spark all read it.
+
+use crate::BoxError;
+use std::io::Write;
+
+const MAGIC: &[u8] = b"PAR1";
+
+// Thrift compact protocol field types.
+const I32: u8 = 5;
+const I64: u8 = 6;
+const BINARY: u8 = 8;
+const LIST: u8 = 9;
+const STRUCT: u8 = 12;
+
+// Parquet enum values.
+const TYPE_INT64: i32 = 2;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const OPTIONAL: i32 = 1;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+/// One named column of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub values: Values,
+}
+
+/// A column's values, `None` for nulls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Values {
+    Int64(Vec<Option<i64>>),
+    /// Seconds since the Unix epoch, stored as a UTC timestamp.
+    Timestamp(Vec<Option<i64>>),
+    Text(Vec<Option<String>>),
+}
+
+impl Values {
+    fn len(&self) -> usize {
+        match self {
+            Values::Int64(values) | Values::Timestamp(values) => values.len(),
+            Values::Text(values) => values.len(),
+        }
+    }
+
+    fn physical_type(&self) -> i32 {
+        match self {
+            Values::Int64(_) | Values::Timestamp(_) => TYPE_INT64,
+            Values::Text(_) => TYPE_BYTE_ARRAY,
+        }
+    }
+
+    fn converted_type(&self) -> Option<i32> {
+        match self {
+            Values::Int64(_) => None,
+            Values::Timestamp(_) => Some(CONVERTED_TIMESTAMP_MILLIS),
+            Values::Text(_) => Some(CONVERTED_UTF8),
+        }
+    }
+
+    fn defined(&self) -> Vec<bool> {
+        match self {
+            Values::Int64(values) | Values::Timestamp(values) => {
+                values.iter().map(Option::is_some).collect()
+            }
+            Values::Text(values) => values.iter().map(Option::is_some).collect(),
+        }
+    }
+
+    /// The non-null values, PLAIN-encoded.
+    fn plain(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Values::Int64(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            Values::Timestamp(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&(value * 1000).to_le_bytes());
+                }
+            }
+            Values::Text(values) => {
+                for value in values.iter().flatten() {
+                    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                    out.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Writes `columns`, which must all have the same length, as a Parquet file.
+/// `created_by` names the writer in the file's metadata.
+pub fn write(mut writer: impl Write, columns: &[Column], created_by: &str) -> Result<(), BoxError> {
+    let rows = columns.first().map_or(0, |column| column.values.len());
+    if let Some(column) = columns.iter().find(|column| column.values.len() != rows) {
+        return Err(format!(
+            "column {} has {} values, not {}",
+            column.name,
+            column.values.len(),
+            rows
+        )
+        .into());
+    }
+
+    let mut file = MAGIC.to_vec();
+    // Where each column chunk starts, and how long it is.
+    let mut chunks = Vec::new();
+    for column in columns {
+        let page = data_page(&column.values);
+        chunks.push((file.len() as i64, page.len() as i64));
+        file.extend_from_slice(&page);
+    }
+
+    let mut meta = Thrift::default();
+    meta.i32(1, 1);
+    meta.list(2, STRUCT, columns.len() + 1);
+    meta.begin_element();
+    meta.binary(4, b"schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end_struct();
+    for column in columns {
+        meta.begin_element();
+        meta.i32(1, column.values.physical_type());
+        meta.i32(3, OPTIONAL);
+        meta.binary(4, column.name.as_bytes());
+        if let Some(converted_type) = column.values.converted_type() {
+            meta.i32(6, converted_type);
+        }
+        meta.end_struct();
+    }
+    meta.i64(3, rows as i64);
+    meta.list(4, STRUCT, 1);
+    meta.begin_element();
+    meta.list(1, STRUCT, columns.len());
+    for (column, (offset, size)) in columns.iter().zip(&chunks) {
+        meta.begin_element();
+        meta.i64(2, *offset);
+        meta.begin_struct(3);
+        meta.i32(1, column.values.physical_type());
+        meta.list(2, I32, 2);
+        meta.element_i32(ENCODING_PLAIN);
+        meta.element_i32(ENCODING_RLE);
+        meta.list(3, BINARY, 1);
+        meta.element_binary(column.name.as_bytes());
+        meta.i32(4, CODEC_UNCOMPRESSED);
+        meta.i64(5, rows as i64);
+        meta.i64(6, *size);
+        meta.i64(7, *size);
+        meta.i64(9, *offset);
+        meta.end_struct();
+        meta.end_struct();
+    }
+    meta.i64(2, chunks.iter().map(|(_, size)| size).sum());
+    meta.i64(3, rows as i64);
+    meta.end_struct();
+    meta.binary(6, created_by.as_bytes());
+    meta.stop();
+
+    file.extend_from_slice(&meta.out);
+    file.extend_from_slice(&(meta.out.len() as u32).to_le_bytes());
+    file.extend_from_slice(MAGIC);
+    writer.write_all(&file)?;
+    Ok(writer.flush()?)
+}
+
+/// A version 1 data page holding every value of a column: its header, the
+/// definition levels that mark nulls, and the non-null values.
+fn data_page(values: &Values) -> Vec<u8> {
+    let levels = definition_levels(&values.defined());
+    let mut body = (levels.len() as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&levels);
+    body.extend_from_slice(&values.plain());
+
+    let mut header = Thrift::default();
+    header.i32(1, PAGE_DATA);
+    header.i32(2, body.len() as i32);
+    header.i32(3, body.len() as i32);
+    header.begin_struct(5);
+    header.i32(1, values.len() as i32);
+    header.i32(2, ENCODING_PLAIN);
+    header.i32(3, ENCODING_RLE);
+    header.i32(4, ENCODING_RLE);
+    header.end_struct();
+    header.stop();
+
+    let mut page = header.out;
+    page.extend_from_slice(&body);
+    page
+}
+
+/// Definition levels (1 for a value, 0 for a null) in the RLE/bit-packing
+/// hybrid encoding, as plain runs of one-byte values.
+fn definition_levels(defined: &[bool]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut rest = defined;
+    while let Some(&first) = rest.first() {
+        let run = rest.iter().take_while(|value| **value == first).count();
+        varint(&mut out, (run as u64) << 1);
+        out.push(first as u8);
+        rest = &rest[run..];
+    }
+    out
+}
+
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// The Thrift compact protocol, as far as Parquet's metadata needs it.
+#[derive(Default)]
+struct Thrift {
+    out: Vec<u8>,
+    /// The last field id written in the current struct; field ids are
+    /// written as deltas from it.
+    last: i16,
+    /// `last` of every enclosing struct.
+    outer: Vec<i16>,
+}
+
+impl Thrift {
+    fn field(&mut self, id: i16, kind: u8) {
+        match id - self.last {
+            delta @ 1..=15 => self.out.push((delta as u8) << 4 | kind),
+            _ => {
+                self.out.push(kind);
+                varint(&mut self.out, zigzag(id.into()));
+            }
+        }
+        self.last = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, I32);
+        self.element_i32(value);
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, I64);
+        varint(&mut self.out, zigzag(value));
+    }
+
+    fn binary(&mut self, id: i16, bytes: &[u8]) {
+        self.field(id, BINARY);
+        self.element_binary(bytes);
+    }
+
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, LIST);
+        if len < 15 {
+            self.out.push((len as u8) << 4 | kind);
+        } else {
+            self.out.push(0xf0 | kind);
+            varint(&mut self.out, len as u64);
+        }
+    }
+
+    fn element_i32(&mut self, value: i32) {
+        varint(&mut self.out, zigzag(value.into()));
+    }
+
+    fn element_binary(&mut self, bytes: &[u8]) {
+        varint(&mut self.out, bytes.len() as u64);
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field(id, STRUCT);
+        self.begin_element();
+    }
+
+    /// Starts a struct that is an element of a list, and so has no header.
+    fn begin_element(&mut self) {
+        self.outer.push(self.last);
+        self.last = 0;
+    }
+
+    fn end_struct(&mut self) {
+        self.stop();
+        self.last = self.outer.pop().unwrap_or(0);
+    }
+
+    fn stop(&mut self) {
+        self.out.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_thrift_fields_and_levels() {
+        let mut thrift = Thrift::default();
+        thrift.i32(1, -1);
+        thrift.i64(3, 300);
+        thrift.binary(20, b"ab");
+        thrift.stop();
+        assert_eq!(
+            thrift.out,
+            [0x15, 0x01, 0x26, 0xd8, 0x04, 0x08, 0x28, 0x02, b'a', b'b', 0x00]
+        );
+        assert_eq!(
+            definition_levels(&[true, true, true, false, true]),
+            [6, 1, 2, 0, 2, 1]
+        );
+    }
+
+    #[test]
+    fn writes_nulls_and_metadata() {
+        let columns = [
+            Column {
+                name: "stars".to_string(),
+                values: Values::Int64(vec![Some(3), None]),
+            },
+            Column {
+                name: "name".to_string(),
+                values: Values::Text(vec![None, Some("ghs".to_string())]),
+            },
+        ];
+        let mut file = Vec::new();
+        write(&mut file, &columns, "test").unwrap();
+        assert!(file.starts_with(MAGIC) && file.ends_with(MAGIC));
+        let footer = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+        let meta = &file[file.len() - 8 - footer as usize..file.len() - 8];
+        assert!(meta.windows(5).any(|window| window == b"stars"));
+        assert!(meta.ends_with(b"test\x00"));
+        // The text column's page ends with its one value.
+        let data = &file[..file.len() - 8 - footer as usize];
+        assert!(data.ends_with(b"\x03\x00\x00\x00ghs"));
+
+        let uneven = [
+            columns[0].clone(),
+            Column {
+                name: "forks".to_string(),
+                values: Values::Int64(vec![Some(1)]),
+            },
+        ];
+        assert!(write(Vec::new(), &uneven, "test").is_err());
+    }
+
+    #[test]
+    fn the_parquet_crate_reads_what_it_writes() {
+        use ::parquet::file::reader::{FileReader, SerializedFileReader};
+        use ::parquet::record::Field as Cell;
+
+        let columns = [
+            Column {
+                name: "stars".to_string(),
+                values: Values::Int64(vec![Some(3), None, Some(-7)]),
+            },
+            Column {
+                name: "taken_at".to_string(),
+                values: Values::Timestamp(vec![Some(1_760_400_000), Some(0), None]),
+            },
+            Column {
+                name: "name".to_string(),
+                values: Values::Text(vec![
+                    Some("ghs".to_string()),
+                    None,
+                    Some("日本".to_string()),
+                ]),
+            },
+        ];
+        let mut file = Vec::new();
+        write(&mut file, &columns, "ghs test").unwrap();
+
+        let reader = SerializedFileReader::new(::bytes::Bytes::from(file)).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 3);
+        assert_eq!(metadata.created_by(), Some("ghs test"));
+        let rows: Vec<Vec<(String, Cell)>> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().into_columns())
+            .collect();
+        let cells = |index: usize| -> Vec<&Cell> { rows.iter().map(|row| &row[index].1).collect() };
+        assert_eq!(
+            rows[0]
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            ["stars", "taken_at", "name"]
+        );
+        assert_eq!(cells(0), [&Cell::Long(3), &Cell::Null, &Cell::Long(-7)]);
+        assert_eq!(
+            cells(1),
+            [
+                &Cell::TimestampMillis(1_760_400_000_000),
+                &Cell::TimestampMillis(0),
+                &Cell::Null
+            ]
+        );
+        assert_eq!(
+            cells(2),
+            [
+                &Cell::Str("ghs".to_string()),
+                &Cell::Null,
+                &Cell::Str("日本".to_string())
+            ]
+        );
+    }
+}
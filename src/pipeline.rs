@@ -0,0 +1,526 @@
+use crate::circuit::CircuitBreaker;
+use crate::config::{Config, Table, Value};
+use crate::dupes;
+use crate::output::Format;
+use crate::predicate::Predicate;
+use crate::smart;
+use crate::sort::{self, SortKey};
+use ghs::filters::{MatchMode, RepositoryFilter};
+use ghs::{BoxError, GhsClient, Repository};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+const CONCURRENCY: usize = 8;
+
+/// One step of a [`Pipeline`], configured by its `[pipeline.NAME.STAGE]`
+/// section.
+#[derive(Debug)]
+enum Stage {
+    Filter {
+        filter: RepositoryFilter,
+        predicates: Vec<Predicate>,
+    },
+    /// Drops the less-starred repository of every pair whose names or
+    /// descriptions are at least `threshold` alike.
+    Dedupe {
+        threshold: f64,
+    },
+    /// Fetches each repository's language breakdown.
+    Enrich,
+    Sort {
+        key: SortKey,
+        descending: bool,
+    },
+    Limit {
+        count: usize,
+    },
+    /// Where the results go; always the last stage.
+    Format {
+        format: Option<Format>,
+        output: Option<String>,
+    },
+}
+
+/// Post-processing named in the config file, as an alternative to a long
+/// list of flags:
+///
+/// ```toml
+/// [pipeline.weekly]
+/// stages = ["filter", "dedupe", "enrich", "sort", "limit", "format"]
+///
+/// [pipeline.weekly.filter]
+/// where = ["stars>=10"]
+/// exclude_archived = true
+///
+/// [pipeline.weekly.sort]
+/// by = "updated"
+///
+/// [pipeline.weekly.format]
+/// format = "csv"
+/// output = "weekly.csv"
+/// ```
+///
+/// Stages run in the order `stages` lists them; one without a section uses
+/// its defaults.
+#[derive(Debug)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Reads `[pipeline.NAME]` and the sections of its stages.
+    pub fn from_config(config: &Config, name: &str) -> Result<Pipeline, String> {
+        let section = format!("pipeline.{}", name);
+        let Some(table) = config.section(&section) else {
+            let known: Vec<&str> = config
+                .subsections("pipeline")
+                .into_iter()
+                .filter(|name| !name.contains('.'))
+                .collect();
+            return Err(if known.is_empty() {
+                format!(
+                    "unknown pipeline `{}`; none are defined in the config file",
+                    name
+                )
+            } else {
+                format!(
+                    "unknown pipeline `{}` (defined: {})",
+                    name,
+                    known.join(", ")
+                )
+            });
+        };
+        let options = Options {
+            section: &section,
+            table,
+        };
+        options.only(&["stages"])?;
+        let names = match table.get("stages") {
+            Some(Value::Array(names)) => names,
+            Some(value) => {
+                return Err(format!(
+                    "{}: `stages` must be an array of stage names, not `{}`",
+                    section, value
+                ))
+            }
+            None => return Err(format!("{}: `stages` is missing", section)),
+        };
+
+        let empty = Table::new();
+        let mut stages = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            let Value::String(name) = name else {
+                return Err(format!("{}: `{}` is not a stage name", section, name));
+            };
+            let stage_section = format!("{}.{}", section, name);
+            let options = Options {
+                section: &stage_section,
+                table: config.section(&stage_section).unwrap_or(&empty),
+            };
+            let stage = options.stage(name)?;
+            if matches!(stage, Stage::Format { .. }) && index + 1 < names.len() {
+                return Err(format!("{}: the format stage must come last", section));
+            }
+            stages.push(stage);
+        }
+        Ok(Pipeline { stages })
+    }
+
+    /// The `--format` and `--output` the format stage sets, if there is one.
+    pub fn output(&self) -> Option<(Option<Format>, Option<&str>)> {
+        self.stages.iter().find_map(|stage| match stage {
+            Stage::Format { format, output } => Some((*format, output.as_deref())),
+            _ => None,
+        })
+    }
+
+    /// Runs every stage but the format stage over `repositories`; a sort
+    /// stage by `smart` scores with `smart`.
+    pub async fn run(
+        &self,
+        api: &GhsClient,
+        mut repositories: Vec<Repository>,
+        smart: &smart::Settings,
+    ) -> Result<Vec<Repository>, BoxError> {
+        for stage in &self.stages {
+            match stage {
+                Stage::Filter { filter, predicates } => repositories.retain(|repo| {
+                    filter.matches(repo)
+                        && predicates.iter().all(|predicate| predicate.matches(repo))
+                }),
+                Stage::Dedupe { threshold } => repositories = dedupe(repositories, *threshold),
+                Stage::Enrich => enrich_languages(api, &mut repositories).await?,
+                Stage::Sort { key, descending } => {
+                    sort::sort_repositories(&mut repositories, *key, *descending, smart)
+                }
+                Stage::Limit { count } => repositories.truncate(*count),
+                Stage::Format { .. } => {}
+            }
+        }
+        Ok(repositories)
+    }
+}
+
+fn dedupe(repositories: Vec<Repository>, threshold: f64) -> Vec<Repository> {
+    let mut dropped = HashSet::new();
+    for pair in dupes::find_duplicates(&repositories, threshold) {
+        if dropped.contains(&pair.first.full_name) || dropped.contains(&pair.second.full_name) {
+            continue;
+        }
+        let weaker = if pair.second.stargazers_count > pair.first.stargazers_count {
+            pair.first
+        } else {
+            pair.second
+        };
+        dropped.insert(weaker.full_name.clone());
+    }
+    repositories
+        .into_iter()
+        .filter(|repo| !dropped.contains(&repo.full_name))
+        .collect()
+}
+
+/// Fills in [`Repository::languages`], and `language` where the search left
+/// it empty. Repositories whose breakdown cannot be fetched are left alone.
+async fn enrich_languages(
+    api: &GhsClient,
+    repositories: &mut [Repository],
+) -> Result<(), BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for (index, repo) in repositories.iter().enumerate() {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let languages = breaker
+                .call(
+                    &format!("languages of {}", full_name),
+                    api.languages(&full_name),
+                )
+                .await?;
+            Ok::<_, BoxError>((index, languages))
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        let (index, Some(languages)) = result?? else {
+            continue;
+        };
+        let repo = &mut repositories[index];
+        if repo.language.is_none() {
+            repo.language = languages
+                .iter()
+                .max_by_key(|(_, bytes)| **bytes)
+                .map(|(language, _)| language.clone());
+        }
+        repo.languages = Some(languages);
+    }
+    Ok(())
+}
+
+/// A stage's section, read with errors that name it.
+struct Options<'a> {
+    section: &'a str,
+    table: &'a Table,
+}
+
+impl Options<'_> {
+    fn stage(&self, name: &str) -> Result<Stage, String> {
+        match name {
+            "filter" => {
+                self.only(&[
+                    "title",
+                    "description",
+                    "language",
+                    "match_mode",
+                    "where",
+                    "exclude_archived",
+                ])?;
+                let predicates = self
+                    .strings("where")?
+                    .iter()
+                    .map(|predicate| Predicate::parse(predicate))
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| format!("{}: {}", self.section, err))?;
+                let match_mode = match self.string("match_mode")? {
+                    Some(mode) => {
+                        MatchMode::parse(&mode).map_err(|err| format!("{}: {}", self.section, err))?
+                    }
+                    None => MatchMode::default(),
+                };
+                Ok(Stage::Filter {
+                    filter: RepositoryFilter {
+                        title: self.string("title")?,
+                        description: self.string("description")?,
+                        language: self.string("language")?,
+                        match_mode,
+                        exclude_archived: self.boolean("exclude_archived")?.unwrap_or(false),
+                    },
+                    predicates,
+                })
+            }
+            "dedupe" => {
+                self.only(&["threshold"])?;
+                Ok(Stage::Dedupe {
+                    threshold: self.number("threshold")?.unwrap_or(0.8),
+                })
+            }
+            "enrich" => {
+                self.only(&["languages"])?;
+                // Languages are the only enrichment so far, so they cannot be
+                // turned off without leaving the stage empty.
+                if self.boolean("languages")? == Some(false) {
+                    return Err(format!(
+                        "{}: `languages` is the only enrichment; leave the stage out instead",
+                        self.section
+                    ));
+                }
+                Ok(Stage::Enrich)
+            }
+            "sort" => {
+                self.only(&["by", "order"])?;
+                let key = match self.string("by")? {
+                    Some(key) => {
+                        SortKey::parse(&key).map_err(|err| format!("{}: {}", self.section, err))?
+                    }
+                    None => SortKey::Stars,
+                };
+                let descending = match self.string("order")?.as_deref() {
+                    Some("desc") => true,
+                    Some("asc") => false,
+                    Some(order) => {
+                        return Err(format!(
+                            "{}: `order` must be asc or desc, not `{}`",
+                            self.section, order
+                        ))
+                    }
+                    None => key.descending_by_default(),
+                };
+                Ok(Stage::Sort { key, descending })
+            }
+            "limit" => {
+                self.only(&["count"])?;
+                match self.table.get("count") {
+                    Some(Value::Integer(count)) if *count >= 0 => Ok(Stage::Limit {
+                        count: *count as usize,
+                    }),
+                    Some(value) => Err(format!(
+                        "{}: `count` must be a whole number, not `{}`",
+                        self.section, value
+                    )),
+                    None => Err(format!("{}: `count` is missing", self.section)),
+                }
+            }
+            "format" => {
+                self.only(&["format", "output"])?;
+                let format = match self.string("format")? {
+                    Some(format) => Some(
+                        Format::parse(&format).map_err(|err| format!("{}: {}", self.section, err))?,
+                    ),
+                    None => None,
+                };
+                Ok(Stage::Format {
+                    format,
+                    output: self.string("output")?,
+                })
+            }
+            _ => Err(format!(
+                "`{}` is not a pipeline stage (expected filter, dedupe, enrich, sort, limit or format)",
+                name
+            )),
+        }
+    }
+
+    /// Rejects keys other than `known`, which are most likely typos.
+    fn only(&self, known: &[&str]) -> Result<(), String> {
+        match self.table.keys().find(|key| !known.contains(&key.as_str())) {
+            Some(key) => Err(format!(
+                "{}: unknown option `{}` (expected {})",
+                self.section,
+                key,
+                known.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(value) => Err(format!(
+                "{}: `{}` must be a string, not `{}`",
+                self.section, key, value
+            )),
+        }
+    }
+
+    /// A string or an array of strings.
+    fn strings(&self, key: &str) -> Result<Vec<String>, String> {
+        match self.table.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::String(value)) => Ok(vec![value.clone()]),
+            Some(Value::Array(values)) => values
+                .iter()
+                .map(|value| match value {
+                    Value::String(value) => Ok(value.clone()),
+                    value => Err(format!(
+                        "{}: `{}` must hold strings, not `{}`",
+                        self.section, key, value
+                    )),
+                })
+                .collect(),
+            Some(value) => Err(format!(
+                "{}: `{}` must be a string or an array of strings, not `{}`",
+                self.section, key, value
+            )),
+        }
+    }
+
+    fn boolean(&self, key: &str) -> Result<Option<bool>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::Boolean(value)) => Ok(Some(*value)),
+            Some(value) => Err(format!(
+                "{}: `{}` must be true or false, not `{}`",
+                self.section, key, value
+            )),
+        }
+    }
+
+    fn number(&self, key: &str) -> Result<Option<f64>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::Float(value)) => Ok(Some(*value)),
+            Some(Value::Integer(value)) => Ok(Some(*value as f64)),
+            Some(value) => Err(format!(
+                "{}: `{}` must be a number, not `{}`",
+                self.section, key, value
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(config: &str) -> Result<Pipeline, String> {
+        Pipeline::from_config(&Config::parse(config).unwrap(), "weekly")
+    }
+
+    fn repo(name: &str, stars: u64, archived: bool) -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "full_name": format!("octo/{}", name),
+            "stargazers_count": stars,
+            "archived": archived,
+        }))
+        .unwrap()
+    }
+
+    fn run(pipeline: &Pipeline, repositories: Vec<Repository>) -> Vec<String> {
+        let api = GhsClient::builder().build().unwrap();
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(pipeline.run(&api, repositories, &smart::Settings::default()))
+            .unwrap()
+            .into_iter()
+            .map(|repo| repo.name)
+            .collect()
+    }
+
+    fn repositories() -> Vec<Repository> {
+        vec![
+            repo("small", 1, false),
+            repo("huge", 900, true),
+            repo("big", 50, false),
+            repo("medium", 10, false),
+        ]
+    }
+
+    #[test]
+    fn reads_stages_and_their_sections() {
+        let pipeline = pipeline(
+            "[pipeline.weekly]\n\
+             stages = [\"filter\", \"sort\", \"limit\", \"format\"]\n\
+             [pipeline.weekly.filter]\n\
+             where = \"stars>=5\"\n\
+             exclude_archived = true\n\
+             [pipeline.weekly.limit]\n\
+             count = 1\n\
+             [pipeline.weekly.format]\n\
+             format = \"csv\"\n\
+             output = \"weekly.csv\"\n",
+        )
+        .unwrap();
+        assert_eq!(pipeline.stages.len(), 4);
+        assert!(matches!(
+            pipeline.stages[1],
+            Stage::Sort {
+                key: SortKey::Stars,
+                descending: true
+            }
+        ));
+        assert_eq!(
+            pipeline.output(),
+            Some((Some(Format::Csv), Some("weekly.csv")))
+        );
+        assert_eq!(run(&pipeline, repositories()), ["big"]);
+    }
+
+    #[test]
+    fn stages_run_in_the_order_listed() {
+        let sort_then_limit = pipeline(
+            "[pipeline.weekly]\nstages = [\"sort\", \"limit\"]\n\
+             [pipeline.weekly.limit]\ncount = 2\n",
+        )
+        .unwrap();
+        assert_eq!(run(&sort_then_limit, repositories()), ["huge", "big"]);
+
+        let limit_then_sort = pipeline(
+            "[pipeline.weekly]\nstages = [\"limit\", \"sort\"]\n\
+             [pipeline.weekly.limit]\ncount = 2\n",
+        )
+        .unwrap();
+        assert_eq!(run(&limit_then_sort, repositories()), ["huge", "small"]);
+        assert_eq!(limit_then_sort.output(), None);
+    }
+
+    #[test]
+    fn rejects_malformed_pipelines() {
+        let error = |config: &str| pipeline(config).unwrap_err();
+
+        assert!(error("").contains("none are defined"));
+        assert!(error("[pipeline.daily]\nstages = []\n").contains("(defined: daily)"));
+        assert!(error("[pipeline.weekly]\n").contains("`stages` is missing"));
+        assert!(error("[pipeline.weekly]\nstages = \"sort\"\n").contains("must be an array"));
+        assert!(error("[pipeline.weekly]\nstages = [\"shuffle\"]\n")
+            .contains("`shuffle` is not a pipeline stage"));
+        assert!(
+            error("[pipeline.weekly]\nstages = [\"format\", \"sort\"]\n")
+                .contains("the format stage must come last")
+        );
+        assert!(error(
+            "[pipeline.weekly]\nstages = [\"sort\"]\n[pipeline.weekly.sort]\nkey = \"stars\"\n"
+        )
+        .contains("pipeline.weekly.sort: unknown option `key`"));
+        assert!(error(
+            "[pipeline.weekly]\nstages = [\"sort\"]\n[pipeline.weekly.sort]\norder = \"up\"\n"
+        )
+        .contains("`order` must be asc or desc"));
+        assert!(error("[pipeline.weekly]\nstages = [\"limit\"]\n").contains("`count` is missing"));
+        assert!(error(
+            "[pipeline.weekly]\nstages = [\"limit\"]\n[pipeline.weekly.limit]\ncount = -1\n"
+        )
+        .contains("must be a whole number"));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::config::{Config, Value};
+use ghs::BoxError;
+use std::sync::OnceLock;
+
+/// What `--null-placeholder` or the config's `[null_placeholder]` section
+/// set, for the rest of the run.
+static PLACEHOLDERS: OnceLock<Placeholders> = OnceLock::new();
+
+/// A field that repositories may leave empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Description,
+    Language,
+}
+
+impl Field {
+    fn parse(field: &str) -> Result<Field, String> {
+        match field {
+            "description" => Ok(Field::Description),
+            "language" => Ok(Field::Language),
+            _ => Err(format!(
+                "`{}` has no placeholder (expected description or language)",
+                field
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Placeholders {
+    description: Option<String>,
+    language: Option<String>,
+}
+
+impl Placeholders {
+    /// The `[null_placeholder]` section of the config file, e.g.
+    /// `description = "-"`.
+    pub fn from_config(config: &Config) -> Result<Placeholders, BoxError> {
+        let mut placeholders = Placeholders::default();
+        for (key, value) in config.section("null_placeholder").into_iter().flatten() {
+            let field = Field::parse(key).map_err(|err| format!("null_placeholder: {}", err))?;
+            let Value::String(text) = value else {
+                return Err(format!(
+                    "null_placeholder: `{}` must be a string, not `{}`",
+                    key, value
+                )
+                .into());
+            };
+            placeholders.set(field, text.clone());
+        }
+        Ok(placeholders)
+    }
+
+    pub fn set(&mut self, field: Field, text: String) {
+        match field {
+            Field::Description => self.description = Some(text),
+            Field::Language => self.language = Some(text),
+        }
+    }
+
+    /// Uses these placeholders for the rest of the run.
+    pub fn install(self) {
+        // Only `run` installs them, once.
+        let _ = PLACEHOLDERS.set(self);
+    }
+}
+
+/// Parses a `--null-placeholder` value, `FIELD=TEXT`; the text may be
+/// empty.
+pub fn parse(arg: &str) -> Result<(Field, String), String> {
+    let (field, text) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("`{}` is not FIELD=TEXT, e.g. description=-", arg))?;
+    Ok((Field::parse(field)?, text.to_string()))
+}
+
+/// `value`, or what to print in its place when it is missing: the
+/// configured placeholder for `field`, else `default`.
+pub fn or<'a>(value: Option<&'a str>, field: Field, default: &'a str) -> &'a str {
+    if let Some(value) = value {
+        return value;
+    }
+    let placeholders = PLACEHOLDERS.get();
+    let text = placeholders.and_then(|placeholders| match field {
+        Field::Description => placeholders.description.as_deref(),
+        Field::Language => placeholders.language.as_deref(),
+    });
+    text.unwrap_or(default)
+}
@@ -0,0 +1,14 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Switches to plain output for the rest of the run: no colors, no
+/// block or symbol graphics, no progress lines, and one line per record,
+/// so that screen readers and dumb terminals get readable text.
+pub fn enable() {
+    PLAIN.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
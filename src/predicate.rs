@@ -0,0 +1,307 @@
+use ghs::Repository;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Comparison operators accepted in filter expressions.
+const OPERATORS: &[(&str, Operator)] = &[
+    (">=", Operator::GreaterOrEqual),
+    ("<=", Operator::LessOrEqual),
+    ("!=", Operator::NotEqual),
+    (">", Operator::Greater),
+    ("<", Operator::Less),
+    ("=", Operator::Equal),
+    (":", Operator::Contains),
+];
+
+const TEXT_FIELDS: &[&str] = &["name", "full_name", "description", "language"];
+
+/// Short names for numeric fields. Any other numeric field of the JSON
+/// output works too, with or without its `_count` suffix (`open_issues`).
+const NUMBER_ALIASES: &[(&str, &str)] = &[
+    ("stars", "stargazers_count"),
+    ("issues", "open_issues_count"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Contains,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// A single `field<op>value` condition such as `language=rust` or `stars>=10`.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    field: String,
+    operator: Operator,
+    value: String,
+}
+
+impl Predicate {
+    pub fn parse(input: &str) -> Result<Predicate, String> {
+        let (position, token, operator) = OPERATORS
+            .iter()
+            .filter_map(|(token, operator)| {
+                input
+                    .find(token)
+                    .map(|position| (position, *token, *operator))
+            })
+            // The leftmost operator wins; on ties the longer token (`>=` over `>`) comes first.
+            .min_by_key(|(position, _, _)| *position)
+            .ok_or_else(|| format!("`{}` is not a `field<op>value` expression", input))?;
+
+        let name = input[..position].trim().to_lowercase();
+        let value = input[position + token.len()..].trim().to_string();
+
+        let field = if TEXT_FIELDS.contains(&name.as_str()) {
+            name
+        } else {
+            let Some(field) = number_field_name(&name) else {
+                let mut numbers: Vec<&str> =
+                    NUMBER_ALIASES.iter().map(|(alias, _)| *alias).collect();
+                numbers.extend(number_fields());
+                return Err(format!(
+                    "unknown field `{}` (expected one of: {}, {})",
+                    name,
+                    TEXT_FIELDS.join(", "),
+                    numbers.join(", ")
+                ));
+            };
+            if value.parse::<f64>().is_err() {
+                return Err(format!("`{}` expects a number, got `{}`", name, value));
+            }
+            field
+        };
+
+        Ok(Predicate {
+            field,
+            operator,
+            value,
+        })
+    }
+
+    pub fn matches(&self, repo: &Repository) -> bool {
+        if let Some(actual) = number_field(repo, &self.field) {
+            let expected: f64 = self.value.parse().unwrap_or_default();
+            return match self.operator {
+                Operator::Contains | Operator::Equal => actual == expected,
+                Operator::NotEqual => actual != expected,
+                Operator::Greater => actual > expected,
+                Operator::GreaterOrEqual => actual >= expected,
+                Operator::Less => actual < expected,
+                Operator::LessOrEqual => actual <= expected,
+            };
+        }
+
+        let actual = text_field(repo, &self.field).map(str::to_lowercase);
+        let expected = self.value.to_lowercase();
+        match (self.operator, actual) {
+            (Operator::NotEqual, None) => true,
+            (_, None) => false,
+            (Operator::Contains, Some(actual)) => actual.contains(&expected),
+            (Operator::Equal, Some(actual)) => actual == expected,
+            (Operator::NotEqual, Some(actual)) => actual != expected,
+            (Operator::Greater, Some(actual)) => actual > expected,
+            (Operator::GreaterOrEqual, Some(actual)) => actual >= expected,
+            (Operator::Less, Some(actual)) => actual < expected,
+            (Operator::LessOrEqual, Some(actual)) => actual <= expected,
+        }
+    }
+}
+
+/// Parses a line of whitespace-separated predicates; values containing spaces
+/// can be double-quoted (`description:"command line"`).
+pub fn parse_line(line: &str) -> Result<Vec<Predicate>, String> {
+    let mut expressions = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    expressions.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    if !current.is_empty() {
+        expressions.push(current);
+    }
+
+    expressions
+        .iter()
+        .map(|expr| Predicate::parse(expr))
+        .collect()
+}
+
+fn text_field<'a>(repo: &'a Repository, field: &str) -> Option<&'a str> {
+    match field {
+        "name" => Some(&repo.name),
+        "full_name" => Some(&repo.full_name),
+        "description" => repo.description.as_deref(),
+        "language" => repo.language.as_deref(),
+        _ => None,
+    }
+}
+
+fn number_field(repo: &Repository, field: &str) -> Option<f64> {
+    let value = match field {
+        "stargazers_count" => repo.stargazers_count,
+        "forks_count" => repo.forks_count,
+        "open_issues_count" => repo.open_issues_count,
+        "watchers_count" => repo.watchers_count,
+        _ if number_fields().contains(&field) => {
+            return serde_json::to_value(repo).ok()?.get(field)?.as_f64();
+        }
+        _ => return None,
+    };
+    Some(value as f64)
+}
+
+/// Resolves an alias or a field name, with or without `_count`, to the name
+/// of a numeric field.
+fn number_field_name(name: &str) -> Option<String> {
+    if let Some((_, field)) = NUMBER_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Some(field.to_string());
+    }
+    let fields = number_fields();
+    [name.to_string(), format!("{}_count", name)]
+        .into_iter()
+        .find(|candidate| fields.contains(&candidate.as_str()))
+}
+
+/// The numeric fields of [`Repository`], read off an empty one so that new
+/// fields become filterable without being listed here.
+fn number_fields() -> &'static [&'static str] {
+    static FIELDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    FIELDS.get_or_init(|| {
+        let empty: Repository =
+            serde_json::from_str(r#"{"name": ""}"#).expect("Repository has defaults");
+        let Ok(Value::Object(fields)) = serde_json::to_value(empty) else {
+            return Vec::new();
+        };
+        fields
+            .into_iter()
+            .filter(|(_, value)| value.is_number())
+            .map(|(field, _)| &*field.leak())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": "ghs",
+            "full_name": "octo/ghs",
+            "description": "A command line tool",
+            "language": "Rust",
+            "stargazers_count": 10,
+            "forks_count": 2,
+        }))
+        .unwrap()
+    }
+
+    fn parsed(input: &str) -> (String, Operator, String) {
+        let predicate = Predicate::parse(input).unwrap();
+        (predicate.field, predicate.operator, predicate.value)
+    }
+
+    #[test]
+    fn the_leftmost_and_then_longest_operator_wins() {
+        assert_eq!(
+            parsed("stars>=10"),
+            (
+                "stargazers_count".into(),
+                Operator::GreaterOrEqual,
+                "10".into()
+            )
+        );
+        assert_eq!(
+            parsed("stars <= 10"),
+            (
+                "stargazers_count".into(),
+                Operator::LessOrEqual,
+                "10".into()
+            )
+        );
+        assert_eq!(
+            parsed("language!=go"),
+            ("language".into(), Operator::NotEqual, "go".into())
+        );
+        // Operators inside the value stay part of it.
+        assert_eq!(
+            parsed("description:a=b>c"),
+            ("description".into(), Operator::Contains, "a=b>c".into())
+        );
+        assert_eq!(
+            parsed("name=a:b"),
+            ("name".into(), Operator::Equal, "a:b".into())
+        );
+    }
+
+    #[test]
+    fn resolves_aliases_and_count_suffixes() {
+        assert_eq!(parsed("Stars>1").0, "stargazers_count");
+        assert_eq!(parsed("issues>1").0, "open_issues_count");
+        assert_eq!(parsed("forks>1").0, "forks_count");
+        assert_eq!(parsed("forks_count>1").0, "forks_count");
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_values_of_the_wrong_type() {
+        let error = |input: &str| Predicate::parse(input).unwrap_err();
+        assert!(error("stars").contains("not a `field<op>value` expression"));
+        assert!(error("colour=red").starts_with("unknown field `colour` (expected one of: name,"));
+        assert_eq!(error("stars>=many"), "`stars` expects a number, got `many`");
+        assert_eq!(error("forks="), "`forks` expects a number, got ``");
+    }
+
+    #[test]
+    fn compares_numbers_and_text() {
+        let matches = |input: &str| Predicate::parse(input).unwrap().matches(&repo());
+        assert!(matches("stars>=10"));
+        assert!(!matches("stars>10"));
+        assert!(matches("stars:10"));
+        assert!(matches("stars!=9.5"));
+        assert!(matches("forks<3"));
+        // Text compares without regard to case.
+        assert!(matches("language=rust"));
+        assert!(matches("description:LINE"));
+        assert!(matches("name>ghr"));
+        // A missing field only satisfies `!=`.
+        let mut bare = repo();
+        bare.description = None;
+        assert!(Predicate::parse("description!=x").unwrap().matches(&bare));
+        assert!(!Predicate::parse("description:x").unwrap().matches(&bare));
+    }
+
+    #[test]
+    fn double_quotes_keep_spaces_in_values() {
+        let predicates = parse_line(r#"description:"command line"  stars>5"#).unwrap();
+        assert_eq!(predicates.len(), 2);
+        assert_eq!(predicates[0].value, "command line");
+        assert!(predicates
+            .iter()
+            .all(|predicate| predicate.matches(&repo())));
+
+        assert!(parse_line("").unwrap().is_empty());
+        assert_eq!(
+            parse_line(r#"description:"command line"#).unwrap_err(),
+            "unterminated quote"
+        );
+        assert!(parse_line("description:command line").is_err());
+    }
+}
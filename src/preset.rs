@@ -0,0 +1,79 @@
+use crate::config::{Config, Value};
+use ghs::BoxError;
+use std::ffi::OsString;
+
+/// Expands every `--preset NAME` in `args` into the flags of the config's
+/// `[preset.NAME]` section, in place. Flags after the preset therefore
+/// override it, and repeatable flags such as `--where` add to it.
+///
+/// `key = "value"` becomes `--key value`, `key = true` becomes `--key`
+/// (`false` leaves it out) and an array repeats the flag.
+pub fn expand(config: &Config, args: Vec<OsString>) -> Result<Vec<OsString>, BoxError> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let name = match arg.to_str() {
+            Some("--") => {
+                expanded.push(arg);
+                expanded.extend(args.by_ref());
+                break;
+            }
+            Some("--preset") => match args.next() {
+                Some(name) => name,
+                // Leave the missing value for clap to report.
+                None => {
+                    expanded.push(arg);
+                    break;
+                }
+            },
+            Some(arg) if arg.starts_with("--preset=") => OsString::from(&arg["--preset=".len()..]),
+            _ => {
+                expanded.push(arg);
+                continue;
+            }
+        };
+
+        let name = name.to_string_lossy();
+        let Some(preset) = config.section(&format!("preset.{}", name)) else {
+            let known = config.subsections("preset");
+            return Err(if known.is_empty() {
+                format!(
+                    "unknown preset `{}`; none are defined in the config file",
+                    name
+                )
+            } else {
+                format!("unknown preset `{}` (defined: {})", name, known.join(", "))
+            }
+            .into());
+        };
+
+        // Keep `--preset NAME` itself so that clap still sees it.
+        expanded.push("--preset".into());
+        expanded.push(name.as_ref().into());
+        for (key, value) in preset {
+            push_flag(&mut expanded, key, value);
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Appends `key = value` to `args` as the flag it stands for.
+pub fn push_flag(args: &mut Vec<OsString>, key: &str, value: &Value) {
+    let flag = OsString::from(format!("--{}", key));
+    match value {
+        Value::Boolean(true) => args.push(flag),
+        Value::Boolean(false) => {}
+        Value::Array(values) => {
+            for value in values {
+                args.push(flag.clone());
+                args.push(value.to_string().into());
+            }
+        }
+        value => {
+            args.push(flag);
+            args.push(value.to_string().into());
+        }
+    }
+}
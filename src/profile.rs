@@ -0,0 +1,138 @@
+use crate::config::{Config, Value};
+use crate::preset;
+use ghs::BoxError;
+use std::env;
+use std::ffi::OsString;
+use std::process::Command;
+
+/// Flags that pick whose repositories are searched. A profile's are left out
+/// when the command line already has one, since they exclude each other.
+const SCOPE_FLAGS: [&str; 4] = ["-u", "--username", "--org", "--query"];
+
+/// A `[profile.NAME]` section of the config file: default flags, such as
+/// `username = "me"` or `language = "rust"`, and the account to use, as a
+/// `token` or a `token_command` printing one, e.g. `"pass show github/work"`.
+pub struct Profile {
+    name: String,
+    flags: Vec<OsString>,
+    token: Option<String>,
+    token_command: Option<String>,
+}
+
+impl Profile {
+    /// The profile `--profile` names in `args`, else `$GHS_PROFILE`, else
+    /// the config file's top-level `profile` setting, if any.
+    pub fn select(config: &Config, args: &[OsString]) -> Result<Option<Profile>, BoxError> {
+        let from_args = args
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .enumerate()
+            .find_map(|(index, arg)| match arg.to_str()? {
+                "--profile" => args.get(index + 1)?.to_str().map(str::to_string),
+                arg => arg.strip_prefix("--profile=").map(str::to_string),
+            });
+        let name = match from_args.or_else(|| env::var("GHS_PROFILE").ok()) {
+            Some(name) => name,
+            None => match config.string("profile")? {
+                Some(name) => name.to_string(),
+                None => return Ok(None),
+            },
+        };
+        let Some(section) = config.section(&format!("profile.{}", name)) else {
+            let known = config.subsections("profile");
+            return Err(if known.is_empty() {
+                format!(
+                    "unknown profile `{}`; none are defined in the config file",
+                    name
+                )
+            } else {
+                format!("unknown profile `{}` (defined: {})", name, known.join(", "))
+            }
+            .into());
+        };
+
+        let mut profile = Profile {
+            name: name.clone(),
+            flags: Vec::new(),
+            token: None,
+            token_command: None,
+        };
+        for (key, value) in section {
+            match (key.as_str(), value) {
+                ("token", Value::String(token)) => profile.token = Some(token.clone()),
+                ("token_command", Value::String(command)) => {
+                    profile.token_command = Some(command.clone())
+                }
+                ("token" | "token_command", value) => {
+                    return Err(format!(
+                        "profile {}: `{}` must be a string, not `{}`",
+                        name, key, value
+                    )
+                    .into())
+                }
+                (key, value) => preset::push_flag(&mut profile.flags, key, value),
+            }
+        }
+        Ok(Some(profile))
+    }
+
+    /// `args` with the profile's flags first, so that the command line
+    /// (presets included) overrides every one of them.
+    pub fn apply(&self, mut args: Vec<OsString>) -> Vec<OsString> {
+        let has_scope = args.iter().take_while(|arg| *arg != "--").any(|arg| {
+            arg.to_str().is_some_and(|arg| {
+                // `-ualice` as well as `-u alice` and `--org=rust-lang`.
+                arg.starts_with("-u")
+                    || SCOPE_FLAGS
+                        .iter()
+                        .any(|flag| arg == *flag || arg.starts_with(&format!("{}=", flag)))
+            })
+        });
+        let mut flags = Vec::with_capacity(self.flags.len());
+        let mut profile_flags = self.flags.iter();
+        while let Some(flag) = profile_flags.next() {
+            if has_scope && SCOPE_FLAGS.iter().any(|scope| flag == *scope) {
+                // Along with its value.
+                profile_flags.next();
+                continue;
+            }
+            flags.push(flag.clone());
+        }
+        let at = 1.min(args.len());
+        args.splice(at..at, flags);
+        args
+    }
+
+    /// The profile's token, running its `token_command` if it has one.
+    pub fn token(&self) -> Result<Option<String>, BoxError> {
+        let Some(command) = &self.token_command else {
+            return Ok(self.token.clone());
+        };
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .map_err(|err| {
+                format!(
+                    "could not run the token_command of profile {}: {}",
+                    self.name, err
+                )
+            })?;
+        if !output.status.success() {
+            return Err(format!(
+                "the token_command of profile {} failed ({}): {}",
+                self.name,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        match token.is_empty() {
+            true => {
+                Err(format!("the token_command of profile {} printed nothing", self.name).into())
+            }
+            false => Ok(Some(token)),
+        }
+    }
+}
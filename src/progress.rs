@@ -0,0 +1,72 @@
+//! Progress reported while a [`GhsClient`](crate::GhsClient) works, for
+//! programs embedding it that show their own progress.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), ghs::BoxError> {
+//! use ghs::progress::Event;
+//! use std::sync::mpsc;
+//!
+//! let (events, received) = mpsc::channel();
+//! let events = std::sync::Mutex::new(events);
+//! let client = ghs::GhsClient::builder()
+//!     .on_progress(move |event| {
+//!         let _ = events.lock().unwrap().send(event.clone());
+//!     })
+//!     .build()?;
+//! client.search_all_repositories("user:octocat", 100, |_, _| {}).await?;
+//! for event in received.try_iter() {
+//!     if let Event::PageFetched { items, .. } = event {
+//!         println!("{} more repositories", items);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Something a client did that a progress display may want to show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A page of search results or of a listing arrived.
+    PageFetched { url: String, items: usize },
+    /// GitHub's rate limit was hit; requests wait until this many seconds
+    /// since the Unix epoch before going on.
+    RateLimited { until: i64 },
+    /// Details of one repository were fetched, e.g. `"readme"` or
+    /// `"languages"`.
+    ItemEnriched {
+        full_name: String,
+        detail: &'static str,
+    },
+}
+
+type Callback = dyn Fn(&Event) + Send + Sync;
+
+/// Where a client sends its [`Event`]s, if anywhere. Called on whichever
+/// thread the request finished on, so it should return quickly.
+#[derive(Clone, Default)]
+pub(crate) struct Listener(Option<Arc<Callback>>);
+
+impl Listener {
+    pub(crate) fn new(listener: impl Fn(&Event) + Send + Sync + 'static) -> Listener {
+        Listener(Some(Arc::new(listener)))
+    }
+
+    pub(crate) fn emit(&self, event: impl FnOnce() -> Event) {
+        if let Some(listener) = &self.0 {
+            listener(&event());
+        }
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.0 {
+            Some(_) => "Listener(Some(..))",
+            None => "Listener(None)",
+        })
+    }
+}
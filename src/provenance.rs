@@ -0,0 +1,32 @@
+use ghs::{datetime, GhsClient};
+use serde::Serialize;
+
+/// Where exported results came from, so that separate runs can be told
+/// apart and reproduced.
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    pub query: String,
+    pub fetched_at: String,
+    pub ghs_version: &'static str,
+    pub host: String,
+}
+
+impl Provenance {
+    pub fn new(query: &str, fetched_at: i64, api: &GhsClient) -> Self {
+        Provenance {
+            query: query.to_string(),
+            fetched_at: datetime::format_timestamp(fetched_at),
+            ghs_version: env!("CARGO_PKG_VERSION"),
+            host: api.base_url().to_string(),
+        }
+    }
+}
+
+/// An exported result set: its provenance next to the repositories.
+#[derive(Debug, Serialize)]
+pub struct Export<'a, T> {
+    /// [`ghs::schema::VERSION`].
+    pub schema: &'static str,
+    pub metadata: &'a Provenance,
+    pub repositories: T,
+}
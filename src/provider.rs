@@ -0,0 +1,232 @@
+//! The forges ghs can search. GitHub is the default; [`GiteaClient`] covers
+//! Gitea and Forgejo instances such as Codeberg, and [`GitLabClient`]
+//! GitLab. [`Federated`] searches several of them at once.
+//!
+//! [`GiteaClient`]: crate::gitea::GiteaClient
+//! [`GitLabClient`]: crate::gitlab::GitLabClient
+//! [`Federated`]: crate::federated::Federated
+
+use crate::client::{self, SearchPage};
+use crate::query::{self, QueryError};
+use crate::{BoxError, GhsClient, Repositories};
+use std::future::Future;
+use std::pin::Pin;
+
+/// What the [`SearchProvider`] methods return, boxed so the trait can be
+/// used as `dyn SearchProvider`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Repository search on one forge. Queries use GitHub's search syntax;
+/// other providers translate the qualifiers they understand and reject the
+/// rest.
+pub trait SearchProvider {
+    /// The forge's name, for messages.
+    fn name(&self) -> &'static str;
+
+    /// Base URL of the forge's API, recorded with exported results.
+    fn base_url(&self) -> &str;
+
+    /// One page of repositories matching `query`.
+    fn search_page<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        page: u32,
+    ) -> BoxFuture<'a, Result<SearchPage, BoxError>>;
+
+    /// The first page again, or `None` if it is unchanged since the response
+    /// that carried `etag`. Providers without conditional requests refetch.
+    fn search_page_if_changed<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        etag: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SearchPage>, BoxError>> {
+        let _ = etag;
+        Box::pin(async move { self.search_page(query, per_page, 1).await.map(Some) })
+    }
+
+    /// Every repository matching `query`, or the first `max_results` of
+    /// them without fetching pages beyond. `on_progress` is called with the
+    /// number fetched so far and the total.
+    fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &'a dyn Fn(usize, u64),
+    ) -> BoxFuture<'a, Result<Repositories, BoxError>>;
+
+    /// The most results [`search_page`](SearchProvider::search_page) will
+    /// page through for one query, if the forge has a limit.
+    fn result_cap(&self) -> Option<u64> {
+        None
+    }
+
+    /// Checks `query` before it is sent: an error for a mistake, warnings
+    /// for anything merely suspicious. Providers that reject what they
+    /// cannot translate have nothing to add.
+    fn lint(&self, query: &str) -> Result<Vec<String>, QueryError> {
+        let _ = query;
+        Ok(Vec::new())
+    }
+}
+
+impl SearchProvider for GhsClient {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn base_url(&self) -> &str {
+        GhsClient::base_url(self)
+    }
+
+    fn search_page<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        page: u32,
+    ) -> BoxFuture<'a, Result<SearchPage, BoxError>> {
+        Box::pin(self.search_repositories_page(query, per_page, page))
+    }
+
+    fn search_page_if_changed<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        etag: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SearchPage>, BoxError>> {
+        Box::pin(self.search_repositories_if_changed(query, per_page, etag))
+    }
+
+    fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+        per_page: u8,
+        max_results: Option<usize>,
+        on_progress: &'a dyn Fn(usize, u64),
+    ) -> BoxFuture<'a, Result<Repositories, BoxError>> {
+        match max_results {
+            Some(max_results) => {
+                Box::pin(self.search_repositories_up_to(query, per_page, max_results, on_progress))
+            }
+            None => Box::pin(self.search_every_repository(query, per_page, on_progress)),
+        }
+    }
+
+    fn result_cap(&self) -> Option<u64> {
+        Some(client::SEARCH_RESULT_CAP)
+    }
+
+    fn lint(&self, query: &str) -> Result<Vec<String>, QueryError> {
+        query::lint(query, "repositories")
+    }
+}
+
+/// The parts of a GitHub-syntax query that other forges' searches can
+/// express.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Criteria {
+    pub keywords: Vec<String>,
+    /// `user:` or `org:`; the other forges do not tell them apart.
+    pub owner: Option<String>,
+    /// `repo:owner/name`, looked up directly rather than searched for.
+    pub repo: Option<String>,
+    pub topic: Option<String>,
+    pub archived: Option<bool>,
+}
+
+impl Criteria {
+    /// Reads `query`, rejecting qualifiers `forge` cannot search by.
+    pub fn parse(query: &str, forge: &str) -> Result<Criteria, BoxError> {
+        let mut criteria = Criteria::default();
+        for term in terms(query) {
+            let Some((qualifier, value)) = term.split_once(':') else {
+                criteria.keywords.push(term);
+                continue;
+            };
+            let value = value.to_string();
+            match qualifier {
+                "user" | "org" if criteria.owner.is_none() => criteria.owner = Some(value),
+                "repo" if criteria.repo.is_none() => criteria.repo = Some(value),
+                "topic" if criteria.topic.is_none() => criteria.topic = Some(value),
+                "archived" => {
+                    criteria.archived =
+                        Some(value.parse().map_err(|_| {
+                            format!("archived: takes true or false, not `{}`", value)
+                        })?)
+                }
+                "user" | "org" | "repo" | "topic" => {
+                    return Err(
+                        format!("{} search takes only one {}: qualifier", forge, qualifier).into(),
+                    )
+                }
+                _ => {
+                    return Err(format!(
+                        "{} search does not support the {}: qualifier",
+                        forge, qualifier
+                    )
+                    .into())
+                }
+            }
+        }
+        Ok(criteria)
+    }
+}
+
+/// Splits a query on whitespace, keeping `"quoted phrases"` (and
+/// `qualifier:"quoted values"`) together without their quotes.
+fn terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_github_qualifiers() {
+        let criteria =
+            Criteria::parse("cli tool user:\"forgejo\" archived:false", "Gitea").unwrap();
+        assert_eq!(
+            criteria,
+            Criteria {
+                keywords: vec!["cli".to_string(), "tool".to_string()],
+                owner: Some("forgejo".to_string()),
+                archived: Some(false),
+                ..Criteria::default()
+            }
+        );
+        assert_eq!(
+            Criteria::parse("repo:forgejo/forgejo", "Gitea")
+                .unwrap()
+                .repo
+                .as_deref(),
+            Some("forgejo/forgejo")
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_qualifiers() {
+        assert!(Criteria::parse("user:a language:rust", "GitLab").is_err());
+        assert!(Criteria::parse("user:a org:b", "GitLab").is_err());
+        assert!(Criteria::parse("archived:maybe", "GitLab").is_err());
+    }
+}
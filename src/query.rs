@@ -0,0 +1,513 @@
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+/// How many `AND`, `OR` and `NOT` operators a GitHub search may have.
+pub const MAX_OPERATORS: usize = 5;
+
+/// Qualifiers GitHub knows, by the kind of search, as in `/search/{kind}`.
+const REPOSITORY_QUALIFIERS: &[&str] = &[
+    "archived",
+    "created",
+    "followers",
+    "fork",
+    "forks",
+    "good-first-issues",
+    "help-wanted-issues",
+    "in",
+    "is",
+    "language",
+    "license",
+    "mirror",
+    "org",
+    "pushed",
+    "repo",
+    "size",
+    "sort",
+    "stars",
+    "template",
+    "topic",
+    "topics",
+    "user",
+];
+const CODE_QUALIFIERS: &[&str] = &[
+    "content",
+    "extension",
+    "filename",
+    "fork",
+    "in",
+    "is",
+    "language",
+    "org",
+    "path",
+    "repo",
+    "size",
+    "symbol",
+    "user",
+];
+const ISSUE_QUALIFIERS: &[&str] = &[
+    "archived",
+    "assignee",
+    "author",
+    "base",
+    "closed",
+    "commenter",
+    "comments",
+    "created",
+    "draft",
+    "head",
+    "in",
+    "interactions",
+    "involves",
+    "is",
+    "label",
+    "language",
+    "linked",
+    "locked",
+    "mentions",
+    "merged",
+    "milestone",
+    "no",
+    "org",
+    "project",
+    "reactions",
+    "reason",
+    "repo",
+    "review",
+    "review-requested",
+    "reviewed-by",
+    "sort",
+    "state",
+    "status",
+    "team",
+    "team-review-requested",
+    "type",
+    "updated",
+    "user",
+    "user-review-requested",
+];
+
+/// Builds a GitHub search query from typed qualifiers.
+///
+/// ```
+/// let query = ghs::SearchQuery::new()
+///     .user("octocat")
+///     .language("rust")
+///     .stars(10..)
+///     .build()
+///     .unwrap();
+/// assert_eq!(query, "user:octocat language:rust stars:10..*");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    keywords: Vec<String>,
+    users: Vec<String>,
+    orgs: Vec<String>,
+    repos: Vec<String>,
+    languages: Vec<String>,
+    topics: Vec<String>,
+    stars: Option<(Bound<u64>, Bound<u64>)>,
+    pushed_after: Option<String>,
+    qualifiers: Vec<(&'static str, String)>,
+}
+
+/// Why a [`SearchQuery`] could not be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// No keyword or qualifier was given.
+    Empty,
+    /// `user:` and `org:` were both given; GitHub would OR them, which is
+    /// almost never what was meant.
+    UserAndOrg,
+    /// The star range can never match, e.g. `100..10`.
+    EmptyStarRange,
+    /// A date was not in `YYYY-MM-DD` form.
+    InvalidDate(String),
+    /// A qualifier value was empty.
+    EmptyValue(&'static str),
+    /// A quoted phrase was never closed.
+    UnbalancedQuotes,
+    /// More `AND`, `OR` and `NOT` operators than GitHub accepts.
+    TooManyOperators(usize),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Empty => write!(f, "the search query is empty"),
+            QueryError::UserAndOrg => write!(f, "a query cannot combine user: and org:"),
+            QueryError::EmptyStarRange => write!(f, "the stars range is empty"),
+            QueryError::InvalidDate(date) => {
+                write!(f, "`{}` is not a date in YYYY-MM-DD form", date)
+            }
+            QueryError::EmptyValue(qualifier) => write!(f, "{}: needs a value", qualifier),
+            QueryError::UnbalancedQuotes => write!(
+                f,
+                "the query has an unmatched \"; close the quoted phrase or drop the quote"
+            ),
+            QueryError::TooManyOperators(count) => write!(
+                f,
+                "the query has {} AND, OR and NOT operators, but GitHub allows {}; \
+                 split it into several --query searches",
+                count, MAX_OPERATORS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        SearchQuery::default()
+    }
+
+    /// Free-form search terms, passed through as written.
+    pub fn keywords(mut self, keywords: impl Into<String>) -> Self {
+        self.keywords.push(keywords.into());
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.users.push(user.into());
+        self
+    }
+
+    pub fn org(mut self, org: impl Into<String>) -> Self {
+        self.orgs.push(org.into());
+        self
+    }
+
+    /// A single repository, as `owner/name`.
+    pub fn repo(mut self, full_name: impl Into<String>) -> Self {
+        self.repos.push(full_name.into());
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topics.push(topic.into());
+        self
+    }
+
+    pub fn stars(mut self, range: impl RangeBounds<u64>) -> Self {
+        self.stars = Some((range.start_bound().cloned(), range.end_bound().cloned()));
+        self
+    }
+
+    /// Only repositories pushed to after `date` (`YYYY-MM-DD`).
+    pub fn pushed_after(mut self, date: impl Into<String>) -> Self {
+        self.pushed_after = Some(date.into());
+        self
+    }
+
+    /// Any other qualifier, such as the `path:` of a code search or the
+    /// `state:` of an issue search.
+    ///
+    /// ```
+    /// let query = ghs::SearchQuery::new()
+    ///     .keywords("TODO")
+    ///     .qualifier("path", "src/bin")
+    ///     .qualifier("extension", "rs")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(query, "TODO path:src/bin extension:rs");
+    /// ```
+    pub fn qualifier(mut self, qualifier: &'static str, value: impl Into<String>) -> Self {
+        self.qualifiers.push((qualifier, value.into()));
+        self
+    }
+
+    /// Renders the query string (not yet URL-encoded).
+    pub fn build(&self) -> Result<String, QueryError> {
+        if !self.users.is_empty() && !self.orgs.is_empty() {
+            return Err(QueryError::UserAndOrg);
+        }
+
+        let mut parts: Vec<String> = self
+            .keywords
+            .iter()
+            .map(|keywords| keywords.trim())
+            .filter(|keywords| !keywords.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        for (qualifier, values) in [
+            ("user", &self.users),
+            ("org", &self.orgs),
+            ("repo", &self.repos),
+            ("language", &self.languages),
+            ("topic", &self.topics),
+        ] {
+            for value in values {
+                parts.push(qualify(qualifier, value)?);
+            }
+        }
+
+        if let Some((start, end)) = self.stars {
+            parts.push(format!("stars:{}", render_range(start, end)?));
+        }
+
+        if let Some(date) = &self.pushed_after {
+            if !is_date(date) {
+                return Err(QueryError::InvalidDate(date.clone()));
+            }
+            parts.push(format!("pushed:>{}", date));
+        }
+
+        for (qualifier, value) in &self.qualifiers {
+            parts.push(qualify(qualifier, value)?);
+        }
+
+        if parts.is_empty() {
+            return Err(QueryError::Empty);
+        }
+        Ok(parts.join(" "))
+    }
+}
+
+/// Renders `qualifier:value`, quoting values that contain whitespace so the
+/// search parser keeps them together. Embedded quotes cannot be escaped in
+/// GitHub's syntax, so they are dropped.
+fn qualify(qualifier: &'static str, value: &str) -> Result<String, QueryError> {
+    let value: String = value.trim().chars().filter(|c| *c != '"').collect();
+    if value.is_empty() {
+        return Err(QueryError::EmptyValue(qualifier));
+    }
+    if value.chars().any(char::is_whitespace) {
+        Ok(format!("{}:\"{}\"", qualifier, value))
+    } else {
+        Ok(format!("{}:{}", qualifier, value))
+    }
+}
+
+fn render_range(start: Bound<u64>, end: Bound<u64>) -> Result<String, QueryError> {
+    let start = match start {
+        Bound::Included(value) => Some(value),
+        Bound::Excluded(value) => Some(value.saturating_add(1)),
+        Bound::Unbounded => None,
+    };
+    let end = match end {
+        Bound::Included(value) => Some(value),
+        Bound::Excluded(0) => return Err(QueryError::EmptyStarRange),
+        Bound::Excluded(value) => Some(value - 1),
+        Bound::Unbounded => None,
+    };
+
+    match (start, end) {
+        (Some(start), Some(end)) if start > end => Err(QueryError::EmptyStarRange),
+        (Some(start), Some(end)) => Ok(format!("{}..{}", start, end)),
+        (Some(start), None) => Ok(format!("{}..*", start)),
+        (None, Some(end)) => Ok(format!("*..{}", end)),
+        (None, None) => Ok(">=0".to_string()),
+    }
+}
+
+fn is_date(date: &str) -> bool {
+    let bytes = date.as_bytes();
+    bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(index, byte)| index == 4 || index == 7 || byte.is_ascii_digit())
+}
+
+/// Rewrites a query into a canonical form so that equivalent queries compare
+/// equal: terms are lowercased (GitHub search is case-insensitive) and the
+/// `qualifier:value` terms sorted and deduplicated after the free text,
+/// with quoted phrases kept together. Free text keeps its order, and the
+/// `AND`, `OR` and `NOT` operators their case; a query using an operator
+/// keeps every term in place, since moving a qualifier would change what
+/// the operator applies to.
+///
+/// ```
+/// assert_eq!(
+///     ghs::query::normalize("language:Rust cli user:foo"),
+///     ghs::query::normalize("user:foo  CLI language:rust"),
+/// );
+/// assert_ne!(
+///     ghs::query::normalize("rust NOT cli"),
+///     ghs::query::normalize("cli NOT rust"),
+/// );
+/// ```
+pub fn normalize(query: &str) -> String {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+
+    let operators = terms.iter().any(|term| is_operator(term));
+    let mut text = Vec::new();
+    let mut qualifiers = Vec::new();
+    for term in terms {
+        if is_operator(&term) {
+            text.push(term);
+            continue;
+        }
+        let term = term.to_lowercase();
+        let qualifier = qualifier(&term).is_some();
+        let term = match term.split_once(':') {
+            Some((qualifier, value)) if value.chars().any(char::is_whitespace) => {
+                format!("{}:\"{}\"", qualifier, value)
+            }
+            _ if term.chars().any(char::is_whitespace) => format!("\"{}\"", term),
+            _ => term,
+        };
+        if qualifier && !operators {
+            qualifiers.push(term);
+        } else {
+            text.push(term);
+        }
+    }
+    qualifiers.sort();
+    qualifiers.dedup();
+    text.extend(qualifiers);
+    text.join(" ")
+}
+
+fn is_operator(term: &str) -> bool {
+    matches!(term, "AND" | "OR" | "NOT")
+}
+
+/// The qualifier and value of a `qualifier:value` term, the qualifier
+/// without a leading `-` for exclusion. URLs are terms, not qualifiers.
+fn qualifier(term: &str) -> Option<(&str, &str)> {
+    let (qualifier, value) = term.split_once(':')?;
+    let qualifier = qualifier.strip_prefix('-').unwrap_or(qualifier);
+    let valid = !qualifier.is_empty()
+        && !value.starts_with("//")
+        && qualifier
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '-' || c == '_');
+    valid.then_some((qualifier, value))
+}
+
+/// Checks a query for a `kind` of search (`repositories`, `code` or
+/// `issues`) before it is sent, so that mistakes get a pointed message
+/// rather than the API's generic 422 or a quietly wrong result.
+///
+/// Unbalanced quotes, `user:` with `org:` and too many operators are
+/// errors. Unknown qualifiers are only suspicious, since GitHub searches
+/// them as text, so they come back as warnings, with the closest known
+/// qualifier when there is one.
+///
+/// ```
+/// let warnings = ghs::query::lint("cli lang:rust", "repositories").unwrap();
+/// assert_eq!(
+///     warnings,
+///     ["unknown qualifier `lang:`, which GitHub searches as text; did you mean `language:`?"]
+/// );
+/// assert!(ghs::query::lint("\"command line", "repositories").is_err());
+/// ```
+pub fn lint(query: &str, kind: &str) -> Result<Vec<String>, QueryError> {
+    if query.matches('"').count() % 2 == 1 {
+        return Err(QueryError::UnbalancedQuotes);
+    }
+    let known = match kind {
+        "code" => CODE_QUALIFIERS,
+        "issues" => ISSUE_QUALIFIERS,
+        _ => REPOSITORY_QUALIFIERS,
+    };
+
+    let mut warnings = Vec::new();
+    let (mut user, mut org, mut operators) = (false, false, 0);
+    let mut quoted = false;
+    for term in query.split(|c: char| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c.is_whitespace() && !quoted
+    }) {
+        if is_operator(term) {
+            operators += 1;
+            continue;
+        }
+        let Some((qualifier, _)) = qualifier(term) else {
+            continue;
+        };
+        let qualifier = qualifier.to_lowercase();
+        user |= qualifier == "user" && !term.starts_with('-');
+        org |= qualifier == "org" && !term.starts_with('-');
+        if known.contains(&qualifier.as_str()) {
+            continue;
+        }
+        let closest = known
+            .iter()
+            .map(|known| (strsim::levenshtein(&qualifier, known), known))
+            .filter(|(distance, known)| {
+                *distance <= 2 || known.starts_with(&qualifier) && qualifier.len() >= 3
+            })
+            .min();
+        warnings.push(match closest {
+            Some((_, known)) => format!(
+                "unknown qualifier `{}:`, which GitHub searches as text; did you mean `{}:`?",
+                qualifier, known
+            ),
+            None => format!(
+                "unknown qualifier `{}:`, which GitHub searches as text",
+                qualifier
+            ),
+        });
+    }
+    if user && org {
+        return Err(QueryError::UserAndOrg);
+    }
+    if operators > MAX_OPERATORS {
+        return Err(QueryError::TooManyOperators(operators));
+    }
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_and_dedups_only_qualifiers() {
+        assert_eq!(
+            normalize("Language:Rust  command LINE user:foo language:rust"),
+            "command line language:rust user:foo"
+        );
+        assert_eq!(normalize("cli rust"), "cli rust");
+        assert_ne!(normalize("cli rust"), normalize("rust cli"));
+        assert_eq!(
+            normalize("description:\"Command  Line\" \"fast tool\" -topic:old"),
+            "\"fast tool\" -topic:old description:\"command  line\""
+        );
+        // URLs are text, not `https:` qualifiers.
+        assert_eq!(
+            normalize("https://example.com stars:>5"),
+            "https://example.com stars:>5"
+        );
+    }
+
+    #[test]
+    fn operators_keep_their_case_and_every_term_its_place() {
+        assert_eq!(normalize("Rust NOT cli"), "rust NOT cli");
+        assert_ne!(normalize("rust NOT cli"), normalize("cli NOT rust"));
+        assert_ne!(normalize("rust NOT cli"), normalize("rust not cli"));
+        assert_eq!(
+            normalize("user:foo OR language:Go"),
+            "user:foo OR language:go"
+        );
+    }
+}
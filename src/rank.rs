@@ -0,0 +1,304 @@
+use ghs::datetime;
+use ghs::Repository;
+
+/// Named `--rank` presets and the expressions they stand for.
+const PRESETS: &[(&str, &str)] = &[
+    ("popular", "stars*0.7 + forks*0.3"),
+    ("active", "recency*0.8 + issues*0.2"),
+    ("fresh", "newness*0.7 + stars*0.3"),
+];
+
+/// Fields usable in a ranking expression. Every field is normalized to the
+/// 0.0-1.0 range across the result set before the expression is evaluated.
+const FIELDS: &[&str] = &["stars", "forks", "issues", "watchers", "recency", "newness"];
+
+/// A parsed `--rank` expression such as `stars*0.6 + recency*0.4`.
+#[derive(Debug, Clone)]
+pub enum RankExpr {
+    Number(f64),
+    Field(String),
+    Negate(Box<RankExpr>),
+    Binary(Box<RankExpr>, char, Box<RankExpr>),
+}
+
+/// Parses either a preset name or an arithmetic expression over [`FIELDS`].
+pub fn parse_rank(input: &str) -> Result<RankExpr, String> {
+    let source = PRESETS
+        .iter()
+        .find(|(name, _)| *name == input)
+        .map_or(input, |(_, expression)| *expression);
+
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    let expr = parser.expression()?;
+    if let Some(token) = parser.tokens.get(parser.position) {
+        return Err(format!("unexpected `{}` in rank expression", token));
+    }
+    Ok(expr)
+}
+
+/// Sorts repositories by descending score of `expr`, keeping GitHub's order for ties.
+pub fn rank_repositories(repositories: &mut Vec<Repository>, expr: &RankExpr) {
+    let columns: Vec<(&str, Vec<f64>)> = FIELDS
+        .iter()
+        .map(|field| {
+            (
+                *field,
+                normalize(repositories.iter().map(|repo| raw_value(repo, field))),
+            )
+        })
+        .collect();
+
+    let mut scored: Vec<(f64, Repository)> = repositories
+        .drain(..)
+        .enumerate()
+        .map(|(index, repo)| {
+            let lookup = |field: &str| {
+                columns
+                    .iter()
+                    .find(|(name, _)| *name == field)
+                    .map_or(0.0, |(_, values)| values[index])
+            };
+            (evaluate(expr, &lookup), repo)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    repositories.extend(scored.into_iter().map(|(_, repo)| repo));
+}
+
+fn raw_value(repo: &Repository, field: &str) -> f64 {
+    let timestamp = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(datetime::parse_timestamp)
+            .unwrap_or_default() as f64
+    };
+
+    match field {
+        // Counts are log-scaled so a single very popular repository does not
+        // flatten everyone else to zero.
+        "stars" => (repo.stargazers_count as f64).ln_1p(),
+        "forks" => (repo.forks_count as f64).ln_1p(),
+        "issues" => (repo.open_issues_count as f64).ln_1p(),
+        "watchers" => (repo.watchers_count as f64).ln_1p(),
+        "recency" => timestamp(&repo.pushed_at),
+        "newness" => timestamp(&repo.created_at),
+        _ => 0.0,
+    }
+}
+
+fn normalize(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let values: Vec<f64> = values.collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    values
+        .into_iter()
+        .map(|value| {
+            if range > 0.0 {
+                (value - min) / range
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn evaluate(expr: &RankExpr, lookup: &dyn Fn(&str) -> f64) -> f64 {
+    match expr {
+        RankExpr::Number(value) => *value,
+        RankExpr::Field(name) => lookup(name),
+        RankExpr::Negate(inner) => -evaluate(inner, lookup),
+        RankExpr::Binary(left, op, right) => {
+            let (left, right) = (evaluate(left, lookup), evaluate(right, lookup));
+            match op {
+                '+' => left + right,
+                '-' => left - right,
+                '*' => left * right,
+                _ if right == 0.0 => 0.0,
+                _ => left / right,
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if "+-*/()".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        } else {
+            return Err(format!("unexpected character `{}` in rank expression", c));
+        }
+    }
+
+    if tokens.is_empty() {
+        return Err("rank expression is empty".to_string());
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Result<RankExpr, String> {
+        let mut left = self.term()?;
+        while let Some(op @ ("+" | "-")) = self.peek() {
+            let op = op.chars().next().unwrap();
+            self.position += 1;
+            left = RankExpr::Binary(Box::new(left), op, Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<RankExpr, String> {
+        let mut left = self.factor()?;
+        while let Some(op @ ("*" | "/")) = self.peek() {
+            let op = op.chars().next().unwrap();
+            self.position += 1;
+            left = RankExpr::Binary(Box::new(left), op, Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<RankExpr, String> {
+        match self.next() {
+            Some(token) if token == "-" => Ok(RankExpr::Negate(Box::new(self.factor()?))),
+            Some(token) if token == "(" => {
+                let inner = self.expression()?;
+                match self.next() {
+                    Some(token) if token == ")" => Ok(inner),
+                    _ => Err("missing `)` in rank expression".to_string()),
+                }
+            }
+            Some(token) => {
+                if let Ok(value) = token.parse::<f64>() {
+                    Ok(RankExpr::Number(value))
+                } else if FIELDS.contains(&token.as_str()) {
+                    Ok(RankExpr::Field(token))
+                } else {
+                    Err(format!(
+                        "unknown rank field `{}` (expected one of: {}; or a preset: {})",
+                        token,
+                        FIELDS.join(", "),
+                        PRESETS
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                }
+            }
+            None => Err("rank expression ended unexpectedly".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(name: &str, stars: u64, pushed_at: Option<&str>) -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "full_name": format!("octo/{}", name),
+            "stargazers_count": stars,
+            "pushed_at": pushed_at,
+        }))
+        .unwrap()
+    }
+
+    fn ranked(expression: &str, mut repositories: Vec<Repository>) -> Vec<String> {
+        rank_repositories(&mut repositories, &parse_rank(expression).unwrap());
+        repositories.into_iter().map(|repo| repo.name).collect()
+    }
+
+    #[test]
+    fn ties_keep_the_order_github_returned() {
+        let repositories = vec![
+            repo("a", 5, None),
+            repo("b", 50, None),
+            repo("c", 5, None),
+            repo("d", 50, None),
+        ];
+        assert_eq!(ranked("stars", repositories.clone()), ["b", "d", "a", "c"]);
+        // A field that is the same everywhere normalizes to zero for all.
+        assert_eq!(ranked("forks", repositories), ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn missing_dates_rank_as_the_oldest() {
+        let repositories = vec![
+            repo("never", 0, None),
+            repo("old", 0, Some("2020-01-01T00:00:00Z")),
+            repo("new", 0, Some("2024-01-01T00:00:00Z")),
+        ];
+        assert_eq!(
+            ranked("recency", repositories.clone()),
+            ["new", "old", "never"]
+        );
+        assert_eq!(ranked("-recency", repositories), ["never", "old", "new"]);
+        assert_eq!(ranked("newness", Vec::new()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        let lookup = |field: &str| if field == "stars" { 2.0 } else { 3.0 };
+        let value = |expression: &str| evaluate(&parse_rank(expression).unwrap(), &lookup);
+        assert_eq!(value("stars + forks * 2"), 8.0);
+        assert_eq!(value("(stars + forks) * 2"), 10.0);
+        assert_eq!(value("stars - -forks / 3"), 3.0);
+        // Division by zero scores zero rather than infinity.
+        assert_eq!(value("stars / 0"), 0.0);
+        assert_eq!(value("popular"), 2.0 * 0.7 + 3.0 * 0.3);
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        let error = |expression: &str| parse_rank(expression).unwrap_err();
+        assert_eq!(error(" "), "rank expression is empty");
+        assert_eq!(
+            error("stars % 2"),
+            "unexpected character `%` in rank expression"
+        );
+        assert!(error("likes").starts_with("unknown rank field `likes`"));
+        assert_eq!(error("(stars"), "missing `)` in rank expression");
+        assert_eq!(error("stars *"), "rank expression ended unexpectedly");
+        assert_eq!(
+            error("stars forks"),
+            "unexpected `forks` in rank expression"
+        );
+    }
+}
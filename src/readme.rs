@@ -0,0 +1,144 @@
+use crate::circuit::CircuitBreaker;
+use crate::interrupt;
+use ghs::GhsClient;
+use ghs::{BoxError, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many README requests may be in flight at once.
+const CONCURRENCY: usize = 8;
+
+/// A run of README lines around one or more matches.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snippet {
+    /// `(line number, line, is a match)` triples, 1-based.
+    pub lines: Vec<(usize, String, bool)>,
+}
+
+/// READMEs searched so far and the snippets found in them, keyed by full name.
+/// Repositories without a match are in `completed` but not in `matches`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GrepProgress {
+    pub completed: BTreeSet<String>,
+    pub matches: HashMap<String, Vec<Snippet>>,
+}
+
+/// Fetches the README of every repository not yet in `progress` concurrently
+/// and records the snippets matching `term` (case-insensitively).
+///
+/// Stops early on Ctrl-C; the returned flag tells whether that happened, in
+/// which case the progress only covers the READMEs that finished. READMEs
+/// that could not be fetched are left out of `completed` too, so a resumed
+/// run retries them; after repeated failures the rest are skipped.
+pub async fn grep_readmes(
+    api: &GhsClient,
+    repositories: &[Repository],
+    term: &str,
+    context: usize,
+    mut progress: GrepProgress,
+) -> Result<(GrepProgress, bool), BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+
+    for repo in repositories {
+        if progress.completed.contains(&repo.full_name) {
+            continue;
+        }
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        let term = term.to_string();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let readme = breaker
+                .call(&format!("README of {}", full_name), api.readme(&full_name))
+                .await?;
+            let snippets = readme.map(|readme| match readme {
+                Some(readme) => find_snippets(&readme, &term, context),
+                None => Vec::new(),
+            });
+            Ok::<_, BoxError>((full_name, snippets))
+        });
+    }
+
+    loop {
+        let result = tokio::select! {
+            result = tasks.join_next() => result,
+            _ = interrupt::wait() => {
+                tasks.abort_all();
+                return Ok((progress, true));
+            }
+        };
+        let Some(result) = result else {
+            return Ok((progress, false));
+        };
+
+        let (full_name, snippets) = result??;
+        let Some(snippets) = snippets else {
+            continue;
+        };
+        if !snippets.is_empty() {
+            progress.matches.insert(full_name.clone(), snippets);
+        }
+        progress.completed.insert(full_name);
+    }
+}
+
+/// Finds lines containing `term` and groups them with `context` lines on
+/// either side, merging groups that overlap.
+pub fn find_snippets(readme: &str, term: &str, context: usize) -> Vec<Snippet> {
+    let term = term.to_lowercase();
+    let lines: Vec<&str> = readme.lines().collect();
+    let mut snippets: Vec<Snippet> = Vec::new();
+    let mut last_end = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        if !line.to_lowercase().contains(&term) {
+            continue;
+        }
+
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(lines.len());
+
+        let merge = !snippets.is_empty() && start <= last_end;
+        if !merge {
+            snippets.push(Snippet { lines: Vec::new() });
+        }
+        let snippet = snippets.last_mut().unwrap();
+        let from = if merge { last_end } else { start };
+        for (offset, text) in lines[from..end].iter().enumerate() {
+            snippet
+                .lines
+                .push((from + offset + 1, text.to_string(), false));
+        }
+        if let Some(entry) = snippet
+            .lines
+            .iter_mut()
+            .find(|(number, _, _)| *number == index + 1)
+        {
+            entry.2 = true;
+        }
+        last_end = end;
+    }
+
+    snippets
+}
+
+pub fn print_snippets(snippets: &[Snippet]) {
+    println!("README matches:");
+    for (position, snippet) in snippets.iter().enumerate() {
+        if position > 0 {
+            println!("  ...");
+        }
+        for (number, line, is_match) in &snippet.lines {
+            let marker = if *is_match { '>' } else { ' ' };
+            println!("  {}{:>4}: {}", marker, number, line);
+        }
+    }
+    println!("---");
+}
@@ -0,0 +1,97 @@
+use ghs::model::User;
+use ghs::Repository;
+use sha2::{Digest, Sha256};
+use std::env;
+
+/// An identifying part of a repository that `--redact` hides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The owner's login, replaced by a pseudonym.
+    Owner,
+    /// The repository's name, replaced by a pseudonym.
+    Name,
+    Description,
+    /// Web, clone, homepage and avatar URLs.
+    Urls,
+}
+
+impl Field {
+    pub fn parse(field: &str) -> Result<Field, String> {
+        match field {
+            "owner" => Ok(Field::Owner),
+            "name" => Ok(Field::Name),
+            "description" => Ok(Field::Description),
+            "urls" => Ok(Field::Urls),
+            _ => Err(format!(
+                "`{}` cannot be redacted (expected owner, name, description or urls)",
+                field
+            )),
+        }
+    }
+}
+
+/// `repo` with `fields` hidden. Owners and names become pseudonyms derived
+/// from a hash, so the same owner reads the same across rows and exports
+/// and results can still be grouped. Set `GHS_REDACT_SALT` to a secret to
+/// stop anyone from confirming a guessed name by hashing it. URLs spell
+/// out the owner and name, so they are removed along with either, and
+/// `--query` tags, which likely do too, become pseudonyms.
+pub fn redact(repo: &Repository, fields: &[Field]) -> Repository {
+    let salt = env::var("GHS_REDACT_SALT").unwrap_or_default();
+    let pseudonym = |kind: &str, value: &str| {
+        let digest = format!(
+            "{:x}",
+            Sha256::digest(format!("{}{}", salt, value.to_lowercase()).as_bytes())
+        );
+        format!("{}-{}", kind, &digest[..10])
+    };
+    let mut repo = repo.clone();
+    let mut owner_login = match &repo.owner {
+        Some(owner) => owner.login.clone(),
+        None => repo
+            .full_name
+            .split_once('/')
+            .map_or(String::new(), |(owner, _)| owner.to_string()),
+    };
+    if fields.contains(&Field::Owner) {
+        owner_login = pseudonym("owner", &owner_login);
+        repo.owner = repo.owner.map(|_| User {
+            login: owner_login.clone(),
+            avatar_url: None,
+        });
+    }
+    if fields.contains(&Field::Name) {
+        // Keyed by the full name, so equally named repositories of different
+        // owners stay apart.
+        repo.name = pseudonym("repo", &repo.full_name);
+    }
+    if fields.contains(&Field::Owner) || fields.contains(&Field::Name) {
+        repo.full_name = match owner_login.is_empty() {
+            true => repo.name.clone(),
+            false => format!("{}/{}", owner_login, repo.name),
+        };
+        repo.query = repo.query.map(|queries| {
+            let queries: Vec<String> = queries
+                .split(", ")
+                .map(|query| pseudonym("query", query))
+                .collect();
+            queries.join(", ")
+        });
+    }
+    if fields.contains(&Field::Description) {
+        repo.description = None;
+    }
+    if fields
+        .iter()
+        .any(|field| matches!(field, Field::Urls | Field::Owner | Field::Name))
+    {
+        repo.html_url = None;
+        repo.clone_url = None;
+        repo.ssh_url = None;
+        repo.homepage = None;
+        if let Some(owner) = &mut repo.owner {
+            owner.avatar_url = None;
+        }
+    }
+    repo
+}
@@ -0,0 +1,99 @@
+use crate::commands::print_repo;
+use crate::export;
+use crate::predicate;
+use crate::provenance::Provenance;
+use ghs::{BoxError, Repository};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+const HELP: &str = "\
+Type filter expressions to narrow the results, e.g. `language=rust stars>=10`.
+Fields: name, full_name, description, language, and numbers such as stars,\nforks, issues, watchers (any numeric field of the JSON output works)
+Operators: `:` (contains), =, !=, >, >=, <, <=
+
+Commands:
+  :print          print the current selection
+  :export PATH    write the current selection to PATH (.json, .csv or .md)
+  :count          show how many repositories are selected
+  :undo           revert the last filter
+  :reset          go back to the full result set
+  :help           show this help
+  :quit           leave (also Ctrl-D)";
+
+/// Interactive loop that narrows `repositories` in memory, without further
+/// API calls, until the user quits. Exports carry `provenance`.
+pub fn run(repositories: Vec<Repository>, provenance: &Provenance) -> Result<(), BoxError> {
+    let mut history: Vec<Vec<usize>> = vec![(0..repositories.len()).collect()];
+
+    eprintln!(
+        "{} repositories loaded. Type :help for commands.",
+        repositories.len()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        let selection = history.last().unwrap();
+        eprint!("ghs ({})> ", selection.len());
+        io::stderr().flush()?;
+
+        let Some(line) = lines.next() else {
+            eprintln!();
+            return Ok(());
+        };
+        let line = line?;
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+
+        match command {
+            "" => {}
+            ":quit" | ":q" => return Ok(()),
+            ":help" => eprintln!("{}", HELP),
+            ":count" => eprintln!("{} repositories selected", selection.len()),
+            ":print" => {
+                for index in selection {
+                    print_repo(&repositories[*index]);
+                }
+            }
+            ":export" => {
+                let path = argument.trim();
+                if path.is_empty() {
+                    eprintln!("usage: :export PATH");
+                    continue;
+                }
+                let selected: Vec<&Repository> = selection
+                    .iter()
+                    .map(|index| &repositories[*index])
+                    .collect();
+                match export::write(Path::new(path), &selected, provenance) {
+                    Ok(()) => eprintln!("Wrote {} repositories to {}", selected.len(), path),
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+            ":undo" => {
+                if history.len() > 1 {
+                    history.pop();
+                }
+            }
+            ":reset" => history.truncate(1),
+            _ if command.starts_with(':') => {
+                eprintln!("Unknown command `{}`. Type :help for commands.", command)
+            }
+            _ => match predicate::parse_line(line) {
+                Ok(predicates) => {
+                    let narrowed: Vec<usize> = selection
+                        .iter()
+                        .copied()
+                        .filter(|index| {
+                            predicates
+                                .iter()
+                                .all(|predicate| predicate.matches(&repositories[*index]))
+                        })
+                        .collect();
+                    history.push(narrowed);
+                }
+                Err(err) => eprintln!("{}", err),
+            },
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! `ghs rename-branch`: renames the default branch of many repositories at
+//! once, e.g. `master` to `main` across an organization.
+
+use crate::interrupt;
+use ghs::{BoxError, GhsClient, Repository};
+
+/// What became of one repository.
+#[derive(Debug)]
+pub enum Outcome {
+    Renamed,
+    /// Left alone, for this reason.
+    Skipped(&'static str),
+    Failed(BoxError),
+}
+
+/// The repositories among `found` whose default branch is `from`, each
+/// with the reason it has to be left alone, if any.
+pub fn plan<'a>(
+    found: &'a [Repository],
+    from: &str,
+) -> Vec<(&'a Repository, Option<&'static str>)> {
+    found
+        .iter()
+        .filter(|repo| repo.default_branch.as_deref() == Some(from))
+        .map(|repo| (repo, repo.archived.then_some("archived, so read-only")))
+        .collect()
+}
+
+/// Renames `from` to `to` in every planned repository that is not skipped,
+/// one after another, carrying on past failures.
+pub async fn apply(
+    api: &GhsClient,
+    plan: &[(&Repository, Option<&'static str>)],
+    from: &str,
+    to: &str,
+) -> Vec<(String, Outcome)> {
+    let mut outcomes = Vec::with_capacity(plan.len());
+    for (repo, skip) in plan {
+        let outcome = match skip {
+            Some(reason) => Outcome::Skipped(reason),
+            None => match interrupt::or_exit(api.rename_branch(&repo.full_name, from, to)).await {
+                Ok(()) => Outcome::Renamed,
+                Err(err) => Outcome::Failed(err),
+            },
+        };
+        outcomes.push((repo.full_name.clone(), outcome));
+    }
+    outcomes
+}
+
+/// A summary line followed by every repository that was not renamed.
+pub fn report(outcomes: &[(String, Outcome)], from: &str, to: &str) -> String {
+    let renamed = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Renamed))
+        .count();
+    let mut skipped = 0;
+    let mut failed = 0;
+    let mut details = String::new();
+    for (full_name, outcome) in outcomes {
+        match outcome {
+            Outcome::Renamed => {}
+            Outcome::Skipped(reason) => {
+                skipped += 1;
+                details.push_str(&format!("  skipped {}: {}\n", full_name, reason));
+            }
+            Outcome::Failed(err) => {
+                failed += 1;
+                details.push_str(&format!("  failed {}: {}\n", full_name, err));
+            }
+        }
+    }
+    format!(
+        "Renamed {} to {} in {} repositories, skipped {} and failed {}\n{}",
+        from, to, renamed, skipped, failed, details
+    )
+}
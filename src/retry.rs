@@ -0,0 +1,65 @@
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// When and how often failed requests are retried.
+///
+/// Reads are always safe to repeat. Mutating requests (starring, forking,
+/// archiving, ...) could apply their side effect twice if the first attempt
+/// reached GitHub but the response was lost, so they are only retried when
+/// `retry_mutations` is set.
+///
+/// `budget` caps the retries spent across every request of a client, so a
+/// batch of requests against a struggling API fails fast instead of each
+/// request sitting through its own full backoff.
+///
+/// Waiting out a rate limit is not a retry and spends none of them, but
+/// `max_rate_limit_waits` caps how often one request waits before it fails
+/// with [`ApiError::RateLimited`](crate::ApiError::RateLimited), so that a
+/// limit that never lifts does not hold it up forever.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub retry_mutations: bool,
+    pub budget: u32,
+    pub max_rate_limit_waits: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            retry_mutations: false,
+            budget: 20,
+            max_rate_limit_waits: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether a request with `method` may be retried under this policy.
+    pub fn allows(&self, method: &Method) -> bool {
+        is_idempotent(method) || self.retry_mutations
+    }
+
+    /// Exponential backoff before retry number `attempt` (starting at 0).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt)
+    }
+}
+
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Server-side failures that are likely to succeed when repeated.
+pub fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
@@ -0,0 +1,55 @@
+use ghs::Repository;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Picks `count` repositories uniformly at random (all of them, shuffled, if
+/// there are fewer). The same `seed` always picks the same repositories.
+pub fn sample(mut repositories: Vec<Repository>, count: usize, seed: u64) -> Vec<Repository> {
+    let mut rng = XorShift::new(seed);
+    let count = count.min(repositories.len());
+    // A partial Fisher-Yates shuffle: the first `count` slots end up holding
+    // a uniform sample.
+    for index in 0..count {
+        let pick = index + rng.below(repositories.len() - index);
+        repositories.swap(index, pick);
+    }
+    repositories.truncate(count);
+    repositories
+}
+
+/// A seed that differs from run to run.
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ (std::process::id() as u64).rotate_left(32)
+}
+
+/// xorshift64*; plenty for picking repositories, not for anything secret.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The all-zero state is a fixed point, so mix the seed first.
+        XorShift(seed.wrapping_mul(0x9e3779b97f4a7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// A number in `0..bound` without modulo bias.
+    fn below(&mut self, bound: usize) -> usize {
+        let bound = bound as u64;
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next();
+            if value < zone {
+                return (value % bound) as usize;
+            }
+        }
+    }
+}
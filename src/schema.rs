@@ -0,0 +1,71 @@
+//! The versioned layout of the JSON documents `ghs` writes.
+
+/// The `schema` member of every JSON document. It stays the same while
+/// fields are only added; removing a field or changing what one means
+/// starts `ghs/v2`.
+pub const VERSION: &str = "ghs/v1";
+
+/// A JSON Schema (draft 2020-12) describing [`VERSION`], as `ghs schema`
+/// prints it.
+pub const DOCUMENT: &str = include_str!("schema.json");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CodeResult, IssueResult, Repository};
+    use serde_json::{json, Value};
+
+    /// The names of the members `value` serializes to.
+    fn keys(value: impl serde::Serialize) -> Vec<String> {
+        match serde_json::to_value(value).unwrap() {
+            Value::Object(object) => object.keys().cloned().collect(),
+            value => panic!("{} is not an object", value),
+        }
+    }
+
+    #[test]
+    fn describes_every_field_of_this_version() {
+        let document: Value = serde_json::from_str(DOCUMENT).unwrap();
+        assert_eq!(document["properties"]["schema"]["const"], VERSION);
+
+        let repository = json!({
+            "name": "ghs",
+            "provider": "github",
+            "query": "cli",
+            "starred": true,
+            "score": 50.0,
+            "languages": {"Rust": 1},
+        });
+        let repository: Repository = serde_json::from_value(repository).unwrap();
+        let code = CodeResult {
+            name: "main.rs".to_string(),
+            path: "src/main.rs".to_string(),
+            html_url: String::new(),
+            repository: repository.clone(),
+            text_matches: Vec::new(),
+        };
+        let issue: IssueResult = serde_json::from_value(json!({
+            "number": 1,
+            "title": "",
+            "html_url": "",
+            "state": "open",
+            "repository_url": "",
+        }))
+        .unwrap();
+        for (name, keys) in [
+            ("repository", keys(&repository)),
+            ("code", keys(&code)),
+            ("issue", keys(&issue)),
+        ] {
+            let properties = &document["$defs"][name]["properties"];
+            for key in keys {
+                assert!(
+                    properties.get(&key).is_some(),
+                    "{}.{} is undocumented",
+                    name,
+                    key
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! `ghs selftest`: a search, a filter and every output format run against
+//! a stand-in for the GitHub API served from this process, so that a build
+//! and its environment can be checked without a token or the real API.
+//!
+//! The stand-in is the integration tests' [`ghs::testing::MockServer`]. It
+//! speaks plain HTTP on the loopback interface, so the proxy variables
+//! apply to it but certificates are never looked at; the TLS backend is
+//! only checked to start.
+
+use crate::output::{self, Format, Formatter};
+use ghs::filters::RepositoryFilter;
+use ghs::retry::RetryPolicy;
+use ghs::testing::{MockServer, Response};
+use ghs::{BoxError, GhsClient, Repository};
+use std::env;
+use std::time::Duration;
+
+/// The token the stand-in insists on, to check that it arrives intact.
+const TOKEN: &str = "ghs-selftest";
+const QUERY: &str = "user:selftest";
+/// Small pages, so that the search has to follow pagination links.
+const PER_PAGE: usize = 2;
+/// Long enough for a loaded machine, short enough that a proxy swallowing
+/// the requests is reported before anyone gives up waiting.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the stand-in finds for [`QUERY`]: name, language and stars.
+const REPOSITORIES: [(&str, &str, u64); 5] = [
+    ("ghs", "Rust", 120),
+    ("octo", "Go", 80),
+    ("hello", "Rust", 40),
+    ("tool", "Python", 20),
+    ("cli", "Rust", 10),
+];
+
+/// The names `--language rust` keeps, sorted.
+const RUST: [&str; 3] = ["cli", "ghs", "hello"];
+
+/// A check's name, and what it saw or why it failed.
+type Outcome = (&'static str, Result<String, String>);
+
+/// Runs the checks, printing a line for each, and fails unless all passed.
+pub async fn run() -> Result<(), BoxError> {
+    let outcomes = checks().await;
+    let width = outcomes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, outcome) in &outcomes {
+        let (status, text) = match outcome {
+            Ok(detail) => ("ok", detail),
+            Err(reason) => ("FAIL", reason),
+        };
+        println!("{:<4}  {:<width$}  {}", status, name, text, width = width);
+    }
+    if outcomes.iter().all(|(_, outcome)| outcome.is_ok()) {
+        println!("selftest passed");
+        return Ok(());
+    }
+    Err("selftest failed; the checks above say where".into())
+}
+
+/// Each check uses what the one before it produced, so the first failure
+/// ends the run.
+async fn checks() -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+    macro_rules! check {
+        ($name:expr, $result:expr) => {
+            match $result {
+                Ok((value, detail)) => {
+                    outcomes.push(($name, Ok(detail)));
+                    value
+                }
+                Err(reason) => {
+                    outcomes.push(($name, Err(reason)));
+                    return outcomes;
+                }
+            }
+        };
+    }
+
+    let url = check!(
+        "server",
+        serve().map(|url| (url.clone(), format!("listening on {}", url)))
+    );
+    let proxies = proxy_variables();
+    let api = check!(
+        "client",
+        GhsClient::builder()
+            .host(&url)
+            .token(TOKEN)
+            .timeout(TIMEOUT)
+            .retry_policy(RetryPolicy {
+                max_retries: 0,
+                ..RetryPolicy::default()
+            })
+            .build()
+            .map(|api| {
+                let detail = match proxies.is_empty() {
+                    true => "TLS backend started, no proxy variables set".to_string(),
+                    false => format!("TLS backend started, with {} set", proxies.join(", ")),
+                };
+                (api, detail)
+            })
+            .map_err(|err| err.to_string())
+    );
+    let found = check!(
+        "search",
+        api.search_all_repositories(QUERY, PER_PAGE as u8, |_, _| {})
+            .await
+            .map_err(|err| match proxies.is_empty() {
+                true => err.to_string(),
+                // Not every proxy passes loopback requests on.
+                false => format!("{} (is 127.0.0.1 in NO_PROXY?)", err),
+            })
+            .and_then(|found| {
+                let pages = REPOSITORIES.len().div_ceil(PER_PAGE);
+                match found.items.len() {
+                    count if count == REPOSITORIES.len() => Ok((
+                        found.items,
+                        format!("{} repositories over {} pages", count, pages),
+                    )),
+                    count => Err(format!(
+                        "found {} repositories over {} pages, not {}",
+                        count,
+                        pages,
+                        REPOSITORIES.len()
+                    )),
+                }
+            })
+    );
+    let kept = check!("filter", filter(found));
+    check!("format", format(&kept).await);
+    check!(
+        "errors",
+        match api.repository("selftest/missing").await {
+            Ok(None) => Ok(((), "a 404 reads as a missing repository".to_string())),
+            Ok(Some(_)) => Err("a 404 read as a repository".to_string()),
+            Err(err) => Err(format!("a 404 failed: {}", err)),
+        }
+    );
+    outcomes
+}
+
+fn filter(found: Vec<Repository>) -> Result<(Vec<Repository>, String), String> {
+    let filter = RepositoryFilter {
+        language: Some("rust".to_string()),
+        ..RepositoryFilter::default()
+    };
+    let kept = filter.apply(found);
+    let mut names: Vec<&str> = kept.iter().map(|repo| repo.name.as_str()).collect();
+    names.sort_unstable();
+    if names != RUST {
+        return Err(format!(
+            "--language rust kept {}, not {}",
+            names.join(", "),
+            RUST.join(", ")
+        ));
+    }
+    let detail = format!(
+        "--language rust kept {} of {}",
+        kept.len(),
+        REPOSITORIES.len()
+    );
+    Ok((kept, detail))
+}
+
+/// Writes `kept` in every format and checks that each lists all of them.
+async fn format(kept: &[Repository]) -> Result<((), String), String> {
+    let formats = [
+        ("plain", Format::Plain),
+        ("json", Format::Json),
+        ("csv", Format::Csv),
+        ("table", Format::Table),
+    ];
+    for (name, format) in formats {
+        let mut written = Vec::new();
+        output::write_all(&mut Formatter::new(format, &mut written), kept)
+            .await
+            .map_err(|err| format!("{} failed: {}", name, err))?;
+        let written = String::from_utf8_lossy(&written);
+        if let Some(missing) = kept.iter().find(|repo| !written.contains(&repo.name)) {
+            return Err(format!("{} left out {}", name, missing.full_name));
+        }
+        if format == Format::Json {
+            let document: serde_json::Value = serde_json::from_str(&written)
+                .map_err(|err| format!("json is not valid JSON: {}", err))?;
+            let listed = document["repositories"].as_array().map_or(0, Vec::len);
+            if listed != kept.len() {
+                return Err(format!(
+                    "json listed {} repositories, not {}",
+                    listed,
+                    kept.len()
+                ));
+            }
+        }
+    }
+    Ok((
+        (),
+        "plain, json, csv and table list every repository".to_string(),
+    ))
+}
+
+/// The proxy variables set in the environment, by name only, since their
+/// values may carry credentials.
+fn proxy_variables() -> Vec<&'static str> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ]
+    .into_iter()
+    .filter(|name| env::var_os(name).is_some_and(|value| !value.is_empty()))
+    .collect()
+}
+
+/// Starts the stand-in on a free loopback port and returns its URL.
+fn serve() -> Result<String, String> {
+    MockServer::bind(|request, base_url| {
+        let authorized = request
+            .headers
+            .get("authorization")
+            .is_some_and(|value| *value == format!("Bearer {}", TOKEN));
+        respond(&request.path, authorized, base_url)
+    })
+    .map(|server| server.url)
+    .map_err(|err| format!("could not listen: {}", err))
+}
+
+/// The answer to a request for `path`.
+fn respond(path: &str, authorized: bool, base_url: &str) -> Response {
+    let message = |message: &str| serde_json::json!({ "message": message }).to_string();
+    if !authorized {
+        return (401, Vec::new(), message("Bad credentials"));
+    }
+    let Some(query) = path.strip_prefix("/search/repositories?") else {
+        return (404, Vec::new(), message("Not Found"));
+    };
+    let page: usize = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("page="))
+        .and_then(|page| page.parse().ok())
+        .unwrap_or(1)
+        .max(1);
+    let pages = REPOSITORIES.len().div_ceil(PER_PAGE);
+    let items: Vec<serde_json::Value> = REPOSITORIES
+        .iter()
+        .skip((page - 1) * PER_PAGE)
+        .take(PER_PAGE)
+        .map(|(name, language, stars)| {
+            serde_json::json!({
+                "name": name,
+                "full_name": format!("selftest/{}", name),
+                "description": format!("The {} repository", name),
+                "html_url": format!("https://github.com/selftest/{}", name),
+                "language": language,
+                "stargazers_count": stars,
+                "pushed_at": "2026-10-01T12:00:00Z",
+            })
+        })
+        .collect();
+    let link = (page == 1).then(|| {
+        let link = format!(
+            "<{}/search/repositories?q={}&per_page={}&page={}>; rel=\"last\"",
+            base_url, QUERY, PER_PAGE, pages
+        );
+        ("link", link)
+    });
+    let body = serde_json::json!({
+        "total_count": REPOSITORIES.len(),
+        "incomplete_results": false,
+        "items": items,
+    });
+    (200, link.into_iter().collect(), body.to_string())
+}
@@ -0,0 +1,77 @@
+use crate::triage::Change;
+use ghs::{BoxError, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// A TUI browsing session: the results being triaged and the state built up
+/// on top of them, so the work can be picked up again another day with
+/// `ghs tui --session NAME`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(skip)]
+    pub name: Option<String>,
+    pub query: String,
+    pub fetched_at: String,
+    #[serde(default)]
+    pub host: String,
+    pub repositories: Vec<Repository>,
+    /// Full names of the marked repositories.
+    #[serde(default)]
+    pub marks: BTreeSet<String>,
+    /// Local tags by full name; they never leave this machine.
+    #[serde(default)]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    pub cursor: usize,
+    #[serde(default)]
+    pub offset: usize,
+    /// What `ghs triage` found new or changed since the session was last
+    /// saved; the TUI starts out showing only these.
+    #[serde(skip)]
+    pub changes: BTreeMap<String, Change>,
+}
+
+impl Session {
+    /// `$XDG_DATA_HOME/ghs/sessions`, falling back to
+    /// `~/.local/share/ghs/sessions`.
+    pub fn dir() -> Option<PathBuf> {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .map(|dir| dir.join("ghs").join("sessions"))
+    }
+
+    fn path(name: &str) -> Result<PathBuf, BoxError> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("`{}` is not a valid session name", name).into());
+        }
+        let dir = Session::dir().ok_or("cannot find a directory for sessions; set HOME")?;
+        Ok(dir.join(format!("{}.json", name)))
+    }
+
+    pub fn load(name: &str) -> Result<Session, BoxError> {
+        let path = Session::path(name)?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("could not read session {}: {}", path.display(), err))?;
+        let mut session: Session = serde_json::from_str(&contents)?;
+        session.name = Some(name.to_string());
+        Ok(session)
+    }
+
+    /// Saves under `name`, which becomes the session's name.
+    pub fn save_as(&mut self, name: &str) -> Result<PathBuf, BoxError> {
+        let path = Session::path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .map_err(|err| format!("could not write session {}: {}", path.display(), err))?;
+        self.name = Some(name.to_string());
+        Ok(path)
+    }
+}
@@ -0,0 +1,417 @@
+//! Destinations for search results. [`stream`] pages through a search and
+//! hands each page to an [`OutputSink`], so results can be written out as
+//! they arrive rather than once every page is in memory.
+
+use crate::provider::{BoxFuture, SearchProvider};
+use crate::{datetime, BoxError, Repository};
+use reqwest::Client;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::io::Write;
+use std::path::Path;
+
+/// The version of ghs that writes the rows.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Somewhere search results are written, a batch at a time.
+pub trait OutputSink {
+    /// Writes the next batch of results.
+    fn write<'a>(
+        &'a mut self,
+        repositories: &'a [Repository],
+    ) -> BoxFuture<'a, Result<(), BoxError>>;
+
+    /// Called once after the last batch, to flush or close the destination.
+    fn finish(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Searches `query` on `provider` a page at a time, writing the repositories
+/// `keep` accepts to `sink` as each page arrives. Stops at the last page or
+/// the provider's result cap, whichever comes first, and returns how many
+/// repositories were written.
+pub async fn stream(
+    provider: &dyn SearchProvider,
+    query: &str,
+    per_page: u8,
+    keep: &dyn Fn(&Repository) -> bool,
+    sink: &mut dyn OutputSink,
+) -> Result<usize, BoxError> {
+    let mut fetched = 0;
+    let mut written = 0;
+    let mut number = 1;
+    loop {
+        let page = provider.search_page(query, per_page, number).await?;
+        let items = page.repositories.items;
+        fetched += items.len() as u64;
+        let kept: Vec<Repository> = items.iter().filter(|repo| keep(repo)).cloned().collect();
+        if !kept.is_empty() {
+            sink.write(&kept).await?;
+            written += kept.len();
+        }
+        let available = match provider.result_cap() {
+            Some(cap) => page.repositories.total_count.min(cap),
+            None => page.repositories.total_count,
+        };
+        if items.is_empty() || fetched >= available {
+            break;
+        }
+        number += 1;
+    }
+    sink.finish().await?;
+    Ok(written)
+}
+
+/// One JSON object per line, to any writer: stdout, a file, a socket.
+pub struct JsonLines<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(writer: W) -> JsonLines<W> {
+        JsonLines { writer }
+    }
+}
+
+impl<W: Write> OutputSink for JsonLines<W> {
+    fn write<'a>(
+        &'a mut self,
+        repositories: &'a [Repository],
+    ) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            for repo in repositories {
+                serde_json::to_writer(&mut self.writer, repo)?;
+                self.writer.write_all(b"\n")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn finish(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move { Ok(self.writer.flush()?) })
+    }
+}
+
+/// POSTs each batch as a JSON array to a URL, e.g. a chat or CI webhook.
+/// Only the URL is contacted; no GitHub token is sent along.
+pub struct Webhook {
+    client: Client,
+    url: String,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>) -> Webhook {
+        Webhook {
+            client: Client::new(),
+            url: url.into(),
+        }
+    }
+}
+
+impl OutputSink for Webhook {
+    fn write<'a>(
+        &'a mut self,
+        repositories: &'a [Repository],
+    ) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&self.url)
+                .json(repositories)
+                .send()
+                .await
+                .map_err(|err| format!("could not reach webhook {}: {}", self.url, err))?;
+            if !response.status().is_success() {
+                return Err(format!("webhook {} answered {}", self.url, response.status()).into());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Columns of the `repositories` table, after `full_name`.
+const SQLITE_COLUMNS: [&str; 11] = [
+    "name",
+    "owner",
+    "description",
+    "html_url",
+    "language",
+    "stargazers_count",
+    "forks_count",
+    "open_issues_count",
+    "archived",
+    "topics",
+    "pushed_at",
+];
+
+/// Columns both tables end in, saying which run wrote the row. Databases
+/// from before they existed get them added, empty for the old rows.
+const PROVENANCE_COLUMNS: [&str; 3] = ["query", "ghs_version", "host"];
+
+/// Which search filled a [`Sqlite`] database, and from where.
+#[derive(Debug, Clone, Default)]
+pub struct SqliteProvenance {
+    pub query: String,
+    pub host: String,
+}
+
+/// Rows of a `repositories` table in an SQLite database, keyed by
+/// `full_name` so repeated runs update rows instead of duplicating them.
+/// Each run also adds its repositories' languages, stars and forks to a
+/// `snapshots` table, keyed by the time it started, so that changes can be
+/// followed over time. Rows in both record the query, ghs version and host
+/// that wrote them.
+///
+/// SQLite is built in, so nothing needs to be installed; the rows are
+/// committed together by [`OutputSink::finish`].
+pub struct Sqlite {
+    connection: Connection,
+    provenance: SqliteProvenance,
+    taken_at: i64,
+}
+
+impl Sqlite {
+    /// Opens (or creates) the database at `path`, whose rows will record
+    /// `provenance`.
+    pub fn open(path: &Path, provenance: SqliteProvenance) -> Result<Sqlite, BoxError> {
+        let connection = Connection::open(path)
+            .map_err(|err| format!("could not open {}: {}", path.display(), err))?;
+        let columns: Vec<String> = SQLITE_COLUMNS
+            .iter()
+            .map(|column| format!("{} {}", column, sqlite_type(column)))
+            .collect();
+        connection.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS repositories (full_name TEXT PRIMARY KEY, {});\n\
+             CREATE TABLE IF NOT EXISTS snapshots (taken_at INTEGER NOT NULL, \
+             full_name TEXT NOT NULL, owner TEXT, language TEXT, stargazers_count INTEGER, \
+             forks_count INTEGER, PRIMARY KEY (taken_at, full_name));",
+            columns.join(", ")
+        ))?;
+        for table in ["repositories", "snapshots"] {
+            add_provenance_columns(&connection, table)?;
+        }
+        connection.execute_batch("BEGIN;")?;
+        Ok(Sqlite {
+            connection,
+            provenance,
+            taken_at: datetime::now(),
+        })
+    }
+
+    /// Runs one `SELECT` against the database at `path`, without writing to
+    /// it, and returns its rows. `?1`, `?2` and so on in `sql` are bound to
+    /// `params`. `NULL`s read as empty strings.
+    pub fn query(path: &Path, sql: &str, params: &[&str]) -> Result<Vec<Vec<String>>, BoxError> {
+        let read_error =
+            |err: rusqlite::Error| format!("could not read {}: {}", path.display(), err);
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(read_error)?;
+        let mut statement = connection.prepare(sql).map_err(read_error)?;
+        let columns = statement.column_count();
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                (0..columns)
+                    .map(|index| {
+                        Ok(match row.get_ref(index)? {
+                            ValueRef::Null => String::new(),
+                            ValueRef::Integer(value) => value.to_string(),
+                            ValueRef::Real(value) => value.to_string(),
+                            ValueRef::Text(text) | ValueRef::Blob(text) => {
+                                String::from_utf8_lossy(text).into_owned()
+                            }
+                        })
+                    })
+                    .collect()
+            })
+            .map_err(read_error)?;
+        Ok(rows.collect::<Result<_, _>>().map_err(read_error)?)
+    }
+
+    /// Writes `repo`'s row and its snapshot for this run.
+    fn insert(&self, repo: &Repository) -> rusqlite::Result<()> {
+        let owner = repo.owner.as_ref().map(|owner| owner.login.as_str());
+        let placeholders: Vec<String> = (1..=SQLITE_COLUMNS.len() + PROVENANCE_COLUMNS.len() + 1)
+            .map(|index| format!("?{}", index))
+            .collect();
+        self.connection.execute(
+            &format!(
+                "INSERT OR REPLACE INTO repositories (full_name, {}, {}) VALUES ({})",
+                SQLITE_COLUMNS.join(", "),
+                PROVENANCE_COLUMNS.join(", "),
+                placeholders.join(", ")
+            ),
+            rusqlite::params![
+                repo.full_name,
+                repo.name,
+                owner,
+                repo.description,
+                repo.html_url,
+                repo.language,
+                repo.stargazers_count,
+                repo.forks_count,
+                repo.open_issues_count,
+                repo.archived,
+                repo.topics.join(","),
+                repo.pushed_at,
+                self.provenance.query,
+                VERSION,
+                self.provenance.host,
+            ],
+        )?;
+        self.connection.execute(
+            &format!(
+                "INSERT OR REPLACE INTO snapshots (taken_at, full_name, owner, language, \
+                 stargazers_count, forks_count, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                PROVENANCE_COLUMNS.join(", ")
+            ),
+            rusqlite::params![
+                self.taken_at,
+                repo.full_name,
+                owner,
+                repo.language,
+                repo.stargazers_count,
+                repo.forks_count,
+                self.provenance.query,
+                VERSION,
+                self.provenance.host,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn sqlite_type(column: &str) -> &'static str {
+    match column {
+        "stargazers_count" | "forks_count" | "open_issues_count" | "archived" => "INTEGER",
+        _ => "TEXT",
+    }
+}
+
+/// Adds whichever of [`PROVENANCE_COLUMNS`] `table` lacks.
+fn add_provenance_columns(connection: &Connection, table: &str) -> rusqlite::Result<()> {
+    let mut statement = connection.prepare(&format!("PRAGMA table_info({})", table))?;
+    let existing: Vec<String> = statement
+        .query_map([], |row| row.get(1))?
+        .collect::<Result<_, _>>()?;
+    for column in PROVENANCE_COLUMNS {
+        if !existing.iter().any(|existing| existing == column) {
+            connection.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} TEXT;",
+                table, column
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+impl OutputSink for Sqlite {
+    fn write<'a>(
+        &'a mut self,
+        repositories: &'a [Repository],
+    ) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            for repo in repositories {
+                self.insert(repo)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn finish(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move { Ok(self.connection.execute_batch("COMMIT;")?) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_rows_with_their_provenance() {
+        let path = std::env::temp_dir().join(format!("ghs-sink-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut repo: Repository =
+            serde_json::from_str(r#"{"name": "it's", "full_name": "a/it's"}"#).unwrap();
+        repo.stargazers_count = 3;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let provenance = SqliteProvenance {
+            query: "user:a 'quoted'".to_string(),
+            host: "https://api.github.com".to_string(),
+        };
+        for _ in 0..2 {
+            let mut sink = Sqlite::open(&path, provenance.clone()).unwrap();
+            runtime
+                .block_on(async {
+                    sink.write(std::slice::from_ref(&repo)).await?;
+                    sink.finish().await
+                })
+                .unwrap();
+        }
+
+        let rows = Sqlite::query(
+            &path,
+            "SELECT name, description, stargazers_count, query, ghs_version FROM repositories \
+             WHERE full_name = ?1",
+            &["a/it's"],
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            [["it's", "", "3", "user:a 'quoted'", VERSION].map(String::from)]
+        );
+        // Both runs were snapshotted, unless they fell in the same second.
+        let snapshots = Sqlite::query(&path, "SELECT host FROM snapshots", &[]).unwrap();
+        assert!(!snapshots.is_empty());
+        assert!(snapshots
+            .iter()
+            .all(|row| row[0] == "https://api.github.com"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn adds_provenance_columns_to_older_databases() {
+        let path = std::env::temp_dir().join(format!("ghs-sink-old-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE repositories (full_name TEXT PRIMARY KEY, name TEXT, owner TEXT, \
+                 description TEXT, html_url TEXT, language TEXT, stargazers_count INTEGER, \
+                 forks_count INTEGER, open_issues_count INTEGER, archived INTEGER, topics TEXT, \
+                 pushed_at TEXT);\n\
+                 INSERT INTO repositories (full_name, name) VALUES ('a/old', 'old');",
+            )
+            .unwrap();
+        let mut sink = Sqlite::open(&path, SqliteProvenance::default()).unwrap();
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(sink.finish())
+            .unwrap();
+        let rows = Sqlite::query(&path, "SELECT name, host FROM repositories", &[]).unwrap();
+        assert_eq!(rows, [["old", ""].map(String::from)]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn writes_json_lines() {
+        let repo: Repository = serde_json::from_str(r#"{"name": "a"}"#).unwrap();
+        let mut out = Vec::new();
+        let mut sink = JsonLines::new(&mut out);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(async {
+                sink.write(&[repo.clone(), repo]).await?;
+                sink.finish().await
+            })
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with("{\"name\":\"a\","));
+    }
+}
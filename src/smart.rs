@@ -0,0 +1,119 @@
+//! `--sort smart`: the results most likely to be what was looked for first.
+
+use crate::config::{Config, Value};
+use ghs::{datetime, BoxError, Repository};
+
+/// After this many days without a push a repository has lost half of its
+/// recency.
+const RECENCY_HALF_DAYS: f64 = 180.0;
+
+/// How much each signal counts, from the config's `[sort.smart]` section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    /// The repository's main language is the one searched for.
+    pub language: f64,
+    pub not_fork: f64,
+    pub not_archived: f64,
+    /// Pushed to recently.
+    pub recency: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            language: 3.0,
+            not_fork: 1.0,
+            not_archived: 2.0,
+            recency: 2.0,
+        }
+    }
+}
+
+impl Weights {
+    /// The defaults with the `[sort.smart]` section's keys in place, e.g.
+    /// `recency = 4` to favor active repositories more.
+    pub fn from_config(config: &Config) -> Result<Weights, BoxError> {
+        let mut weights = Weights::default();
+        for (key, value) in config.section("sort.smart").into_iter().flatten() {
+            let weight = match value {
+                Value::Integer(weight) => *weight as f64,
+                Value::Float(weight) => *weight,
+                _ => {
+                    return Err(
+                        format!("sort.smart: `{}` must be a number, not `{}`", key, value).into(),
+                    )
+                }
+            };
+            let slot = match key.as_str() {
+                "language" => &mut weights.language,
+                "not_fork" => &mut weights.not_fork,
+                "not_archived" => &mut weights.not_archived,
+                "recency" => &mut weights.recency,
+                _ => {
+                    return Err(format!(
+                        "sort.smart: `{}` is not a weight (expected language, not_fork, not_archived or recency)",
+                        key
+                    )
+                    .into())
+                }
+            };
+            *slot = weight;
+        }
+        Ok(weights)
+    }
+
+    /// The weighted sum of the signals, each from 0 to 1. The language only
+    /// counts when one was searched for.
+    fn score(&self, repo: &Repository, language: Option<&str>, now: i64) -> f64 {
+        let signal = |holds: bool| if holds { 1.0 } else { 0.0 };
+        let language_match = match (language, repo.language.as_deref()) {
+            (Some(wanted), Some(language)) => signal(wanted.eq_ignore_ascii_case(language)),
+            _ => 0.0,
+        };
+        let recency = repo
+            .pushed_at
+            .as_deref()
+            .and_then(datetime::parse_timestamp)
+            .map_or(0.0, |pushed_at| {
+                let days = (now - pushed_at).max(0) as f64 / 86_400.0;
+                1.0 / (1.0 + days / RECENCY_HALF_DAYS)
+            });
+        self.language * language_match
+            + self.not_fork * signal(!repo.fork)
+            + self.not_archived * signal(!repo.archived)
+            + self.recency * recency
+    }
+}
+
+/// The language a search asked for: `--language`, else the query's
+/// `language:` qualifier.
+pub fn wanted_language(filter: Option<&str>, query: &str) -> Option<String> {
+    filter
+        .or_else(|| {
+            query
+                .split_whitespace()
+                .find_map(|word| word.strip_prefix("language:"))
+                .map(|language| language.trim_matches('"'))
+        })
+        .map(String::from)
+}
+
+/// What `--sort smart` scores by in a run: the config's weights and the
+/// language searched for.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub weights: Weights,
+    pub language: Option<String>,
+}
+
+impl Settings {
+    /// How high each of `repositories` ranks, in their order. Recency is
+    /// measured from one moment for all of them.
+    pub fn scores(&self, repositories: &[Repository]) -> Vec<f64> {
+        let now = datetime::now();
+        repositories
+            .iter()
+            .map(|repo| self.weights.score(repo, self.language.as_deref(), now))
+            .collect()
+    }
+}
@@ -0,0 +1,155 @@
+use crate::smart;
+use ghs::Repository;
+use std::cmp::Ordering;
+
+/// What `--sort` orders results by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Stars,
+    Forks,
+    Updated,
+    Name,
+    /// The `--score` of each result.
+    Score,
+    /// Language matches and active, original repositories first; see
+    /// [`smart::Settings`].
+    Smart,
+}
+
+impl SortKey {
+    pub fn parse(key: &str) -> Result<SortKey, String> {
+        match key {
+            "stars" => Ok(SortKey::Stars),
+            "forks" => Ok(SortKey::Forks),
+            "updated" => Ok(SortKey::Updated),
+            "name" => Ok(SortKey::Name),
+            "score" => Ok(SortKey::Score),
+            "smart" => Ok(SortKey::Smart),
+            _ => Err(format!(
+                "`{}` is not a sort key (expected stars, forks, updated, name, score or smart)",
+                key
+            )),
+        }
+    }
+
+    /// Counts and dates read best from the top down, names from A to Z.
+    pub fn descending_by_default(self) -> bool {
+        self != SortKey::Name
+    }
+
+    fn compare(self, a: &Repository, b: &Repository) -> Ordering {
+        match self {
+            SortKey::Stars => a.stargazers_count.cmp(&b.stargazers_count),
+            SortKey::Forks => a.forks_count.cmp(&b.forks_count),
+            // Timestamps are all in the same ISO 8601 form, so they sort as text.
+            SortKey::Updated => a.updated_at.cmp(&b.updated_at),
+            SortKey::Name => a.full_name.to_lowercase().cmp(&b.full_name.to_lowercase()),
+            SortKey::Score => a
+                .score
+                .unwrap_or_default()
+                .total_cmp(&b.score.unwrap_or_default()),
+            // Scored up front by `sort_repositories`.
+            SortKey::Smart => Ordering::Equal,
+        }
+    }
+}
+
+/// Sorts by `key`, keeping the search's order for ties. Repositories without
+/// an update time come last either way; `smart` scores `SortKey::Smart`.
+pub fn sort_repositories(
+    repositories: &mut Vec<Repository>,
+    key: SortKey,
+    descending: bool,
+    smart: &smart::Settings,
+) {
+    if key == SortKey::Smart {
+        let mut scored: Vec<(f64, Repository)> = smart
+            .scores(repositories)
+            .into_iter()
+            .zip(repositories.drain(..))
+            .collect();
+        scored.sort_by(|a, b| {
+            let ordering = a.0.total_cmp(&b.0);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        repositories.extend(scored.into_iter().map(|(_, repo)| repo));
+        return;
+    }
+    repositories.sort_by(|a, b| {
+        if key == SortKey::Updated {
+            match (&a.updated_at, &b.updated_at) {
+                (None, Some(_)) => return Ordering::Greater,
+                (Some(_), None) => return Ordering::Less,
+                _ => {}
+            }
+        }
+        let ordering = key.compare(a, b);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(name: &str, language: &str, fork: bool) -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "full_name": format!("octo/{}", name),
+            "language": language,
+            "fork": fork,
+        }))
+        .unwrap()
+    }
+
+    fn names(repositories: &[Repository]) -> Vec<&str> {
+        repositories.iter().map(|repo| repo.name.as_str()).collect()
+    }
+
+    #[test]
+    fn smart_sorts_by_the_settings_it_is_given() {
+        let repositories = vec![
+            repo("go-fork", "Go", true),
+            repo("rust-fork", "Rust", true),
+            repo("go", "Go", false),
+            repo("rust", "Rust", false),
+        ];
+        let mut sorted = repositories.clone();
+        sort_repositories(
+            &mut sorted,
+            SortKey::Smart,
+            true,
+            &smart::Settings::default(),
+        );
+        // Without a language, only forks differ; ties keep their order.
+        assert_eq!(names(&sorted), ["go", "rust", "go-fork", "rust-fork"]);
+
+        let rust = smart::Settings {
+            language: Some("rust".to_string()),
+            ..smart::Settings::default()
+        };
+        let mut sorted = repositories.clone();
+        sort_repositories(&mut sorted, SortKey::Smart, true, &rust);
+        assert_eq!(names(&sorted), ["rust", "rust-fork", "go", "go-fork"]);
+
+        // Ascending, with not being a fork weighing more than the language.
+        let originals = smart::Settings {
+            weights: smart::Weights {
+                not_fork: 5.0,
+                ..smart::Weights::default()
+            },
+            ..rust
+        };
+        let mut sorted = repositories;
+        sort_repositories(&mut sorted, SortKey::Smart, false, &originals);
+        assert_eq!(names(&sorted), ["go-fork", "rust-fork", "go", "rust"]);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::circuit::CircuitBreaker;
+use ghs::{BoxError, GhsClient, Repository};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// How many repositories may be checked at once.
+const CONCURRENCY: usize = 8;
+
+/// Sets `starred` on each of `repositories` to whether the authenticated
+/// user has starred it. Repositories that could not be checked are left
+/// unmarked.
+pub async fn annotate(api: &GhsClient, repositories: &mut [Repository]) -> Result<(), BoxError> {
+    let semaphore = Arc::new(Semaphore::new(CONCURRENCY));
+    let breaker = Arc::new(CircuitBreaker::new());
+    let mut tasks = JoinSet::new();
+    for repo in repositories.iter() {
+        let api = api.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let breaker = Arc::clone(&breaker);
+        let full_name = repo.full_name.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let starred = breaker
+                .call(
+                    &format!("whether you starred {}", full_name),
+                    api.is_starred(&full_name),
+                )
+                .await?;
+            Ok::<_, BoxError>((full_name, starred))
+        });
+    }
+
+    let mut starred = HashMap::new();
+    while let Some(result) = tasks.join_next().await {
+        if let (full_name, Some(is_starred)) = result?? {
+            starred.insert(full_name, is_starred);
+        }
+    }
+    for repo in repositories {
+        repo.starred = starred.get(&repo.full_name).copied();
+    }
+    Ok(())
+}
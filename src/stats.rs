@@ -0,0 +1,188 @@
+use crate::deprecation::Deprecations;
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Runs with more requests than this get a percentile summary instead of one
+/// line per request.
+const LISTED_REQUESTS: usize = 10;
+
+/// Everything collected during a run for the `--stats` reports, along with
+/// any deprecation notices the API sent.
+#[derive(Debug)]
+pub struct Stats {
+    pub timings: Timings,
+    pub quota: Quota,
+    pub deprecations: Deprecations,
+}
+
+impl Stats {
+    pub fn start() -> Self {
+        Stats {
+            timings: Timings::start(),
+            quota: Quota::default(),
+            deprecations: Deprecations::default(),
+        }
+    }
+}
+
+/// Wall-clock timings collected during a run and reported by `--stats timing`.
+#[derive(Debug)]
+pub struct Timings {
+    started: Instant,
+    requests: Vec<Duration>,
+    rate_limit_wait: Duration,
+    serialization: Duration,
+}
+
+impl Timings {
+    pub fn start() -> Self {
+        Timings {
+            started: Instant::now(),
+            requests: Vec::new(),
+            rate_limit_wait: Duration::ZERO,
+            serialization: Duration::ZERO,
+        }
+    }
+
+    pub fn record_request(&mut self, elapsed: Duration) {
+        self.requests.push(elapsed);
+    }
+
+    /// How many requests were sent so far.
+    pub fn request_count(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn record_rate_limit_wait(&mut self, waited: Duration) {
+        self.rate_limit_wait += waited;
+    }
+
+    pub fn record_serialization(&mut self, elapsed: Duration) {
+        self.serialization += elapsed;
+    }
+
+    /// Runs `f` and adds its duration to the serialization total.
+    pub fn time_serialization<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.serialization += start.elapsed();
+        value
+    }
+
+    pub fn report(&self) -> String {
+        let total: Duration = self.requests.iter().sum();
+        let average = if self.requests.is_empty() {
+            Duration::ZERO
+        } else {
+            total / self.requests.len() as u32
+        };
+
+        let mut report = String::from("--- timing ---\n");
+        report.push_str(&format!(
+            "Total wall time: {}\n",
+            format_duration(self.started.elapsed())
+        ));
+        report.push_str(&format!(
+            "Requests: {} (total {}, average {})\n",
+            self.requests.len(),
+            format_duration(total),
+            format_duration(average)
+        ));
+        if self.requests.len() <= LISTED_REQUESTS {
+            for (index, elapsed) in self.requests.iter().enumerate() {
+                report.push_str(&format!(
+                    "  #{}: {}\n",
+                    index + 1,
+                    format_duration(*elapsed)
+                ));
+            }
+        } else {
+            let mut sorted = self.requests.clone();
+            sorted.sort();
+            report.push_str(&format!(
+                "  min {}, median {}, p95 {}, max {}\n",
+                format_duration(sorted[0]),
+                format_duration(percentile(&sorted, 50)),
+                format_duration(percentile(&sorted, 95)),
+                format_duration(sorted[sorted.len() - 1])
+            ));
+        }
+        report.push_str(&format!(
+            "Rate limit wait: {}\n",
+            format_duration(self.rate_limit_wait)
+        ));
+        report.push_str(&format!(
+            "Serialization: {}\n",
+            format_duration(self.serialization)
+        ));
+        report
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// The `p`th percentile of `sorted`, which must not be empty.
+pub fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    sorted[(sorted.len() - 1) * p / 100]
+}
+
+/// API calls consumed per rate-limit resource, reported by `--stats quota`.
+#[derive(Debug, Default)]
+pub struct Quota {
+    resources: BTreeMap<String, ResourceUsage>,
+}
+
+#[derive(Debug, Default)]
+struct ResourceUsage {
+    calls: u32,
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    reset: Option<u64>,
+}
+
+impl Quota {
+    /// Counts one call against the resource named in the response headers and
+    /// remembers the most recent `X-RateLimit-*` values for it.
+    pub fn record(&mut self, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let number = |name: &str| header(name).and_then(|value| value.parse::<u64>().ok());
+
+        let resource = header("x-ratelimit-resource").unwrap_or("core");
+        let usage = self.resources.entry(resource.to_string()).or_default();
+        usage.calls += 1;
+        usage.limit = number("x-ratelimit-limit").or(usage.limit);
+        usage.remaining = number("x-ratelimit-remaining").or(usage.remaining);
+        usage.reset = number("x-ratelimit-reset").or(usage.reset);
+    }
+
+    pub fn report(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        let mut report = String::from("--- quota ---\n");
+        if self.resources.is_empty() {
+            report.push_str("No API calls made\n");
+        }
+        for (resource, usage) in &self.resources {
+            let remaining = match (usage.remaining, usage.limit) {
+                (Some(remaining), Some(limit)) => format!("{}/{} remaining", remaining, limit),
+                (Some(remaining), None) => format!("{} remaining", remaining),
+                _ => "remaining unknown".to_string(),
+            };
+            let reset = usage
+                .reset
+                .map(|reset| format!(", resets in {}s", reset.saturating_sub(now)))
+                .unwrap_or_default();
+            report.push_str(&format!(
+                "{}: {} call(s), {}{}\n",
+                resource, usage.calls, remaining, reset
+            ));
+        }
+        report
+    }
+}
@@ -0,0 +1,78 @@
+use ghs::parquet::{self, Column, Values};
+use ghs::sink::Sqlite;
+use ghs::BoxError;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// The tables `--output sqlite:PATH` writes.
+pub const TABLES: [&str; 2] = ["snapshots", "repositories"];
+
+/// Writes `table` of the SQLite store at `db` to a Parquet file at `path`
+/// and returns how many rows it held. INTEGER columns become 64-bit
+/// integers, `taken_at` a timestamp, and the rest strings.
+pub fn export_parquet(db: &Path, table: &str, path: &Path) -> Result<usize, BoxError> {
+    let schema = Sqlite::query(db, "SELECT name, type FROM pragma_table_info(?1)", &[table])?;
+    if schema.is_empty() {
+        return Err(format!(
+            "{} has no {} table; fill it with `ghs ... --output sqlite:{}`",
+            db.display(),
+            table,
+            db.display()
+        )
+        .into());
+    }
+    // Rows read NULL and '' alike, but `'v' || x` is only NULL when x is,
+    // so prefixed values tell them apart.
+    let select: Vec<String> = schema
+        .iter()
+        .map(|column| format!("'v' || \"{}\"", column[0]))
+        .collect();
+    let rows = Sqlite::query(
+        db,
+        &format!("SELECT {} FROM {}", select.join(", "), table),
+        &[],
+    )?;
+
+    let mut columns = Vec::new();
+    for (index, column) in schema.iter().enumerate() {
+        let [name, kind] = column.as_slice() else {
+            continue;
+        };
+        let cells = rows
+            .iter()
+            .map(|row| row.get(index).and_then(|cell| cell.strip_prefix('v')));
+        let numbers = || -> Result<Vec<Option<i64>>, BoxError> {
+            cells
+                .clone()
+                .map(|cell| {
+                    cell.map(|cell| {
+                        cell.parse().map_err(|_| {
+                            format!("{}.{} holds `{}`, not a number", table, name, cell)
+                        })
+                    })
+                    .transpose()
+                })
+                .collect::<Result<_, _>>()
+                .map_err(Into::into)
+        };
+        let values = match (name.as_str(), kind.as_str()) {
+            ("taken_at", _) => Values::Timestamp(numbers()?),
+            (_, "INTEGER") => Values::Int64(numbers()?),
+            _ => Values::Text(cells.clone().map(|cell| cell.map(String::from)).collect()),
+        };
+        columns.push(Column {
+            name: name.clone(),
+            values,
+        });
+    }
+
+    let file = File::create(path)
+        .map_err(|err| format!("could not create {}: {}", path.display(), err))?;
+    parquet::write(
+        BufWriter::new(file),
+        &columns,
+        concat!("ghs version ", env!("CARGO_PKG_VERSION")),
+    )?;
+    Ok(rows.len())
+}
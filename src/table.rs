@@ -0,0 +1,75 @@
+use crate::plain;
+
+/// Renders rows as left-aligned columns separated by two spaces. The first
+/// row is treated as the header and underlined, except in plain mode where
+/// a line of dashes is only noise. Cells may contain ANSI color sequences;
+/// they do not count towards the width.
+pub fn render(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(width(cell));
+        }
+    }
+
+    let mut output = String::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        output.push_str(&render_row(row, &widths));
+        output.push('\n');
+        if row_index == 0 && !plain::enabled() {
+            let underline: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            output.push_str(&render_row(&underline, &widths));
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn render_row(row: &[String], widths: &[usize]) -> String {
+    let cells: Vec<String> = row
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| {
+            let padding = width.saturating_sub(self::width(cell));
+            format!("{}{}", cell, " ".repeat(padding))
+        })
+        .collect();
+    cells.join("  ").trim_end().to_string()
+}
+
+/// The number of columns `cell` takes up on screen, skipping SGR escape
+/// sequences such as those written by `color::paint`, and counting two for
+/// the wide characters of Chinese, Japanese and Korean.
+fn width(cell: &str) -> usize {
+    let mut width = 0;
+    let mut chars = cell.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            width += if is_wide(c) { 2 } else { 1 };
+        }
+    }
+    width
+}
+
+/// Whether terminals draw `c` two columns wide: the East Asian Wide and
+/// Fullwidth blocks, roughly.
+fn is_wide(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115f
+            | 0x2e80..=0x303e
+            | 0x3041..=0x33ff
+            | 0x3400..=0x4dbf
+            | 0x4e00..=0x9fff
+            | 0xa000..=0xa4cf
+            | 0xac00..=0xd7a3
+            | 0xf900..=0xfaff
+            | 0xfe30..=0xfe4f
+            | 0xff00..=0xff60
+            | 0xffe0..=0xffe6
+            | 0x20000..=0x3fffd
+    )
+}
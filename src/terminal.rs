@@ -0,0 +1,236 @@
+//! Just enough terminal handling for the full-screen TUI: raw mode, the
+//! alternate screen, the window size and decoding key presses. On Windows
+//! the console is switched to virtual terminal input, so key presses arrive
+//! as the same escape sequences as on Unix.
+
+#[cfg(windows)]
+use crate::console::sys;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+/// A key press, decoded from the bytes the terminal sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Esc,
+}
+
+/// Puts the terminal in raw mode on the alternate screen until dropped.
+pub struct Terminal {
+    #[cfg(unix)]
+    original: libc::termios,
+    /// Console modes of stdin and stdout before entering.
+    #[cfg(windows)]
+    original: (u32, u32),
+    /// Keys decoded from a read but not yet returned, e.g. pasted text.
+    pending: VecDeque<Key>,
+}
+
+#[cfg(unix)]
+impl Terminal {
+    pub fn enter() -> io::Result<Terminal> {
+        // SAFETY: tcgetattr/tcsetattr only read and write the termios struct
+        // passed to them, and fd 0 stays open for the life of the process.
+        let original = unsafe {
+            let mut original = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            original
+        };
+
+        let terminal = Terminal {
+            original,
+            pending: VecDeque::new(),
+        };
+        let mut stdout = io::stdout();
+        // Alternate screen, hidden cursor.
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        stdout.flush()?;
+        Ok(terminal)
+    }
+
+    /// `(columns, rows)`, or a conventional 80x24 if the size is unknown.
+    pub fn size(&self) -> (usize, usize) {
+        // SAFETY: TIOCGWINSZ fills in the winsize struct it is given.
+        let size = unsafe {
+            let mut size = std::mem::zeroed::<libc::winsize>();
+            (libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0).then_some(size)
+        };
+        match size {
+            Some(size) if size.ws_col > 0 && size.ws_row > 0 => {
+                (size.ws_col as usize, size.ws_row as usize)
+            }
+            _ => (80, 24),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Terminal {
+    pub fn enter() -> io::Result<Terminal> {
+        // SAFETY: the console functions only read and write the modes passed
+        // to them; failures are reported through their return values.
+        let original = unsafe {
+            let stdin = sys::GetStdHandle(sys::STD_INPUT_HANDLE);
+            let stdout = sys::GetStdHandle(sys::STD_OUTPUT_HANDLE);
+            let (mut input, mut output) = (0, 0);
+            if sys::GetConsoleMode(stdin, &mut input) == 0
+                || sys::GetConsoleMode(stdout, &mut output) == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            // Dropping line input, echo and processed input is raw mode.
+            if sys::SetConsoleMode(stdin, sys::ENABLE_VIRTUAL_TERMINAL_INPUT) == 0
+                || sys::SetConsoleMode(stdout, output | sys::ENABLE_VIRTUAL_TERMINAL_PROCESSING)
+                    == 0
+            {
+                sys::SetConsoleMode(stdin, input);
+                return Err(io::Error::last_os_error());
+            }
+            (input, output)
+        };
+
+        let terminal = Terminal {
+            original,
+            pending: VecDeque::new(),
+        };
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        stdout.flush()?;
+        Ok(terminal)
+    }
+
+    /// `(columns, rows)` of the visible window, or 80x24 if unknown.
+    pub fn size(&self) -> (usize, usize) {
+        let mut info = sys::ScreenBufferInfo::default();
+        // SAFETY: fills in the struct it is given.
+        let known = unsafe {
+            sys::GetConsoleScreenBufferInfo(sys::GetStdHandle(sys::STD_OUTPUT_HANDLE), &mut info)
+                != 0
+        };
+        let columns = info.window.right - info.window.left + 1;
+        let rows = info.window.bottom - info.window.top + 1;
+        if known && columns > 0 && rows > 0 {
+            (columns as usize, rows as usize)
+        } else {
+            (80, 24)
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+impl Terminal {
+    pub fn enter() -> io::Result<Terminal> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the TUI needs a Unix or Windows terminal",
+        ))
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (80, 24)
+    }
+}
+
+impl Terminal {
+    /// Blocks until the next key press.
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        let mut buffer = [0u8; 256];
+        loop {
+            if let Some(key) = self.pending.pop_front() {
+                return Ok(key);
+            }
+            let read = io::stdin().read(&mut buffer)?;
+            if read == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            self.pending.extend(decode(&buffer[..read]));
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        #[cfg(unix)]
+        // SAFETY: restores the settings read in `enter`.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original);
+        }
+        #[cfg(windows)]
+        // SAFETY: restores the modes read in `enter`.
+        unsafe {
+            sys::SetConsoleMode(sys::GetStdHandle(sys::STD_INPUT_HANDLE), self.original.0);
+            sys::SetConsoleMode(sys::GetStdHandle(sys::STD_OUTPUT_HANDLE), self.original.1);
+        }
+    }
+}
+
+/// Decodes the keys in one read from the terminal; unknown escape
+/// sequences are skipped.
+fn decode(mut bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    while let Some(&first) = bytes.first() {
+        let (key, length) = match bytes {
+            [0x1b, b'[' | b'O', rest @ ..] => {
+                // Parameters, then a final byte in `@`..=`~`.
+                let end = rest
+                    .iter()
+                    .position(|byte| (0x40..=0x7e).contains(byte))
+                    .unwrap_or(rest.len().saturating_sub(1));
+                let key = match &rest[..(end + 1).min(rest.len())] {
+                    b"A" => Some(Key::Up),
+                    b"B" => Some(Key::Down),
+                    b"C" => Some(Key::Right),
+                    b"D" => Some(Key::Left),
+                    b"H" | b"1~" | b"7~" => Some(Key::Home),
+                    b"F" | b"4~" | b"8~" => Some(Key::End),
+                    b"5~" => Some(Key::PageUp),
+                    b"6~" => Some(Key::PageDown),
+                    _ => None,
+                };
+                (key, 2 + end + 1)
+            }
+            [0x1b, ..] => (Some(Key::Esc), 1),
+            [b'\r' | b'\n', ..] => (Some(Key::Enter), 1),
+            [0x7f | 0x08, ..] => (Some(Key::Backspace), 1),
+            [byte @ 0x01..=0x1a, ..] => (Some(Key::Ctrl((b'a' + byte - 1) as char)), 1),
+            _ => {
+                let length = match first {
+                    0xf0.. => 4,
+                    0xe0.. => 3,
+                    0xc0.. => 2,
+                    _ => 1,
+                };
+                let length = length.min(bytes.len());
+                let key = std::str::from_utf8(&bytes[..length])
+                    .ok()
+                    .and_then(|text| text.chars().next())
+                    .map(Key::Char);
+                (key, length)
+            }
+        };
+        keys.extend(key);
+        bytes = &bytes[length.min(bytes.len())..];
+    }
+    keys
+}
@@ -0,0 +1,133 @@
+//! A stand-in for the GitHub API on the loopback interface, for checking
+//! a client without a token or the real API: the integration tests use it,
+//! and so does `ghs selftest`. Only built with the `testing` feature.
+//!
+//! It speaks plain HTTP, one request per connection, and answers each with
+//! whatever the closure it was started with returns:
+//!
+//! ```no_run
+//! use ghs::testing::MockServer;
+//!
+//! let server = MockServer::start(|request, _base_url| match request.path.as_str() {
+//!     "/repos/octo/ghs" => (200, Vec::new(), r#"{"name": "ghs", "full_name": "octo/ghs"}"#.into()),
+//!     _ => (404, Vec::new(), r#"{"message": "Not Found"}"#.into()),
+//! });
+//! let api = server.client(None);
+//! ```
+
+use crate::retry::RetryPolicy;
+use crate::GhsClient;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request as the server saw it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// Path and query string.
+    pub path: String,
+    /// Headers by lowercase name.
+    pub headers: HashMap<String, String>,
+}
+
+/// A canned response: status, extra headers and JSON body.
+pub type Response = (u16, Vec<(&'static str, String)>, String);
+
+/// Serves answers on a local port and records every request it gets. It
+/// lives until the process exits.
+#[derive(Debug)]
+pub struct MockServer {
+    /// `http://127.0.0.1:<port>`, to pass to [`GhsClientBuilder::host`](crate::GhsClientBuilder::host).
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Starts a server answering with `respond`, which gets each request
+    /// and the server's URL, for `Link` headers.
+    ///
+    /// # Panics
+    ///
+    /// If no loopback port is free; see [`bind`](Self::bind).
+    pub fn start(respond: impl Fn(&Request, &str) -> Response + Send + 'static) -> MockServer {
+        MockServer::bind(respond).expect("could not listen on the loopback interface")
+    }
+
+    /// [`start`](Self::start), failing instead of panicking.
+    pub fn bind(
+        respond: impl Fn(&Request, &str) -> Response + Send + 'static,
+    ) -> io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        let base_url = url.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A client that hangs up early only fails its own request.
+                let _ = answer(stream, &base_url, &respond, &recorded);
+            }
+        });
+        Ok(MockServer { url, requests })
+    }
+
+    /// Every request answered so far, oldest first.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// A client for this server that does not retry, with `token` if given.
+    pub fn client(&self, token: Option<&str>) -> GhsClient {
+        let builder = GhsClient::builder()
+            .host(&self.url)
+            .retry_policy(RetryPolicy {
+                max_retries: 0,
+                ..RetryPolicy::default()
+            });
+        match token {
+            Some(token) => builder.token(token),
+            None => builder,
+        }
+        .build()
+        .unwrap()
+    }
+}
+
+fn answer(
+    mut stream: TcpStream,
+    base_url: &str,
+    respond: &impl Fn(&Request, &str) -> Response,
+    recorded: &Mutex<Vec<Request>>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let path = line.split(' ').nth(1).unwrap_or_default().to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let Some((name, value)) = header.trim_end().split_once(": ") else {
+            break;
+        };
+        headers.insert(name.to_lowercase(), value.to_string());
+    }
+    let request = Request { path, headers };
+    let (status, extra, body) = respond(&request, base_url);
+    recorded.lock().unwrap().push(request);
+
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\n\
+         content-length: {}\r\nconnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in extra {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    stream.write_all(response.as_bytes())
+}
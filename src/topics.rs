@@ -0,0 +1,143 @@
+use crate::table;
+use ghs::Repository;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// At most this many topics are suggested for one repository.
+const MAX_SUGGESTIONS: usize = 5;
+/// GitHub allows this many topics on a repository.
+const MAX_TOPICS: usize = 20;
+
+/// Topics that a repository might add, and the ones it already has.
+#[derive(Debug)]
+pub struct Suggestion<'a> {
+    pub repo: &'a Repository,
+    pub topics: Vec<String>,
+}
+
+impl Suggestion<'_> {
+    /// `current`, the repository's topics, with the suggested ones added.
+    pub fn combined(&self, current: &[String]) -> Vec<String> {
+        let mut topics = current.to_vec();
+        for topic in &self.topics {
+            if !topics.contains(topic) {
+                topics.push(topic.clone());
+            }
+        }
+        topics.truncate(MAX_TOPICS);
+        topics
+    }
+}
+
+/// The topic for a language, e.g. `cpp` for C++ and `vim-script` for Vim
+/// Script, in GitHub's syntax: lowercase letters, digits and hyphens.
+fn language_topic(language: &str) -> String {
+    let language = language
+        .to_lowercase()
+        .replace("c++", "cpp")
+        .replace('#', "sharp")
+        .replace('+', "plus");
+    words(&language).join("-")
+}
+
+/// The lowercase words of `text`, split at anything but letters and
+/// digits, `camelCase` included.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            previous_lowercase = false;
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && previous_lowercase && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        previous_lowercase = c.is_lowercase();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Suggests topics for each of `repositories` from what the others use:
+/// a topic one of them has fits another whose name or description spells
+/// it out, as `command-line` fits "A command line tool". So does the
+/// topic of a repository's main language. Topics used more widely across
+/// `repositories` come first. Repositories with nothing to add are left out.
+pub fn suggest(repositories: &[Repository]) -> Vec<Suggestion<'_>> {
+    let mut usage: BTreeMap<String, usize> = BTreeMap::new();
+    for repo in repositories {
+        for topic in repo.topics.iter().collect::<BTreeSet<_>>() {
+            *usage.entry(topic.to_lowercase()).or_default() += 1;
+        }
+    }
+    let mut vocabulary: Vec<(&String, &usize)> = usage.iter().collect();
+    vocabulary.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut suggestions = Vec::new();
+    for repo in repositories {
+        if repo.topics.len() >= MAX_TOPICS {
+            continue;
+        }
+        let text = format!(
+            "-{}-{}-",
+            words(&repo.name).join("-"),
+            words(repo.description.as_deref().unwrap_or_default()).join("-")
+        );
+        let has = |topic: &str| {
+            repo.topics
+                .iter()
+                .any(|own| own.eq_ignore_ascii_case(topic))
+        };
+
+        let mut topics = Vec::new();
+        if let Some(language) = repo.language.as_deref().map(language_topic) {
+            if !language.is_empty() && !has(&language) {
+                topics.push(language);
+            }
+        }
+        for (topic, _) in &vocabulary {
+            if !has(topic)
+                && !topics.contains(*topic)
+                && text.contains(&format!("-{}-", words(topic).join("-")))
+            {
+                topics.push(topic.to_string());
+            }
+        }
+        topics.truncate(MAX_SUGGESTIONS.min(MAX_TOPICS - repo.topics.len()));
+        if !topics.is_empty() {
+            suggestions.push(Suggestion { repo, topics });
+        }
+    }
+    suggestions
+}
+
+/// One row per repository: its topics and the suggested ones.
+pub fn print_suggestions(suggestions: &[Suggestion], repositories: usize) {
+    let mut rows = vec![vec![
+        "Repository".to_string(),
+        "Topics".to_string(),
+        "Suggested".to_string(),
+    ]];
+    for suggestion in suggestions {
+        rows.push(vec![
+            suggestion.repo.full_name.clone(),
+            suggestion.repo.topics.join(", "),
+            suggestion.topics.join(", "),
+        ]);
+    }
+    if !suggestions.is_empty() {
+        print!("{}", table::render(&rows));
+    }
+    println!(
+        "Suggested topics for {} of {} repositories",
+        suggestions.len(),
+        repositories
+    );
+}
@@ -0,0 +1,43 @@
+//! `ghs repo transfer`: moves repositories to another owner and checks
+//! that they arrived.
+
+use crate::interrupt;
+use ghs::{BoxError, GhsClient};
+use std::time::Duration;
+
+/// How often to look for a transferred repository under its new name, and
+/// how long to wait in between; GitHub moves it in the background.
+const VERIFY_ATTEMPTS: u32 = 5;
+const VERIFY_DELAY: Duration = Duration::from_secs(2);
+
+/// Transfers `full_name` to `new_owner` and waits until the repository
+/// answers under its new full name, which is returned.
+pub async fn transfer(
+    api: &GhsClient,
+    full_name: &str,
+    new_owner: &str,
+) -> Result<String, BoxError> {
+    interrupt::or_exit(api.transfer_repository(full_name, new_owner)).await?;
+    let name = full_name
+        .split_once('/')
+        .map_or(full_name, |(_, name)| name);
+    let new_full_name = format!("{}/{}", new_owner, name);
+    for attempt in 1..=VERIFY_ATTEMPTS {
+        let repo = interrupt::or_exit(api.repository(&new_full_name)).await?;
+        let arrived = repo
+            .and_then(|repo| repo.owner)
+            .is_some_and(|owner| owner.login.eq_ignore_ascii_case(new_owner));
+        if arrived {
+            return Ok(new_full_name);
+        }
+        if attempt < VERIFY_ATTEMPTS {
+            tokio::time::sleep(VERIFY_DELAY).await;
+        }
+    }
+    Err(format!(
+        "GitHub accepted the transfer of {}, but {} has not appeared yet; \
+         a user receiving it has to accept it first",
+        full_name, new_full_name
+    )
+    .into())
+}
@@ -0,0 +1,385 @@
+use crate::config::{Config, Value};
+use ghs::{datetime, BoxError, Repository};
+
+/// The fields transforms can read and rewrite.
+const FIELDS: [&str; 14] = [
+    "name",
+    "full_name",
+    "description",
+    "html_url",
+    "clone_url",
+    "ssh_url",
+    "homepage",
+    "language",
+    "default_branch",
+    "created_at",
+    "updated_at",
+    "pushed_at",
+    "provider",
+    "query",
+];
+
+/// A field of a repository, possibly passed through functions, e.g.
+/// `truncate(upper(description), 40)`.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Field(&'static str),
+    /// At most this many characters; longer text is cut and ends in `...`.
+    Truncate(Box<Expr>, usize),
+    Upper(Box<Expr>),
+    Lower(Box<Expr>),
+    /// A timestamp formatted by [`datetime::strftime`]; text that is not a
+    /// timestamp is left alone.
+    Date(Box<Expr>, String),
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Expr, String> {
+        let mut parser = Parser { input, rest: input };
+        let expr = parser.expr()?;
+        if !parser.rest.trim().is_empty() {
+            return Err(parser.error("unexpected text after the expression"));
+        }
+        Ok(expr)
+    }
+
+    /// The value for `repo`; `None` if the field is empty.
+    pub fn eval(&self, repo: &Repository) -> Option<String> {
+        match self {
+            Expr::Field(field) => get(repo, field).map(str::to_string),
+            Expr::Truncate(expr, width) => expr.eval(repo).map(|text| truncate(&text, *width)),
+            Expr::Upper(expr) => expr.eval(repo).map(|text| text.to_uppercase()),
+            Expr::Lower(expr) => expr.eval(repo).map(|text| text.to_lowercase()),
+            Expr::Date(expr, format) => expr.eval(repo).map(|text| {
+                datetime::parse_timestamp(&text)
+                    .and_then(|timestamp| datetime::strftime(timestamp, format).ok())
+                    .unwrap_or(text)
+            }),
+        }
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width <= 3 {
+        return text.chars().take(width).collect();
+    }
+    let cut: String = text.chars().take(width - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// A recursive descent parser over what is left of `input`.
+struct Parser<'a> {
+    input: &'a str,
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        let at = self.input.len() - self.rest.len();
+        format!("{} at column {} of `{}`", message, at + 1, self.input)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let name = self.identifier()?;
+        if !self.eat('(') {
+            return FIELDS
+                .iter()
+                .find(|field| **field == name)
+                .map(|field| Expr::Field(field))
+                .ok_or_else(|| {
+                    format!(
+                        "`{}` is not a field transforms can read (expected one of {})",
+                        name,
+                        FIELDS.join(", ")
+                    )
+                });
+        }
+        let inner = Box::new(self.expr()?);
+        let expr = match name.as_str() {
+            "truncate" => {
+                self.expect(',')?;
+                Expr::Truncate(inner, self.number()?)
+            }
+            "upper" => Expr::Upper(inner),
+            "lower" => Expr::Lower(inner),
+            "date" => {
+                self.expect(',')?;
+                let format = self.string()?;
+                datetime::strftime(0, &format)?;
+                Expr::Date(inner, format)
+            }
+            _ => {
+                return Err(format!(
+                    "`{}` is not a transform (expected truncate, upper, lower or date)",
+                    name
+                ))
+            }
+        };
+        self.expect(')')?;
+        Ok(expr)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected `{}`", c))),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(self.error("expected a field or function"));
+        }
+        let (identifier, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(identifier.to_string())
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let number = self.rest[..end]
+            .parse()
+            .map_err(|_| self.error("expected a number"))?;
+        self.rest = &self.rest[end..];
+        Ok(number)
+    }
+
+    /// A double-quoted string, in which `\"` and `\\` stand for `"` and `\`.
+    fn string(&mut self) -> Result<String, String> {
+        if !self.eat('"') {
+            return Err(self.error("expected a quoted string"));
+        }
+        let mut string = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[index + 1..];
+                    return Ok(string);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\'))) => string.push(escaped),
+                    _ => return Err(self.error("only `\\\"` and `\\\\` may follow `\\`")),
+                },
+                c => string.push(c),
+            }
+        }
+        Err(self.error("unclosed string"))
+    }
+}
+
+fn get<'a>(repo: &'a Repository, field: &str) -> Option<&'a str> {
+    match field {
+        "name" => Some(&repo.name),
+        "full_name" => Some(&repo.full_name),
+        "description" => repo.description.as_deref(),
+        "html_url" => repo.html_url.as_deref(),
+        "clone_url" => repo.clone_url.as_deref(),
+        "ssh_url" => repo.ssh_url.as_deref(),
+        "homepage" => repo.homepage.as_deref(),
+        "language" => repo.language.as_deref(),
+        "default_branch" => repo.default_branch.as_deref(),
+        "created_at" => repo.created_at.as_deref(),
+        "updated_at" => repo.updated_at.as_deref(),
+        "pushed_at" => repo.pushed_at.as_deref(),
+        "provider" => repo.provider.as_deref(),
+        "query" => repo.query.as_deref(),
+        _ => unreachable!("`{}` is not in FIELDS", field),
+    }
+}
+
+fn set(repo: &mut Repository, field: &str, value: Option<String>) {
+    let slot = match field {
+        "name" => return repo.name = value.unwrap_or_default(),
+        "full_name" => return repo.full_name = value.unwrap_or_default(),
+        "description" => &mut repo.description,
+        "html_url" => &mut repo.html_url,
+        "clone_url" => &mut repo.clone_url,
+        "ssh_url" => &mut repo.ssh_url,
+        "homepage" => &mut repo.homepage,
+        "language" => &mut repo.language,
+        "default_branch" => &mut repo.default_branch,
+        "created_at" => &mut repo.created_at,
+        "updated_at" => &mut repo.updated_at,
+        "pushed_at" => &mut repo.pushed_at,
+        "provider" => &mut repo.provider,
+        "query" => &mut repo.query,
+        _ => unreachable!("`{}` is not in FIELDS", field),
+    };
+    *slot = value;
+}
+
+/// What to print in place of one field.
+#[derive(Debug, Clone)]
+pub struct Transform {
+    field: &'static str,
+    expr: Expr,
+}
+
+/// Parses a `--transform` value, `FIELD=EXPR`, e.g.
+/// `description=truncate(description, 80)`.
+pub fn parse(arg: &str) -> Result<Transform, String> {
+    let (field, expr) = arg.split_once('=').ok_or_else(|| {
+        format!(
+            "`{}` is not FIELD=EXPR, e.g. description=truncate(description, 80)",
+            arg
+        )
+    })?;
+    transform(field.trim(), expr)
+}
+
+fn transform(field: &str, expr: &str) -> Result<Transform, String> {
+    let Some(field) = FIELDS.iter().find(|known| **known == field) else {
+        return Err(format!(
+            "`{}` cannot be transformed (expected one of {})",
+            field,
+            FIELDS.join(", ")
+        ));
+    };
+    Ok(Transform {
+        field,
+        expr: Expr::parse(expr)?,
+    })
+}
+
+/// The `[transform]` section of the config file, e.g.
+/// `pushed_at = "date(pushed_at, \"%Y-%m-%d\")"`, followed by `args`, so
+/// that a `--transform` replaces the config's for the same field.
+pub fn from_config(config: &Config, args: &[Transform]) -> Result<Vec<Transform>, BoxError> {
+    let mut transforms = Vec::new();
+    for (key, value) in config.section("transform").into_iter().flatten() {
+        let Value::String(expr) = value else {
+            return Err(format!("transform: `{}` must be a string, not `{}`", key, value).into());
+        };
+        let transform = transform(key, expr).map_err(|err| format!("transform: {}", err))?;
+        if !args.iter().any(|arg| arg.field == transform.field) {
+            transforms.push(transform);
+        }
+    }
+    transforms.extend(args.iter().cloned());
+    Ok(transforms)
+}
+
+/// `repo` with `transforms` applied. Every transform reads the fields as
+/// they were, so their order does not matter.
+pub fn apply(repo: &Repository, transforms: &[Transform]) -> Repository {
+    let mut transformed = repo.clone();
+    for transform in transforms {
+        set(&mut transformed, transform.field, transform.expr.eval(repo));
+    }
+    transformed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> Repository {
+        serde_json::from_value(serde_json::json!({
+            "name": "ghs",
+            "full_name": "octo/ghs",
+            "description": "Search GitHub repositories from the terminal",
+            "pushed_at": "2026-10-14T08:30:00Z",
+            "language": "not a date",
+        }))
+        .unwrap()
+    }
+
+    fn eval(expr: &str) -> Option<String> {
+        Expr::parse(expr).unwrap().eval(&repo())
+    }
+
+    #[test]
+    fn parses_nested_calls_with_spaces() {
+        assert_eq!(eval("name").as_deref(), Some("ghs"));
+        assert_eq!(
+            eval(" upper( lower(full_name) ) ").as_deref(),
+            Some("OCTO/GHS")
+        );
+        assert_eq!(
+            eval(r#"truncate(upper(description), 10)"#).as_deref(),
+            Some("SEARCH...")
+        );
+        assert_eq!(eval("upper(homepage)"), None);
+    }
+
+    #[test]
+    fn says_where_parsing_failed() {
+        let error = |expr: &str| Expr::parse(expr).unwrap_err();
+        assert!(error("stars").contains("`stars` is not a field"));
+        assert!(error("reverse(name)").contains("`reverse` is not a transform"));
+        assert_eq!(
+            error("upper(name"),
+            "expected `)` at column 11 of `upper(name`"
+        );
+        assert!(error("truncate(name)").contains("expected `,`"));
+        assert!(error("truncate(name, -1)").contains("expected a number"));
+        assert!(error("name name").contains("unexpected text after the expression"));
+        assert!(error(r#"date(pushed_at, "%Y)"#).contains("unclosed string"));
+        assert!(error(r#"date(pushed_at, "\n")"#).contains("may follow"));
+    }
+
+    #[test]
+    fn truncates_by_characters_and_leaves_room_for_the_ellipsis() {
+        assert_eq!(truncate("abcdef", 6), "abcdef");
+        assert_eq!(truncate("abcdefg", 6), "abc...");
+        // Too narrow for an ellipsis: just cut.
+        assert_eq!(truncate("abcdef", 3), "abc");
+        assert_eq!(truncate("abcdef", 1), "a");
+        assert_eq!(truncate("abcdef", 0), "");
+        // Characters, not bytes, and no space left before the ellipsis.
+        assert_eq!(truncate("日本語のテキスト", 5), "日本...");
+        assert_eq!(truncate("ab cdefgh", 6), "ab...");
+    }
+
+    #[test]
+    fn formats_dates_and_leaves_other_text_alone() {
+        assert_eq!(
+            eval(r#"date(pushed_at, "%Y/%m/%d")"#).as_deref(),
+            Some("2026/10/14")
+        );
+        assert_eq!(
+            eval(r#"date(language, "%Y")"#).as_deref(),
+            Some("not a date")
+        );
+        assert_eq!(eval(r#"date(created_at, "%Y")"#), None);
+    }
+
+    #[test]
+    fn every_transform_reads_the_original_fields() {
+        let transforms = [
+            parse("name=upper(full_name)").unwrap(),
+            parse("full_name = lower(name)").unwrap(),
+            parse("description=truncate(description, 9)").unwrap(),
+        ];
+        let transformed = apply(&repo(), &transforms);
+        assert_eq!(transformed.name, "OCTO/GHS");
+        assert_eq!(transformed.full_name, "ghs");
+        assert_eq!(transformed.description.as_deref(), Some("Search..."));
+        assert!(parse("stars=upper(name)").is_err());
+        assert!(parse("upper(name)").unwrap_err().contains("FIELD=EXPR"));
+    }
+}
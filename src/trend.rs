@@ -0,0 +1,125 @@
+use crate::{activity, plain, table};
+use ghs::sink::Sqlite;
+use ghs::{datetime, BoxError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Languages listed by name; the rest are summed up as "Other".
+const TOP_LANGUAGES: usize = 8;
+
+/// The share of `owner`'s repositories written in each language, per
+/// snapshot saved to the SQLite store at `db`, as a table from the most
+/// used language in the latest snapshot down.
+pub fn language_report(db: &Path, owner: &str) -> Result<String, BoxError> {
+    let rows = Sqlite::query(
+        db,
+        "SELECT taken_at, COALESCE(language, ''), COUNT(*) FROM snapshots \
+             WHERE lower(owner) = lower(?1) GROUP BY taken_at, language ORDER BY taken_at",
+        &[owner],
+    )?;
+
+    // Repositories per language, per snapshot.
+    let mut snapshots: BTreeMap<i64, BTreeMap<String, u64>> = BTreeMap::new();
+    for row in rows {
+        let [taken_at, language, count] = row.as_slice() else {
+            continue;
+        };
+        let (Ok(taken_at), Ok(count)) = (taken_at.parse::<i64>(), count.parse::<u64>()) else {
+            continue;
+        };
+        let language = match language.as_str() {
+            "" => "No language".to_string(),
+            language => language.to_string(),
+        };
+        *snapshots
+            .entry(taken_at)
+            .or_default()
+            .entry(language)
+            .or_default() += count;
+    }
+    let (Some(first), Some(last)) = (snapshots.keys().next(), snapshots.keys().last()) else {
+        return Err(format!(
+            "{} has no snapshots of {}; save some with `ghs --org {} --all --output sqlite:{}`",
+            db.display(),
+            owner,
+            owner,
+            db.display()
+        )
+        .into());
+    };
+    let date = |timestamp: i64| datetime::format_date(timestamp.div_euclid(86_400));
+    let mut report = format!(
+        "Language mix of {} across {} snapshots, {} to {}:\n",
+        owner,
+        snapshots.len(),
+        date(*first),
+        date(*last)
+    );
+
+    // Tenths of a percent, so that small shifts still show.
+    let shares: Vec<BTreeMap<&str, u64>> = snapshots
+        .values()
+        .map(|counts| {
+            let total: u64 = counts.values().sum();
+            counts
+                .iter()
+                .map(|(language, count)| (language.as_str(), count * 1000 / total.max(1)))
+                .collect()
+        })
+        .collect();
+    let latest = shares.last().unwrap();
+    let mut languages: Vec<&str> = shares
+        .iter()
+        .flat_map(|shares| shares.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    languages.sort_by_key(|language| std::cmp::Reverse(latest.get(language).copied()));
+    let other: BTreeSet<&str> = languages.iter().skip(TOP_LANGUAGES).copied().collect();
+    languages.truncate(TOP_LANGUAGES);
+
+    let series = |language: &str| -> Vec<u64> {
+        shares
+            .iter()
+            .map(|shares| match language {
+                "Other" => shares
+                    .iter()
+                    .filter(|(name, _)| other.contains(*name))
+                    .map(|(_, share)| share)
+                    .sum(),
+                language => shares.get(language).copied().unwrap_or(0),
+            })
+            .collect()
+    };
+    let percent = |share: u64| format!("{:.1}%", share as f64 / 10.0);
+
+    let mut header = vec!["Language".to_string()];
+    if !plain::enabled() {
+        header.push("Trend".to_string());
+    }
+    header.extend([
+        "First".to_string(),
+        "Last".to_string(),
+        "Change".to_string(),
+    ]);
+    let mut rows = vec![header];
+    if !other.is_empty() {
+        languages.push("Other");
+    }
+    for language in languages {
+        let series = series(language);
+        let (first, last) = (series[0], series[series.len() - 1]);
+        let mut row = vec![language.to_string()];
+        if !plain::enabled() {
+            row.push(activity::sparkline(&series));
+        }
+        row.extend([
+            percent(first),
+            percent(last),
+            format!("{:+.1} pts", (last as f64 - first as f64) / 10.0),
+        ]);
+        rows.push(row);
+    }
+    report.push_str(&table::render(&rows));
+    Ok(report)
+}
@@ -0,0 +1,132 @@
+//! `ghs trending`: an approximation of GitHub's trending page. The search
+//! API knows how many stars a repository has but not when it got them, so
+//! the gain over the period is estimated from how fast it gathered them.
+
+use crate::table;
+use ghs::{datetime, BoxError, Repository, SearchQuery};
+use std::collections::BTreeMap;
+
+/// Repositories older than the period need this many stars to be searched
+/// for at all; below it a lifetime average says little.
+const ESTABLISHED_MIN_STARS: u64 = 100;
+
+/// The period trending is measured over, as on github.com/trending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Since {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Since {
+    pub fn parse(since: &str) -> Result<Since, String> {
+        match since {
+            "daily" => Ok(Since::Daily),
+            "weekly" => Ok(Since::Weekly),
+            "monthly" => Ok(Since::Monthly),
+            _ => Err(format!(
+                "`{}` is not a period (expected daily, weekly or monthly)",
+                since
+            )),
+        }
+    }
+
+    fn days(self) -> i64 {
+        match self {
+            Since::Daily => 1,
+            Since::Weekly => 7,
+            Since::Monthly => 30,
+        }
+    }
+}
+
+/// The two searches trending is drawn from, most starred first: the
+/// repositories created within the period, and older ones with a following
+/// that were pushed to within it.
+pub fn queries(language: Option<&str>, since: Since, now: i64) -> Result<[String; 2], BoxError> {
+    let start = datetime::format_date(now.div_euclid(86_400) - since.days());
+    let search = || {
+        let query = SearchQuery::new().qualifier("sort", "stars");
+        match language {
+            Some(language) => query.language(language),
+            None => query,
+        }
+    };
+    Ok([
+        search()
+            .qualifier("created", format!(">={}", start))
+            .build()?,
+        search()
+            .pushed_after(start)
+            .stars(ESTABLISHED_MIN_STARS..)
+            .build()?,
+    ])
+}
+
+/// The stars `repo` probably gained over `days` days: all of them if it is
+/// younger than that, else its average rate since it was created.
+fn estimated_gain(repo: &Repository, days: i64, now: i64) -> u64 {
+    let age = repo
+        .created_at
+        .as_deref()
+        .and_then(datetime::parse_timestamp)
+        .map_or(i64::MAX, |created_at| (now - created_at) / 86_400);
+    match age <= days {
+        true => repo.stargazers_count,
+        false => (repo.stargazers_count as f64 * days as f64 / age as f64).round() as u64,
+    }
+}
+
+/// The `limit` repositories among `found` that gained the most stars over
+/// the period, with their estimated gains. Repositories found by both
+/// searches count once.
+pub fn rank(
+    found: Vec<Repository>,
+    since: Since,
+    now: i64,
+    limit: usize,
+) -> Vec<(u64, Repository)> {
+    let unique: BTreeMap<String, Repository> = found
+        .into_iter()
+        .map(|repo| (repo.full_name.clone(), repo))
+        .collect();
+    let mut ranked: Vec<(u64, Repository)> = unique
+        .into_values()
+        .map(|repo| (estimated_gain(&repo, since.days(), now), repo))
+        .filter(|(gain, _)| *gain > 0)
+        .collect();
+    // Stable, so ties stay in name order.
+    ranked.sort_by_key(|(gain, _)| std::cmp::Reverse(*gain));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// The ranked repositories as a table under a title naming the period.
+pub fn report(ranked: &[(u64, Repository)], since: Since, language: Option<&str>) -> String {
+    let period = match since {
+        Since::Daily => "today",
+        Since::Weekly => "this week",
+        Since::Monthly => "this month",
+    };
+    let title = match language {
+        Some(language) => format!("Trending {} repositories {}", language, period),
+        None => format!("Trending repositories {}", period),
+    };
+    let mut rows = vec![vec![
+        "#".to_string(),
+        "Repository".to_string(),
+        "Stars".to_string(),
+        "Gained (est.)".to_string(),
+        "Description".to_string(),
+    ]];
+    rows.extend(ranked.iter().enumerate().map(|(rank, (gain, repo))| {
+        vec![
+            (rank + 1).to_string(),
+            repo.full_name.clone(),
+            repo.stargazers_count.to_string(),
+            format!("+{}", gain),
+            repo.description.clone().unwrap_or_default(),
+        ]
+    }));
+    format!("{}:\n{}", title, table::render(&rows))
+}
@@ -0,0 +1,66 @@
+//! `ghs triage`: runs a saved session's search again and opens the TUI on
+//! what is new or changed since the session was last saved.
+
+use ghs::{datetime, Repository};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How a repository differs from the last run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The last run did not find it.
+    New,
+    /// Pushed to, starred, described or archived differently since.
+    Changed,
+}
+
+impl Change {
+    /// The marker the TUI shows next to the repository.
+    pub fn marker(self) -> char {
+        match self {
+            Change::New => '+',
+            Change::Changed => '~',
+        }
+    }
+}
+
+/// What changed between the `previous` results and the `current` ones, by
+/// full name, and how many of the previous ones are gone.
+pub fn diff(previous: &[Repository], current: &[Repository]) -> (BTreeMap<String, Change>, usize) {
+    let before: BTreeMap<&str, &Repository> = previous
+        .iter()
+        .map(|repo| (repo.full_name.as_str(), repo))
+        .collect();
+    let mut changes = BTreeMap::new();
+    for repo in current {
+        let change = match before.get(repo.full_name.as_str()) {
+            None => Change::New,
+            Some(old) if changed(old, repo) => Change::Changed,
+            Some(_) => continue,
+        };
+        changes.insert(repo.full_name.clone(), change);
+    }
+    let now: BTreeSet<&str> = current.iter().map(|repo| repo.full_name.as_str()).collect();
+    let gone = before.keys().filter(|name| !now.contains(*name)).count();
+    (changes, gone)
+}
+
+fn changed(old: &Repository, new: &Repository) -> bool {
+    old.pushed_at != new.pushed_at
+        || old.stargazers_count != new.stargazers_count
+        || old.description != new.description
+        || old.archived != new.archived
+}
+
+/// One line on what the new run found, e.g. `3 new, 1 changed and 2 gone
+/// since 2024-05-01T09:00:00Z`.
+pub fn summary(changes: &BTreeMap<String, Change>, gone: usize, since: &str) -> String {
+    let count = |kind| changes.values().filter(|change| **change == kind).count();
+    let since = datetime::to_utc(since).unwrap_or_else(|| since.to_string());
+    format!(
+        "{} new, {} changed and {} gone since {}",
+        count(Change::New),
+        count(Change::Changed),
+        gone,
+        since
+    )
+}
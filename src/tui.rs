@@ -0,0 +1,631 @@
+use crate::browser;
+use crate::export;
+use crate::plain;
+use crate::provenance::Provenance;
+use crate::session::Session;
+use crate::terminal::{Key, Terminal};
+use ghs::filters;
+use ghs::{BoxError, Repository};
+use std::cmp::Reverse;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const HELP: &[&str] = &[
+    "j, Down         next repository",
+    "k, Up           previous repository",
+    "Space, PgDn     next page",
+    "b, PgUp         previous page",
+    "Ctrl-d, Ctrl-u  half a page down / up",
+    "g, Home         first repository",
+    "G, End          last repository",
+    "/               search (incremental)",
+    "n, N            next / previous match",
+    "f               fuzzy filter (Esc clears it)",
+    "o               open in the browser",
+    "c               git clone into the current directory",
+    "a               all / only new and changed (triage)",
+    "m               mark / unmark",
+    "t, T            add a tag / clear tags",
+    "e               export the marked repositories",
+    "s               save the session",
+    "?               toggle this help",
+    "q               quit (saves a named session)",
+];
+
+/// Full-screen, less-like browser over the session's repositories. A named
+/// session is saved again on quit.
+///
+/// As a `picker`, the fuzzy filter is open from the start and Enter opens
+/// the selected repository in the browser.
+pub fn run(session: Session, picker: bool) -> Result<(), BoxError> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err("the TUI needs an interactive terminal".into());
+    }
+    if plain::enabled() {
+        return Err("the TUI is not available in plain mode".into());
+    }
+
+    let mut tui = Tui::new(session, picker);
+    {
+        let mut terminal = Terminal::enter()?;
+        loop {
+            let (width, height) = terminal.size();
+            tui.height = height;
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(tui.render(width).as_bytes())?;
+            stdout.flush()?;
+            drop(stdout);
+
+            if !tui.handle(terminal.read_key()?) {
+                break;
+            }
+        }
+    }
+
+    tui.set_filter(None);
+    let mut session = tui.session;
+    if let Some(name) = session.name.clone() {
+        let path = session.save_as(&name)?;
+        eprintln!("Saved session {} to {}", name, path.display());
+    }
+    Ok(())
+}
+
+/// What the status line is reading.
+enum Prompt {
+    /// A search, and where the cursor was when it started.
+    Search {
+        origin: usize,
+    },
+    /// The fuzzy filter, applied as it is typed.
+    Filter,
+    Tag,
+    SaveAs,
+    ExportTo,
+}
+
+struct Tui {
+    session: Session,
+    /// Indices into the session's repositories of those the filter lets
+    /// through, best match first. The cursor and offset index this list.
+    visible: Vec<usize>,
+    /// The fuzzy filter in effect.
+    filter: Option<String>,
+    /// Whether only the session's new and changed repositories are listed.
+    only_changes: bool,
+    /// Whether Enter opens the repository rather than moving down.
+    picker: bool,
+    /// Terminal rows, including the status line.
+    height: usize,
+    /// The last search, for `n` and `N`.
+    search: Option<String>,
+    input: Option<(Prompt, String)>,
+    help: bool,
+    message: Option<String>,
+}
+
+impl Tui {
+    fn new(session: Session, picker: bool) -> Self {
+        let mut tui = Tui {
+            visible: Vec::new(),
+            only_changes: !session.changes.is_empty(),
+            session,
+            filter: None,
+            picker,
+            height: 24,
+            search: None,
+            input: picker.then(|| (Prompt::Filter, String::new())),
+            help: false,
+            message: None,
+        };
+        let cursor = tui.session.cursor;
+        tui.set_filter(None);
+        tui.move_to(cursor);
+        tui
+    }
+
+    fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// The repository at `position` in the filtered list.
+    fn repository(&self, position: usize) -> Option<&Repository> {
+        self.visible
+            .get(position)
+            .map(|index| &self.session.repositories[*index])
+    }
+
+    fn current(&self) -> Option<&Repository> {
+        self.repository(self.session.cursor)
+    }
+
+    fn page(&self) -> usize {
+        self.height.saturating_sub(1).max(1)
+    }
+
+    /// Handles a key press; returns `false` to quit.
+    fn handle(&mut self, key: Key) -> bool {
+        self.message = None;
+        if self.help {
+            self.help = false;
+            return true;
+        }
+        if self.input.is_some() {
+            self.handle_input(key);
+            return true;
+        }
+
+        let cursor = self.session.cursor;
+        let last = self.len().saturating_sub(1);
+        let page = self.page();
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            Key::Enter if self.picker => self.open(),
+            Key::Char('j') | Key::Down | Key::Enter => self.move_to(cursor + 1),
+            Key::Char('k') | Key::Up => self.move_to(cursor.saturating_sub(1)),
+            Key::Char(' ') | Key::PageDown | Key::Ctrl('f') => self.move_to(cursor + page),
+            Key::Char('b') | Key::PageUp | Key::Ctrl('b') => {
+                self.move_to(cursor.saturating_sub(page))
+            }
+            Key::Ctrl('d') => self.move_to(cursor + page / 2),
+            Key::Ctrl('u') => self.move_to(cursor.saturating_sub(page / 2)),
+            Key::Char('g') | Key::Home => self.move_to(0),
+            Key::Char('G') | Key::End => self.move_to(last),
+            Key::Char('/') => self.input = Some((Prompt::Search { origin: cursor }, String::new())),
+            Key::Char('n') => self.jump(true),
+            Key::Char('N') => self.jump(false),
+            Key::Char('f') => {
+                let text = self.filter.clone().unwrap_or_default();
+                self.input = Some((Prompt::Filter, text));
+            }
+            Key::Char('a') if self.session.changes.is_empty() => {
+                self.message = Some("Nothing is new or changed; run ghs triage".to_string())
+            }
+            Key::Char('a') => {
+                self.only_changes = !self.only_changes;
+                self.set_filter(self.filter.clone().as_deref());
+            }
+            Key::Char('o') => self.open(),
+            Key::Char('c') => self.clone_current(),
+            Key::Char('m') => {
+                if let Some(full_name) = self.current().map(|repo| repo.full_name.clone()) {
+                    if !self.session.marks.remove(&full_name) {
+                        self.session.marks.insert(full_name);
+                    }
+                }
+            }
+            Key::Char('t') if self.current().is_some() => {
+                self.input = Some((Prompt::Tag, String::new()))
+            }
+            Key::Char('T') => {
+                if let Some(full_name) = self.current().map(|repo| repo.full_name.clone()) {
+                    self.session.tags.remove(&full_name);
+                }
+            }
+            Key::Char('s') => match self.session.name.clone() {
+                Some(name) => self.save(&name),
+                None => self.input = Some((Prompt::SaveAs, String::new())),
+            },
+            Key::Char('e') if self.session.marks.is_empty() => {
+                self.message = Some("Mark repositories with m first".to_string())
+            }
+            Key::Char('e') => self.input = Some((Prompt::ExportTo, String::new())),
+            Key::Char('?') => self.help = true,
+            _ => {}
+        }
+        true
+    }
+
+    fn handle_input(&mut self, key: Key) {
+        let Some((prompt, mut text)) = self.input.take() else {
+            return;
+        };
+        if let Prompt::Filter = prompt {
+            self.handle_filter(key, text);
+            return;
+        }
+        match key {
+            Key::Esc | Key::Ctrl('c') => {
+                if let Prompt::Search { origin } = prompt {
+                    self.move_to(origin);
+                }
+                return;
+            }
+            Key::Enter => {
+                self.submit(prompt, text);
+                return;
+            }
+            Key::Backspace => {
+                text.pop();
+            }
+            Key::Char(c) => text.push(c),
+            _ => {}
+        }
+
+        // Searches are incremental: show the first match from where the
+        // search started.
+        if let Prompt::Search { origin } = prompt {
+            match self.find(&text, origin, true) {
+                Some(index) if !text.is_empty() => self.move_to(index),
+                _ => self.move_to(origin),
+            }
+        }
+        self.input = Some((prompt, text));
+    }
+
+    /// Keys while typing the filter: arrows still move, Enter keeps the
+    /// filter (and opens the selection in a picker), Esc drops it.
+    fn handle_filter(&mut self, key: Key, mut text: String) {
+        match key {
+            Key::Esc | Key::Ctrl('c') => {
+                self.set_filter(None);
+                return;
+            }
+            Key::Enter => {
+                if self.picker {
+                    self.open();
+                }
+                return;
+            }
+            Key::Up => self.move_to(self.session.cursor.saturating_sub(1)),
+            Key::Down => self.move_to(self.session.cursor + 1),
+            Key::Backspace => {
+                text.pop();
+                self.set_filter(Some(&text));
+            }
+            Key::Char(c) => {
+                text.push(c);
+                self.set_filter(Some(&text));
+            }
+            _ => {}
+        }
+        self.input = Some((Prompt::Filter, text));
+    }
+
+    /// Lists only the repositories matching `filter`, best first, or all of
+    /// them again for `None` (or a blank filter). Either way only the new
+    /// and changed ones while `only_changes` is set.
+    fn set_filter(&mut self, filter: Option<&str>) {
+        let current = self.visible.get(self.session.cursor).copied();
+        let filter = filter.map(str::trim).filter(|filter| !filter.is_empty());
+        self.filter = filter.map(String::from);
+        self.session.offset = 0;
+        let session = &self.session;
+        let listed = session.repositories.iter().enumerate().filter(|(_, repo)| {
+            !self.only_changes || session.changes.contains_key(&repo.full_name)
+        });
+        let Some(filter) = filter else {
+            self.visible = listed.map(|(index, _)| index).collect();
+            let position = current.and_then(|index| self.visible.iter().position(|i| *i == index));
+            self.move_to(position.unwrap_or(0));
+            return;
+        };
+        let mut scored: Vec<(u32, usize)> = listed
+            .filter_map(|(index, repo)| Some((fuzzy_score(repo, filter)?, index)))
+            .collect();
+        // The sort is stable, so equally good matches keep the list order.
+        scored.sort_by_key(|(score, _)| Reverse(*score));
+        self.visible = scored.into_iter().map(|(_, index)| index).collect();
+        self.move_to(0);
+    }
+
+    fn open(&mut self) {
+        let Some(repo) = self.current() else {
+            return;
+        };
+        self.message = Some(match &repo.html_url {
+            Some(url) => match browser::open(url) {
+                Ok(()) => format!("Opened {}", url),
+                Err(err) => err.to_string(),
+            },
+            None => format!("{} has no web page", repo.full_name),
+        });
+    }
+
+    fn clone_current(&mut self) {
+        let Some(repo) = self.current() else {
+            return;
+        };
+        let Some(url) = repo.clone_url.as_ref().or(repo.html_url.as_ref()) else {
+            self.message = Some(format!("{} has no clone URL", repo.full_name));
+            return;
+        };
+        let full_name = repo.full_name.clone();
+        self.message = Some(match git_clone(url) {
+            Ok(directory) => format!("Cloned {} into {}", full_name, directory),
+            Err(err) => err,
+        });
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: String) {
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match prompt {
+            Prompt::Search { origin } => {
+                if self.find(&text, origin, true).is_none() {
+                    self.message = Some(format!("Pattern not found: {}", text));
+                }
+                self.search = Some(text);
+            }
+            Prompt::Tag => {
+                if let Some(full_name) = self.current().map(|repo| repo.full_name.clone()) {
+                    let tags = self.session.tags.entry(full_name).or_default();
+                    tags.extend(
+                        text.split([',', ' '])
+                            .filter(|tag| !tag.is_empty())
+                            .map(String::from),
+                    );
+                }
+            }
+            Prompt::Filter => {}
+            Prompt::SaveAs => self.save(&text),
+            Prompt::ExportTo => self.export(&text),
+        }
+    }
+
+    /// Writes the marked repositories, in list order, to `path`.
+    fn export(&mut self, path: &str) {
+        let session = &self.session;
+        let marked: Vec<&Repository> = session
+            .repositories
+            .iter()
+            .filter(|repo| session.marks.contains(&repo.full_name))
+            .collect();
+        let provenance = Provenance {
+            query: session.query.clone(),
+            fetched_at: session.fetched_at.clone(),
+            ghs_version: env!("CARGO_PKG_VERSION"),
+            host: session.host.clone(),
+        };
+        self.message = Some(match export::write(Path::new(path), &marked, &provenance) {
+            Ok(()) => format!("Exported {} repositories to {}", marked.len(), path),
+            Err(err) => err.to_string(),
+        });
+    }
+
+    /// Saves the session with the cursor on the current repository of the
+    /// full list, since the filter is not saved.
+    fn save(&mut self, name: &str) {
+        let position = (self.session.cursor, self.session.offset);
+        let index = self.visible.get(position.0).copied().unwrap_or(0);
+        (self.session.cursor, self.session.offset) = (index, index);
+        let saved = self.session.save_as(name);
+        (self.session.cursor, self.session.offset) = position;
+        self.message = Some(match saved {
+            Ok(_) => format!("Saved session {}", name),
+            Err(err) => err.to_string(),
+        });
+    }
+
+    /// Moves to the next (or previous) match of the last search, wrapping.
+    fn jump(&mut self, forward: bool) {
+        let Some(search) = self.search.clone() else {
+            self.message = Some("No previous search".to_string());
+            return;
+        };
+        let len = self.len();
+        if len == 0 {
+            return;
+        }
+        let cursor = self.session.cursor;
+        let start = if forward {
+            (cursor + 1) % len
+        } else {
+            (cursor + len - 1) % len
+        };
+        match self.find(&search, start, forward) {
+            Some(index) => self.move_to(index),
+            None => self.message = Some(format!("Pattern not found: {}", search)),
+        }
+    }
+
+    /// The first repository matching `text` from `start` on (or backwards),
+    /// wrapping around. Tags are searched too.
+    fn find(&self, text: &str, start: usize, forward: bool) -> Option<usize> {
+        let len = self.len();
+        let text = text.to_lowercase();
+        (0..len)
+            .map(|step| {
+                if forward {
+                    (start + step) % len
+                } else {
+                    (start + len - step % len) % len
+                }
+            })
+            .find(|index| self.matches(self.repository(*index).unwrap(), &text))
+    }
+
+    fn matches(&self, repo: &Repository, text: &str) -> bool {
+        let tags = self.session.tags.get(&repo.full_name).into_iter().flatten();
+        [
+            Some(repo.full_name.as_str()),
+            repo.description.as_deref(),
+            repo.language.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(tags.map(String::as_str))
+        .any(|field| field.to_lowercase().contains(text))
+    }
+
+    fn move_to(&mut self, index: usize) {
+        let cursor = index.min(self.len().saturating_sub(1));
+        let page = self.page();
+        let session = &mut self.session;
+        session.cursor = cursor;
+        if cursor < session.offset {
+            session.offset = cursor;
+        } else if cursor >= session.offset + page {
+            session.offset = cursor + 1 - page;
+        }
+    }
+
+    fn render(&self, width: usize) -> String {
+        let page = self.page();
+        let mut screen = String::from("\x1b[H");
+
+        let name_width = (0..self.len())
+            .filter_map(|position| self.repository(position))
+            .map(|repo| repo.full_name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(width / 2);
+        for row in 0..page {
+            let index = self.session.offset + row;
+            if let Some(repo) = self.repository(index) {
+                let mark = if self.session.marks.contains(&repo.full_name) {
+                    '*'
+                } else {
+                    ' '
+                };
+                let change = match self.session.changes.get(&repo.full_name) {
+                    Some(change) => change.marker().to_string(),
+                    None if self.session.changes.is_empty() => String::new(),
+                    None => " ".to_string(),
+                };
+                let tags = match self.session.tags.get(&repo.full_name) {
+                    Some(tags) if !tags.is_empty() => {
+                        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                        format!("[{}] ", tags.join(","))
+                    }
+                    _ => String::new(),
+                };
+                let line = format!(
+                    "{}{}{:<name_width$}  {:>6}\u{2605}  {:<12}  {}{}",
+                    mark,
+                    change,
+                    truncate(&repo.full_name, name_width),
+                    repo.stargazers_count,
+                    truncate(repo.language.as_deref().unwrap_or("-"), 12),
+                    tags,
+                    repo.description.as_deref().unwrap_or(""),
+                );
+                let line = truncate(&line, width);
+                if index == self.session.cursor {
+                    screen.push_str(&format!("\x1b[7m{:<width$}\x1b[0m", line));
+                } else {
+                    screen.push_str(&line);
+                }
+            }
+            screen.push_str("\x1b[K\r\n");
+        }
+
+        if self.help {
+            self.render_help(&mut screen, width);
+        }
+
+        let status = match (&self.input, &self.message) {
+            (Some((Prompt::Search { .. }, text)), _) => format!("/{}", text),
+            (Some((Prompt::Filter, text)), _) => {
+                format!(
+                    "Filter: {}  ({}/{})",
+                    text,
+                    self.len(),
+                    self.session.repositories.len()
+                )
+            }
+            (Some((Prompt::Tag, text)), _) => format!("Tag: {}", text),
+            (Some((Prompt::SaveAs, text)), _) => format!("Save session as: {}", text),
+            (Some((Prompt::ExportTo, text)), _) => {
+                format!("Export to (.json, .csv, .md): {}", text)
+            }
+            (None, Some(message)) => message.clone(),
+            (None, None) => {
+                let mut status = format!(
+                    "{}/{}",
+                    (self.session.cursor + 1).min(self.len()),
+                    self.len()
+                );
+                if let Some(filter) = &self.filter {
+                    status.push_str(&format!("  filter {}", filter));
+                }
+                if self.only_changes {
+                    status.push_str("  new and changed only (a: all)");
+                }
+                if !self.session.marks.is_empty() {
+                    status.push_str(&format!("  {} marked", self.session.marks.len()));
+                }
+                if let Some(name) = &self.session.name {
+                    status.push_str(&format!("  session {}", name));
+                }
+                status.push_str("  ? help  q quit");
+                status
+            }
+        };
+        screen.push_str(&format!(
+            "\x1b[7m{:<width$}\x1b[0m",
+            truncate(&status, width)
+        ));
+        screen
+    }
+
+    /// Draws the key bindings in a box over the list.
+    fn render_help(&self, screen: &mut String, width: usize) {
+        let inner = HELP.iter().map(|line| line.len()).max().unwrap_or(0) + 2;
+        let left = width.saturating_sub(inner + 2) / 2 + 1;
+        let top = self.page().saturating_sub(HELP.len() + 2) / 2 + 1;
+
+        let mut lines = vec![format!("\u{250c}{}\u{2510}", "\u{2500}".repeat(inner))];
+        lines.extend(
+            HELP.iter()
+                .map(|line| format!("\u{2502} {:<w$} \u{2502}", line, w = inner - 2)),
+        );
+        lines.push(format!("\u{2514}{}\u{2518}", "\u{2500}".repeat(inner)));
+        for (row, line) in lines.iter().enumerate().take(self.page()) {
+            screen.push_str(&format!("\x1b[{};{}H{}", top + row, left, line));
+        }
+        screen.push_str(&format!("\x1b[{};1H", self.height));
+    }
+}
+
+/// How well every word of `filter` fuzzily matches the repository's name,
+/// description or language; the name counts double.
+fn fuzzy_score(repo: &Repository, filter: &str) -> Option<u32> {
+    filter
+        .split_whitespace()
+        .map(|word| {
+            let fields = [repo.description.as_deref(), repo.language.as_deref()];
+            let name = filters::fuzzy_score(&repo.full_name, word).map(|score| score * 2);
+            fields
+                .into_iter()
+                .flatten()
+                .filter_map(|field| filters::fuzzy_score(field, word))
+                .chain(name)
+                .max()
+        })
+        .sum()
+}
+
+/// `git clone`s `url` into the current directory and returns the directory
+/// it was cloned to. git's output is captured so it does not scribble over
+/// the screen, and it may not prompt for credentials.
+fn git_clone(url: &str) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["clone", "--quiet", url])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("could not run git: {}", err))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr
+            .lines()
+            .last()
+            .unwrap_or("git clone failed")
+            .to_string());
+    }
+    let directory = url.trim_end_matches('/').trim_end_matches(".git");
+    Ok(directory
+        .rsplit('/')
+        .next()
+        .unwrap_or(directory)
+        .to_string())
+}
+
+/// Cuts `text` to at most `width` characters.
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
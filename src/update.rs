@@ -0,0 +1,264 @@
+use crate::exec;
+use ghs::model::ReleaseAsset;
+use ghs::{datetime, BoxError, GhsClient};
+use minisign_verify::{PublicKey, Signature};
+use std::env::{self, consts};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Where ghs itself is released.
+const REPOSITORY: &str = "shinshin86/ghs";
+
+/// How often the startup notice looks for a new release, in seconds.
+const CHECK_INTERVAL: i64 = 24 * 60 * 60;
+
+/// The startup check gives up quickly rather than hold up the command.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// File in the cache directory recording the last check: the time and the
+/// newest version seen, separated by a space.
+const STATE_FILE: &str = "latest-version";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The minisign public key releases are signed with, e.g. `RWQ...`, built in
+/// from `GHS_RELEASE_PUBLIC_KEY` when release binaries are compiled. Without
+/// it `self-update` cannot tell a genuine release from a replaced one, so it
+/// refuses to install anything.
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("GHS_RELEASE_PUBLIC_KEY");
+
+/// `v1.2.3` or `1.2.3` as a comparable triple; pre-release and build
+/// suffixes are ignored.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn is_newer(candidate: &str) -> bool {
+    match (parse_version(candidate), parse_version(VERSION)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+/// Prints a one-line notice when a newer release is known, checking GitHub
+/// at most once a day. Failures are ignored: the notice is a courtesy.
+pub async fn notify_if_outdated(api: &GhsClient, cache_dir: &Path) {
+    let state_path = cache_dir.join(STATE_FILE);
+    let state = fs::read_to_string(&state_path).unwrap_or_default();
+    let (checked_at, latest) = state.split_once(' ').unwrap_or(("0", ""));
+    let mut latest = latest.trim().to_string();
+
+    let now = datetime::now();
+    if now - checked_at.parse::<i64>().unwrap_or(0) >= CHECK_INTERVAL {
+        if let Ok(Ok(Some(release))) =
+            tokio::time::timeout(CHECK_TIMEOUT, api.latest_release(REPOSITORY)).await
+        {
+            latest = release.tag_name;
+        }
+        let _ = fs::create_dir_all(cache_dir);
+        let _ = fs::write(&state_path, format!("{} {}\n", now, latest));
+    }
+
+    if is_newer(&latest) {
+        eprintln!(
+            "ghs {} is available (this is {}); run `ghs self-update` to upgrade",
+            latest.trim_start_matches('v'),
+            VERSION
+        );
+    }
+}
+
+/// Whether an asset name mentions this platform's OS and architecture.
+fn is_platform_asset(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let os: &[&str] = match consts::OS {
+        "macos" => &["macos", "darwin", "apple"],
+        "windows" => &["windows"],
+        os => &[os],
+    };
+    let arch: &[&str] = match consts::ARCH {
+        "x86_64" => &["x86_64", "amd64"],
+        "aarch64" => &["aarch64", "arm64"],
+        arch => &[arch],
+    };
+    os.iter().any(|os| name.contains(os)) && arch.iter().any(|arch| name.contains(arch))
+}
+
+/// The bare executable built for this platform. Archives are skipped since
+/// ghs cannot unpack them, as are checksum and signature files.
+fn platform_asset(assets: &[ReleaseAsset]) -> Option<&ReleaseAsset> {
+    const SKIPPED: [&str; 8] = [
+        ".sha256", ".sig", ".minisig", ".asc", ".tar.gz", ".tgz", ".zip", ".txt",
+    ];
+    assets.iter().find(|asset| {
+        let name = asset.name.to_lowercase();
+        is_platform_asset(&name) && !SKIPPED.iter().any(|suffix| name.ends_with(suffix))
+    })
+}
+
+/// The minisign signature published for `asset`, `<asset>.minisig`.
+async fn published_signature(
+    api: &GhsClient,
+    assets: &[ReleaseAsset],
+    asset: &ReleaseAsset,
+) -> Result<Option<String>, BoxError> {
+    let name = format!("{}.minisig", asset.name);
+    match assets.iter().find(|candidate| candidate.name == name) {
+        Some(file) => Ok(Some(String::from_utf8(
+            api.download(&file.browser_download_url).await?,
+        )?)),
+        None => Ok(None),
+    }
+}
+
+/// Checks that `signature`, a minisign `.minisig` file, was made over
+/// `binary` with the secret key belonging to `public_key`. A checksum from
+/// the same release would prove nothing, since whoever could replace the
+/// binary could replace it too.
+fn verify_signature(binary: &[u8], signature: &str, public_key: &str) -> Result<(), BoxError> {
+    let public_key = PublicKey::from_base64(public_key.trim())
+        .map_err(|err| format!("the built-in release key is invalid: {}", err))?;
+    let signature =
+        Signature::decode(signature).map_err(|err| format!("unreadable signature: {}", err))?;
+    // Both the prehashed signatures minisign makes by default and the
+    // legacy ones over the raw bytes are Ed25519 signatures by the key.
+    public_key
+        .verify(binary, &signature, true)
+        .map_err(|err| format!("bad signature: {}", err).into())
+}
+
+/// Installs the latest release over the running executable after checking
+/// the download's signature against [`RELEASE_PUBLIC_KEY`].
+pub async fn self_update(api: &GhsClient, yes: bool) -> Result<(), BoxError> {
+    let release = api
+        .latest_release(REPOSITORY)
+        .await?
+        .ok_or("no releases have been published yet")?;
+    let latest = release.tag_name.trim_start_matches('v');
+    if !is_newer(&release.tag_name) {
+        println!("ghs {} is up to date", VERSION);
+        return Ok(());
+    }
+
+    let asset = platform_asset(&release.assets).ok_or_else(|| {
+        format!(
+            "release {} has no executable for {}-{}",
+            release.tag_name,
+            consts::ARCH,
+            consts::OS
+        )
+    })?;
+    let public_key = RELEASE_PUBLIC_KEY.ok_or(
+        "this build of ghs has no release key built in, so it cannot verify updates; \
+         install the new release by hand",
+    )?;
+    let signature = published_signature(api, &release.assets, asset)
+        .await?
+        .ok_or_else(|| {
+            format!(
+                "release {} publishes no signature for {}; refusing to install it unverified",
+                release.tag_name, asset.name
+            )
+        })?;
+
+    let executable = env::current_exe()?;
+    if !yes
+        && !exec::confirm(&format!(
+            "Replace {} ({}) with ghs {}?",
+            executable.display(),
+            VERSION,
+            latest
+        ))?
+    {
+        return Ok(());
+    }
+
+    eprintln!("Downloading {}...", asset.name);
+    let binary = api.download(&asset.browser_download_url).await?;
+    verify_signature(&binary, &signature, public_key)
+        .map_err(|err| format!("{} failed verification: {}", asset.name, err))?;
+
+    replace_executable(&executable, &binary)?;
+    println!("Updated ghs {} -> {}", VERSION, latest);
+    Ok(())
+}
+
+/// Writes the new binary next to the old one and renames it into place, so
+/// the executable is never left half-written.
+fn replace_executable(executable: &Path, binary: &[u8]) -> Result<(), BoxError> {
+    let staged = sibling(executable, "new");
+    fs::write(&staged, binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // A running executable cannot be overwritten on Windows, but it can be
+    // renamed out of the way.
+    #[cfg(windows)]
+    {
+        let old = sibling(executable, "old");
+        let _ = fs::remove_file(&old);
+        fs::rename(executable, &old)?;
+    }
+
+    if let Err(err) = fs::rename(&staged, executable) {
+        let _ = fs::remove_file(&staged);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// `ghs` → `.ghs.new`, in the same directory so the rename stays on one
+/// filesystem.
+fn sibling(executable: &Path, suffix: &str) -> PathBuf {
+    let name = executable
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ghs".to_string());
+    executable.with_file_name(format!(".{}.{}", name, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example key and signature of `test` from the minisign-verify
+    /// documentation.
+    const PUBLIC_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=
+trusted comment: timestamp:1555779966\tfile:test
+QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==
+";
+
+    #[test]
+    fn accepts_only_what_the_key_signed() {
+        verify_signature(b"test", SIGNATURE, PUBLIC_KEY).unwrap();
+        assert!(verify_signature(b"tesT", SIGNATURE, PUBLIC_KEY).is_err());
+        // Another key's signature of the same bytes.
+        let other = "RWTAPRW2qy9FjsBiMVqKyJ8NUIyNlcKU3Uu8fM4KRDaRXT47tWjsfTq9";
+        assert!(verify_signature(b"test", SIGNATURE, other).is_err());
+        assert!(verify_signature(b"test", "not a signature", PUBLIC_KEY).is_err());
+    }
+
+    #[test]
+    fn skips_signatures_when_picking_the_executable() {
+        let asset = |name: &str| ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{}", name),
+            size: 0,
+        };
+        let name = format!("ghs-{}-{}", consts::ARCH, consts::OS);
+        let assets = [asset(&format!("{}.minisig", name)), asset(&name)];
+        assert_eq!(platform_asset(&assets).unwrap().name, name);
+    }
+}
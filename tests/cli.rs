@@ -0,0 +1,148 @@
+//! The `ghs` binary against a local HTTP server standing in for the GitHub
+//! API.
+
+use ghs::testing::MockServer;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A directory of its own for each test, for the cache and config.
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ghs-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs `ghs` against `server`, with `dir` for its cache and config and no
+/// token from the environment.
+fn ghs(server: &MockServer, dir: &Path, args: &[&str]) -> Output {
+    ghs_with_token(server, dir, None, args)
+}
+
+/// [`ghs`], searching with `token` if given.
+fn ghs_with_token(server: &MockServer, dir: &Path, token: Option<&str>, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ghs"));
+    match token {
+        Some(token) => command.env("GH_TOKEN", token),
+        None => command.env_remove("GH_TOKEN"),
+    };
+    let output = command
+        .args(["--host", &server.url, "--cache-dir"])
+        .arg(dir.join("cache"))
+        .args(args)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_CACHE_HOME", dir.join("xdg-cache"))
+        .env("XDG_STATE_HOME", dir.join("state"))
+        .env("GHS_NO_UPDATE_CHECK", "1")
+        .env_remove("GITHUB_ACCESS_TOKEN")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ghs {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn searches(server: &MockServer) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/search/repositories"))
+        .count()
+}
+
+#[test]
+fn mutations_search_again_rather_than_use_a_cached_listing() {
+    let server = MockServer::start(|_, _| {
+        let body = serde_json::json!({
+            "total_count": 1,
+            "incomplete_results": false,
+            "items": [{"name": "ghs", "full_name": "octo/ghs", "archived": false}],
+        });
+        (200, Vec::new(), body.to_string())
+    });
+    let dir = scratch("mutations");
+
+    // A listing is cached, and served from the cache the second time.
+    let listing = ["org:octo", "--all", "--per-page", "100"];
+    ghs(&server, &dir, &listing);
+    ghs(&server, &dir, &listing);
+    assert_eq!(searches(&server), 1);
+
+    // The same search behind a mutation goes to the server every time.
+    let create = [
+        "issue",
+        "create",
+        "--query",
+        "org:octo",
+        "--title",
+        "Hello",
+        "--dry-run",
+    ];
+    let output = ghs(&server, &dir, &create);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("octo/ghs: Hello"));
+    assert_eq!(searches(&server), 2);
+    ghs(&server, &dir, &create);
+    assert_eq!(searches(&server), 3);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn searches_with_different_tokens_are_cached_apart() {
+    let server = MockServer::start(|_, _| {
+        let body = serde_json::json!({
+            "total_count": 1,
+            "items": [{"name": "secret", "full_name": "octo/secret", "private": true}],
+        });
+        (200, Vec::new(), body.to_string())
+    });
+    let dir = scratch("tokens");
+    let listing = ["org:octo", "--all", "--per-page", "100"];
+
+    ghs_with_token(&server, &dir, Some("work"), &listing);
+    ghs_with_token(&server, &dir, Some("work"), &listing);
+    assert_eq!(searches(&server), 1);
+    // Neither another account nor nobody at all get what `work` could see.
+    ghs_with_token(&server, &dir, Some("personal"), &listing);
+    assert_eq!(searches(&server), 2);
+    ghs(&server, &dir, &listing);
+    assert_eq!(searches(&server), 3);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn only_issues_opened_across_a_search_are_templates() {
+    let server = MockServer::start(|request, _| {
+        let repo = serde_json::json!({"name": "ghs", "full_name": "octo/ghs", "archived": false});
+        match request.path.starts_with("/search/") {
+            true => {
+                let body = serde_json::json!({"total_count": 1, "items": [repo]});
+                (200, Vec::new(), body.to_string())
+            }
+            false => (200, Vec::new(), repo.to_string()),
+        }
+    });
+    let dir = scratch("templates");
+    let create = |target: &[&str], title: &str| {
+        let mut args = vec!["issue", "create", "--title", title, "--dry-run"];
+        args.extend(target);
+        String::from_utf8(ghs(&server, &dir, &args).stdout).unwrap()
+    };
+
+    // One repository: the title as written, braces and all.
+    let output = create(&["octo/ghs"], "Handle {name} in config {");
+    assert!(output.starts_with("octo/ghs: Handle {name} in config {\n"));
+
+    // Across a search: placeholders filled, doubled braces literal.
+    let output = create(&["--query", "org:octo"], "Bump {name} to {{version}}");
+    assert!(output.starts_with("octo/ghs: Bump ghs to {version}\n"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,369 @@
+//! `GhsClient` against a local HTTP server standing in for the GitHub API.
+
+use ghs::cancel::Cancel;
+use ghs::progress::Event;
+use ghs::retry::RetryPolicy;
+use ghs::testing::MockServer;
+use ghs::{ApiError, GhsClient};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// A search result page holding `names`, out of `total` matches.
+fn page(total: u64, names: &[&str]) -> String {
+    let items: Vec<serde_json::Value> = names
+        .iter()
+        .map(|name| {
+            serde_json::json!({
+                "name": name,
+                "full_name": format!("octo/{}", name),
+                "stargazers_count": name.len(),
+            })
+        })
+        .collect();
+    serde_json::json!({"total_count": total, "incomplete_results": false, "items": items})
+        .to_string()
+}
+
+#[test]
+fn searches_with_the_query_encoded_and_the_token() {
+    let server = MockServer::start(|_, _| (200, Vec::new(), page(2, &["ghs", "octo"])));
+    let repositories = block_on(
+        server
+            .client(Some("t0ken"))
+            .search_repositories("user:octo language:rust", 50),
+    )
+    .unwrap();
+
+    assert_eq!(repositories.total_count, 2);
+    let names: Vec<&str> = repositories
+        .items
+        .iter()
+        .map(|repo| repo.full_name.as_str())
+        .collect();
+    assert_eq!(names, ["octo/ghs", "octo/octo"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].path,
+        "/search/repositories?q=user%3Aocto+language%3Arust&per_page=50"
+    );
+    assert_eq!(requests[0].headers["authorization"], "Bearer t0ken");
+    assert_eq!(requests[0].headers["accept"], "application/vnd.github+json");
+}
+
+#[test]
+fn searches_unauthenticated_without_a_token() {
+    let server = MockServer::start(|_, _| (200, Vec::new(), page(0, &[])));
+    block_on(server.client(None).search_repositories("rust", 10)).unwrap();
+    assert!(!server.requests()[0].headers.contains_key("authorization"));
+}
+
+#[test]
+fn takes_the_token_from_an_auth_provider() {
+    let server = MockServer::start(|_, _| (200, Vec::new(), page(0, &[])));
+    let vault = || -> Result<Option<String>, ghs::BoxError> { Ok(Some("fr0m-vault".into())) };
+    let client = GhsClient::builder()
+        .host(&server.url)
+        .token("overridden")
+        .auth(vault)
+        .build()
+        .unwrap();
+    block_on(client.search_repositories("rust", 10)).unwrap();
+    assert_eq!(
+        server.requests()[0].headers["authorization"],
+        "Bearer fr0m-vault"
+    );
+
+    let sealed = || -> Result<Option<String>, ghs::BoxError> { Err("vault is sealed".into()) };
+    let err = GhsClient::builder().auth(sealed).build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "could not get an access token: vault is sealed"
+    );
+}
+
+#[test]
+fn fetches_every_page_the_first_one_links_to() {
+    let server = MockServer::start(|request, base_url| {
+        let link = format!(
+            "<{}/search/repositories?q=rust&per_page=2&page=3>; rel=\"last\"",
+            base_url
+        );
+        match request.path.rsplit_once("&page=") {
+            None => (200, vec![("link", link)], page(5, &["a", "b"])),
+            Some((_, "2")) => (200, Vec::new(), page(5, &["c", "d"])),
+            Some((_, _)) => (200, Vec::new(), page(5, &["e"])),
+        }
+    });
+    let repositories = block_on(
+        server
+            .client(None)
+            .search_all_repositories("rust", 2, |_, _| {}),
+    )
+    .unwrap();
+    let mut names: Vec<String> = repositories
+        .items
+        .into_iter()
+        .map(|repo| repo.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, ["a", "b", "c", "d", "e"]);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn a_cancelled_search_keeps_the_pages_it_fetched() {
+    let cancel = Cancel::new();
+    let server = MockServer::start({
+        let cancel = cancel.clone();
+        move |request, base_url| {
+            let link = format!(
+                "<{}/search/repositories?q=rust&per_page=2&page=3>; rel=\"last\"",
+                base_url
+            );
+            if !request.path.contains("&page=") {
+                return (200, vec![("link", link)], page(5, &["a", "b"]));
+            }
+            // Ctrl-C while the other pages are on their way.
+            cancel.cancel();
+            thread::sleep(Duration::from_millis(500));
+            (200, Vec::new(), page(5, &["c", "d"]))
+        }
+    });
+    let api = GhsClient::builder()
+        .host(&server.url)
+        .cancel(cancel.clone())
+        .build()
+        .unwrap();
+    let repositories = block_on(api.search_all_repositories("rust", 2, |_, _| {})).unwrap();
+    let names: Vec<&str> = repositories
+        .items
+        .iter()
+        .map(|repo| repo.name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "b"]);
+    assert!(repositories.incomplete_results);
+
+    // Cancelled before the first page: nothing.
+    let repositories = block_on(api.search_all_repositories("rust", 2, |_, _| {})).unwrap();
+    assert!(repositories.items.is_empty() && repositories.incomplete_results);
+}
+
+#[test]
+fn reports_progress_to_the_listener() {
+    let limited = Mutex::new(false);
+    let server = MockServer::start(move |request, _| {
+        if request.path.ends_with("/readme") {
+            return (200, Vec::new(), "# ghs".to_string());
+        }
+        let mut limited = limited.lock().unwrap();
+        if !*limited {
+            *limited = true;
+            let message = serde_json::json!({"message": "secondary rate limit"}).to_string();
+            return (403, vec![("retry-after", "0".to_string())], message);
+        }
+        (200, Vec::new(), page(2, &["ghs", "octo"]))
+    });
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+    let client = GhsClient::builder()
+        .host(&server.url)
+        .on_progress(move |event| recorded.lock().unwrap().push(event.clone()))
+        .build()
+        .unwrap();
+    block_on(async {
+        client.search_repositories("rust", 2).await.unwrap();
+        client.readme("octo/ghs").await.unwrap();
+    });
+
+    let events = events.lock().unwrap();
+    assert!(matches!(events[0], Event::RateLimited { .. }));
+    assert_eq!(
+        events[1..],
+        [
+            Event::PageFetched {
+                url: format!("{}/search/repositories?q=rust&per_page=2", server.url),
+                items: 2,
+            },
+            Event::ItemEnriched {
+                full_name: "octo/ghs".to_string(),
+                detail: "readme",
+            },
+        ]
+    );
+}
+
+#[test]
+fn gives_up_on_a_rate_limit_that_never_lifts() {
+    let server = MockServer::start(|_, _| {
+        let message = serde_json::json!({"message": "secondary rate limit"}).to_string();
+        (429, vec![("retry-after", "0".to_string())], message)
+    });
+    let client = GhsClient::builder()
+        .host(&server.url)
+        .retry_policy(RetryPolicy {
+            max_rate_limit_waits: 2,
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+    let err = block_on(client.search_repositories("rust", 2)).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::RateLimited { message: Some(message), .. })
+            if message == "secondary rate limit"
+    ));
+    // The first request and two after waiting.
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn tells_api_failures_apart() {
+    let server = MockServer::start(|request, _| {
+        let message = |message: &str| serde_json::json!({ "message": message }).to_string();
+        if request.path.contains("badtoken") {
+            (401, Vec::new(), message("Bad credentials"))
+        } else if request.path.contains("limited") {
+            let headers = vec![
+                ("x-ratelimit-remaining", "0".to_string()),
+                ("x-ratelimit-reset", "4102444800".to_string()),
+            ];
+            (403, headers, message("API rate limit exceeded"))
+        } else if request.path.contains("nosuchuser") {
+            let body = serde_json::json!({
+                "message": "Validation Failed",
+                "errors": [{"message": "The listed users cannot be searched"}],
+            });
+            (422, Vec::new(), body.to_string())
+        } else {
+            (503, Vec::new(), String::new())
+        }
+    });
+    let client = server.client(Some("t0ken"));
+    let error = |query: &str| {
+        let err = block_on(client.search_repositories(query, 10)).unwrap_err();
+        err.downcast::<ApiError>().map(|err| *err).unwrap()
+    };
+
+    assert!(matches!(
+        error("badtoken"),
+        ApiError::Unauthorized { message: Some(message), .. } if message == "Bad credentials"
+    ));
+    assert!(matches!(
+        error("limited"),
+        ApiError::RateLimited {
+            reset: Some(4102444800),
+            ..
+        }
+    ));
+    let rejected = error("user:nosuchuser");
+    assert_eq!(rejected.exit_code(), ghs::error::EXIT_USAGE);
+    assert!(rejected
+        .to_string()
+        .contains("Validation Failed: The listed users cannot be searched"));
+    assert!(matches!(
+        error("flaky"),
+        ApiError::Status { status, .. } if status.as_u16() == 503
+    ));
+}
+
+#[test]
+fn sends_nothing_offline() {
+    let server = MockServer::start(|_, _| (200, Vec::new(), page(0, &[])));
+    let client = GhsClient::builder()
+        .host(&server.url)
+        .offline(true)
+        .build()
+        .unwrap();
+    let err = block_on(client.search_repositories("rust", 10)).unwrap_err();
+    assert!(err.to_string().contains("offline"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn replays_recorded_fixtures_without_the_network() {
+    let server = MockServer::start(|request, base_url| match request.path.contains("&page=2") {
+        false => (
+            200,
+            vec![(
+                "link",
+                format!(
+                    "<{}/search/repositories?q=rust&per_page=2&page=2>; rel=\"last\"",
+                    base_url
+                ),
+            )],
+            page(3, &["ghs", "octo"]),
+        ),
+        true => (200, Vec::new(), page(3, &["hub"])),
+    });
+    let dir = std::env::temp_dir().join(format!("ghs-fixtures-{}", std::process::id()));
+    let recording = GhsClient::builder()
+        .host(&server.url)
+        .record_fixtures(&dir, true)
+        .build()
+        .unwrap();
+    let recorded = block_on(recording.search_all_repositories("rust", 2, |_, _| {})).unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+    // Another base URL, as a test would have, with nothing listening.
+    let replaying = GhsClient::builder()
+        .host("http://127.0.0.1:9")
+        .replay_fixtures(&dir)
+        .build()
+        .unwrap();
+    let replayed = block_on(replaying.search_all_repositories("rust", 2, |_, _| {})).unwrap();
+    assert_eq!(replayed.total_count, 3);
+    let names = |repositories: &ghs::Repositories| {
+        repositories
+            .items
+            .iter()
+            .map(|repo| repo.full_name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&replayed), ["octo/ghs", "octo/octo", "octo/hub"]);
+    assert_eq!(names(&replayed), names(&recorded));
+    assert_eq!(server.requests().len(), 2);
+
+    let err = block_on(replaying.search_repositories("go", 2)).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("no fixture for GET /search/repositories?q=go"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn searches_issues_by_kind() {
+    let server = MockServer::start(|_, base_url| {
+        let body = serde_json::json!({
+            "total_count": 1,
+            "items": [{
+                "number": 7,
+                "title": "Crash on start",
+                "html_url": "https://github.com/octo/ghs/pull/7",
+                "state": "open",
+                "repository_url": format!("{}/repos/octo/ghs", base_url),
+                "pull_request": {},
+            }],
+        });
+        (200, Vec::new(), body.to_string())
+    });
+    let results = block_on(server.client(None).search_issues("crash repo:octo/ghs", 5)).unwrap();
+    assert_eq!(results.items.len(), 1);
+    assert_eq!(results.items[0].repository(), "octo/ghs");
+    assert!(results.items[0].pull_request.is_some());
+    assert_eq!(
+        server.requests()[0].path,
+        "/search/issues?q=crash+repo%3Aocto%2Fghs&per_page=5"
+    );
+}